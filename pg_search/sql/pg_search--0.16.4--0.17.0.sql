@@ -7,3 +7,467 @@ CREATE  FUNCTION "fsm_info"(
     STRICT
     LANGUAGE c /* Rust */
 AS 'MODULE_PATHNAME', 'fsm_info_wrapper';
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:806
+-- pg_search::api::builder_fns::rrf_keys
+CREATE  FUNCTION "rrf"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"ranked_keys" anyarray, /* pgrx::datum::anyarray::AnyArray */
+	"k" INT DEFAULT NULL, /* core::option::Option<i32> */
+	"window_size" INT DEFAULT NULL /* core::option::Option<i32> */
+) RETURNS SearchQueryInput /* core::result::Result<pg_search::query::SearchQueryInput, anyhow::Error> */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'rrf_keys_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:840
+-- pg_search::api::builder_fns::rrf_query
+CREATE  FUNCTION "rrf"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"ranked_query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"k" INT DEFAULT NULL, /* core::option::Option<i32> */
+	"window_size" INT DEFAULT NULL /* core::option::Option<i32> */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'rrf_query_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:868
+-- pg_search::api::builder_fns::search_after
+CREATE  FUNCTION "search_after"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"last_key" anyelement, /* pgrx::datum::anyelement::AnyElement */
+	"last_score" real DEFAULT NULL /* core::option::Option<f32> */
+) RETURNS SearchQueryInput /* core::result::Result<pg_search::query::SearchQueryInput, anyhow::Error> */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'search_after_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/postgres/customscan/pdbscan/projections/window_agg.rs:53
+-- pg_search::postgres::customscan::pdbscan::projections::window_agg::agg_sfunc
+CREATE  FUNCTION "agg_sfunc"(
+	"_state" jsonb, /* core::option::Option<pgrx::datum::json::JsonB> */
+	"_definition" jsonb /* pgrx::datum::json::JsonB */
+) RETURNS jsonb /* core::option::Option<pgrx::datum::json::JsonB> */
+STABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'agg_sfunc_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/postgres/customscan/pdbscan/projections/window_agg.rs:60
+-- requires:
+--   agg_sfunc
+CREATE AGGREGATE agg(jsonb) (
+    SFUNC = agg_sfunc,
+    STYPE = jsonb
+);
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/postgres/customscan/pdbscan/projections/window_agg.rs:71
+-- pg_search::postgres::customscan::pdbscan::projections::window_agg::window_agg_placeholder
+CREATE  FUNCTION "window_agg"(
+	"_definition" jsonb /* pgrx::datum::json::JsonB */
+) RETURNS jsonb /* core::option::Option<pgrx::datum::json::JsonB> */
+STRICT STABLE PARALLEL SAFE COST 1
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'window_agg_placeholder_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/postgres/customscan/pdbscan/projections/snippet.rs:109
+-- pg_search::postgres::customscan::pdbscan::projections::snippet::snippets
+CREATE  FUNCTION "snippets"(
+	"field" anyelement, /* pgrx::datum::anyelement::AnyElement */
+	"max_fragments" INT DEFAULT 3, /* i32 */
+	"fragment_size" INT DEFAULT 150, /* i32 */
+	"order_by" TEXT DEFAULT 'score', /* alloc::string::String */
+	"start_tag" TEXT DEFAULT '<b>', /* alloc::string::String */
+	"end_tag" TEXT DEFAULT '</b>' /* alloc::string::String */
+) RETURNS jsonb /* core::option::Option<pgrx::datum::json::JsonB> */
+STRICT STABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'snippets_from_relation_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/postgres/customscan/pdbscan/projections/snippet.rs:137
+-- requires:
+--   snippets_from_relation
+--   placeholder_support
+ALTER FUNCTION snippets SUPPORT placeholder_support;
+/* </end connected objects> */
+
+DROP FUNCTION IF EXISTS tokenizer(name text,
+    remove_long pg_catalog.int4,
+    lowercase bool,
+    min_gram pg_catalog.int4,
+    max_gram pg_catalog.int4,
+    prefix_only bool,
+    language text,
+    pattern text,
+    stemmer text,
+    stopwords_language text,
+    stopwords text[]);
+
+CREATE OR REPLACE FUNCTION tokenizer(
+    name text,
+    remove_long pg_catalog.int4 DEFAULT '255',
+    lowercase bool DEFAULT true,
+    min_gram pg_catalog.int4 DEFAULT NULL,
+    max_gram pg_catalog.int4 DEFAULT NULL,
+    prefix_only bool DEFAULT NULL,
+    language text DEFAULT NULL,
+    pattern text DEFAULT NULL,
+    stemmer text DEFAULT NULL,
+    stopwords_language text DEFAULT NULL,
+    stopwords text[] DEFAULT NULL,
+    synonyms text[] DEFAULT NULL,
+    ascii_folding bool DEFAULT NULL,
+    unicode_normalization text DEFAULT NULL,
+    phonetic text DEFAULT NULL,
+    phonetic_mode text DEFAULT NULL,
+    shingle jsonb DEFAULT NULL,
+    edge_ngram jsonb DEFAULT NULL,
+    char_filters jsonb DEFAULT NULL,
+    word_delimiter jsonb DEFAULT NULL,
+    decompounder jsonb DEFAULT NULL
+)
+RETURNS jsonb AS 'MODULE_PATHNAME', 'tokenizer_wrapper' IMMUTABLE LANGUAGE c PARALLEL SAFE;
+
+/* <begin connected objects> */
+-- pg_search/src/api/analyzer.rs:28
+-- analyzers_table
+CREATE TABLE paradedb.analyzers (
+    name text PRIMARY KEY,
+    definition jsonb NOT NULL
+);
+GRANT SELECT ON paradedb.analyzers TO PUBLIC;
+SELECT pg_catalog.pg_extension_config_dump('paradedb.analyzers', '');
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/analyzer.rs:45
+-- pg_search::api::analyzer::create_analyzer
+CREATE  FUNCTION "create_analyzer"(
+	"name" TEXT, /* &str */
+	"tokenizer" jsonb, /* pgrx::datum::json::JsonB */
+	"filters" jsonb[] DEFAULT '{}' /* alloc::vec::Vec<pgrx::datum::json::JsonB> */
+) RETURNS void
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'create_analyzer_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/analyzer.rs:59
+-- pg_search::api::analyzer::drop_analyzer
+CREATE  FUNCTION "drop_analyzer"(
+	"name" TEXT /* &str */
+) RETURNS void
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'drop_analyzer_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/analyzer.rs:75
+-- pg_search::api::analyzer::analyzer
+CREATE  FUNCTION "analyzer"(
+	"name" TEXT /* &str */
+) RETURNS jsonb /* pgrx::datum::json::JsonB */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'analyzer_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:884
+-- pg_search::api::builder_fns::span_term
+CREATE  FUNCTION "span_term"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"value" TEXT /* alloc::string::String */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'span_term_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:900
+-- pg_search::api::builder_fns::span_near
+CREATE  FUNCTION "span_near"(
+	"clauses" SearchQueryInput[], /* pgrx::datum::array::Array<pg_search::query::SearchQueryInput> */
+	"slop" INT DEFAULT 0, /* i32 */
+	"in_order" bool DEFAULT false /* bool */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'span_near_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:914
+-- pg_search::api::builder_fns::span_or
+CREATE  FUNCTION "span_or"(
+	"clauses" SearchQueryInput[] /* pgrx::datum::array::Array<pg_search::query::SearchQueryInput> */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'span_or_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:923
+-- pg_search::api::builder_fns::span_not
+CREATE  FUNCTION "span_not"(
+	"include" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"exclude" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"pre" INT DEFAULT 0, /* i32 */
+	"post" INT DEFAULT 0 /* i32 */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'span_not_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:939
+-- pg_search::api::builder_fns::span_first
+CREATE  FUNCTION "span_first"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"end_position" INT /* i32 */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'span_first_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:189
+-- pg_search::api::builder_fns::decay
+CREATE  FUNCTION "decay"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"function" TEXT, /* alloc::string::String */
+	"origin" TEXT, /* alloc::string::String */
+	"scale" TEXT, /* alloc::string::String */
+	"origin_offset" TEXT DEFAULT NULL, /* core::option::Option<alloc::string::String> */
+	"decay" double precision DEFAULT NULL, /* core::option::Option<f64> */
+	"weight" real DEFAULT NULL /* core::option::Option<f32> */
+) RETURNS jsonb /* core::result::Result<pgrx::datum::json::JsonB, anyhow::Error> */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'decay_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:273
+-- pg_search::api::builder_fns::field_value_factor
+CREATE  FUNCTION "field_value_factor"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"factor" double precision DEFAULT NULL, /* core::option::Option<f64> */
+	"modifier" TEXT DEFAULT NULL, /* core::option::Option<alloc::string::String> */
+	"missing" double precision DEFAULT NULL, /* core::option::Option<f64> */
+	"weight" real DEFAULT NULL /* core::option::Option<f32> */
+) RETURNS jsonb /* core::result::Result<pgrx::datum::json::JsonB, anyhow::Error> */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'field_value_factor_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:309
+-- pg_search::api::builder_fns::function_score
+CREATE  FUNCTION "function_score"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"functions" jsonb[], /* alloc::vec::Vec<pgrx::datum::json::JsonB> */
+	"score_mode" TEXT DEFAULT NULL, /* core::option::Option<alloc::string::String> */
+	"boost_mode" TEXT DEFAULT NULL /* core::option::Option<alloc::string::String> */
+) RETURNS SearchQueryInput /* core::result::Result<pg_search::query::SearchQueryInput, anyhow::Error> */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'function_score_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:363
+-- pg_search::api::builder_fns::geo_bounding_box
+CREATE  FUNCTION "geo_bounding_box"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"top_left" point, /* pgrx_pg_sys::include::pg16::Point */
+	"bottom_right" point /* pgrx_pg_sys::include::pg16::Point */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'geo_bounding_box_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:378
+-- pg_search::api::builder_fns::geo_distance
+CREATE  FUNCTION "geo_distance"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"origin" point, /* pgrx_pg_sys::include::pg16::Point */
+	"distance" TEXT /* alloc::string::String */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'geo_distance_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:390
+-- pg_search::api::builder_fns::geo_distance_score
+CREATE  FUNCTION "geo_distance_score"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"origin" point /* pgrx_pg_sys::include::pg16::Point */
+) RETURNS jsonb /* core::result::Result<pgrx::datum::json::JsonB, anyhow::Error> */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'geo_distance_score_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:399
+-- pg_search::api::builder_fns::geo_polygon
+CREATE  FUNCTION "geo_polygon"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"points" point[] /* alloc::vec::Vec<pgrx_pg_sys::include::pg16::Point> */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'geo_polygon_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/percolate.rs:46
+-- pg_search::api::percolate::percolate
+CREATE  FUNCTION "percolate"(
+	"index" regclass, /* pgrx::rel::PgRelation */
+	"document" jsonb /* pgrx::datum::json::JsonB */
+) RETURNS TABLE (
+	"key" TEXT,  /* alloc::string::String */
+	"query" SearchQueryInput  /* pg_search::query::SearchQueryInput */
+)
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'percolate_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/suggest.rs:45
+-- pg_search::api::suggest::suggest
+CREATE  FUNCTION "suggest"(
+	"index" regclass, /* pgrx::rel::PgRelation */
+	"field" FieldName, /* pg_search::api::FieldName */
+	"text" TEXT, /* &str */
+	"max_edits" INT DEFAULT 2, /* i32 */
+	"limit" INT DEFAULT 5 /* i32 */
+) RETURNS TABLE (
+	"token" TEXT,  /* core::option::Option<alloc::string::String> */
+	"suggestion" TEXT,  /* alloc::string::String */
+	"distance" INT,  /* i32 */
+	"doc_freq" bigint  /* core::option::Option<i64> */
+)
+STABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'suggest_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/complete.rs:41
+-- pg_search::api::complete::complete
+CREATE  FUNCTION "complete"(
+	"index" regclass, /* pgrx::rel::PgRelation */
+	"field" FieldName, /* pg_search::api::FieldName */
+	"prefix" TEXT, /* &str */
+	"completion_limit" INT DEFAULT 10, /* i32 */
+	"weight_field" FieldName DEFAULT NULL /* core::option::Option<pg_search::api::FieldName> */
+) RETURNS TABLE (
+	"completion" TEXT,  /* alloc::string::String */
+	"score" double precision  /* f64 */
+)
+STABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'complete_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/explain.rs:36
+-- pg_search::api::explain::explain_score
+CREATE  FUNCTION "explain_score"(
+	"index" regclass, /* pgrx::rel::PgRelation */
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"key_value" anyelement /* pgrx::datum::anyelement::AnyElement */
+) RETURNS jsonb /* core::result::Result<core::option::Option<pgrx::datum::json::JsonB>, anyhow::Error> */
+STABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'explain_score_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:180
+-- pg_search::api::builder_fns::named
+CREATE  FUNCTION "named"(
+	"name" TEXT, /* alloc::string::String */
+	"query" SearchQueryInput /* pg_search::query::SearchQueryInput */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'named_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/postgres/customscan/pdbscan/projections/matched_queries.rs:28
+-- pg_search::postgres::customscan::pdbscan::projections::matched_queries::matched_queries_from_relation
+CREATE  FUNCTION "matched_queries"(
+	"_relation_reference" anyelement /* pgrx::datum::anyelement::AnyElement */
+) RETURNS TEXT[] /* core::option::Option<alloc::vec::Vec<alloc::string::String>> */
+STRICT STABLE PARALLEL SAFE COST 1
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'matched_queries_from_relation_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/postgres/customscan/pdbscan/projections/matched_queries.rs:33
+-- requires:
+--   matched_queries_from_relation
+--   placeholder_support
+ALTER FUNCTION matched_queries SUPPORT placeholder_support;
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:511
+-- pg_search::api::builder_fns::nested
+CREATE  FUNCTION "nested"(
+	"path" FieldName, /* pg_search::api::FieldName */
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"score_mode" TEXT DEFAULT NULL /* core::option::Option<alloc::string::String> */
+) RETURNS SearchQueryInput /* core::result::Result<pg_search::query::SearchQueryInput, anyhow::Error> */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'nested_wrapper';
+/* </end connected objects> */
+
+/* <begin connected objects> */
+-- pg_search/src/api/builder_fns.rs:1058
+-- pg_search::api::builder_fns::rescore
+CREATE  FUNCTION "rescore"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"rescore_query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"window_size" INT DEFAULT NULL, /* core::option::Option<i32> */
+	"query_weight" real DEFAULT NULL, /* core::option::Option<f32> */
+	"rescore_weight" real DEFAULT NULL /* core::option::Option<f32> */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'rescore_wrapper';
+/* </end connected objects> */
@@ -14,6 +14,7 @@ use pgrx::{check_for_interrupts, default, pg_extern, pg_sys, Json, JsonB, PgRela
 use rustc_hash::FxHashSet;
 use std::error::Error;
 use tantivy::aggregation::agg_req::Aggregations;
+use tantivy::aggregation::agg_result::AggregationResults;
 use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use tantivy::aggregation::{AggregationLimitsGuard, DistributedAggregationCollector};
 use tantivy::collector::Collector;
@@ -278,18 +279,40 @@ pub fn aggregate(
     memory_limit: default!(i64, 500000000),
     bucket_limit: default!(i64, 65000),
 ) -> Result<JsonB, Box<dyn Error>> {
+    let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+    let agg_req = serde_json::from_value(agg.0)?;
+    match execute_aggregate(
+        &index,
+        query,
+        agg_req,
+        solve_mvcc,
+        memory_limit.try_into()?,
+        bucket_limit.try_into()?,
+    )? {
+        Some(result) => Ok(JsonB(serde_json::to_value(result)?)),
+        None => Ok(JsonB(serde_json::Value::Null)),
+    }
+}
+
+/// Run the aggregations in `agg_req` over the documents matching `query`, in parallel when
+/// possible.  Returns `None` if the index doesn't have any segments to aggregate.
+pub fn execute_aggregate(
+    index: &PgSearchRelation,
+    query: SearchQueryInput,
+    agg_req: Aggregations,
+    solve_mvcc: bool,
+    memory_limit: u64,
+    bucket_limit: u32,
+) -> Result<Option<AggregationResults>, Box<dyn Error>> {
     unsafe {
-        let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
-        let reader =
-            SearchIndexReader::open(&index, query.clone(), false, MvccSatisfies::Snapshot)?;
-        let agg_req = serde_json::from_value(agg.0)?;
+        let reader = SearchIndexReader::open(index, query.clone(), false, MvccSatisfies::Snapshot)?;
         let process = ParallelAggregation::new(
             index.oid(),
             &query,
             &agg_req,
             solve_mvcc,
-            memory_limit.try_into()?,
-            bucket_limit.try_into()?,
+            memory_limit,
+            bucket_limit,
             reader.segment_ids(),
         )?;
 
@@ -351,21 +374,15 @@ pub fn aggregate(
             let merged = {
                 let collector = DistributedAggregationCollector::from_aggs(
                     agg_req.clone(),
-                    AggregationLimitsGuard::new(
-                        Some(memory_limit.try_into()?),
-                        Some(bucket_limit.try_into()?),
-                    ),
+                    AggregationLimitsGuard::new(Some(memory_limit), Some(bucket_limit)),
                 );
                 collector.merge_fruits(agg_results)?.into_final_result(
                     agg_req,
-                    AggregationLimitsGuard::new(
-                        Some(memory_limit.try_into()?),
-                        Some(bucket_limit.try_into()?),
-                    ),
+                    AggregationLimitsGuard::new(Some(memory_limit), Some(bucket_limit)),
                 )?
             };
 
-            Ok(JsonB(serde_json::to_value(merged)?))
+            Ok(Some(merged))
         } else {
             // couldn't launch any workers, so we just execute the aggregate right here in this backend
             let segment_ids = reader.segment_ids();
@@ -380,21 +397,18 @@ pub fn aggregate(
                 segment_ids,
                 index.oid(),
                 solve_mvcc,
-                memory_limit as _,
-                bucket_limit as _,
+                memory_limit,
+                bucket_limit,
                 &mut state,
             );
             if let Some(agg_results) = worker.execute_aggregate(QueryWorkerStyle::NonParallel)? {
                 let result = agg_results.into_final_result(
                     agg_req,
-                    AggregationLimitsGuard::new(
-                        Some(memory_limit.try_into()?),
-                        Some(bucket_limit.try_into()?),
-                    ),
+                    AggregationLimitsGuard::new(Some(memory_limit), Some(bucket_limit)),
                 )?;
-                Ok(JsonB(serde_json::to_value(result)?))
+                Ok(Some(result))
             } else {
-                Ok(JsonB(serde_json::Value::Null))
+                Ok(None)
             }
         }
     }
@@ -471,7 +485,10 @@ pub mod mvcc_collector {
         type Fruit = SC::Fruit;
 
         fn collect(&mut self, doc: DocId, score: Score) {
-            let ctid = self.ctid_ff.as_u64(doc).expect("ctid should be present");
+            // the child documents of nested fields have no ctid, as they aren't rows
+            let Some(ctid) = self.ctid_ff.as_u64(doc) else {
+                return;
+            };
             if self.lock.lock().is_visible(ctid) {
                 self.inner.collect(doc, score);
             }
@@ -489,7 +506,9 @@ pub mod mvcc_collector {
             self.filtered_buffer.clear();
             let mut vischeck = self.lock.lock();
             for (doc, ctid) in docs.iter().zip(self.ctids_buffer.iter()) {
-                let ctid = ctid.expect("ctid should be present");
+                let Some(ctid) = *ctid else {
+                    continue;
+                };
                 if vischeck.is_visible(ctid) {
                     self.filtered_buffer.push(*doc);
                 }
@@ -505,7 +524,7 @@ pub mod mvcc_collector {
     }
 }
 
-mod vischeck {
+pub mod vischeck {
     use crate::postgres::utils;
     use pgrx::itemptr::item_pointer_get_block_number;
     use pgrx::pg_sys;
@@ -0,0 +1,229 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Named analyzers, which are tokenizer definitions stored in the `paradedb.analyzers` table so
+//! that many indexes can share them.  Field configs refer to one with
+//! `{"type": "analyzer", "name": "..."}`, which `paradedb.analyzer()` returns.
+//!
+//! Tokenizers are resolved here too, which loads the word lists that their filters read from
+//! tables.  Indexes save their resolved tokenizers when they're built, so changing an analyzer
+//! or a word list only changes an index that uses it once the index is reindexed.
+
+use anyhow::{anyhow, bail, Result};
+use pgrx::{default, extension_sql, pg_extern, pg_sys, JsonB, Spi};
+use serde_json::{json, Value};
+use tokenizers::SearchTokenizer;
+
+extension_sql!(
+    r#"
+CREATE TABLE paradedb.analyzers (
+    name text PRIMARY KEY,
+    definition jsonb NOT NULL
+);
+GRANT SELECT ON paradedb.analyzers TO PUBLIC;
+SELECT pg_catalog.pg_extension_config_dump('paradedb.analyzers', '');
+"#,
+    name = "analyzers_table"
+);
+
+/// Create a named analyzer from a tokenizer, such as one returned by `paradedb.tokenizer()`, and
+/// any number of filters, which are JSON objects of filter options like `{"stemmer": "English"}`.
+///
+/// An existing analyzer with the same name is replaced.  Indexes that use it keep tokenizing with
+/// the old definition until they're reindexed.
+#[pg_extern]
+pub fn create_analyzer(
+    name: &str,
+    tokenizer: JsonB,
+    filters: default!(Vec<JsonB>, "'{}'"),
+) -> Result<()> {
+    let mut definition = analyzer_definition(tokenizer.0, filters.into_iter().map(|f| f.0))
+        .map_err(|e| anyhow!("invalid definition for analyzer '{name}': {e}"))?;
+    qualify_word_list_table(&mut definition)?;
+
+    Spi::run_with_args(
+        "INSERT INTO paradedb.analyzers (name, definition) VALUES ($1, $2)
+         ON CONFLICT (name) DO UPDATE SET definition = excluded.definition",
+        &[name.into(), JsonB(definition).into()],
+    )?;
+    Ok(())
+}
+
+/// Drop a named analyzer.  Indexes that use it keep tokenizing with its definition, but can't be
+/// reindexed until it's created again.
+#[pg_extern]
+pub fn drop_analyzer(name: &str) -> Result<()> {
+    let dropped = Spi::get_one_with_args::<bool>(
+        "WITH dropped AS (DELETE FROM paradedb.analyzers WHERE name = $1 RETURNING 1)
+         SELECT EXISTS (SELECT 1 FROM dropped)",
+        &[name.into()],
+    )?;
+
+    if dropped != Some(true) {
+        bail!("analyzer '{name}' does not exist");
+    }
+    Ok(())
+}
+
+/// A reference to a named analyzer, for use as the `tokenizer` of a text or JSON field, or with
+/// `paradedb.tokenize()`
+#[pg_extern(immutable, parallel_safe)]
+pub fn analyzer(name: &str) -> JsonB {
+    JsonB(json!({ "type": "analyzer", "name": name }))
+}
+
+/// Merge `filters` into `tokenizer`, and make sure the result is a valid tokenizer
+fn analyzer_definition(tokenizer: Value, filters: impl Iterator<Item = Value>) -> Result<Value> {
+    let Value::Object(mut definition) = tokenizer else {
+        bail!("the tokenizer must be a JSON object");
+    };
+    for filter in filters {
+        let Value::Object(filter) = filter else {
+            bail!("each filter must be a JSON object, found: {filter}");
+        };
+        definition.extend(filter);
+    }
+
+    let definition = Value::Object(definition);
+    let tokenizer = SearchTokenizer::from_json_value(&definition)?;
+    if matches!(tokenizer, SearchTokenizer::Analyzer(_)) {
+        bail!("an analyzer cannot be defined as another analyzer");
+    }
+    Ok(definition)
+}
+
+/// The definition of the named analyzer `name`
+pub fn lookup_analyzer(name: &str) -> Result<SearchTokenizer> {
+    // the subquery makes sure there's always a row, even if the analyzer doesn't exist
+    let definition = Spi::get_one_with_args::<JsonB>(
+        "SELECT (SELECT definition FROM paradedb.analyzers WHERE name = $1)",
+        &[name.into()],
+    )?
+    .ok_or_else(|| anyhow!("analyzer '{name}' does not exist"))?;
+    let mut tokenizer = SearchTokenizer::from_json_value(&definition.0)?;
+    load_word_lists(&mut tokenizer)?;
+    Ok(tokenizer)
+}
+
+/// `tokenizer`, or if it refers to a named analyzer, the analyzer's definition
+pub fn resolve_analyzer(tokenizer: SearchTokenizer) -> Result<SearchTokenizer> {
+    match tokenizer {
+        SearchTokenizer::Analyzer(name) => lookup_analyzer(&name),
+        mut tokenizer => {
+            load_word_lists(&mut tokenizer)?;
+            Ok(tokenizer)
+        }
+    }
+}
+
+/// Schema-qualify the `word_list_table` of the decompounder in `tokenizer`, a tokenizer's JSON
+/// definition, using the current `search_path`.  Names are qualified where they're written, like
+/// in `CREATE INDEX`, so that they name the same table when they're read later on.
+pub fn qualify_word_list_table(tokenizer: &mut Value) -> Result<()> {
+    let Some(Value::String(table)) = tokenizer.pointer_mut("/decompounder/word_list_table") else {
+        return Ok(());
+    };
+
+    // the subquery makes sure there's always a row, even if the table doesn't exist
+    let qualified = Spi::get_one_with_args::<String>(
+        "SELECT (SELECT format('%I.%I', n.nspname, c.relname)
+                 FROM pg_catalog.pg_class c JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                 WHERE c.oid = pg_catalog.to_regclass($1))",
+        &[table.as_str().into()],
+    )?
+    .ok_or_else(|| anyhow!("word list table '{table}' does not exist"))?;
+    *table = qualified;
+    Ok(())
+}
+
+/// Schema-qualify the word list tables of the tokenizers in `configs`, the JSON value of a
+/// `text_fields` or `json_fields` index option.  Returns whether any were found.
+pub fn qualify_field_word_list_tables(configs: &mut Value) -> Result<bool> {
+    let Value::Object(configs) = configs else {
+        return Ok(false);
+    };
+
+    let mut found = false;
+    for config in configs.values_mut() {
+        if let Some(tokenizer) = config.get_mut("tokenizer") {
+            found |= tokenizer.pointer("/decompounder/word_list_table").is_some();
+            qualify_word_list_table(tokenizer)?;
+        }
+    }
+    Ok(found)
+}
+
+/// Load the words of `tokenizer`'s decompounder, if it reads them from a table, which must be
+/// schema-qualified.
+///
+/// An index reads the table when it's built, so changing its words only changes how the index
+/// tokenizes once it's reindexed.
+fn load_word_lists(tokenizer: &mut SearchTokenizer) -> Result<()> {
+    let Some(decompounder) = tokenizer
+        .filters_mut()
+        .and_then(|filters| filters.decompounder.as_mut())
+    else {
+        return Ok(());
+    };
+    let Some(table) = decompounder.word_list_table.as_deref() else {
+        return Ok(());
+    };
+
+    let words = with_safe_search_path(|| {
+        // quote the table and column names so they can't be used to inject SQL, and only read
+        // text columns, whose cast to text can't run anyone else's code
+        let query = Spi::get_one_with_args::<String>(
+            "SELECT (SELECT format('SELECT array_agg(%I::text) FROM %s WHERE %I IS NOT NULL', attname, attrelid::regclass, attname)
+                     FROM pg_catalog.pg_attribute
+                     WHERE attrelid = $1::regclass AND attname = $2 AND NOT attisdropped
+                       AND atttypid IN ('text'::regtype, 'varchar'::regtype))",
+            &[table.into(), decompounder.word_list_column.as_str().into()],
+        )?
+        .ok_or_else(|| {
+            anyhow!(
+                "the word list table '{table}' must have a text column named '{}'",
+                decompounder.word_list_column
+            )
+        })?;
+        Ok::<_, anyhow::Error>(Spi::get_one::<Vec<String>>(&query)?.unwrap_or_default())
+    })?;
+    decompounder.word_list = Some(words);
+    Ok(())
+}
+
+/// Run `f` with a `search_path` of only `pg_catalog` and `pg_temp`, like Postgres uses for
+/// maintenance operations, so the functions and operators it runs can't be replaced by ones from
+/// other schemas
+fn with_safe_search_path<T>(f: impl FnOnce() -> T) -> T {
+    unsafe {
+        let nest_level = pg_sys::NewGUCNestLevel();
+        pg_sys::set_config_option(
+            c"search_path".as_ptr(),
+            c"pg_catalog, pg_temp".as_ptr(),
+            pg_sys::GucContext::PGC_USERSET,
+            pg_sys::GucSource::PGC_S_SESSION,
+            pg_sys::GucAction::GUC_ACTION_SAVE,
+            true,
+            0,
+            false,
+        );
+        // if `f` raises an error, aborting the transaction restores the search_path
+        let result = f();
+        pg_sys::AtEOXact_GUC(true, nest_level);
+        result
+    }
+}
@@ -24,6 +24,11 @@ use crate::index::reader::index::SearchIndexReader;
 use crate::postgres::index::IndexKind;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::types::{TantivyValue, TantivyValueError};
+use crate::query::function_score::{
+    DecayFunction, FieldValueModifier, FunctionBoostMode, FunctionScoreMode, ScoreFunction,
+};
+use crate::query::geo::GeoPoint;
+use crate::query::nested::NestedScoreMode;
 use crate::query::{SearchQueryInput, TermInput};
 use crate::schema::AnyEnum;
 use crate::schema::IndexRecordOption;
@@ -172,6 +177,15 @@ pub fn boost(factor: f32, query: SearchQueryInput) -> SearchQueryInput {
     }
 }
 
+/// Give `query` a name, which `paradedb.matched_queries()` reports for each row it matches
+#[pg_extern(immutable, parallel_safe)]
+pub fn named(name: String, query: SearchQueryInput) -> SearchQueryInput {
+    SearchQueryInput::Named {
+        name,
+        query: Box::new(query),
+    }
+}
+
 #[pg_extern(immutable, parallel_safe)]
 pub fn const_score(score: f32, query: SearchQueryInput) -> SearchQueryInput {
     SearchQueryInput::ConstScore {
@@ -180,6 +194,40 @@ pub fn const_score(score: f32, query: SearchQueryInput) -> SearchQueryInput {
     }
 }
 
+/// A decay function for `paradedb.function_score()`, which scores documents by how far their
+/// `field` value is from `origin`.  For date fields, `origin` is a timestamp or `now`, and `scale`
+/// and `offset` are durations like `7d` or `12h`.
+#[pg_extern(immutable, parallel_safe)]
+pub fn decay(
+    field: FieldName,
+    function: String,
+    origin: String,
+    scale: String,
+    origin_offset: default!(Option<String>, "NULL"),
+    decay: default!(Option<f64>, "NULL"),
+    weight: default!(Option<f32>, "NULL"),
+) -> anyhow::Result<JsonB> {
+    let function: DecayFunction = serde_json::from_value(serde_json::Value::String(function))?;
+    let function = ScoreFunction::Decay {
+        field,
+        function,
+        origin: number_or_text(origin),
+        scale: number_or_text(scale),
+        offset: origin_offset.map(number_or_text),
+        decay,
+        weight,
+    };
+    Ok(JsonB(serde_json::to_value(function)?))
+}
+
+/// `value` as a number if it is one, or else as text
+fn number_or_text(value: String) -> OwnedValue {
+    match value.parse::<f64>() {
+        Ok(number) => OwnedValue::F64(number),
+        Err(_) => OwnedValue::Str(value),
+    }
+}
+
 #[pg_extern(immutable, parallel_safe)]
 pub fn disjunction_max(
     disjuncts: Array<SearchQueryInput>,
@@ -231,6 +279,72 @@ pub fn fast_field_range_weight(
     }
 }
 
+/// A function for `paradedb.function_score()` that scores documents by their `field` value,
+/// multiplied by `factor` and then put through `modifier`, such as `log1p`
+#[pg_extern(immutable, parallel_safe)]
+pub fn field_value_factor(
+    field: FieldName,
+    factor: default!(Option<f64>, "NULL"),
+    modifier: default!(Option<String>, "NULL"),
+    missing: default!(Option<f64>, "NULL"),
+    weight: default!(Option<f32>, "NULL"),
+) -> anyhow::Result<JsonB> {
+    let modifier: FieldValueModifier = match modifier {
+        Some(modifier) => serde_json::from_value(serde_json::Value::String(modifier))?,
+        None => Default::default(),
+    };
+    let function = ScoreFunction::FieldValueFactor {
+        field,
+        factor,
+        modifier,
+        missing,
+        weight,
+    };
+    Ok(JsonB(serde_json::to_value(function)?))
+}
+
+/// Adjust the scores of `query` with `functions` of each document's fast field values, such as
+/// the ones returned by `paradedb.decay()` and `paradedb.field_value_factor()`.
+///
+/// ```sql
+/// SELECT id, paradedb.score(id) FROM mock_items
+/// WHERE id @@@ paradedb.function_score(
+///     paradedb.match('description', 'shoes'),
+///     ARRAY[
+///         paradedb.decay('created_at', 'gauss', origin => 'now', scale => '30d'),
+///         paradedb.field_value_factor('rating', modifier => 'log1p')
+///     ]
+/// )
+/// ORDER BY paradedb.score(id) DESC LIMIT 10;
+/// ```
+#[pg_extern(immutable, parallel_safe)]
+pub fn function_score(
+    query: SearchQueryInput,
+    functions: Vec<JsonB>,
+    score_mode: default!(Option<String>, "NULL"),
+    boost_mode: default!(Option<String>, "NULL"),
+) -> anyhow::Result<SearchQueryInput> {
+    let functions = functions
+        .into_iter()
+        .map(|function| serde_json::from_value(function.0))
+        .collect::<Result<Vec<ScoreFunction>, _>>()?;
+    let score_mode: FunctionScoreMode = match score_mode {
+        Some(score_mode) => serde_json::from_value(serde_json::Value::String(score_mode))?,
+        None => Default::default(),
+    };
+    let boost_mode: FunctionBoostMode = match boost_mode {
+        Some(boost_mode) => serde_json::from_value(serde_json::Value::String(boost_mode))?,
+        None => Default::default(),
+    };
+
+    Ok(SearchQueryInput::FunctionScore {
+        query: Box::new(query),
+        functions,
+        score_mode,
+        boost_mode,
+    })
+}
+
 #[pg_extern(immutable, parallel_safe)]
 pub fn fuzzy_term(
     field: FieldName,
@@ -248,6 +362,58 @@ pub fn fuzzy_term(
     }
 }
 
+/// A `point`'s x is its longitude and y is its latitude
+fn geo_point(point: pg_sys::Point) -> GeoPoint {
+    GeoPoint {
+        lat: point.y,
+        lon: point.x,
+    }
+}
+
+#[pg_extern(immutable, parallel_safe)]
+pub fn geo_bounding_box(
+    field: FieldName,
+    top_left: pg_sys::Point,
+    bottom_right: pg_sys::Point,
+) -> SearchQueryInput {
+    SearchQueryInput::GeoBoundingBox {
+        field,
+        top_left: geo_point(top_left),
+        bottom_right: geo_point(bottom_right),
+    }
+}
+
+/// Matches the documents whose `field` point is within `distance` of `origin`, where `distance`
+/// is a number of meters or a length like `5km` or `2mi`
+#[pg_extern(immutable, parallel_safe)]
+pub fn geo_distance(field: FieldName, origin: pg_sys::Point, distance: String) -> SearchQueryInput {
+    SearchQueryInput::GeoDistance {
+        field,
+        origin: geo_point(origin),
+        distance: number_or_text(distance),
+    }
+}
+
+/// A function for `paradedb.function_score()` whose value is the distance in meters from
+/// `origin` to the document's `field` point.  With `boost_mode => 'replace'`, ordering by
+/// `paradedb.score()` ascending returns the nearest documents first.
+#[pg_extern(immutable, parallel_safe)]
+pub fn geo_distance_score(field: FieldName, origin: pg_sys::Point) -> anyhow::Result<JsonB> {
+    let function = ScoreFunction::GeoDistance {
+        field,
+        origin: geo_point(origin),
+    };
+    Ok(JsonB(serde_json::to_value(function)?))
+}
+
+#[pg_extern(immutable, parallel_safe)]
+pub fn geo_polygon(field: FieldName, points: Vec<pg_sys::Point>) -> SearchQueryInput {
+    SearchQueryInput::GeoPolygon {
+        field,
+        points: points.into_iter().map(geo_point).collect(),
+    }
+}
+
 #[pg_extern(name = "match", immutable, parallel_safe)]
 pub fn match_query(
     field: FieldName,
@@ -340,6 +506,26 @@ pub fn more_like_this_id(
     }
 }
 
+/// Match rows with an element of the JSON array at `path` that `query` matches on its own,
+/// scoring each row by combining the scores of its matching elements with `score_mode`
+#[pg_extern(immutable, parallel_safe)]
+pub fn nested(
+    path: FieldName,
+    query: SearchQueryInput,
+    score_mode: default!(Option<String>, "NULL"),
+) -> anyhow::Result<SearchQueryInput> {
+    let score_mode: NestedScoreMode = match score_mode {
+        Some(score_mode) => serde_json::from_value(serde_json::Value::String(score_mode))?,
+        None => Default::default(),
+    };
+
+    Ok(SearchQueryInput::Nested {
+        path,
+        query: Box::new(query),
+        score_mode,
+    })
+}
+
 #[pg_extern(immutable, parallel_safe)]
 pub fn parse(
     query_string: String,
@@ -792,6 +978,202 @@ pub fn regex_phrase(
     }
 }
 
+/// Fuse the BM25 ranking of `query` with an externally ranked list of key field values using
+/// Reciprocal Rank Fusion.  The fused score is available through `paradedb.score()`.
+///
+/// ```sql
+/// SELECT id, paradedb.score(id) FROM mock_items
+/// WHERE id @@@ paradedb.rrf(
+///     paradedb.match('description', 'running shoes'),
+///     ARRAY(SELECT id FROM mock_items ORDER BY embedding <-> '[1,2,3]' LIMIT 100)
+/// )
+/// ORDER BY paradedb.score(id) DESC LIMIT 10;
+/// ```
+#[pg_extern(name = "rrf", immutable, parallel_safe)]
+pub fn rrf_keys(
+    query: SearchQueryInput,
+    ranked_keys: AnyArray,
+    k: default!(Option<i32>, "NULL"),
+    window_size: default!(Option<i32>, "NULL"),
+) -> anyhow::Result<SearchQueryInput> {
+    let element_type = unsafe { pg_sys::get_element_type(ranked_keys.oid()) };
+    if element_type == pg_sys::InvalidOid {
+        anyhow::bail!("rrf: ranked_keys is not an array");
+    }
+
+    let array = unsafe { Array::<pg_sys::Datum>::from_datum(ranked_keys.datum(), false) }
+        .ok_or_else(|| anyhow::anyhow!("rrf: ranked_keys should not be NULL"))?;
+    let element_type = PgOid::from_untagged(element_type);
+    let ranked_keys = array
+        .into_nullable_iter()
+        // a NULL key can't identify a row, so it doesn't take up a rank
+        .filter(|datum| datum.is_valid())
+        .map(|datum| unsafe { TantivyValue::try_from_datum(datum.unwrap(), element_type) })
+        .map(|value| value.map(OwnedValue::from))
+        .collect::<Result<Vec<_>, TantivyValueError>>()?;
+
+    Ok(SearchQueryInput::Rrf {
+        query: Box::new(query),
+        ranked_keys,
+        ranked_query: None,
+        k: positive("rrf", "k", k)?,
+        window_size: positive("rrf", "window_size", window_size)?,
+    })
+}
+
+/// Fuse the BM25 ranking of `query` with the ranking of `ranked_query` using Reciprocal Rank
+/// Fusion.  The fused score is available through `paradedb.score()`.
+#[pg_extern(name = "rrf", immutable, parallel_safe)]
+pub fn rrf_query(
+    query: SearchQueryInput,
+    ranked_query: SearchQueryInput,
+    k: default!(Option<i32>, "NULL"),
+    window_size: default!(Option<i32>, "NULL"),
+) -> anyhow::Result<SearchQueryInput> {
+    Ok(SearchQueryInput::Rrf {
+        query: Box::new(query),
+        ranked_keys: vec![],
+        ranked_query: Some(Box::new(ranked_query)),
+        k: positive("rrf", "k", k)?,
+        window_size: positive("rrf", "window_size", window_size)?,
+    })
+}
+
+/// Checks that an optional count argument of `function` is greater than zero, so it can't wrap
+/// around when it's converted to an unsigned integer
+fn positive(function: &str, name: &str, value: Option<i32>) -> anyhow::Result<Option<u32>> {
+    match value {
+        Some(n) if n <= 0 => anyhow::bail!("{function}: {name} must be greater than zero"),
+        value => Ok(value.map(|n| n as u32)),
+    }
+}
+
+/// Rescore the best `window_size` matches of `query` in each segment with `rescore_query`, which
+/// is typically too expensive to score every match with.  A rescored row's score is
+/// `query_weight` times its original score plus `rescore_weight` times its `rescore_query` score.
+///
+/// The results must be ordered by `paradedb.score()` descending and limited, and any other query
+/// is an error.  The rescored rows come before the rest.
+///
+/// ```sql
+/// SELECT id, paradedb.score(id) FROM mock_items
+/// WHERE id @@@ paradedb.rescore(
+///     paradedb.match('description', 'running shoes'),
+///     paradedb.phrase('description', ARRAY['running', 'shoes'], slop => 2),
+///     window_size => 50
+/// )
+/// ORDER BY paradedb.score(id) DESC LIMIT 10;
+/// ```
+#[pg_extern(immutable, parallel_safe)]
+pub fn rescore(
+    query: SearchQueryInput,
+    rescore_query: SearchQueryInput,
+    window_size: default!(Option<i32>, "NULL"),
+    query_weight: default!(Option<f32>, "NULL"),
+    rescore_weight: default!(Option<f32>, "NULL"),
+) -> anyhow::Result<SearchQueryInput> {
+    Ok(SearchQueryInput::Rescore {
+        query: Box::new(query),
+        rescore_query: Box::new(rescore_query),
+        window_size: positive("rescore", "window_size", window_size)?,
+        query_weight,
+        rescore_weight,
+    })
+}
+
+/// Keyset pagination:  match the documents of `query` that come after the last row of the
+/// previous page, given that row's key field value and, when ordering by score, its score.
+///
+/// Pages must be ordered by `paradedb.score()` descending and then by the key field, or by the
+/// key field alone when `last_score` is NULL, with a `LIMIT`.  Any other query is an error, and
+/// so is `search_after` anywhere but the outermost query.  Unlike `OFFSET`, the rows of the pages
+/// before aren't collected and thrown away.  Ordered by the key alone, they aren't looked at all,
+/// but ordered by score, every match is still scored.
+///
+/// ```sql
+/// SELECT id, paradedb.score(id) FROM mock_items
+/// WHERE id @@@ paradedb.search_after(paradedb.match('description', 'shoes'), 42, 2.4849067)
+/// ORDER BY paradedb.score(id) DESC, id LIMIT 20;
+/// ```
+#[pg_extern(immutable, parallel_safe)]
+pub fn search_after(
+    query: SearchQueryInput,
+    last_key: AnyElement,
+    last_score: default!(Option<f32>, "NULL"),
+) -> anyhow::Result<SearchQueryInput> {
+    let last_key = unsafe { TantivyValue::try_from_anyelement(last_key)? };
+    Ok(SearchQueryInput::SearchAfter {
+        query: Box::new(query),
+        last_key: OwnedValue::from(last_key),
+        last_score,
+    })
+}
+
+/// `value` as a clause of the other span queries.  It's tokenized with the field's tokenizer, and
+/// if that produces several tokens, they must appear together as a phrase.
+#[pg_extern(immutable, parallel_safe)]
+pub fn span_term(field: FieldName, value: String) -> SearchQueryInput {
+    SearchQueryInput::SpanTerm { field, value }
+}
+
+/// Match when every one of `clauses` matches with at most `slop` other words between them, and in
+/// the order they're given if `in_order` is true.  All the clauses must be on the same field.
+///
+/// ```sql
+/// SELECT id, description FROM mock_items
+/// WHERE id @@@ paradedb.span_near(
+///     ARRAY[paradedb.span_term('description', 'running'), paradedb.span_term('description', 'shoes')],
+///     slop => 1,
+///     in_order => true
+/// );
+/// ```
+#[pg_extern(immutable, parallel_safe)]
+pub fn span_near(
+    clauses: Array<SearchQueryInput>,
+    slop: default!(i32, 0),
+    in_order: default!(bool, false),
+) -> SearchQueryInput {
+    SearchQueryInput::SpanNear {
+        clauses: clauses.iter_deny_null().collect(),
+        slop: slop as u32,
+        in_order,
+    }
+}
+
+/// Match wherever any of `clauses` match
+#[pg_extern(immutable, parallel_safe)]
+pub fn span_or(clauses: Array<SearchQueryInput>) -> SearchQueryInput {
+    SearchQueryInput::SpanOr {
+        clauses: clauses.iter_deny_null().collect(),
+    }
+}
+
+/// Match wherever `include` matches, unless `exclude` matches within `pre` words before it or
+/// `post` words after it
+#[pg_extern(immutable, parallel_safe)]
+pub fn span_not(
+    include: SearchQueryInput,
+    exclude: SearchQueryInput,
+    pre: default!(i32, 0),
+    post: default!(i32, 0),
+) -> SearchQueryInput {
+    SearchQueryInput::SpanNot {
+        include: Box::new(include),
+        exclude: Box::new(exclude),
+        pre: pre as u32,
+        post: post as u32,
+    }
+}
+
+/// Match wherever `query` matches within the first `end_position` words of the field
+#[pg_extern(immutable, parallel_safe)]
+pub fn span_first(query: SearchQueryInput, end_position: i32) -> SearchQueryInput {
+    SearchQueryInput::SpanFirst {
+        query: Box::new(query),
+        end: end_position as u32,
+    }
+}
+
 macro_rules! term_fn {
     ($func_name:ident, $value_type:ty) => {
         #[pg_extern(name = "term", immutable, parallel_safe)]
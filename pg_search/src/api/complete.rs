@@ -0,0 +1,288 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Autocompletion of a prefix, from the terms that a text field has indexed.  Completions come
+//! straight from the term dictionary, so fields don't need a separate prefix ngram tokenizer.
+
+use crate::api::aggregate::vischeck::TSVisibilityChecker;
+use crate::api::{FieldName, HashMap};
+use crate::index::fast_fields_helper::FFType;
+use crate::index::mvcc::MvccSatisfies;
+use crate::index::reader::index::SearchIndexReader;
+use crate::postgres::index::IndexKind;
+use crate::postgres::rel::PgSearchRelation;
+use crate::schema::SearchFieldType;
+use anyhow::{anyhow, Result};
+use pgrx::{default, iter::TableIterator, name, pg_extern, pg_sys, PgRelation};
+use std::sync::Arc;
+use tantivy::columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+use tantivy::schema::{Field, IndexRecordOption, Term};
+use tantivy::{DocId, DocSet, InvertedIndexReader, SegmentReader, TERMINATED};
+
+/// Complete the last token of `prefix` with the terms of `field` that start with it.  Terms are
+/// ranked by how many visible rows contain them or, if `weight_field` is given, by the largest
+/// value of that numeric fast field among those rows.
+///
+/// Each completion is `prefix` with its last token replaced by the completed term.
+#[pg_extern(stable, parallel_safe)]
+pub fn complete(
+    index: PgRelation,
+    field: FieldName,
+    prefix: &str,
+    completion_limit: default!(i32, 10),
+    weight_field: default!(Option<FieldName>, "NULL"),
+) -> Result<TableIterator<'static, (name!(completion, String), name!(score, f64))>> {
+    let limit = usize::try_from(completion_limit)
+        .map_err(|_| anyhow!("completion_limit must not be negative"))?;
+    if limit == 0 {
+        return Ok(TableIterator::new(vec![]));
+    }
+
+    // # Safety
+    //
+    // Lock the index relation until the end of this function so it is not dropped or
+    // altered while we are reading it.
+    let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+    let mut partitions = IndexKind::for_index(index)?
+        .partitions()
+        .map(|partition| CompletionPartition::open(partition, &field, weight_field.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+
+    // the completions keep everything before the last token of the prefix as it was typed
+    let (head, last_token) = {
+        let reader = &partitions
+            .first()
+            .expect("expected at least one partition of the index")
+            .reader;
+        let search_field = reader
+            .schema()
+            .search_field(&field)
+            .filter(|search_field| search_field.is_text())
+            .ok_or_else(|| anyhow!("'{field}' is not a text field of the index"))?;
+        let mut analyzer = reader
+            .searcher()
+            .index()
+            .tokenizer_for_field(search_field.field())?;
+
+        let mut stream = analyzer.token_stream(prefix);
+        let mut last = None;
+        while let Some(token) = stream.next() {
+            last = Some((token.offset_from, token.text.clone()));
+        }
+        match last {
+            Some((offset, token)) => (&prefix[..offset], token),
+            None => return Ok(TableIterator::new(vec![])),
+        }
+    };
+
+    // every term that starts with the last token, along with how many documents contain it,
+    // which is an upper bound on the number of visible rows that do
+    let mut candidates = HashMap::<String, u64>::default();
+    for partition in &partitions {
+        for segment in &partition.segments {
+            let mut terms = segment
+                .inverted_index
+                .terms()
+                .range()
+                .ge(last_token.as_bytes())
+                .into_stream()?;
+            while terms.advance() {
+                if !terms.key().starts_with(last_token.as_bytes()) {
+                    break;
+                }
+                if let Ok(term) = std::str::from_utf8(terms.key()) {
+                    *candidates.entry(term.to_string()).or_default() +=
+                        terms.value().doc_freq as u64;
+                }
+            }
+        }
+    }
+    let mut candidates = candidates.into_iter().collect::<Vec<_>>();
+    candidates.sort_by(|(a, a_freq), (b, b_freq)| b_freq.cmp(a_freq).then_with(|| a.cmp(b)));
+
+    let mut completions: Vec<(String, f64)> = Vec::new();
+    for (term, doc_freq) in candidates {
+        // without weights, no remaining term can outrank the completions we already have
+        if weight_field.is_none()
+            && completions.len() >= limit
+            && completions[limit - 1].1 >= doc_freq as f64
+        {
+            break;
+        }
+
+        let mut count = 0;
+        let mut weight: Option<f64> = None;
+        for partition in partitions.iter_mut() {
+            partition.visit_visible(&term, |value| {
+                count += 1;
+                if let Some(value) = value {
+                    weight = Some(weight.map_or(value, |weight| weight.max(value)));
+                }
+            })?;
+        }
+        if count == 0 {
+            continue;
+        }
+
+        let score = match &weight_field {
+            None => count as f64,
+            Some(_) => weight.unwrap_or(0.0),
+        };
+        completions.push((format!("{head}{term}"), score));
+        completions.sort_by(|(a, a_score), (b, b_score)| {
+            b_score.total_cmp(a_score).then_with(|| a.cmp(b))
+        });
+    }
+
+    completions.truncate(limit);
+    Ok(TableIterator::new(completions))
+}
+
+struct CompletionSegment {
+    reader: SegmentReader,
+    inverted_index: Arc<InvertedIndexReader>,
+    ctid: FFType,
+    weight: Option<(Column<u64>, ColumnType)>,
+}
+
+struct CompletionPartition {
+    reader: SearchIndexReader,
+    field: Option<Field>,
+    segments: Vec<CompletionSegment>,
+    vischeck: TSVisibilityChecker,
+
+    // we hold onto this b/c `vischeck` points to it
+    _heaprel: PgSearchRelation,
+}
+
+impl CompletionPartition {
+    fn open(
+        partition: PgSearchRelation,
+        field: &FieldName,
+        weight_field: Option<&FieldName>,
+    ) -> Result<Self> {
+        const WEIGHT_TYPES: [ColumnType; 3] = [ColumnType::I64, ColumnType::U64, ColumnType::F64];
+
+        let reader = SearchIndexReader::empty(&partition, MvccSatisfies::Snapshot)?;
+        let heaprel = partition
+            .heap_relation()
+            .expect("index should belong to a heap relation");
+        let vischeck = TSVisibilityChecker::with_rel_and_snap(heaprel.as_ptr(), unsafe {
+            pg_sys::GetActiveSnapshot()
+        });
+
+        if let Some(weight_field) = weight_field {
+            let is_numeric_fast = match reader.schema().search_field(weight_field) {
+                Some(search_field) => {
+                    search_field.is_numeric_fast()
+                        && matches!(
+                            search_field.field_type(),
+                            SearchFieldType::I64(_)
+                                | SearchFieldType::U64(_)
+                                | SearchFieldType::F64(_)
+                        )
+                }
+                None => false,
+            };
+            if !is_numeric_fast {
+                return Err(anyhow!(
+                    "'{weight_field}' is not a numeric fast field of the index"
+                ));
+            }
+        }
+
+        let field = reader
+            .schema()
+            .search_field(field)
+            .map(|search_field| search_field.field());
+        let mut segments = Vec::new();
+        if let Some(field) = field {
+            for segment_reader in reader.segment_readers() {
+                let fast_fields = segment_reader.fast_fields();
+                let weight = match weight_field {
+                    Some(weight_field) => fast_fields
+                        .u64_lenient_for_type(Some(&WEIGHT_TYPES), weight_field.as_ref())?,
+                    None => None,
+                };
+
+                segments.push(CompletionSegment {
+                    reader: segment_reader.clone(),
+                    inverted_index: segment_reader.inverted_index(field)?,
+                    ctid: FFType::new_ctid(fast_fields),
+                    weight,
+                });
+            }
+        }
+
+        Ok(Self {
+            reader,
+            field,
+            segments,
+            vischeck,
+            _heaprel: heaprel,
+        })
+    }
+
+    /// Call `visit` with the weight, if any, of each row visible to the current snapshot whose
+    /// document contains `term`
+    fn visit_visible(&mut self, term: &str, mut visit: impl FnMut(Option<f64>)) -> Result<()> {
+        let Some(field) = self.field else {
+            return Ok(());
+        };
+        let term = Term::from_field_text(field, term);
+
+        for segment in &self.segments {
+            let Some(mut postings) = segment
+                .inverted_index
+                .read_postings(&term, IndexRecordOption::Basic)?
+            else {
+                continue;
+            };
+
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if segment.is_alive(doc) {
+                    // the child documents of nested fields have no ctid, as they aren't rows
+                    let ctid = segment.ctid.as_u64(doc);
+                    if ctid.is_some_and(|ctid| self.vischeck.is_visible(ctid)) {
+                        visit(segment.weight(doc));
+                    }
+                }
+                doc = postings.advance();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CompletionSegment {
+    fn is_alive(&self, doc: DocId) -> bool {
+        self.reader
+            .alive_bitset()
+            .is_none_or(|alive_bitset| alive_bitset.is_alive(doc))
+    }
+
+    fn weight(&self, doc: DocId) -> Option<f64> {
+        let (column, column_type) = self.weight.as_ref()?;
+        let value = column.first(doc)?;
+        Some(match column_type {
+            ColumnType::I64 => i64::from_u64(value) as f64,
+            ColumnType::F64 => f64::from_u64(value),
+            _ => value as f64,
+        })
+    }
+}
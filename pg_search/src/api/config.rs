@@ -70,6 +70,16 @@ pub fn tokenizer(
     stemmer: default!(Option<String>, "NULL"),
     stopwords_language: default!(Option<String>, "NULL"),
     stopwords: default!(Option<Vec<String>>, "NULL"),
+    synonyms: default!(Option<Vec<String>>, "NULL"),
+    ascii_folding: default!(Option<bool>, "NULL"),
+    unicode_normalization: default!(Option<String>, "NULL"),
+    phonetic: default!(Option<String>, "NULL"),
+    phonetic_mode: default!(Option<String>, "NULL"),
+    shingle: default!(Option<JsonB>, "NULL"),
+    edge_ngram: default!(Option<JsonB>, "NULL"),
+    char_filters: default!(Option<JsonB>, "NULL"),
+    word_delimiter: default!(Option<JsonB>, "NULL"),
+    decompounder: default!(Option<JsonB>, "NULL"),
 ) -> JsonB {
     let mut config = Map::new();
 
@@ -86,6 +96,26 @@ pub fn tokenizer(
             Value::Array(v.into_iter().map(Value::String).collect()),
         )
     });
+    synonyms.map(|v| {
+        config.insert(
+            "synonyms".to_string(),
+            Value::Array(v.into_iter().map(Value::String).collect()),
+        )
+    });
+    ascii_folding.map(|v| config.insert("ascii_folding".to_string(), Value::Bool(v)));
+    unicode_normalization
+        .map(|v| config.insert("unicode_normalization".to_string(), Value::String(v)));
+    if phonetic.is_some() || phonetic_mode.is_some() {
+        let mut options = Map::new();
+        phonetic.map(|v| options.insert("encoder".to_string(), Value::String(v)));
+        phonetic_mode.map(|v| options.insert("mode".to_string(), Value::String(v)));
+        config.insert("phonetic".to_string(), Value::Object(options));
+    }
+    shingle.map(|v| config.insert("shingle".to_string(), v.0));
+    edge_ngram.map(|v| config.insert("edge_ngram".to_string(), v.0));
+    char_filters.map(|v| config.insert("char_filters".to_string(), v.0));
+    word_delimiter.map(|v| config.insert("word_delimiter".to_string(), v.0));
+    decompounder.map(|v| config.insert("decompounder".to_string(), v.0));
     // Options for type = ngram
     min_gram.map(|v| config.insert("min_gram".to_string(), Value::Number(v.into())));
     max_gram.map(|v| config.insert("max_gram".to_string(), Value::Number(v.into())));
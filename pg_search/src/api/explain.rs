@@ -0,0 +1,124 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::aggregate::vischeck::TSVisibilityChecker;
+use crate::index::fast_fields_helper::FFType;
+use crate::index::mvcc::MvccSatisfies;
+use crate::index::reader::index::SearchIndexReader;
+use crate::postgres::index::IndexKind;
+use crate::postgres::rel::PgSearchRelation;
+use crate::postgres::types::TantivyValue;
+use crate::query::{value_to_term, SearchQueryInput};
+use anyhow::{anyhow, Result};
+use pgrx::{pg_extern, pg_sys, AnyElement, JsonB, PgRelation};
+use tantivy::schema::IndexRecordOption;
+use tantivy::{DocAddress, DocSet, SegmentOrdinal, TERMINATED};
+
+/// Explain how the row of `index` whose key is `key_value` is scored by `query`, as a tree of
+/// the term frequencies, inverse document frequencies, field norms and boosts, and how the
+/// clauses of compound queries combine them.
+///
+/// Returns `NULL` if the row doesn't match the query.
+#[pg_extern(stable, parallel_safe)]
+pub fn explain_score(
+    index: PgRelation,
+    query: SearchQueryInput,
+    key_value: AnyElement,
+) -> Result<Option<JsonB>> {
+    let key_value = unsafe { TantivyValue::try_from_anyelement(key_value)? }.0;
+
+    // # Safety
+    //
+    // Lock the index relation until the end of this function so it is not dropped or
+    // altered while we are reading it.
+    let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+
+    for partition in IndexKind::for_index(index)?.partitions() {
+        let reader =
+            SearchIndexReader::open(&partition, query.clone(), true, MvccSatisfies::Snapshot)?;
+        let Some(doc_address) = find_visible_doc(&partition, &reader, &key_value)? else {
+            continue;
+        };
+
+        // check that the query matches the row first, so that any error from explaining it is
+        // a real error rather than the query not matching
+        let segment_reader = reader.searcher().segment_reader(doc_address.segment_ord);
+        let weight = reader.weight();
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        if scorer.seek(doc_address.doc_id) != doc_address.doc_id {
+            return Ok(None);
+        }
+
+        let explanation = weight.explain(segment_reader, doc_address.doc_id)?;
+        return Ok(Some(JsonB(serde_json::to_value(explanation)?)));
+    }
+
+    Err(anyhow!("no row with key {}", TantivyValue(key_value)))
+}
+
+/// The address of the document for the row whose key is `key_value` and that's visible to the
+/// current snapshot, if the row is in this index
+fn find_visible_doc(
+    index: &PgSearchRelation,
+    reader: &SearchIndexReader,
+    key_value: &tantivy::schema::OwnedValue,
+) -> Result<Option<DocAddress>> {
+    let schema = reader.schema();
+    let key_field = schema
+        .search_field(schema.key_field_name())
+        .expect("the key field should be in the index");
+    let term = value_to_term(
+        key_field.field(),
+        key_value,
+        key_field.field_entry().field_type(),
+        None,
+        false,
+    )
+    .map_err(|e| anyhow!("{e}"))?;
+
+    let heaprel = index
+        .heap_relation()
+        .expect("index should belong to a heap relation");
+    let mut vischeck = TSVisibilityChecker::with_rel_and_snap(heaprel.as_ptr(), unsafe {
+        pg_sys::GetActiveSnapshot()
+    });
+
+    for (segment_ord, segment_reader) in reader.segment_readers().iter().enumerate() {
+        let Some(mut postings) = segment_reader
+            .inverted_index(key_field.field())?
+            .read_postings(&term, IndexRecordOption::Basic)?
+        else {
+            continue;
+        };
+
+        let ctid_ff = FFType::new_ctid(segment_reader.fast_fields());
+        let mut doc = postings.doc();
+        while doc != TERMINATED {
+            let is_alive = segment_reader
+                .alive_bitset()
+                .is_none_or(|alive_bitset| alive_bitset.is_alive(doc));
+            // the child documents of nested fields have no ctid, as they aren't rows
+            if let Some(ctid) = ctid_ff.as_u64(doc) {
+                if is_alive && vischeck.is_visible(ctid) {
+                    return Ok(Some(DocAddress::new(segment_ord as SegmentOrdinal, doc)));
+                }
+            }
+            doc = postings.advance();
+        }
+    }
+    Ok(None)
+}
@@ -19,9 +19,14 @@
 #![allow(for_loops_over_fallibles)]
 
 pub mod aggregate;
+pub mod analyzer;
 pub mod builder_fns;
+pub mod complete;
 pub mod config;
+pub mod explain;
 pub mod operator;
+pub mod percolate;
+pub mod suggest;
 pub mod tokenize;
 
 use pgrx::{
@@ -163,6 +163,12 @@ pub fn search_with_query_input(
             SearchQueryInput::from_datum(query_datum, query_datum.is_null())
                 .expect("the query argument cannot be NULL")
         };
+        search_query_input
+            .check_no_search_after()
+            .unwrap_or_else(|err| pgrx::error!("{err}"));
+        search_query_input
+            .check_no_rescore()
+            .unwrap_or_else(|err| pgrx::error!("{err}"));
 
         // optimize the case where the user asked for literally every matching document to avoid
         // making a copy of every primary key in ram
@@ -0,0 +1,179 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Percolation matches a document against the queries stored in an index, rather than a query
+//! against the documents.  Queries are stored in columns of type `paradedb.searchqueryinput`,
+//! which are indexed whole as fast fields.
+
+use crate::api::aggregate::vischeck::TSVisibilityChecker;
+use crate::api::FieldName;
+use crate::index::fast_fields_helper::FFType;
+use crate::index::mvcc::MvccSatisfies;
+use crate::index::reader::index::SearchIndexReader;
+use crate::index::setup_tokenizers;
+use crate::postgres::index::IndexKind;
+use crate::postgres::rel::PgSearchRelation;
+use crate::query::SearchQueryInput;
+use crate::schema::SearchIndexSchema;
+use anyhow::{anyhow, Result};
+use pgrx::{iter::TableIterator, name, pg_extern, pg_sys, JsonB, PgRelation};
+use serde_json::Value;
+use tantivy::query::QueryParser;
+use tantivy::{
+    Index, IndexSettings, ReloadPolicy, Searcher, SingleSegmentIndexWriter, TantivyDocument,
+};
+
+/// The smallest memory budget tantivy accepts, which is plenty for a single document
+const MEMORY_BUDGET: usize = 15_000_000;
+
+/// Return the key of each row of `index` with a stored query that matches `document`, along with
+/// the query.  `document` is a JSON object of column names to values, and is analyzed with the
+/// same tokenizers as the index's fields.
+#[pg_extern]
+pub fn percolate(
+    index: PgRelation,
+    document: JsonB,
+) -> Result<TableIterator<'static, (name!(key, String), name!(query, SearchQueryInput))>> {
+    let Value::Object(document) = document.0 else {
+        return Err(anyhow!("the document to percolate must be a JSON object"));
+    };
+
+    // # Safety
+    //
+    // Lock the index relation until the end of this function so it is not dropped or
+    // altered while we are reading it.
+    let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+
+    let mut matches = Vec::new();
+    for partition in IndexKind::for_index(index)?.partitions() {
+        let reader = SearchIndexReader::empty(&partition, MvccSatisfies::Snapshot)?;
+        let schema = reader.schema();
+        let query_fields = schema
+            .categorized_fields()
+            .iter()
+            .filter(|(field, _)| field.is_query())
+            .map(|(field, _)| field.field_name().clone())
+            .collect::<Vec<FieldName>>();
+        if query_fields.is_empty() {
+            continue;
+        }
+
+        let searcher = document_searcher(&partition, schema, document.clone())?;
+        let mut parser = QueryParser::for_index(
+            searcher.index(),
+            schema.fields().map(|(field, _)| field).collect::<Vec<_>>(),
+        );
+
+        let heaprel = partition
+            .heap_relation()
+            .expect("index should belong to a heap relation");
+        let mut vischeck = TSVisibilityChecker::with_rel_and_snap(heaprel.as_ptr(), unsafe {
+            pg_sys::GetActiveSnapshot()
+        });
+        let key_field_name = schema.key_field_name();
+
+        let mut serialized = String::new();
+        for segment_reader in reader.segment_readers() {
+            let fast_fields = segment_reader.fast_fields();
+            let ctid_ff = FFType::new_ctid(fast_fields);
+            let key_ff = FFType::new(fast_fields, key_field_name.as_ref());
+            let query_ffs = query_fields
+                .iter()
+                .map(|field| FFType::new(fast_fields, field.as_ref()))
+                .collect::<Vec<_>>();
+
+            for doc in segment_reader.doc_ids_alive() {
+                // the child documents of nested fields aren't rows
+                let Some(ctid) = ctid_ff.as_u64(doc) else {
+                    continue;
+                };
+                if !vischeck.is_visible(ctid) {
+                    continue;
+                }
+
+                for query_ff in &query_ffs {
+                    if query_ff.string(doc, &mut serialized).is_none() {
+                        // the row's query is NULL
+                        continue;
+                    }
+
+                    let query: SearchQueryInput = serde_json::from_str(&serialized)?;
+                    let tantivy_query = query
+                        .clone()
+                        .into_tantivy_query(
+                            schema,
+                            &mut parser,
+                            &searcher,
+                            partition.oid(),
+                            partition.rel_oid(),
+                        )
+                        .map_err(|e| {
+                            anyhow!("could not percolate stored query {serialized}: {e}")
+                        })?;
+
+                    if tantivy_query.count(&searcher)? > 0 {
+                        matches.push((key_ff.value(doc).to_string(), query));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(TableIterator::new(matches))
+}
+
+/// A [`Searcher`] over an in-memory index with the same schema and tokenizers as `index`, that
+/// holds only `document`
+fn document_searcher(
+    index: &PgSearchRelation,
+    schema: &SearchIndexSchema,
+    mut document: serde_json::Map<String, Value>,
+) -> Result<Searcher> {
+    // fields that take their values from another column, like differently tokenized copies of
+    // a text column, are indexed from the value of that column
+    for (column, fields) in schema.alias_lookup() {
+        if let Some(value) = document.get(&column).cloned() {
+            for field in fields {
+                document.insert(field.field_name().to_string(), value.clone());
+            }
+        }
+    }
+    document.retain(|_, value| !value.is_null());
+
+    let settings = IndexSettings {
+        docstore_compress_dedicated_thread: false,
+        ..IndexSettings::default()
+    };
+    let mut memory_index = Index::builder()
+        .schema(schema.tantivy_schema().clone())
+        .settings(settings)
+        .create_in_ram()?;
+    setup_tokenizers(index, &mut memory_index)?;
+
+    let mut writer = SingleSegmentIndexWriter::new(memory_index, MEMORY_BUDGET)?;
+    writer.add_document(TantivyDocument::from_json_object(
+        schema.tantivy_schema(),
+        document,
+    )?)?;
+    let memory_index = writer.finalize()?;
+
+    let reader = memory_index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    Ok(reader.searcher())
+}
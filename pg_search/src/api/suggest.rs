@@ -0,0 +1,235 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Spelling suggestions, drawn from the terms that a text field has indexed
+
+use crate::api::{FieldName, HashMap};
+use crate::index::mvcc::MvccSatisfies;
+use crate::index::reader::index::SearchIndexReader;
+use crate::postgres::index::IndexKind;
+use crate::postgres::rel::PgSearchRelation;
+use anyhow::{anyhow, Result};
+use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA};
+use pgrx::{default, iter::TableIterator, name, pg_extern, pg_sys, PgRelation};
+use tantivy::tokenizer::Token;
+use tantivy_fst::Automaton;
+
+/// The most edits a suggestion can be from its token, as with fuzzy term queries
+const MAX_EDITS: i32 = 2;
+
+struct Suggestion {
+    term: String,
+    distance: u8,
+    doc_freq: u64,
+}
+
+/// A Levenshtein automaton that a term dictionary can be searched with, so that only the terms
+/// it can match are visited
+struct LevenshteinDfa<'a>(&'a DFA);
+
+impl Automaton for LevenshteinDfa<'_> {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(self.0.distance(*state), Distance::Exact(_))
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != levenshtein_automata::SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
+
+/// Suggest corrections for each token of `text`, as the terms of `field` within `max_edits`
+/// edits of it.  The closest and most common terms are suggested first, up to `limit` per token.
+///
+/// The first row, whose `token` is `NULL`, is a suggestion for the whole of `text`, with each
+/// misspelled token replaced by its best correction and everything else left as it was typed.
+///
+/// A term's `doc_freq` comes from the statistics of the term dictionaries, so it also counts
+/// deleted documents that haven't been merged away yet, rows that aren't visible to the
+/// current transaction, and the elements of nested fields, each of which is a document of its own.
+#[allow(clippy::type_complexity)]
+#[pg_extern(stable, parallel_safe)]
+pub fn suggest(
+    index: PgRelation,
+    field: FieldName,
+    text: &str,
+    max_edits: default!(i32, 2),
+    limit: default!(i32, 5),
+) -> Result<
+    TableIterator<
+        'static,
+        (
+            name!(token, Option<String>),
+            name!(suggestion, String),
+            name!(distance, i32),
+            name!(doc_freq, Option<i64>),
+        ),
+    >,
+> {
+    if !(0..=MAX_EDITS).contains(&max_edits) {
+        return Err(anyhow!("max_edits must be between 0 and {MAX_EDITS}"));
+    }
+    let limit = usize::try_from(limit).map_err(|_| anyhow!("limit must not be negative"))?;
+
+    // # Safety
+    //
+    // Lock the index relation until the end of this function so it is not dropped or
+    // altered while we are reading it.
+    let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+    let partitions = IndexKind::for_index(index)?
+        .partitions()
+        .collect::<Vec<_>>();
+
+    let tokens = {
+        let partition = partitions
+            .first()
+            .expect("expected at least one partition of the index");
+        let reader = SearchIndexReader::empty(partition, MvccSatisfies::Snapshot)?;
+        let search_field = reader
+            .schema()
+            .search_field(&field)
+            .filter(|search_field| search_field.is_text())
+            .ok_or_else(|| anyhow!("'{field}' is not a text field of the index"))?;
+        let mut analyzer = reader
+            .searcher()
+            .index()
+            .tokenizer_for_field(search_field.field())?;
+
+        let mut tokens = Vec::new();
+        let mut stream = analyzer.token_stream(text);
+        while let Some(token) = stream.next() {
+            tokens.push(token.clone());
+        }
+        tokens
+    };
+
+    let builder = LevenshteinAutomatonBuilder::new(max_edits as u8, true);
+    let automata = tokens
+        .iter()
+        .map(|token| builder.build_dfa(&token.text))
+        .collect::<Vec<_>>();
+
+    // the distance and document frequency of every term that's close enough to each token
+    let mut candidates = vec![HashMap::<String, (u8, u64)>::default(); tokens.len()];
+    for partition in &partitions {
+        let reader = SearchIndexReader::empty(partition, MvccSatisfies::Snapshot)?;
+        let Some(search_field) = reader.schema().search_field(&field) else {
+            continue;
+        };
+
+        for segment_reader in reader.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(search_field.field())?;
+            for (automaton, candidates) in automata.iter().zip(candidates.iter_mut()) {
+                let mut terms = inverted_index
+                    .terms()
+                    .search(LevenshteinDfa(automaton))
+                    .into_stream()?;
+                while terms.advance() {
+                    let Distance::Exact(distance) = automaton.eval(terms.key()) else {
+                        continue;
+                    };
+                    let Ok(term) = std::str::from_utf8(terms.key()) else {
+                        continue;
+                    };
+                    candidates
+                        .entry(term.to_string())
+                        .or_insert((distance, 0))
+                        .1 += terms.value().doc_freq as u64;
+                }
+            }
+        }
+    }
+
+    let suggestions = candidates
+        .into_iter()
+        .map(|candidates| {
+            let mut suggestions = candidates
+                .into_iter()
+                .map(|(term, (distance, doc_freq))| Suggestion {
+                    term,
+                    distance,
+                    doc_freq,
+                })
+                .collect::<Vec<_>>();
+            suggestions.sort_by(|a, b| {
+                a.distance
+                    .cmp(&b.distance)
+                    .then(b.doc_freq.cmp(&a.doc_freq))
+                    .then_with(|| a.term.cmp(&b.term))
+            });
+            suggestions
+        })
+        .collect::<Vec<_>>();
+
+    let mut rows = Vec::new();
+    if !tokens.is_empty() {
+        let (phrase, distance) = suggest_phrase(text, &tokens, &suggestions);
+        rows.push((None, phrase, distance as i32, None));
+    }
+    for (token, suggestions) in tokens.into_iter().zip(suggestions) {
+        for suggestion in suggestions.into_iter().take(limit) {
+            rows.push((
+                Some(token.text.clone()),
+                suggestion.term,
+                suggestion.distance as i32,
+                Some(suggestion.doc_freq as i64),
+            ));
+        }
+    }
+
+    Ok(TableIterator::new(rows))
+}
+
+/// Replace each of `tokens` in `text` with its best suggestion, keeping what was typed for
+/// those that have none or are already spelled right, and return the suggested phrase with its
+/// total number of edits
+fn suggest_phrase(text: &str, tokens: &[Token], suggestions: &[Vec<Suggestion>]) -> (String, u32) {
+    let mut phrase = String::with_capacity(text.len());
+    let mut distance = 0;
+    let mut end = 0;
+    for (token, suggestions) in tokens.iter().zip(suggestions) {
+        // tokens that overlap one already replaced, like the parts of a compound word, are
+        // covered by its suggestion
+        if token.offset_from < end {
+            continue;
+        }
+        let Some(best) = suggestions.first().filter(|best| best.distance > 0) else {
+            continue;
+        };
+        let (Some(before), true) = (
+            text.get(end..token.offset_from),
+            text.is_char_boundary(token.offset_to),
+        ) else {
+            continue;
+        };
+        phrase.push_str(before);
+        phrase.push_str(&best.term);
+        distance += best.distance as u32;
+        end = token.offset_to;
+    }
+    phrase.push_str(&text[end..]);
+    (phrase, distance)
+}
@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::analyzer::{qualify_word_list_table, resolve_analyzer};
 use pgrx::{iter::TableIterator, name, pg_extern, JsonB};
 use strum::VariantNames;
 use tokenizers::SearchTokenizer;
@@ -24,11 +25,13 @@ use tokenizers::SearchTokenizer;
 pub fn tokenize(
     tokenizer_setting: JsonB,
     input_text: &str,
-) -> TableIterator<(name!(token, String), name!(position, i32))> {
-    let tokenizer_setting = serde_json::to_value(tokenizer_setting)
+) -> anyhow::Result<TableIterator<(name!(token, String), name!(position, i32))>> {
+    let mut tokenizer_setting = serde_json::to_value(tokenizer_setting)
         .expect("invalid tokenizer setting, expected paradedb.tokenizer()");
+    qualify_word_list_table(&mut tokenizer_setting)?;
     let tokenizer = SearchTokenizer::from_json_value(&tokenizer_setting)
         .expect("invalid tokenizer setting, expected paradedb.tokenizer()");
+    let tokenizer = resolve_analyzer(tokenizer)?;
 
     let mut analyzer = tokenizer
         .to_tantivy_tokenizer()
@@ -42,7 +45,7 @@ pub fn tokenize(
         result.push((token.text.to_string(), token.position as i32));
     }
 
-    TableIterator::new(result)
+    Ok(TableIterator::new(result))
 }
 
 /// List all available tokenizers names in ParadeDB
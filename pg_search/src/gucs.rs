@@ -35,6 +35,11 @@ static ENABLE_CUSTOM_SCAN_WITHOUT_OPERATOR: GucSetting<bool> = GucSetting::<bool
 /// The default is `true`.
 static ENABLE_FILTER_PUSHDOWN: GucSetting<bool> = GucSetting::<bool>::new(true);
 
+/// Allows the user to toggle the use of our "ParadeDB Aggregate Scan", which answers `COUNT(*)`,
+/// `SUM()`, `AVG()`, `MIN()`, and `MAX()` over `@@@` queries, optionally with a `GROUP BY`, directly
+/// from the index.  The default is `false`.
+static ENABLE_AGGREGATE_CUSTOM_SCAN: GucSetting<bool> = GucSetting::<bool>::new(false);
+
 /// Allows the user to enable or disable the FastFieldsExecState executor. Default is `true`.
 static ENABLE_FAST_FIELD_EXEC: GucSetting<bool> = GucSetting::<bool>::new(true);
 
@@ -98,6 +103,15 @@ pub fn init() {
         GucFlags::default(),
     );
 
+    GucRegistry::define_bool_guc(
+        c"paradedb.enable_aggregate_custom_scan",
+        c"Enable ParadeDB's aggregate custom scan",
+        c"Enable ParadeDB's custom scan for aggregate queries, which computes COUNT/SUM/AVG/MIN/MAX over fast fields, optionally grouped by a fast field, directly from the index",
+        &ENABLE_AGGREGATE_CUSTOM_SCAN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_bool_guc(
         c"paradedb.enable_fast_field_exec",
         c"Enable StringFastFieldsExecState and NumericFastFieldsExecState executor",
@@ -153,6 +167,10 @@ pub fn enable_filter_pushdown() -> bool {
     ENABLE_FILTER_PUSHDOWN.get()
 }
 
+pub fn enable_aggregate_custom_scan() -> bool {
+    ENABLE_AGGREGATE_CUSTOM_SCAN.get()
+}
+
 pub fn is_fast_field_exec_enabled() -> bool {
     ENABLE_FAST_FIELD_EXEC.get()
 }
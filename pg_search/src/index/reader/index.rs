@@ -20,10 +20,15 @@ use crate::api::HashMap;
 use crate::index::fast_fields_helper::FFType;
 use crate::index::mvcc::MvccSatisfies;
 use crate::index::reader::index::scorer_iter::DeferredScorer;
+use crate::index::reader::named_queries::NamedQueryMatcher;
+use crate::index::reader::order_by::{CompoundTopDocs, OrderByFeature, SortKey};
+use crate::index::reader::snippet::FragmentGenerator;
 use crate::index::setup_tokenizers;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::storage::buffer::PinnedBuffer;
 use crate::postgres::storage::metadata::MetaPage;
+use crate::query::nested::ParentsQuery;
+use crate::query::rescore::{Rescorer, DEFAULT_RESCORE_WINDOW_SIZE};
 use crate::query::SearchQueryInput;
 use crate::schema::SearchIndexSchema;
 use anyhow::Result;
@@ -96,6 +101,11 @@ pub enum SearchResults {
         std::vec::IntoIter<(TweakedScore, DocAddress)>,
     ),
     TopNByField(Searcher, FastFieldCache, std::vec::IntoIter<DocAddress>),
+    TopNByCompound(
+        Searcher,
+        FastFieldCache,
+        std::vec::IntoIter<(Score, DocAddress)>,
+    ),
     MultiSegment(
         Searcher,
         Option<FFType>,
@@ -126,69 +136,71 @@ impl Iterator for SearchResults {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (searcher, ff_lookup, (score, doc_address)) = match self {
-            SearchResults::None => return None,
-            SearchResults::TopNByScore(searcher, ff_lookup, iter) => {
-                (searcher, ff_lookup, iter.next()?)
-            }
-            SearchResults::TopNByTweakedScore(searcher, ff_lookup, iter) => {
-                let (score, doc_id) = iter.next()?;
-                (searcher, ff_lookup, (score.score, doc_id))
-            }
-            SearchResults::TopNByField(searcher, ff_lookup, iter) => {
-                let doc_id = iter.next()?;
-                (searcher, ff_lookup, (1.0, doc_id))
-            }
-            SearchResults::MultiSegment(searcher, fftype, iters, offset) => loop {
-                let last = iters.last_mut()?;
-                match last.next() {
-                    Some((score, doc_address)) => {
-                        if *offset > 0 {
-                            *offset -= 1;
+        loop {
+            let (searcher, ff_lookup, (score, doc_address)) = match self {
+                SearchResults::None => return None,
+                SearchResults::TopNByScore(searcher, ff_lookup, iter) => {
+                    (searcher, ff_lookup, iter.next()?)
+                }
+                SearchResults::TopNByTweakedScore(searcher, ff_lookup, iter) => {
+                    let (score, doc_id) = iter.next()?;
+                    (searcher, ff_lookup, (score.score, doc_id))
+                }
+                SearchResults::TopNByField(searcher, ff_lookup, iter) => {
+                    let doc_id = iter.next()?;
+                    (searcher, ff_lookup, (1.0, doc_id))
+                }
+                SearchResults::TopNByCompound(searcher, ff_lookup, iter) => {
+                    (searcher, ff_lookup, iter.next()?)
+                }
+                SearchResults::MultiSegment(searcher, fftype, iters, offset) => loop {
+                    let last = iters.last_mut()?;
+                    match last.next() {
+                        Some((score, doc_address)) => {
+                            let ctid_ff = fftype.get_or_insert_with(|| {
+                                FFType::new_ctid(
+                                    searcher
+                                        .segment_reader(doc_address.segment_ord)
+                                        .fast_fields(),
+                                )
+                            });
+                            // the child documents of nested fields have no ctid, as they aren't rows
+                            let Some(ctid) = ctid_ff.as_u64(doc_address.doc_id) else {
+                                continue;
+                            };
+
+                            if *offset > 0 {
+                                *offset -= 1;
+                                continue;
+                            }
+
+                            let scored = SearchIndexScore { ctid, bm25: score };
+
+                            return Some((scored, doc_address));
+                        }
+                        None => {
+                            // last iterator is empty, so pop it off, clear the fast field type cache,
+                            // and loop back around to get the next one
+                            iters.pop();
+                            *fftype = None;
                             continue;
                         }
-
-                        let ctid_ff = fftype.get_or_insert_with(|| {
-                            FFType::new_ctid(
-                                searcher
-                                    .segment_reader(doc_address.segment_ord)
-                                    .fast_fields(),
-                            )
-                        });
-                        let scored = SearchIndexScore {
-                            ctid: ctid_ff
-                                .as_u64(doc_address.doc_id)
-                                .expect("ctid should be present"),
-                            bm25: score,
-                        };
-
-                        return Some((scored, doc_address));
-                    }
-                    None => {
-                        // last iterator is empty, so pop it off, clear the fast field type cache,
-                        // and loop back around to get the next one
-                        iters.pop();
-                        *fftype = None;
-                        continue;
                     }
-                }
-            },
-        };
-
-        let ctid_ff = ff_lookup.entry(doc_address.segment_ord).or_insert_with(|| {
-            FFType::new_ctid(
-                searcher
-                    .segment_reader(doc_address.segment_ord)
-                    .fast_fields(),
-            )
-        });
-        let scored = SearchIndexScore {
-            ctid: ctid_ff
-                .as_u64(doc_address.doc_id)
-                .expect("ctid should be present"),
-            bm25: score,
-        };
-        Some((scored, doc_address))
+                },
+            };
+
+            let ctid_ff = ff_lookup.entry(doc_address.segment_ord).or_insert_with(|| {
+                FFType::new_ctid(
+                    searcher
+                        .segment_reader(doc_address.segment_ord)
+                        .fast_fields(),
+                )
+            });
+            let Some(ctid) = ctid_ff.as_u64(doc_address.doc_id) else {
+                continue;
+            };
+            return Some((SearchIndexScore { ctid, bm25: score }, doc_address));
+        }
     }
 
     #[inline]
@@ -198,6 +210,7 @@ impl Iterator for SearchResults {
             SearchResults::TopNByScore(_, _, iter) => iter.size_hint(),
             SearchResults::TopNByTweakedScore(_, _, iter) => iter.size_hint(),
             SearchResults::TopNByField(_, _, iter) => iter.size_hint(),
+            SearchResults::TopNByCompound(_, _, iter) => iter.size_hint(),
             SearchResults::MultiSegment(_, _, iters, offset) => {
                 let hint = iters
                     .first()
@@ -218,6 +231,7 @@ impl Iterator for SearchResults {
             SearchResults::TopNByScore(_, _, iter) => iter.count(),
             SearchResults::TopNByTweakedScore(_, _, iter) => iter.count(),
             SearchResults::TopNByField(_, _, iter) => iter.count(),
+            SearchResults::TopNByCompound(_, _, iter) => iter.count(),
             SearchResults::MultiSegment(_, _, iters, offset) => {
                 let total: usize = iters.into_iter().map(|iter| iter.count()).sum();
                 total.saturating_sub(offset)
@@ -294,13 +308,15 @@ impl SearchIndexReader {
             .try_into()?;
         let searcher = reader.searcher();
 
+        search_query_input.rescore()?;
+        search_query_input.search_after()?;
         let need_scores = need_scores || search_query_input.need_scores();
         let query = {
             let mut parser = QueryParser::for_index(
                 &index,
                 schema.fields().map(|(field, _)| field).collect::<Vec<_>>(),
             );
-            search_query_input
+            let query = search_query_input
                 .into_tantivy_query(
                     &schema,
                     &mut parser,
@@ -308,7 +324,8 @@ impl SearchIndexReader {
                     index_relation.oid(),
                     index_relation.rel_oid(),
                 )
-                .expect("must be able to parse query")
+                .expect("must be able to parse query");
+            ParentsQuery::wrap(&schema, query)
         };
 
         Ok(Self {
@@ -363,7 +380,7 @@ impl SearchIndexReader {
                 .map(|(field, _)| field)
                 .collect::<Vec<_>>(),
         );
-        search_query_input
+        let query = search_query_input
             .clone()
             .into_tantivy_query(
                 &self.schema,
@@ -372,7 +389,8 @@ impl SearchIndexReader {
                 self.index_rel.oid(),
                 self.index_rel.rel_oid(),
             )
-            .expect("must be able to parse query")
+            .expect("must be able to parse query");
+        ParentsQuery::wrap(&self.schema, query)
     }
 
     pub fn get_doc(&self, doc_address: DocAddress) -> tantivy::Result<TantivyDocument> {
@@ -428,6 +446,67 @@ impl SearchIndexReader {
         }
     }
 
+    /// A generator for `paradedb.snippets()`.  If `field_name` has a JSON path, only the query
+    /// terms at that path are highlighted.
+    pub fn fragment_generator(
+        &self,
+        field_name: &FieldName,
+        query: SearchQueryInput,
+        fragment_size: usize,
+    ) -> FragmentGenerator {
+        let search_field = self
+            .schema
+            .search_field(field_name.root())
+            .unwrap_or_else(|| panic!("fragment_generator: field {field_name} should exist"));
+        if search_field.is_text() || search_field.is_json() {
+            FragmentGenerator::create(
+                &self.searcher,
+                &self.make_query(query),
+                search_field.field(),
+                field_name.path().as_deref(),
+                fragment_size,
+            )
+            .unwrap_or_else(|err| {
+                panic!("failed to create fragment generator for field: {field_name}... {err}")
+            })
+        } else {
+            panic!("failed to create fragment generator for field: {field_name}... can only highlight text fields")
+        }
+    }
+
+    /// A matcher for `paradedb.matched_queries()`, for the named clauses of `query`
+    pub fn named_query_matcher(&self, query: &SearchQueryInput) -> NamedQueryMatcher {
+        let named_queries = query
+            .named_queries()
+            .into_iter()
+            .map(|(name, query)| (name.to_string(), self.make_query(query.clone())))
+            .collect();
+        NamedQueryMatcher::create(&self.searcher, named_queries)
+            .unwrap_or_else(|err| pgrx::error!("failed to create named query matcher: {err}"))
+    }
+
+    /// A rescorer for the "top N" results of `query`, if it has a `Rescore` clause
+    pub fn rescorer(&self, query: &SearchQueryInput) -> Result<Option<Rescorer>> {
+        let Some(SearchQueryInput::Rescore {
+            rescore_query,
+            window_size,
+            query_weight,
+            rescore_weight,
+            ..
+        }) = query.rescore()?
+        else {
+            return Ok(None);
+        };
+        let rescorer = Rescorer::create(
+            &self.searcher,
+            self.make_query(rescore_query.as_ref().clone()),
+            window_size.unwrap_or(DEFAULT_RESCORE_WINDOW_SIZE) as usize,
+            query_weight.unwrap_or(1.0),
+            rescore_weight.unwrap_or(1.0),
+        )?;
+        Ok(Some(rescorer))
+    }
+
     /// Search the Tantivy index for matching documents.
     ///
     /// The order of returned docs is unspecified.
@@ -491,15 +570,19 @@ impl SearchIndexReader {
     ///
     /// It has no understanding of Postgres MVCC visibility.  It is the caller's responsibility to
     /// handle that, if it's necessary.
+    ///
+    /// If `aggregations` is provided, it's collected over every matching document in the same pass
+    /// as the "top N", and its fruit is returned alongside the results.
     #[allow(clippy::too_many_arguments)]
-    pub fn search_top_n_in_segments(
+    pub fn search_top_n_in_segments<A: Collector>(
         &self,
         segment_ids: impl Iterator<Item = SegmentId>,
         sort_field: Option<FieldName>,
         sortdir: SortDirection,
         n: usize,
         offset: usize,
-    ) -> SearchResults {
+        aggregations: Option<A>,
+    ) -> (SearchResults, Option<A::Fruit>) {
         if let Some(sort_field) = sort_field {
             let field = self
                 .schema
@@ -512,32 +595,94 @@ impl SearchIndexReader {
                     sortdir,
                     n,
                     offset,
+                    aggregations,
+                ),
+                _ => self.top_by_field_in_segments(
+                    segment_ids,
+                    sort_field,
+                    sortdir,
+                    n,
+                    offset,
+                    aggregations,
                 ),
-                _ => self.top_by_field_in_segments(segment_ids, sort_field, sortdir, n, offset),
             }
         } else {
-            self.top_by_score_in_segments(segment_ids, sortdir, n, offset)
+            self.top_by_score_in_segments(segment_ids, sortdir, n, offset, aggregations)
         }
     }
 
-    /// Search the Tantivy index for the "top N" matching documents (ordered by a field) in the given segments.
+    /// Search the Tantivy index for the "top N" matching documents in the given segments, ordered by
+    /// a compound sort key.
     ///
-    /// The documents are returned in field order.  Largest first if `sortdir` is [`SortDirection::Desc`],
-    /// or smallest first if it's [`SortDirection::Asc`].
+    /// Each key of `orderby` is either the score or a numeric or string fast field, and each is
+    /// sorted in its own direction.  Later keys break ties in the earlier ones.
     ///
     /// It has no understanding of Postgres MVCC visibility.  It is the caller's responsibility to
     /// handle that, if it's necessary.
-    fn top_by_field_in_segments(
+    ///
+    /// If `aggregations` is provided, it's collected over every matching document in the same pass
+    /// as the "top N", and its fruit is returned alongside the results.
+    pub fn search_top_n_compound_in_segments<A: Collector>(
         &self,
         segment_ids: impl Iterator<Item = SegmentId>,
-        sort_field: impl AsRef<str> + Display,
-        sortdir: SortDirection,
+        orderby: &[(OrderByFeature, SortDirection)],
         n: usize,
         offset: usize,
-    ) -> SearchResults {
-        let collector = TopDocs::with_limit(n)
-            .and_offset(offset)
-            .order_by_u64_field(&sort_field, sortdir.into());
+        aggregations: Option<A>,
+    ) -> Result<(SearchResults, Option<A::Fruit>)> {
+        let orderby = orderby
+            .iter()
+            .map(|(feature, sortdir)| {
+                let sort_key = match feature {
+                    OrderByFeature::Score => SortKey::Score,
+                    OrderByFeature::Field(sort_field) => {
+                        let Some(field) = self.schema.search_field(sort_field) else {
+                            anyhow::bail!("sort field '{sort_field}' is not in the index");
+                        };
+                        match field.field_entry().field_type().value_type() {
+                            tantivy::schema::Type::Str => SortKey::Str(sort_field.clone()),
+                            _ => SortKey::Numeric(sort_field.clone()),
+                        }
+                    }
+                };
+                Ok((sort_key, *sortdir))
+            })
+            .collect::<Result<_>>()?;
+        // the collector only asks for scores when one of the keys is the score, or they're projected
+        let collector = CompoundTopDocs::new(orderby, self.need_scores, n, offset);
+        let (top_docs, aggregations) =
+            self.collect_top_n_scored(segment_ids, collector, aggregations, false);
+        let results = SearchResults::TopNByCompound(
+            self.searcher.clone(),
+            Default::default(),
+            top_docs
+                .into_iter()
+                .map(|doc| (doc.score, doc.doc_address))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        );
+        Ok((results, aggregations))
+    }
+
+    /// Search the Tantivy index for the "top N" matching documents in the given segments, after
+    /// the best matches of each segment are rescored by `rescorer`.
+    ///
+    /// The rescored documents are returned first, most relevant first, followed by the rest of the
+    /// matches in the order of their original scores.
+    ///
+    /// It has no understanding of Postgres MVCC visibility.  It is the caller's responsibility to
+    /// handle that, if it's necessary.
+    ///
+    /// If `aggregations` is provided, it's collected over every matching document in the same pass
+    /// as the "top N", and its fruit is returned alongside the results.
+    pub fn search_top_n_rescored_in_segments<A: Collector>(
+        &self,
+        segment_ids: impl Iterator<Item = SegmentId>,
+        rescorer: &Rescorer,
+        n: usize,
+        offset: usize,
+        aggregations: Option<A>,
+    ) -> Result<(SearchResults, Option<A::Fruit>)> {
         let weight = self
             .query
             .weight(tantivy::query::EnableScoring::Enabled {
@@ -546,16 +691,69 @@ impl SearchIndexReader {
             })
             .expect("creating a Weight from a Query should not fail");
 
-        let top_docs = self.collect_segments(segment_ids, |segment_ord, segment_reader| {
-            collector
-                .collect_segment(weight.as_ref(), segment_ord, segment_reader)
-                .expect("should be able to collect top-n in segment")
-        });
+        // each segment's window, along with as many of its other matches as could be returned
+        let collector = (
+            TopDocs::with_limit(rescorer.window_size() + n + offset),
+            aggregations,
+        );
+        let mut rescored = Vec::new();
+        let mut rest = Vec::new();
+        let mut aggregation_fruits = Vec::new();
+        self.collect_segments(segment_ids, |segment_ord, segment_reader| {
+            let (top_docs, aggregation_fruit) =
+                collector.collect_segment(weight.as_ref(), segment_ord, segment_reader)?;
+            rescored.extend(rescorer.rescore_segment(segment_reader, &top_docs)?);
+            rest.extend(top_docs.into_iter().skip(rescorer.window_size()));
+            aggregation_fruits.push(aggregation_fruit);
+            Ok::<_, anyhow::Error>(())
+        })
+        .into_iter()
+        .collect::<Result<()>>()?;
 
-        let top_docs = collector
-            .merge_fruits(top_docs)
-            .expect("should be able to merge top-n in segments");
-        SearchResults::TopNByField(
+        let by_score_desc = |a: &(Score, DocAddress), b: &(Score, DocAddress)| {
+            b.0.partial_cmp(&a.0)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.1.cmp(&b.1))
+        };
+        rescored.sort_unstable_by(by_score_desc);
+        rest.sort_unstable_by(by_score_desc);
+
+        let results = SearchResults::TopNByScore(
+            self.searcher.clone(),
+            Default::default(),
+            rescored
+                .into_iter()
+                .chain(rest)
+                .skip(offset)
+                .take(n)
+                .collect::<Vec<_>>()
+                .into_iter(),
+        );
+        let aggregations = collector.1.merge_fruits(aggregation_fruits)?;
+        Ok((results, aggregations))
+    }
+
+    /// Search the Tantivy index for the "top N" matching documents (ordered by a field) in the given segments.
+    ///
+    /// The documents are returned in field order.  Largest first if `sortdir` is [`SortDirection::Desc`],
+    /// or smallest first if it's [`SortDirection::Asc`].
+    ///
+    /// It has no understanding of Postgres MVCC visibility.  It is the caller's responsibility to
+    /// handle that, if it's necessary.
+    fn top_by_field_in_segments<A: Collector>(
+        &self,
+        segment_ids: impl Iterator<Item = SegmentId>,
+        sort_field: impl AsRef<str> + Display,
+        sortdir: SortDirection,
+        n: usize,
+        offset: usize,
+        aggregations: Option<A>,
+    ) -> (SearchResults, Option<A::Fruit>) {
+        let collector = TopDocs::with_limit(n)
+            .and_offset(offset)
+            .order_by_u64_field(&sort_field, sortdir.into());
+        let (top_docs, aggregations) = self.collect_top_n(segment_ids, collector, aggregations);
+        let results = SearchResults::TopNByField(
             self.searcher.clone(),
             Default::default(),
             // TODO: We are discarding a u64-encoded numeric field value here.
@@ -566,7 +764,8 @@ impl SearchIndexReader {
                 .map(|(_, doc)| doc)
                 .collect::<Vec<_>>()
                 .into_iter(),
-        )
+        );
+        (results, aggregations)
     }
 
     /// Search the Tantivy index for the "top N" matching documents (ordered by a field) in the given segments.
@@ -576,35 +775,20 @@ impl SearchIndexReader {
     ///
     /// It has no understanding of Postgres MVCC visibility.  It is the caller's responsibility to
     /// handle that, if it's necessary.
-    fn top_by_string_field_in_segments(
+    fn top_by_string_field_in_segments<A: Collector>(
         &self,
         segment_ids: impl Iterator<Item = SegmentId>,
         sort_field: FieldName,
         sortdir: SortDirection,
         n: usize,
         offset: usize,
-    ) -> SearchResults {
+        aggregations: Option<A>,
+    ) -> (SearchResults, Option<A::Fruit>) {
         let collector = TopDocs::with_limit(n)
             .and_offset(offset)
             .order_by_string_fast_field(&sort_field, sortdir.into());
-        let weight = self
-            .query
-            .weight(tantivy::query::EnableScoring::Enabled {
-                searcher: &self.searcher,
-                statistics_provider: &self.searcher,
-            })
-            .expect("creating a Weight from a Query should not fail");
-
-        let top_docs = self.collect_segments(segment_ids, |segment_ord, segment_reader| {
-            collector
-                .collect_segment(weight.as_ref(), segment_ord, segment_reader)
-                .expect("should be able to collect top-n in segment")
-        });
-
-        let top_docs = collector
-            .merge_fruits(top_docs)
-            .expect("should be able to merge top-n in segments");
-        SearchResults::TopNByField(
+        let (top_docs, aggregations) = self.collect_top_n(segment_ids, collector, aggregations);
+        let results = SearchResults::TopNByField(
             self.searcher.clone(),
             Default::default(),
             // TODO: We are discarding a valid string field value here, but could in theory actually
@@ -614,7 +798,8 @@ impl SearchIndexReader {
                 .map(|(_, doc)| doc)
                 .collect::<Vec<_>>()
                 .into_iter(),
-        )
+        );
+        (results, aggregations)
     }
 
     /// Search the Tantivy index for the "top N" matching documents (ordered by score) in the given segments.
@@ -624,24 +809,17 @@ impl SearchIndexReader {
     ///
     /// It has no understanding of Postgres MVCC visibility.  It is the caller's responsibility to
     /// handle that, if it's necessary.
-    fn top_by_score_in_segments(
+    fn top_by_score_in_segments<A: Collector>(
         &self,
         segment_ids: impl Iterator<Item = SegmentId>,
         sortdir: SortDirection,
         n: usize,
         offset: usize,
-    ) -> SearchResults {
+        aggregations: Option<A>,
+    ) -> (SearchResults, Option<A::Fruit>) {
         match sortdir {
             // requires tweaking the score, which is a bit slower
             SortDirection::Asc => {
-                let weight = self
-                    .query
-                    .weight(tantivy::query::EnableScoring::Enabled {
-                        searcher: &self.searcher,
-                        statistics_provider: &self.searcher,
-                    })
-                    .expect("creating a Weight from a Query should not fail");
-
                 let collector = TopDocs::with_limit(n).and_offset(offset).tweak_score(
                     move |_segment_reader: &tantivy::SegmentReader| {
                         move |_doc: DocId, original_score: Score| TweakedScore {
@@ -651,53 +829,33 @@ impl SearchIndexReader {
                     },
                 );
 
-                let top_docs = self.collect_segments(segment_ids, |segment_ord, segment_reader| {
-                    collector
-                        .collect_segment(weight.as_ref(), segment_ord, segment_reader)
-                        .expect("should be able to collect top-n in segment")
-                });
-
-                let top_docs = collector
-                    .merge_fruits(top_docs)
-                    .expect("should be able to merge top-n in segment");
-
-                SearchResults::TopNByTweakedScore(
+                let (top_docs, aggregations) =
+                    self.collect_top_n(segment_ids, collector, aggregations);
+                let results = SearchResults::TopNByTweakedScore(
                     self.searcher.clone(),
                     Default::default(),
                     top_docs.into_iter(),
-                )
+                );
+                (results, aggregations)
             }
 
             // can use tantivy's score directly
             SortDirection::Desc => {
-                let weight = self
-                    .query
-                    .weight(tantivy::query::EnableScoring::Enabled {
-                        searcher: &self.searcher,
-                        statistics_provider: &self.searcher,
-                    })
-                    .expect("creating a Weight from a Query should not fail");
-
                 let collector = TopDocs::with_limit(n).and_offset(offset);
 
-                let top_docs = self.collect_segments(segment_ids, |segment_ord, segment_reader| {
-                    collector
-                        .collect_segment(weight.as_ref(), segment_ord, segment_reader)
-                        .expect("should be able to collect top-n in segment")
-                });
-
-                let top_docs = collector
-                    .merge_fruits(top_docs)
-                    .expect("should be able to merge top-n in segment");
-
-                SearchResults::TopNByScore(
+                let (top_docs, aggregations) =
+                    self.collect_top_n(segment_ids, collector, aggregations);
+                let results = SearchResults::TopNByScore(
                     self.searcher.clone(),
                     Default::default(),
                     top_docs.into_iter(),
-                )
+                );
+                (results, aggregations)
             }
 
-            SortDirection::None => self.search_segments(segment_ids, offset),
+            // the documents are returned lazily, so there's no collector pass for the
+            // aggregations to share
+            SortDirection::None => (self.search_segments(segment_ids, offset), None),
         }
     }
 
@@ -731,6 +889,46 @@ impl SearchIndexReader {
             .expect("search should not fail")
     }
 
+    /// Collect the "top N" documents with `collector` in the given segments.  If `aggregations` is
+    /// provided, it sees the same matching documents, in the same pass over each segment.
+    fn collect_top_n<C: Collector, A: Collector>(
+        &self,
+        segment_ids: impl Iterator<Item = SegmentId>,
+        collector: C,
+        aggregations: Option<A>,
+    ) -> (C::Fruit, Option<A::Fruit>) {
+        self.collect_top_n_scored(segment_ids, collector, aggregations, true)
+    }
+
+    /// Like [`Self::collect_top_n`], but documents are only scored when `need_scores` is true or
+    /// the collectors require it
+    fn collect_top_n_scored<C: Collector, A: Collector>(
+        &self,
+        segment_ids: impl Iterator<Item = SegmentId>,
+        collector: C,
+        aggregations: Option<A>,
+        need_scores: bool,
+    ) -> (C::Fruit, Option<A::Fruit>) {
+        let collector = (collector, aggregations);
+        let weight = self
+            .query
+            .weight(enable_scoring(
+                need_scores || collector.requires_scoring(),
+                &self.searcher,
+            ))
+            .expect("creating a Weight from a Query should not fail");
+
+        let fruits = self.collect_segments(segment_ids, |segment_ord, segment_reader| {
+            collector
+                .collect_segment(weight.as_ref(), segment_ord, segment_reader)
+                .expect("should be able to collect top-n in segment")
+        });
+
+        collector
+            .merge_fruits(fruits)
+            .expect("should be able to merge top-n in segments")
+    }
+
     fn collect_segments<T>(
         &self,
         segment_ids: impl Iterator<Item = SegmentId>,
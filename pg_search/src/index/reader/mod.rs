@@ -1,2 +1,5 @@
 pub mod index;
+pub mod named_queries;
+pub mod order_by;
 pub mod segment_component;
+pub mod snippet;
@@ -0,0 +1,90 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Which of the named clauses of a query match a document, for `paradedb.matched_queries()`
+
+use crate::api::HashMap;
+use std::collections::hash_map::Entry;
+use tantivy::query::{EnableScoring, Query, Weight};
+use tantivy::{DocAddress, DocId, Searcher, SegmentOrdinal, SegmentReader};
+
+pub struct NamedQueryMatcher {
+    segment_readers: Vec<SegmentReader>,
+    weights: Vec<(String, Box<dyn Weight>)>,
+    /// The ids of the documents each clause matches in each segment, in ascending order,
+    /// collected the first time a document from that segment is asked about
+    matches: HashMap<(SegmentOrdinal, usize), Vec<DocId>>,
+}
+
+impl NamedQueryMatcher {
+    pub fn create(
+        searcher: &Searcher,
+        named_queries: Vec<(String, Box<dyn Query>)>,
+    ) -> tantivy::Result<Self> {
+        let weights = named_queries
+            .into_iter()
+            .map(|(name, query)| {
+                Ok((
+                    name,
+                    query.weight(EnableScoring::disabled_from_searcher(searcher))?,
+                ))
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            segment_readers: searcher.segment_readers().to_vec(),
+            weights,
+            matches: Default::default(),
+        })
+    }
+
+    /// The distinct names of the clauses that match the document at `doc_address`, in the order
+    /// they appear in the query.
+    ///
+    /// Documents can arrive in any order, such as by score for a "top N" scan, so rather than
+    /// seeking a scorer to each one, every clause is run over a segment once and its matches
+    /// are looked up from then on.
+    pub fn matched(&mut self, doc_address: DocAddress) -> tantivy::Result<Vec<String>> {
+        let DocAddress {
+            segment_ord,
+            doc_id,
+        } = doc_address;
+        let segment_reader = &self.segment_readers[segment_ord as usize];
+
+        let mut matched = Vec::new();
+        for (idx, (name, weight)) in self.weights.iter().enumerate() {
+            if matched.contains(name) {
+                continue;
+            }
+
+            let docs = match self.matches.entry((segment_ord, idx)) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let mut docs = Vec::new();
+                    weight.for_each_no_score(segment_reader, &mut |block| {
+                        docs.extend_from_slice(block)
+                    })?;
+                    entry.insert(docs)
+                }
+            };
+            if docs.binary_search(&doc_id).is_ok() {
+                matched.push(name.clone());
+            }
+        }
+        Ok(matched)
+    }
+}
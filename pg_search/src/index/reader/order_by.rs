@@ -0,0 +1,294 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::FieldName;
+use crate::index::reader::index::SortDirection;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::{Column, StrColumn};
+use tantivy::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// One of the keys of an `ORDER BY` that can be evaluated directly from the index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderByFeature {
+    Score,
+    Field(FieldName),
+}
+
+/// An [`OrderByFeature`] that has been resolved against the index schema
+#[derive(Debug, Clone)]
+pub enum SortKey {
+    Score,
+    Numeric(FieldName),
+    Str(FieldName),
+}
+
+/// The value of one sort key for a document.
+///
+/// Within a segment, string fields are compared by their term ordinals, which are much cheaper to
+/// read than the strings themselves.  Only the documents that survive a segment's "top N" have
+/// their ordinals resolved into strings, so that they can be compared against the documents of
+/// other segments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortValue {
+    Score(Score),
+    Numeric(Option<u64>),
+    Ordinal(Option<u64>),
+    Str(Option<String>),
+}
+
+impl SortValue {
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (SortValue::Score(a), SortValue::Score(b)) => a.total_cmp(b),
+            (SortValue::Numeric(a), SortValue::Numeric(b))
+            | (SortValue::Ordinal(a), SortValue::Ordinal(b)) => cmp_nullable(a, b),
+            (SortValue::Str(a), SortValue::Str(b)) => cmp_nullable(a, b),
+            // values of different kinds can't be compared to each other, so, like Postgres does
+            // with NULLs, missing values sort after every other value, and the rest by their kind
+            (a, b) => match (a.is_missing(), b.is_missing()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => a.kind().cmp(&b.kind()),
+            },
+        }
+    }
+
+    fn is_missing(&self) -> bool {
+        matches!(
+            self,
+            SortValue::Numeric(None) | SortValue::Ordinal(None) | SortValue::Str(None)
+        )
+    }
+
+    fn kind(&self) -> u8 {
+        match self {
+            SortValue::Score(_) => 0,
+            SortValue::Numeric(_) => 1,
+            SortValue::Ordinal(_) => 2,
+            SortValue::Str(_) => 3,
+        }
+    }
+}
+
+/// Like Postgres, NULL sorts as if it were larger than any other value.  This means NULLs are last
+/// for ascending sorts and first for descending sorts, which are Postgres' defaults.
+fn cmp_nullable<T: Ord>(a: &Option<T>, b: &Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+    }
+}
+
+/// A document collected by [`CompoundTopDocs`]
+#[derive(Debug, Clone)]
+pub struct SortedDoc {
+    values: Vec<SortValue>,
+    pub score: Score,
+    pub doc_address: DocAddress,
+}
+
+fn compare_docs(directions: &[SortDirection], a: &SortedDoc, b: &SortedDoc) -> Ordering {
+    for ((direction, a), b) in directions.iter().zip(&a.values).zip(&b.values) {
+        let ordering = match direction {
+            SortDirection::Desc => a.compare(b).reverse(),
+            SortDirection::Asc => a.compare(b),
+            SortDirection::None => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    // break any remaining ties by index order so that the results are stable between queries
+    a.doc_address.cmp(&b.doc_address)
+}
+
+/// A [`Collector`] for the "top N" documents ordered by any mix of score, numeric fast fields,
+/// and string fast fields, each in its own direction.
+pub struct CompoundTopDocs {
+    sort_keys: Vec<SortKey>,
+    directions: Vec<SortDirection>,
+    need_scores: bool,
+    limit: usize,
+    offset: usize,
+}
+
+impl CompoundTopDocs {
+    pub fn new(
+        orderby: Vec<(SortKey, SortDirection)>,
+        need_scores: bool,
+        limit: usize,
+        offset: usize,
+    ) -> Self {
+        let (sort_keys, directions): (Vec<_>, Vec<_>) = orderby.into_iter().unzip();
+        let need_scores = need_scores
+            || sort_keys
+                .iter()
+                .any(|sort_key| matches!(sort_key, SortKey::Score));
+        Self {
+            sort_keys,
+            directions,
+            need_scores,
+            limit,
+            offset,
+        }
+    }
+}
+
+enum SortColumn {
+    Score,
+    Numeric(Option<Column<u64>>),
+    Str(Option<StrColumn>),
+}
+
+impl SortColumn {
+    #[inline]
+    fn value(&self, doc: DocId, score: Score) -> SortValue {
+        match self {
+            SortColumn::Score => SortValue::Score(score),
+            SortColumn::Numeric(column) => {
+                SortValue::Numeric(column.as_ref().and_then(|column| column.first(doc)))
+            }
+            SortColumn::Str(column) => {
+                SortValue::Ordinal(column.as_ref().and_then(|column| column.ords().first(doc)))
+            }
+        }
+    }
+}
+
+impl Collector for CompoundTopDocs {
+    type Fruit = Vec<SortedDoc>;
+    type Child = CompoundTopDocsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let fast_fields = segment_reader.fast_fields();
+        let columns = self
+            .sort_keys
+            .iter()
+            .map(|sort_key| {
+                Ok(match sort_key {
+                    SortKey::Score => SortColumn::Score,
+                    SortKey::Numeric(field) => SortColumn::Numeric(
+                        fast_fields
+                            .u64_lenient(field)?
+                            .map(|(column, _column_type)| column),
+                    ),
+                    SortKey::Str(field) => SortColumn::Str(fast_fields.str(field)?),
+                })
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
+
+        Ok(CompoundTopDocsSegmentCollector {
+            segment_ord,
+            columns,
+            directions: self.directions.clone(),
+            limit: self.limit + self.offset,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.need_scores
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<SortedDoc>>) -> tantivy::Result<Self::Fruit> {
+        let mut docs = segment_fruits.into_iter().flatten().collect::<Vec<_>>();
+        docs.sort_unstable_by(|a, b| compare_docs(&self.directions, a, b));
+        Ok(docs
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit)
+            .collect())
+    }
+}
+
+pub struct CompoundTopDocsSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    columns: Vec<SortColumn>,
+    directions: Vec<SortDirection>,
+    limit: usize,
+    buffer: Vec<SortedDoc>,
+}
+
+impl CompoundTopDocsSegmentCollector {
+    /// Reduce the buffer to just the "top N" docs seen so far.  Doing this only once the buffer
+    /// has doubled in size keeps the cost of collection linear in the number of matching docs.
+    fn truncate(&mut self) {
+        if self.buffer.len() > self.limit {
+            let directions = &self.directions;
+            self.buffer
+                .select_nth_unstable_by(self.limit, |a, b| compare_docs(directions, a, b));
+            self.buffer.truncate(self.limit);
+        }
+    }
+}
+
+impl SegmentCollector for CompoundTopDocsSegmentCollector {
+    type Fruit = Vec<SortedDoc>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let values = self
+            .columns
+            .iter()
+            .map(|column| column.value(doc, score))
+            .collect();
+        self.buffer.push(SortedDoc {
+            values,
+            score,
+            doc_address: DocAddress::new(self.segment_ord, doc),
+        });
+
+        if self.buffer.len() >= self.limit.max(1) * 2 {
+            self.truncate();
+        }
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        self.truncate();
+
+        // resolve term ordinals into their strings so the docs can be compared across segments
+        let mut buffer = String::new();
+        for doc in &mut self.buffer {
+            for (column, value) in self.columns.iter().zip(doc.values.iter_mut()) {
+                if let (SortColumn::Str(column), SortValue::Ordinal(ordinal)) = (column, &*value) {
+                    let term = match (column, *ordinal) {
+                        (Some(column), Some(ordinal)) => {
+                            buffer.clear();
+                            column
+                                .ord_to_str(ordinal, &mut buffer)
+                                .expect("term ordinal should be valid")
+                                .then(|| buffer.clone())
+                        }
+                        _ => None,
+                    };
+                    *value = SortValue::Str(term);
+                }
+            }
+        }
+
+        self.buffer
+    }
+}
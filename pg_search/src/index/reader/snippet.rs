@@ -0,0 +1,383 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use tantivy::query::Query;
+use tantivy::schema::{Field, Type, ValueBytes};
+use tantivy::tokenizer::{TextAnalyzer, TokenStream};
+use tantivy::{Score, Searcher, Term};
+
+/// How tantivy separates the segments of a JSON term's path, and ends the path
+const JSON_PATH_SEGMENT_SEP: u8 = 1;
+const JSON_END_OF_PATH: u8 = 0;
+
+/// How the fragments returned by [`FragmentGenerator::fragments`] are ordered
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum FragmentOrder {
+    /// Best fragment first
+    Score,
+    /// In the order they appear in the text
+    Position,
+}
+
+/// One highlighted fragment of a document's text.
+///
+/// `start`, `end`, and `positions` are byte offsets into the whole text, not the fragment.  For
+/// multi-valued fields, `element` is the (zero-based) index of the value the fragment is from,
+/// and the offsets are into that value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Fragment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<usize>,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    pub score: Score,
+    pub positions: Vec<(usize, usize)>,
+}
+
+/// Like tantivy's [`tantivy::snippet::SnippetGenerator`], but able to return more than just the
+/// single best fragment of the text.
+///
+/// The text is cut into consecutive fragments of at most `fragment_size` bytes, on token
+/// boundaries, and each fragment is scored by the query terms it contains.  Rarer terms are worth
+/// more.
+#[derive(Clone)]
+pub struct FragmentGenerator {
+    terms: BTreeMap<String, Score>,
+    tokenizer: TextAnalyzer,
+    fragment_size: usize,
+}
+
+impl FragmentGenerator {
+    /// `json_path` is the dotted path to highlight when `field` is a JSON field.  Only the query
+    /// terms at that path are highlighted.
+    pub fn create(
+        searcher: &Searcher,
+        query: &dyn Query,
+        field: Field,
+        json_path: Option<&str>,
+        fragment_size: usize,
+    ) -> tantivy::Result<Self> {
+        let mut query_terms = BTreeSet::<Term>::new();
+        for segment_reader in searcher.segment_readers() {
+            query.query_terms(field, segment_reader, &mut |term, _| {
+                if term.field() == field {
+                    query_terms.insert(term.clone());
+                }
+            });
+        }
+
+        let mut terms = BTreeMap::new();
+        for term in query_terms {
+            let Some(text) = term_text(&term, json_path) else {
+                continue;
+            };
+            let doc_freq = searcher.doc_freq(&term)?;
+            if doc_freq > 0 {
+                terms.insert(text, 1.0 / (1.0 + doc_freq as Score));
+            }
+        }
+
+        Ok(Self {
+            terms,
+            tokenizer: searcher.index().tokenizer_for_field(field)?,
+            fragment_size,
+        })
+    }
+
+    /// The (at most) `max_fragments` best fragments of `text` that contain a query term, where
+    /// each query term is wrapped in `start_tag` and `end_tag`
+    pub fn fragments(
+        &self,
+        text: &str,
+        max_fragments: usize,
+        order: FragmentOrder,
+        start_tag: &str,
+        end_tag: &str,
+    ) -> Vec<Fragment> {
+        let candidates = self.candidates(text, None);
+        select(
+            &[text],
+            candidates,
+            max_fragments,
+            order,
+            start_tag,
+            end_tag,
+        )
+    }
+
+    /// Like [`FragmentGenerator::fragments`], but for a multi-valued field, where each value is
+    /// highlighted on its own and fragments never span values
+    pub fn element_fragments(
+        &self,
+        elements: &[&str],
+        max_fragments: usize,
+        order: FragmentOrder,
+        start_tag: &str,
+        end_tag: &str,
+    ) -> Vec<Fragment> {
+        let candidates = elements
+            .iter()
+            .enumerate()
+            .flat_map(|(element, text)| self.candidates(text, Some(element)))
+            .collect();
+        select(
+            elements,
+            candidates,
+            max_fragments,
+            order,
+            start_tag,
+            end_tag,
+        )
+    }
+
+    /// Cut `text` into consecutive fragments, and return the ones with a query term in them
+    fn candidates(&self, text: &str, element: Option<usize>) -> Vec<Candidate> {
+        let mut tokenizer = self.tokenizer.clone();
+        let mut token_stream = tokenizer.token_stream(text);
+        let mut candidate = Candidate::new(element, 0);
+        let mut candidates = vec![];
+        while let Some(token) = token_stream.next() {
+            if token.offset_to - candidate.start > self.fragment_size {
+                if candidate.score > 0.0 {
+                    candidates.push(candidate);
+                }
+                candidate = Candidate::new(element, token.offset_from);
+            }
+
+            candidate.end = token.offset_to;
+            if let Some(score) = self.terms.get(&token.text) {
+                candidate.score += score;
+                candidate
+                    .highlighted
+                    .push(token.offset_from..token.offset_to);
+            }
+        }
+        if candidate.score > 0.0 {
+            candidates.push(candidate);
+        }
+        candidates
+    }
+}
+
+/// The text of a query term, if it's a term we can highlight.  For JSON fields, that's a string
+/// term at `json_path`.
+fn term_text(term: &Term, json_path: Option<&str>) -> Option<String> {
+    if term.typ() != Type::Json {
+        return term.value().as_str().map(str::to_string);
+    }
+
+    // a JSON term's value is its path, the end of path marker, and then its typed value
+    let bytes = term.serialized_value_bytes();
+    let end_of_path = bytes.iter().position(|b| *b == JSON_END_OF_PATH)?;
+    let (path, value) = (&bytes[..end_of_path], &bytes[end_of_path + 1..]);
+    let path = std::str::from_utf8(path)
+        .ok()?
+        .replace(JSON_PATH_SEGMENT_SEP as char, ".");
+    if json_path.is_some_and(|json_path| json_path != path) {
+        return None;
+    }
+    ValueBytes::wrap(value).as_str().map(str::to_string)
+}
+
+fn select(
+    texts: &[&str],
+    mut candidates: Vec<Candidate>,
+    max_fragments: usize,
+    order: FragmentOrder,
+    start_tag: &str,
+    end_tag: &str,
+) -> Vec<Fragment> {
+    // keep the best, breaking ties in favor of fragments earlier in the text
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then((a.element, a.start).cmp(&(b.element, b.start)))
+    });
+    candidates.truncate(max_fragments);
+    if order == FragmentOrder::Position {
+        candidates.sort_by_key(|candidate| (candidate.element, candidate.start));
+    }
+
+    candidates
+        .into_iter()
+        .map(|candidate| Fragment {
+            element: candidate.element,
+            text: candidate.to_html(
+                texts[candidate.element.unwrap_or_default()],
+                start_tag,
+                end_tag,
+            ),
+            start: candidate.start,
+            end: candidate.end,
+            score: candidate.score,
+            positions: candidate
+                .highlighted
+                .iter()
+                .map(|range| (range.start, range.end))
+                .collect(),
+        })
+        .collect()
+}
+
+struct Candidate {
+    element: Option<usize>,
+    start: usize,
+    end: usize,
+    score: Score,
+    highlighted: Vec<Range<usize>>,
+}
+
+impl Candidate {
+    fn new(element: Option<usize>, start: usize) -> Self {
+        Self {
+            element,
+            start,
+            end: start,
+            score: 0.0,
+            highlighted: vec![],
+        }
+    }
+
+    fn to_html(&self, text: &str, start_tag: &str, end_tag: &str) -> String {
+        let mut html = String::new();
+        let mut from = self.start;
+        for range in &self.highlighted {
+            // tokenizers can produce overlapping tokens, such as ngrams
+            if range.start < from {
+                continue;
+            }
+            html.push_str(&escape_html(&text[from..range.start]));
+            html.push_str(start_tag);
+            html.push_str(&escape_html(&text[range.clone()]));
+            html.push_str(end_tag);
+            from = range.end;
+        }
+        html.push_str(&escape_html(&text[from..self.end]));
+        html
+    }
+}
+
+/// Escape the same characters as tantivy does when it renders a snippet to html
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::SimpleTokenizer;
+
+    fn generator(terms: &[(&str, Score)], fragment_size: usize) -> FragmentGenerator {
+        FragmentGenerator {
+            terms: terms
+                .iter()
+                .map(|(term, score)| (term.to_string(), *score))
+                .collect(),
+            tokenizer: TextAnalyzer::from(SimpleTokenizer::default()),
+            fragment_size,
+        }
+    }
+
+    const TEXT: &str = "red shoes are nice. blue socks are warm. red socks & shoes are best.";
+
+    #[test]
+    fn fragments_by_score() {
+        let generator = generator(&[("shoes", 1.0), ("socks", 0.5)], 20);
+        let fragments = generator.fragments(TEXT, 2, FragmentOrder::Score, "<b>", "</b>");
+
+        assert_eq!(fragments.len(), 2);
+        // "red socks & shoes" is 17 bytes, and the "are" after it would make it 21, which is more
+        // than the fragment_size of 20, so "are" starts the next fragment
+        assert_eq!(fragments[0].text, "red <b>socks</b> &amp; <b>shoes</b>");
+        assert_eq!(fragments[0].score, 1.5);
+        assert_eq!(fragments[1].text, "red <b>shoes</b> are nice");
+        assert_eq!(fragments[1].positions, vec![(4, 9)]);
+    }
+
+    #[test]
+    fn fragments_by_position() {
+        let generator = generator(&[("shoes", 1.0), ("socks", 0.5)], 20);
+        let fragments = generator.fragments(TEXT, 2, FragmentOrder::Position, "<b>", "</b>");
+
+        assert_eq!(fragments.len(), 2);
+        assert!(fragments[0].start < fragments[1].start);
+        assert_eq!(
+            &TEXT[fragments[0].start..fragments[0].end],
+            "red shoes are nice"
+        );
+    }
+
+    #[test]
+    fn no_matching_terms() {
+        let generator = generator(&[("boots", 1.0)], 20);
+        assert!(generator
+            .fragments(TEXT, 3, FragmentOrder::Score, "<b>", "</b>")
+            .is_empty());
+    }
+
+    #[test]
+    fn fragments_of_elements() {
+        let generator = generator(&[("shoes", 1.0)], 20);
+        let fragments = generator.element_fragments(
+            &["blue socks", "red shoes", "green shoes"],
+            3,
+            FragmentOrder::Position,
+            "<b>",
+            "</b>",
+        );
+
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0].element, Some(1));
+        assert_eq!(fragments[0].text, "red <b>shoes</b>");
+        assert_eq!(fragments[0].positions, vec![(4, 9)]);
+        assert_eq!(fragments[1].element, Some(2));
+        assert_eq!(fragments[1].text, "green <b>shoes</b>");
+    }
+
+    #[test]
+    fn json_term_text() {
+        let field = Field::from_field_id(0);
+        let mut term = Term::from_field_json_path(field, "details.author", false);
+        term.append_type_and_str("shoes");
+
+        assert_eq!(
+            term_text(&term, Some("details.author")),
+            Some("shoes".into())
+        );
+        assert_eq!(term_text(&term, Some("details")), None);
+        assert_eq!(term_text(&term, None), Some("shoes".into()));
+        assert_eq!(
+            term_text(&Term::from_field_text(field, "socks"), None),
+            Some("socks".into())
+        );
+    }
+}
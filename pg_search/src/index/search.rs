@@ -15,12 +15,66 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::analyzer::resolve_analyzer;
 use crate::postgres::rel::PgSearchRelation;
+use crate::postgres::storage::metadata::MetaPage;
 use anyhow::Result;
+use serde_json::{Map, Value};
 use tantivy::Index;
 use tokenizers::{create_normalizer_manager, create_tokenizer_manager, SearchTokenizer};
 
 pub fn setup_tokenizers(index_relation: &PgSearchRelation, index: &mut Index) -> Result<()> {
+    let tokenizers = load_tokenizers(index_relation)?;
+    index.set_tokenizers(create_tokenizer_manager(tokenizers));
+    index.set_fast_field_tokenizers(create_normalizer_manager());
+    Ok(())
+}
+
+/// Resolve the tokenizers of the index's fields, and save them in the index.
+///
+/// Named analyzers and the word lists that filters read from tables are copied as they are when
+/// the index is built, so the index keeps tokenizing the same way, and never reads them again,
+/// until it's reindexed.
+pub fn save_tokenizers(index_relation: &PgSearchRelation) -> Result<()> {
+    let mut resolved = Map::new();
+    for tokenizer in field_tokenizers(index_relation)? {
+        let name = tokenizer.name();
+        if resolved.contains_key(&name) {
+            continue;
+        }
+
+        let mut definition = resolve_analyzer(tokenizer)?;
+        if let Some(decompounder) = definition
+            .filters_mut()
+            .and_then(|filters| filters.decompounder.as_mut())
+        {
+            // save the words that were loaded rather than the table they were loaded from
+            decompounder.word_list_table = None;
+        }
+        resolved.insert(name, definition.to_json_value());
+    }
+
+    MetaPage::open(index_relation).record_tokenizers(&serde_json::to_vec(&resolved)?)
+}
+
+/// The tokenizers saved by [`save_tokenizers`], paired with the names that fields refer to them by
+fn load_tokenizers(index_relation: &PgSearchRelation) -> Result<Vec<(String, SearchTokenizer)>> {
+    let Some(tokenizers) = MetaPage::open(index_relation).tokenizers_bytes() else {
+        // indexes built before tokenizers were saved can't have any that need resolving
+        return Ok(field_tokenizers(index_relation)?
+            .into_iter()
+            .map(|tokenizer| (tokenizer.name(), tokenizer))
+            .collect());
+    };
+
+    let resolved: Map<String, Value> = serde_json::from_slice(&unsafe { tokenizers.read_all() })?;
+    resolved
+        .into_iter()
+        .map(|(name, definition)| Ok((name, SearchTokenizer::from_json_value(&definition)?)))
+        .collect()
+}
+
+fn field_tokenizers(index_relation: &PgSearchRelation) -> Result<Vec<SearchTokenizer>> {
     let schema = index_relation.schema()?;
     let categorized_fields = schema.categorized_fields();
 
@@ -35,8 +89,5 @@ pub fn setup_tokenizers(index_relation: &PgSearchRelation, index: &mut Index) ->
             tokenizers.push(tokenizer.clone());
         }
     }
-
-    index.set_tokenizers(create_tokenizer_manager(tokenizers));
-    index.set_fast_field_tokenizers(create_normalizer_manager());
-    Ok(())
+    Ok(tokenizers)
 }
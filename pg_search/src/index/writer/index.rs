@@ -30,6 +30,7 @@ use crate::index::mvcc::{MVCCDirectory, MvccSatisfies};
 use crate::index::setup_tokenizers;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::storage::block::SegmentMetaEntry;
+use crate::query::nested::child_documents;
 use crate::{postgres::types::TantivyValueError, schema::SearchIndexSchema};
 
 struct PendingSegment {
@@ -103,6 +104,7 @@ pub struct SerialIndexWriter {
     id: i32,
     indexrel: PgSearchRelation,
     ctid_field: Field,
+    nested_fields: Vec<Field>,
     config: IndexWriterConfig,
     index: Index,
     pending_segment: Option<PendingSegment>,
@@ -142,11 +144,13 @@ impl SerialIndexWriter {
         let schema = index_relation.schema()?;
         setup_tokenizers(index_relation, &mut index)?;
         let ctid_field = schema.ctid_field();
+        let nested_fields = schema.nested_fields();
 
         Ok(Self {
             id: worker_number,
             indexrel: Clone::clone(index_relation),
             ctid_field,
+            nested_fields,
             config,
             index,
             pending_segment: Default::default(),
@@ -165,16 +169,18 @@ impl SerialIndexWriter {
         ctid: u64,
         on_finalize: OnFinalize,
     ) -> Result<Option<SegmentMeta>> {
-        document.add_u64(self.ctid_field, ctid);
-
         if self.pending_segment.is_none() {
             self.pending_segment = Some(self.new_segment()?);
         }
+        let pending_segment = self.pending_segment.as_mut().unwrap();
 
-        self.pending_segment
-            .as_mut()
-            .unwrap()
-            .add_document(document)?;
+        // the elements of nested fields go right before the row's own document, which is the
+        // only one of the block with a ctid, and the segment is only finalized after it
+        for child in child_documents(&document, &self.nested_fields) {
+            pending_segment.add_document(child)?;
+        }
+        document.add_u64(self.ctid_field, ctid);
+        pending_segment.add_document(document)?;
 
         let pending_segment = self.pending_segment.as_ref().unwrap();
         let mem_usage = pending_segment.mem_usage();
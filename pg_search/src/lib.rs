@@ -75,10 +75,13 @@ pub unsafe extern "C-unwind" fn _PG_init() {
 
     #[cfg(not(feature = "pg17"))]
     postgres::fake_aminsertcleanup::register();
+    postgres::utility::register();
 
     #[allow(static_mut_refs)]
     #[allow(deprecated)]
     customscan::register_rel_pathlist(customscan::pdbscan::PdbScan);
+    customscan::register_upper_path(customscan::aggregatescan::AggregateScan);
+    customscan::pdbscan::projections::window_agg::register();
 }
 
 #[pg_extern]
@@ -17,6 +17,7 @@
 
 use crate::api::FieldName;
 use crate::index::mvcc::MvccSatisfies;
+use crate::index::save_tokenizers;
 use crate::postgres::build_parallel::build_index;
 use crate::postgres::options::BM25IndexOptions;
 use crate::postgres::rel::PgSearchRelation;
@@ -97,6 +98,8 @@ unsafe fn build_empty(index_relation: &PgSearchRelation) {
     validate_index_config(index_relation);
 
     create_index(index_relation).unwrap_or_else(|e| panic!("{e}"));
+
+    save_tokenizers(index_relation).unwrap_or_else(|e| panic!("{e}"));
 }
 
 unsafe fn validate_index_config(index_relation: &PgSearchRelation) {
@@ -265,6 +268,8 @@ fn create_index(index_relation: &PgSearchRelation) -> Result<()> {
             SearchFieldType::Bool(_) => builder.add_bool_field(name.as_ref(), config.clone()),
             SearchFieldType::Json(_) => builder.add_json_field(name.as_ref(), config.clone()),
             SearchFieldType::Range(_) => builder.add_json_field(name.as_ref(), config.clone()),
+            SearchFieldType::Geo(_) => builder.add_json_field(name.as_ref(), config.clone()),
+            SearchFieldType::Query(_) => builder.add_text_field(name.as_ref(), config.clone()),
             SearchFieldType::Date(_) => builder.add_date_field(name.as_ref(), config.clone()),
         };
     }
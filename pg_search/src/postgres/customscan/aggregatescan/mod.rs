@@ -0,0 +1,514 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! A custom scan that answers `COUNT(*)`, `SUM()`, `AVG()`, `MIN()` and `MAX()` queries, optionally
+//! with a `GROUP BY`, using tantivy's aggregation collectors rather than visiting each matching
+//! heap tuple.
+//!
+//! It is planned for Postgres' `UPPERREL_GROUP_AGG` relation, which means it replaces both the
+//! scan of the table and the `Aggregate` node above it.
+
+#![allow(clippy::unnecessary_cast)] // helps with integer casting differences between postgres versions
+mod privdat;
+mod scan_state;
+
+use crate::api::aggregate::execute_aggregate;
+use crate::api::operator::anyelement_query_input_opoid;
+use crate::api::FieldName;
+use crate::gucs;
+use crate::nodecast;
+use crate::postgres::customscan::aggregatescan::privdat::{
+    AggregateKind, PrivateData, TargetColumn,
+};
+use crate::postgres::customscan::aggregatescan::scan_state::{AggregateScanState, MAX_GROUPS};
+use crate::postgres::customscan::builders::custom_path::{
+    CustomPathBuilder, Flags, RestrictInfoType,
+};
+use crate::postgres::customscan::builders::custom_scan::CustomScanBuilder;
+use crate::postgres::customscan::builders::custom_state::{
+    CustomScanStateBuilder, CustomScanStateWrapper,
+};
+use crate::postgres::customscan::explainer::Explainer;
+use crate::postgres::customscan::pdbscan::qual_inspect::{extract_quals, QualExtractState};
+use crate::postgres::customscan::pdbscan::PdbScan;
+use crate::postgres::customscan::{CustomScan, CustomUpperScan, ExecMethod, PlainExecCapable};
+use crate::postgres::rel_get_bm25_index;
+use crate::postgres::var::fieldname_from_var;
+use crate::query::SearchQueryInput;
+use crate::schema::SearchIndexSchema;
+use crate::DEFAULT_STARTUP_COST;
+use pgrx::pg_sys::CustomExecMethods;
+use pgrx::{pg_sys, PgList, PgMemoryContexts, PgTupleDesc};
+use std::ffi::CStr;
+
+/// The same memory limit `paradedb.aggregate()` uses by default
+const MEMORY_LIMIT: u64 = 500_000_000;
+
+#[derive(Default)]
+pub struct AggregateScan;
+
+impl AggregateScan {
+    /// Run the aggregation and produce every row the scan will return
+    fn aggregate(state: &AggregateScanState) -> Vec<Vec<scan_state::AggregateValue>> {
+        let aggregations = serde_json::from_value(state.aggregation_request())
+            .expect("aggregation request should be valid");
+        let results = execute_aggregate(
+            state.indexrel(),
+            state.query.clone(),
+            aggregations,
+            true,
+            MEMORY_LIMIT,
+            MAX_GROUPS,
+        )
+        .unwrap_or_else(|e| pgrx::error!("aggregate scan: {e}"));
+        state.make_rows(results)
+    }
+}
+
+impl ExecMethod for AggregateScan {
+    fn exec_methods() -> *const CustomExecMethods {
+        <AggregateScan as PlainExecCapable>::exec_methods()
+    }
+}
+
+impl PlainExecCapable for AggregateScan {}
+
+impl CustomScan for AggregateScan {
+    const NAME: &'static CStr = c"ParadeDB Aggregate Scan";
+
+    type State = AggregateScanState;
+    type PrivateData = PrivateData;
+
+    fn rel_pathlist_callback(
+        builder: CustomPathBuilder<Self::PrivateData>,
+    ) -> Option<pg_sys::CustomPath> {
+        // we only ever replace the grouping/aggregation of a relation, never a plain scan of it
+        None
+    }
+
+    fn plan_custom_path(builder: CustomScanBuilder<Self::PrivateData>) -> pg_sys::CustomScan {
+        unsafe {
+            // we don't scan a relation, so we describe the tuples we produce ourselves:  one column
+            // for each of the path's targets, which is also the order of our `TargetColumn`s.
+            // Postgres then rewrites the plan's target list to reference these columns.
+            //
+            // NB:  the `tlist` we were given can be empty if Postgres intends to replace it, so
+            // it's not what we use here
+            let pathtarget = (*builder.args().best_path).path.pathtarget;
+            let exprs = PgList::<pg_sys::Expr>::from_pg((*pathtarget).exprs);
+            let mut custom_scan_tlist = PgList::<pg_sys::TargetEntry>::new();
+            for (i, expr) in exprs.iter_ptr().enumerate() {
+                custom_scan_tlist.push(pg_sys::makeTargetEntry(
+                    expr,
+                    (i + 1) as pg_sys::AttrNumber,
+                    std::ptr::null_mut(),
+                    false,
+                ));
+            }
+
+            builder
+                .set_custom_scan_tlist(custom_scan_tlist.into_pg())
+                .build()
+        }
+    }
+
+    fn create_custom_scan_state(
+        mut builder: CustomScanStateBuilder<Self, Self::PrivateData>,
+    ) -> *mut CustomScanStateWrapper<Self> {
+        builder.custom_state().heaprelid = builder
+            .custom_private()
+            .heaprelid()
+            .expect("heaprelid should have a value");
+        builder.custom_state().indexrelid = builder
+            .custom_private()
+            .indexrelid()
+            .expect("indexrelid should have a value");
+        builder.custom_state().query = builder
+            .custom_private()
+            .query()
+            .clone()
+            .expect("should have a SearchQueryInput");
+        builder.custom_state().group_by = builder.custom_private().group_by().clone();
+        builder.custom_state().targets = builder.custom_private().targets().to_vec();
+
+        builder
+            .custom_state()
+            .open_relations(pg_sys::AccessShareLock as _);
+
+        builder.build()
+    }
+
+    fn explain_custom_scan(
+        state: &CustomScanStateWrapper<Self>,
+        ancestors: *mut pg_sys::List,
+        explainer: &mut Explainer,
+    ) {
+        explainer.add_text("Table", state.custom_state().heaprelname());
+        explainer.add_text("Index", state.custom_state().indexrelname());
+
+        let mut json_value =
+            serde_json::to_value(&state.custom_state().query).expect("query should serialize");
+        PdbScan::cleanup_varibilities_from_tantivy_query(&mut json_value);
+        explainer.add_text(
+            "Tantivy Query",
+            serde_json::to_string(&json_value).expect("query should serialize to json"),
+        );
+        explainer.add_text(
+            "Aggregate Definition",
+            serde_json::to_string(&state.custom_state().aggregation_request())
+                .expect("aggregation request should serialize to json"),
+        );
+    }
+
+    fn begin_custom_scan(
+        state: &mut CustomScanStateWrapper<Self>,
+        estate: *mut pg_sys::EState,
+        eflags: i32,
+    ) {
+        // ExecInitCustomScan has already set up our scan slot, from the `custom_scan_tlist`, and
+        // the projection of it into our target list.  the aggregation itself waits for the first
+        // tuple to be requested
+    }
+
+    fn rescan_custom_scan(state: &mut CustomScanStateWrapper<Self>) {
+        state.custom_state_mut().rows = None;
+    }
+
+    fn exec_custom_scan(state: &mut CustomScanStateWrapper<Self>) -> *mut pg_sys::TupleTableSlot {
+        if state.custom_state().rows.is_none() {
+            let rows = Self::aggregate(state.custom_state());
+            state.custom_state_mut().rows = Some(rows.into_iter());
+        }
+
+        let Some(row) = state.custom_state_mut().rows.as_mut().unwrap().next() else {
+            return std::ptr::null_mut();
+        };
+
+        unsafe {
+            let slot = state.scanslot();
+            let tupdesc = PgTupleDesc::from_pg_unchecked((*slot).tts_tupleDescriptor);
+            let natts = tupdesc.len();
+            assert_eq!(natts, row.len(), "row should have a value for each column");
+
+            // any Datums we make only need to live until the next row is requested
+            let mut per_tuple_context =
+                PgMemoryContexts::For((*state.csstate.ss.ps.ps_ExprContext).ecxt_per_tuple_memory);
+            per_tuple_context.reset();
+
+            pg_sys::ExecClearTuple(slot);
+            let datums = std::slice::from_raw_parts_mut((*slot).tts_values, natts);
+            let isnull = std::slice::from_raw_parts_mut((*slot).tts_isnull, natts);
+            per_tuple_context.switch_to(|_| {
+                for (i, value) in row.into_iter().enumerate() {
+                    let typoid = tupdesc.get(i).expect("attribute should exist").atttypid;
+                    match value.into_datum(typoid) {
+                        Some(datum) => {
+                            datums[i] = datum;
+                            isnull[i] = false;
+                        }
+                        None => {
+                            datums[i] = pg_sys::Datum::null();
+                            isnull[i] = true;
+                        }
+                    }
+                }
+            });
+            pg_sys::ExecStoreVirtualTuple(slot);
+
+            let projection_info = state.projection_info();
+            if projection_info.is_null() {
+                return slot;
+            }
+            (*(*projection_info).pi_exprContext).ecxt_scantuple = slot;
+            pg_sys::ExecProject(projection_info)
+        }
+    }
+
+    fn shutdown_custom_scan(state: &mut CustomScanStateWrapper<Self>) {}
+
+    fn end_custom_scan(state: &mut CustomScanStateWrapper<Self>) {
+        state.custom_state_mut().rows.take();
+        state.custom_state_mut().heaprel.take();
+        state.custom_state_mut().indexrel.take();
+    }
+}
+
+impl CustomUpperScan for AggregateScan {
+    fn create_upper_paths_callback(
+        mut builder: CustomPathBuilder<Self::PrivateData>,
+        stage: pg_sys::UpperRelationKind::Type,
+        input_rel: *mut pg_sys::RelOptInfo,
+        extra: *mut std::ffi::c_void,
+    ) -> Option<pg_sys::CustomPath> {
+        if stage != pg_sys::UpperRelationKind::UPPERREL_GROUP_AGG
+            || !gucs::enable_aggregate_custom_scan()
+        {
+            return None;
+        }
+
+        unsafe {
+            let root = builder.args().root;
+            let parse = (*root).parse;
+
+            // HAVING, GROUPING SETS/ROLLUP/CUBE, and set-returning functions all need Postgres
+            // to do more work on top of the groups, which we can't describe to it
+            if !(*parse).havingQual.is_null()
+                || !(*parse).groupingSets.is_null()
+                || (*parse).hasTargetSRFs
+            {
+                return None;
+            }
+
+            // we only aggregate a single plain relation, not joins or subqueries
+            if (*input_rel).reloptkind != pg_sys::RelOptKind::RELOPT_BASEREL {
+                return None;
+            }
+            let rti = (*input_rel).relid;
+            let rte = pg_sys::rt_fetch(rti, (*parse).rtable);
+            if (*rte).rtekind != pg_sys::RTEKind::RTE_RELATION || (*rte).inh {
+                return None;
+            }
+            let relkind = pg_sys::get_rel_relkind((*rte).relid) as u8;
+            if relkind != pg_sys::RELKIND_RELATION && relkind != pg_sys::RELKIND_MATVIEW {
+                return None;
+            }
+
+            // and that relation must have a `USING bm25` index
+            let (table, bm25_index) = rel_get_bm25_index((*rte).relid)?;
+
+            // a partial index might not contain every row the query matches
+            if !bm25_index.rd_indpred.is_null() {
+                return None;
+            }
+            let schema = bm25_index.schema().ok()?;
+
+            //
+            // the entire WHERE clause must be answerable by the index, as there's no chance
+            // to filter heap tuples once they've been aggregated
+            //
+            let mut state = QualExtractState::default();
+            let quals = extract_quals(
+                root,
+                rti,
+                (*input_rel).baserestrictinfo.cast(),
+                anyelement_query_input_opoid(),
+                RestrictInfoType::BaseRelation,
+                &schema,
+                false,
+                &mut state,
+            )?;
+            if !(state.uses_our_operator || gucs::enable_custom_scan_without_operator())
+                || state.uses_heap_expr
+                || quals.contains_exprs()
+                || quals.contains_external_var()
+            {
+                return None;
+            }
+
+            //
+            // at most one GROUP BY column, which must be a fast field
+            //
+            let group_clause = PgList::<pg_sys::SortGroupClause>::from_pg((*parse).groupClause);
+            let group_by = match group_clause.len() {
+                0 => None,
+                1 => Some(group_by_var(
+                    group_clause.get_ptr(0)?,
+                    (*parse).targetList,
+                    rti,
+                    table.oid(),
+                    &schema,
+                )?),
+                _ => return None,
+            };
+
+            //
+            // and every output column must be either the grouping column or an aggregate we
+            // can compute
+            //
+            let exprs = PgList::<pg_sys::Expr>::from_pg((*builder.path_target()).exprs);
+            let mut targets = Vec::with_capacity(exprs.len());
+            for expr in exprs.iter_ptr() {
+                if let Some(var) = nodecast!(Var, T_Var, expr) {
+                    let (group_var, _) = group_by.as_ref()?;
+                    if (*var).varno != (**group_var).varno
+                        || (*var).varattno != (**group_var).varattno
+                    {
+                        return None;
+                    }
+                    targets.push(TargetColumn::GroupKey);
+                } else if let Some(aggref) = nodecast!(Aggref, T_Aggref, expr) {
+                    targets.push(aggregate_target(aggref, rti, table.oid(), &schema)?);
+                } else {
+                    return None;
+                }
+            }
+
+            // one row without a GROUP BY, otherwise as many groups as Postgres estimates the
+            // input relation's rows have, which is how it estimates its own aggregation paths
+            let rows = match &group_by {
+                None => 1.0,
+                Some((group_var, _)) => {
+                    let mut group_exprs = PgList::<pg_sys::Node>::new();
+                    group_exprs.push((*group_var).cast());
+                    pg_sys::estimate_num_groups(
+                        root,
+                        group_exprs.into_pg(),
+                        (*input_rel).rows,
+                        std::ptr::null_mut(),
+                        std::ptr::null_mut(),
+                    )
+                }
+            };
+
+            builder.custom_private().set_heaprelid(table.oid());
+            builder.custom_private().set_indexrelid(bm25_index.oid());
+            builder
+                .custom_private()
+                .set_query(SearchQueryInput::from(&quals));
+            builder
+                .custom_private()
+                .set_group_by(group_by.map(|(_, field)| field));
+            builder.custom_private().set_targets(targets);
+
+            // the aggregation never visits the heap, so it's cheaper than any plan that does
+            let startup_cost = DEFAULT_STARTUP_COST;
+            let total_cost = startup_cost + (rows * pg_sys::cpu_tuple_cost);
+
+            builder = builder.set_rows(rows);
+            builder = builder.set_startup_cost(startup_cost);
+            builder = builder.set_total_cost(total_cost);
+
+            // indicate that we'll be doing projection ourselves
+            builder = builder.set_flag(Flags::Projection);
+
+            Some(builder.build())
+        }
+    }
+}
+
+/// A column we can read directly from the relation being aggregated
+unsafe fn is_plain_var(var: *mut pg_sys::Var, rti: pg_sys::Index) -> bool {
+    (*var).varno as pg_sys::Index == rti && (*var).varlevelsup == 0 && (*var).varattno > 0
+}
+
+/// Can the index produce values of this type from its fast fields?
+fn is_supported_type(typoid: pg_sys::Oid, allow_text: bool) -> bool {
+    match typoid {
+        pg_sys::INT2OID
+        | pg_sys::INT4OID
+        | pg_sys::INT8OID
+        | pg_sys::FLOAT4OID
+        | pg_sys::FLOAT8OID => true,
+        pg_sys::TEXTOID | pg_sys::VARCHAROID => allow_text,
+        _ => false,
+    }
+}
+
+/// Find the Var, and its index field, that a `GROUP BY` clause groups on
+unsafe fn group_by_var(
+    sgc: *mut pg_sys::SortGroupClause,
+    target_list: *mut pg_sys::List,
+    rti: pg_sys::Index,
+    heaprelid: pg_sys::Oid,
+    schema: &SearchIndexSchema,
+) -> Option<(*mut pg_sys::Var, FieldName)> {
+    let expr = pg_sys::get_sortgroupclause_expr(sgc, target_list);
+    let var = nodecast!(Var, T_Var, expr)?;
+    if !is_plain_var(var, rti) || !is_supported_type((*var).vartype, true) {
+        return None;
+    }
+
+    // the terms aggregation groups on the values as they are in the fast field, so text fields
+    // must not have been normalized
+    let field = fieldname_from_var(heaprelid, var, (*var).varattno)?;
+    schema
+        .search_field(&field)?
+        .is_raw_sortable()
+        .then_some((var, field))
+}
+
+/// Describe an aggregate function call as a [`TargetColumn`], if we can compute it
+unsafe fn aggregate_target(
+    aggref: *mut pg_sys::Aggref,
+    rti: pg_sys::Index,
+    heaprelid: pg_sys::Oid,
+    schema: &SearchIndexSchema,
+) -> Option<TargetColumn> {
+    if (*aggref).aggkind as u8 != pg_sys::AGGKIND_NORMAL
+        || (*aggref).agglevelsup != 0
+        || !(*aggref).aggdistinct.is_null()
+        || !(*aggref).aggorder.is_null()
+        || !(*aggref).aggfilter.is_null()
+        || (*aggref).aggsplit != pg_sys::AggSplit::AGGSPLIT_SIMPLE
+    {
+        return None;
+    }
+
+    // tantivy computes everything as f64s, so the result must be a plain integer or float.
+    // notably this rules out `AVG(int)` and `SUM(bigint)`, whose `numeric` results Postgres
+    // computes exactly
+    if !is_supported_type((*aggref).aggtype, false) {
+        return None;
+    }
+
+    // only the builtin aggregates, not user-defined ones that happen to share their names
+    let namespace = pg_sys::get_func_namespace((*aggref).aggfnoid);
+    if CStr::from_ptr(pg_sys::get_namespace_name(namespace)) != c"pg_catalog" {
+        return None;
+    }
+    let name = CStr::from_ptr(pg_sys::get_func_name((*aggref).aggfnoid));
+
+    if (*aggref).aggstar {
+        return (name == c"count").then_some(TargetColumn::Aggregate {
+            kind: AggregateKind::CountStar,
+            field: None,
+        });
+    }
+
+    let kind = match name.to_str().ok()? {
+        "count" => AggregateKind::Count,
+        "sum" => AggregateKind::Sum,
+        "avg" => AggregateKind::Avg,
+        "min" => AggregateKind::Min,
+        "max" => AggregateKind::Max,
+        _ => return None,
+    };
+
+    let args = PgList::<pg_sys::TargetEntry>::from_pg((*aggref).args);
+    if args.len() != 1 {
+        return None;
+    }
+    let var = nodecast!(Var, T_Var, (*args.get_ptr(0)?).expr)?;
+    if !is_plain_var(var, rti) || !is_supported_type((*var).vartype, false) {
+        return None;
+    }
+
+    // a bigint above 2^53 can't be represented exactly as an f64, so the only aggregate we can
+    // compute exactly over one is its count
+    if (*var).vartype == pg_sys::INT8OID && kind != AggregateKind::Count {
+        return None;
+    }
+
+    let field = fieldname_from_var(heaprelid, var, (*var).varattno)?;
+    schema
+        .search_field(&field)?
+        .is_numeric_fast()
+        .then_some(TargetColumn::Aggregate {
+            kind,
+            field: Some(field),
+        })
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::AsCStr;
+use crate::api::FieldName;
+use crate::query::SearchQueryInput;
+use pgrx::pg_sys::AsPgCStr;
+use pgrx::{pg_sys, PgList};
+use serde::{Deserialize, Serialize};
+
+/// The aggregate functions we know how to compute from the index
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggregateKind {
+    CountStar,
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// One column of the tuples produced by the scan, in target list order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TargetColumn {
+    /// The value of the `GROUP BY` field for the group
+    GroupKey,
+    /// An aggregate over the group, where `field` is `None` only for `COUNT(*)`
+    Aggregate {
+        kind: AggregateKind,
+        field: Option<FieldName>,
+    },
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct PrivateData {
+    heaprelid: Option<pg_sys::Oid>,
+    indexrelid: Option<pg_sys::Oid>,
+    query: Option<SearchQueryInput>,
+    group_by: Option<FieldName>,
+    targets: Vec<TargetColumn>,
+}
+
+impl From<*mut pg_sys::List> for PrivateData {
+    fn from(list: *mut pg_sys::List) -> Self {
+        unsafe {
+            let list = PgList::<pg_sys::Node>::from_pg(list);
+            let node = list.get_ptr(0).unwrap();
+            let content = node
+                .as_c_str()
+                .unwrap()
+                .to_str()
+                .expect("string node should be valid utf8");
+            serde_json::from_str(content).unwrap()
+        }
+    }
+}
+
+impl From<PrivateData> for *mut pg_sys::List {
+    fn from(value: PrivateData) -> Self {
+        let content = serde_json::to_string(&value).unwrap();
+        unsafe {
+            let mut ser = PgList::new();
+            ser.push(pg_sys::makeString(content.as_pg_cstr()).cast::<pg_sys::Node>());
+            ser.into_pg()
+        }
+    }
+}
+
+//
+// setter functions
+//
+
+impl PrivateData {
+    pub fn set_heaprelid(&mut self, oid: pg_sys::Oid) {
+        self.heaprelid = Some(oid);
+    }
+
+    pub fn set_indexrelid(&mut self, oid: pg_sys::Oid) {
+        self.indexrelid = Some(oid);
+    }
+
+    pub fn set_query(&mut self, query: SearchQueryInput) {
+        self.query = Some(query);
+    }
+
+    pub fn set_group_by(&mut self, group_by: Option<FieldName>) {
+        self.group_by = group_by;
+    }
+
+    pub fn set_targets(&mut self, targets: Vec<TargetColumn>) {
+        self.targets = targets;
+    }
+}
+
+//
+// getter functions
+//
+
+impl PrivateData {
+    pub fn heaprelid(&self) -> Option<pg_sys::Oid> {
+        self.heaprelid
+    }
+
+    pub fn indexrelid(&self) -> Option<pg_sys::Oid> {
+        self.indexrelid
+    }
+
+    pub fn query(&self) -> &Option<SearchQueryInput> {
+        &self.query
+    }
+
+    pub fn group_by(&self) -> &Option<FieldName> {
+        &self.group_by
+    }
+
+    pub fn targets(&self) -> &[TargetColumn] {
+        &self.targets
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::FieldName;
+use crate::postgres::customscan::aggregatescan::privdat::{AggregateKind, TargetColumn};
+use crate::postgres::customscan::CustomScanState;
+use crate::postgres::rel::PgSearchRelation;
+use crate::query::SearchQueryInput;
+use pgrx::{name_data_to_str, pg_sys, IntoDatum, PgBuiltInOids, PgOid};
+use serde_json::json;
+use tantivy::aggregation::agg_result::{
+    AggregationResult, AggregationResults, BucketResult, MetricResult,
+};
+use tantivy::aggregation::metric::Stats;
+use tantivy::aggregation::Key;
+
+/// The name of the terms aggregation that does the grouping
+const GROUP_AGG_NAME: &str = "group";
+
+/// The name of the aggregation that counts the matching documents when there's no grouping
+const DOC_COUNT_AGG_NAME: &str = "doc_count";
+
+/// The bucket keys tantivy uses for documents that don't have a value for the grouping field,
+/// which are Postgres' `NULL` group.  The key is always of the other type than the field's values,
+/// a number for a text field and a string for a numeric field, so it can't be a real group's key.
+const NULL_GROUP_STR_KEY: &str = "NULL";
+const NULL_GROUP_NUMERIC_KEY: f64 = 0.0;
+
+/// The most groups the terms aggregation may return.  Anything more than this is an error rather
+/// than silently wrong results.
+pub const MAX_GROUPS: u32 = 65000;
+
+/// A single output value, before it's converted to the Datum type of its column
+#[derive(Debug, Clone)]
+pub enum AggregateValue {
+    Null,
+    Count(u64),
+    Number(f64),
+    Key(Key),
+}
+
+impl AggregateValue {
+    pub fn into_datum(self, typoid: pg_sys::Oid) -> Option<pg_sys::Datum> {
+        let number = match self {
+            AggregateValue::Null => return None,
+            AggregateValue::Count(count) => return (count as i64).into_datum(),
+            AggregateValue::Key(Key::Str(key)) => return key.into_datum(),
+            AggregateValue::Key(Key::I64(key)) => return int_into_datum(key, typoid),
+            AggregateValue::Key(Key::U64(key)) => return int_into_datum(key as i64, typoid),
+            AggregateValue::Key(Key::F64(key)) => key,
+            AggregateValue::Number(number) => number,
+        };
+
+        match PgOid::from(typoid) {
+            PgOid::BuiltIn(PgBuiltInOids::FLOAT4OID) => (number as f32).into_datum(),
+            PgOid::BuiltIn(PgBuiltInOids::FLOAT8OID) => number.into_datum(),
+            _ => int_into_datum(number as i64, typoid),
+        }
+    }
+}
+
+fn int_into_datum(value: i64, typoid: pg_sys::Oid) -> Option<pg_sys::Datum> {
+    match PgOid::from(typoid) {
+        PgOid::BuiltIn(PgBuiltInOids::INT2OID) => (value as i16).into_datum(),
+        PgOid::BuiltIn(PgBuiltInOids::INT4OID) => (value as i32).into_datum(),
+        PgOid::BuiltIn(PgBuiltInOids::INT8OID) => value.into_datum(),
+        PgOid::BuiltIn(PgBuiltInOids::FLOAT4OID) => (value as f32).into_datum(),
+        PgOid::BuiltIn(PgBuiltInOids::FLOAT8OID) => (value as f64).into_datum(),
+        other => pgrx::error!("aggregate scan cannot produce values of type {other:?}"),
+    }
+}
+
+#[derive(Default)]
+pub struct AggregateScanState {
+    pub heaprelid: pg_sys::Oid,
+    pub indexrelid: pg_sys::Oid,
+    pub heaprel: Option<PgSearchRelation>,
+    pub indexrel: Option<PgSearchRelation>,
+
+    pub query: SearchQueryInput,
+    pub group_by: Option<FieldName>,
+    pub targets: Vec<TargetColumn>,
+
+    /// The rows left to return, which are computed by the first call to `exec_custom_scan`
+    pub rows: Option<std::vec::IntoIter<Vec<AggregateValue>>>,
+}
+
+impl CustomScanState for AggregateScanState {
+    fn init_exec_method(&mut self, cstate: *mut pg_sys::CustomScanState) {
+        // no-op: the aggregates are computed all at once
+    }
+}
+
+impl AggregateScanState {
+    pub fn open_relations(&mut self, lockmode: pg_sys::LOCKMODE) {
+        if self.heaprel.is_none() {
+            self.heaprel = Some(PgSearchRelation::with_lock(self.heaprelid, lockmode));
+        }
+        if self.indexrel.is_none() {
+            self.indexrel = Some(PgSearchRelation::with_lock(self.indexrelid, lockmode));
+        }
+    }
+
+    #[inline(always)]
+    pub fn indexrel(&self) -> &PgSearchRelation {
+        self.indexrel
+            .as_ref()
+            .expect("AggregateScanState: indexrel should be initialized")
+    }
+
+    #[inline(always)]
+    pub fn heaprelname(&self) -> &str {
+        let heaprel = self
+            .heaprel
+            .as_ref()
+            .expect("AggregateScanState: heaprel should be initialized");
+        unsafe { name_data_to_str(&(*heaprel.rd_rel).relname) }
+    }
+
+    #[inline(always)]
+    pub fn indexrelname(&self) -> &str {
+        unsafe { name_data_to_str(&(*self.indexrel().rd_rel).relname) }
+    }
+
+    /// The distinct fields that are aggregated over, in the order their aggregations are named
+    fn metric_fields(&self) -> Vec<&FieldName> {
+        let mut fields = Vec::new();
+        for target in &self.targets {
+            if let TargetColumn::Aggregate {
+                field: Some(field), ..
+            } = target
+            {
+                if !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
+        }
+        fields
+    }
+
+    fn metric_name(&self, field: &FieldName) -> String {
+        self.metric_fields()
+            .iter()
+            .position(|f| *f == field)
+            .expect("field should be aggregated")
+            .to_string()
+    }
+
+    /// Whether the `GROUP BY` field is a text field, whose values are strings
+    fn group_by_is_text(&self) -> bool {
+        let Some(group_by) = &self.group_by else {
+            return false;
+        };
+        let schema = self
+            .indexrel()
+            .schema()
+            .unwrap_or_else(|err| pgrx::error!("aggregate scan: {err}"));
+        schema.search_field(group_by).is_some_and(|field| {
+            field.field_entry().field_type().value_type() == tantivy::schema::Type::Str
+        })
+    }
+
+    /// The tantivy aggregation request, as JSON, that computes every target.  Each aggregated
+    /// field gets a single `stats` aggregation, from which all of its aggregates are read.
+    pub fn aggregation_request(&self) -> serde_json::Value {
+        let mut metrics = serde_json::Map::new();
+        for (i, field) in self.metric_fields().into_iter().enumerate() {
+            metrics.insert(i.to_string(), json!({ "stats": { "field": field } }));
+        }
+
+        match &self.group_by {
+            Some(group_by) => json!({
+                GROUP_AGG_NAME: {
+                    "terms": {
+                        "field": group_by,
+                        "size": MAX_GROUPS,
+                        "segment_size": MAX_GROUPS,
+                        "missing": if self.group_by_is_text() {
+                            json!(NULL_GROUP_NUMERIC_KEY)
+                        } else {
+                            json!(NULL_GROUP_STR_KEY)
+                        },
+                    },
+                    "aggs": metrics,
+                }
+            }),
+            None => {
+                if self
+                    .targets
+                    .iter()
+                    .any(|target| matches!(target, TargetColumn::Aggregate { field: None, .. }))
+                {
+                    // every document has a ctid, so counting them counts the matching documents
+                    metrics.insert(
+                        DOC_COUNT_AGG_NAME.to_string(),
+                        json!({ "value_count": { "field": "ctid" } }),
+                    );
+                }
+                serde_json::Value::Object(metrics)
+            }
+        }
+    }
+
+    /// Turn the aggregation results into the rows this scan returns.  `results` is `None` when
+    /// the index has no segments at all.
+    pub fn make_rows(&self, results: Option<AggregationResults>) -> Vec<Vec<AggregateValue>> {
+        let Some(mut results) = results else {
+            return match self.group_by {
+                // no groups
+                Some(_) => vec![],
+                // one row of empty aggregates
+                None => vec![self.make_row(None, 0, &AggregationResults::default())],
+            };
+        };
+
+        if self.group_by.is_none() {
+            let doc_count = match results.0.get(DOC_COUNT_AGG_NAME) {
+                Some(AggregationResult::MetricResult(MetricResult::Count(count))) => {
+                    count.value.unwrap_or_default() as u64
+                }
+                _ => 0,
+            };
+            return vec![self.make_row(None, doc_count, &results)];
+        }
+
+        match results.0.remove(GROUP_AGG_NAME) {
+            Some(AggregationResult::BucketResult(BucketResult::Terms {
+                buckets,
+                sum_other_doc_count,
+                ..
+            })) => {
+                if sum_other_doc_count > 0 {
+                    pgrx::error!("aggregate scan: GROUP BY produced more than {MAX_GROUPS} groups");
+                }
+                let group_by_is_text = self.group_by_is_text();
+                buckets
+                    .into_iter()
+                    .map(|bucket| {
+                        // only the `NULL` group's key has a different type than the field's values
+                        let is_null_group = matches!(bucket.key, Key::Str(_)) != group_by_is_text;
+                        let key = (!is_null_group).then_some(bucket.key);
+                        self.make_row(key, bucket.doc_count, &bucket.sub_aggregation)
+                    })
+                    .collect()
+            }
+            other => pgrx::error!("aggregate scan: unexpected aggregation result: {other:?}"),
+        }
+    }
+
+    fn make_row(
+        &self,
+        key: Option<Key>,
+        doc_count: u64,
+        metrics: &AggregationResults,
+    ) -> Vec<AggregateValue> {
+        self.targets
+            .iter()
+            .map(|target| match target {
+                TargetColumn::GroupKey => key
+                    .clone()
+                    .map(AggregateValue::Key)
+                    .unwrap_or(AggregateValue::Null),
+                TargetColumn::Aggregate {
+                    kind: AggregateKind::CountStar,
+                    ..
+                } => AggregateValue::Count(doc_count),
+                TargetColumn::Aggregate {
+                    kind,
+                    field: Some(field),
+                } => {
+                    let stats = match metrics.0.get(&self.metric_name(field)) {
+                        Some(AggregationResult::MetricResult(MetricResult::Stats(stats))) => {
+                            stats.clone()
+                        }
+                        _ => Stats {
+                            count: 0,
+                            sum: 0.0,
+                            min: None,
+                            max: None,
+                            avg: None,
+                        },
+                    };
+                    stats_value(*kind, &stats)
+                }
+                TargetColumn::Aggregate { field: None, .. } => {
+                    unreachable!("only COUNT(*) has no field")
+                }
+            })
+            .collect()
+    }
+}
+
+fn stats_value(kind: AggregateKind, stats: &Stats) -> AggregateValue {
+    let number = |value: Option<f64>| {
+        value
+            .map(AggregateValue::Number)
+            .unwrap_or(AggregateValue::Null)
+    };
+
+    match kind {
+        AggregateKind::CountStar | AggregateKind::Count => AggregateValue::Count(stats.count),
+        // like Postgres, the sum of no values is NULL
+        AggregateKind::Sum => number((stats.count > 0).then_some(stats.sum)),
+        AggregateKind::Avg => number(stats.avg),
+        AggregateKind::Min => number(stats.min),
+        AggregateKind::Max => number(stats.max),
+    }
+}
@@ -19,6 +19,7 @@ use crate::api::Cardinality;
 use crate::api::FieldName;
 use crate::api::HashSet;
 use crate::index::fast_fields_helper::WhichFastField;
+use crate::index::reader::order_by::OrderByFeature;
 use crate::postgres::customscan::CustomScan;
 use pgrx::{pg_sys, PgList};
 use serde::{Deserialize, Serialize};
@@ -81,7 +82,7 @@ impl From<SortDirection> for u32 {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum OrderByStyle {
     Score(*mut pg_sys::PathKey),
     Field(*mut pg_sys::PathKey, FieldName),
@@ -95,6 +96,13 @@ impl OrderByStyle {
         }
     }
 
+    pub fn feature(&self) -> OrderByFeature {
+        match self {
+            OrderByStyle::Score(_) => OrderByFeature::Score,
+            OrderByStyle::Field(_, name) => OrderByFeature::Field(name.clone()),
+        }
+    }
+
     pub fn direction(&self) -> SortDirection {
         unsafe {
             let pathkey = self.pathkey();
@@ -251,6 +259,39 @@ impl<P: Into<*mut pg_sys::List> + Default> CustomPathBuilder<P> {
         }
     }
 
+    /// Start a path for an "upper" relation, such as the result of grouping and aggregation.
+    /// These aren't associated with any one range table entry, nor can they be parameterized.
+    pub fn new_upper<CS: CustomScan>(
+        root: *mut pg_sys::PlannerInfo,
+        rel: *mut pg_sys::RelOptInfo,
+    ) -> CustomPathBuilder<P> {
+        unsafe {
+            Self {
+                args: Args {
+                    root,
+                    rel,
+                    rti: 0,
+                    rte: std::ptr::null_mut(),
+                },
+                flags: Default::default(),
+
+                custom_path_node: pg_sys::CustomPath {
+                    path: pg_sys::Path {
+                        type_: pg_sys::NodeTag::T_CustomPath,
+                        pathtype: pg_sys::NodeTag::T_CustomScan,
+                        parent: rel,
+                        pathtarget: (*rel).reltarget,
+                        ..Default::default()
+                    },
+                    methods: CS::custom_path_methods(),
+                    ..Default::default()
+                },
+                custom_paths: PgList::default(),
+                custom_private: P::default(),
+            }
+        }
+    }
+
     pub fn args(&self) -> &Args {
         &self.args
     }
@@ -114,6 +114,13 @@ impl<P: Into<*mut pg_sys::List> + From<*mut pg_sys::List> + Default> CustomScanB
         &mut self.custom_private
     }
 
+    /// Describe the tuples this scan produces when it doesn't scan a single base relation, in
+    /// which case the plan's target list refers to these entries as `INDEX_VAR` Vars
+    pub fn set_custom_scan_tlist(mut self, tlist: *mut pg_sys::List) -> Self {
+        self.custom_scan_node.custom_scan_tlist = tlist;
+        self
+    }
+
     pub fn build(self) -> pg_sys::CustomScan {
         let mut node = self.custom_scan_node;
         node.custom_private = self.custom_private.into();
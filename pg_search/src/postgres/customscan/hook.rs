@@ -18,7 +18,7 @@
 use crate::api::HashMap;
 use crate::gucs;
 use crate::postgres::customscan::builders::custom_path::{CustomPathBuilder, Flags};
-use crate::postgres::customscan::CustomScan;
+use crate::postgres::customscan::{CustomScan, CustomUpperScan};
 use once_cell::sync::Lazy;
 use pgrx::{pg_guard, pg_sys, PgMemoryContexts};
 use std::collections::hash_map::Entry;
@@ -113,3 +113,68 @@ pub extern "C-unwind" fn paradedb_rel_pathlist_callback<CS: CustomScan>(
         }
     }
 }
+
+pub fn register_upper_path<CS: CustomUpperScan>(_: CS) {
+    unsafe {
+        static mut PREV_HOOKS: Lazy<
+            HashMap<std::any::TypeId, pg_sys::create_upper_paths_hook_type>,
+        > = Lazy::new(Default::default);
+
+        #[pg_guard]
+        extern "C-unwind" fn __priv_callback<CS: CustomUpperScan>(
+            root: *mut pg_sys::PlannerInfo,
+            stage: pg_sys::UpperRelationKind::Type,
+            input_rel: *mut pg_sys::RelOptInfo,
+            output_rel: *mut pg_sys::RelOptInfo,
+            extra: *mut std::ffi::c_void,
+        ) {
+            unsafe {
+                #[allow(static_mut_refs)]
+                if let Some(Some(prev_hook)) = PREV_HOOKS.get(&std::any::TypeId::of::<CS>()) {
+                    (*prev_hook)(root, stage, input_rel, output_rel, extra);
+                }
+
+                paradedb_upper_paths_callback::<CS>(root, stage, input_rel, output_rel, extra);
+            }
+        }
+
+        #[allow(static_mut_refs)]
+        match PREV_HOOKS.entry(std::any::TypeId::of::<CS>()) {
+            Entry::Occupied(_) => panic!("{} is already registered", std::any::type_name::<CS>()),
+            Entry::Vacant(entry) => entry.insert(pg_sys::create_upper_paths_hook),
+        };
+
+        pg_sys::create_upper_paths_hook = Some(__priv_callback::<CS>);
+
+        pg_sys::RegisterCustomScanMethods(CS::custom_scan_methods())
+    }
+}
+
+/// This hook is called once for each "stage" of upper-relation planning (grouping, window
+/// functions, DISTINCT, ordering, and so on), after Postgres has added its own paths to
+/// `output_rel`.  Like with [`paradedb_rel_pathlist_callback`], we only ever add a CustomPath.
+#[pg_guard]
+pub extern "C-unwind" fn paradedb_upper_paths_callback<CS: CustomUpperScan>(
+    root: *mut pg_sys::PlannerInfo,
+    stage: pg_sys::UpperRelationKind::Type,
+    input_rel: *mut pg_sys::RelOptInfo,
+    output_rel: *mut pg_sys::RelOptInfo,
+    extra: *mut std::ffi::c_void,
+) {
+    unsafe {
+        if !gucs::enable_custom_scan() {
+            return;
+        }
+
+        if let Some(mut path) = CS::create_upper_paths_callback(
+            CustomPathBuilder::new_upper::<CS>(root, output_rel),
+            stage,
+            input_rel,
+            extra,
+        ) {
+            let custom_path = PgMemoryContexts::CurrentMemoryContext
+                .copy_ptr_into(&mut path, std::mem::size_of_val(&path));
+            pg_sys::add_path(output_rel, custom_path.cast());
+        }
+    }
+}
@@ -20,7 +20,10 @@
 #![allow(unused_variables)]
 #![allow(clippy::tabs_in_doc_comments)]
 
+use crate::api::HashMap;
+use once_cell::sync::Lazy;
 use pgrx::{direct_function_call, pg_sys, IntoDatum, PgMemoryContexts};
+use std::any::TypeId;
 use std::ffi::{CStr, CString};
 
 mod builders;
@@ -30,6 +33,7 @@ mod hook;
 mod path;
 mod scan;
 
+pub mod aggregatescan;
 mod explainer;
 pub mod pdbscan;
 
@@ -46,14 +50,14 @@ use crate::postgres::customscan::builders::custom_state::{
 use crate::postgres::customscan::explainer::Explainer;
 use crate::postgres::customscan::path::{plan_custom_path, reparameterize_custom_path_by_child};
 use crate::postgres::customscan::scan::create_custom_scan_state;
-pub use hook::register_rel_pathlist;
+pub use hook::{register_rel_pathlist, register_upper_path};
 use std::ptr::NonNull;
 
 pub trait CustomScanState: Default {
     fn init_exec_method(&mut self, cstate: *mut pg_sys::CustomScanState);
 }
 
-pub trait CustomScan: ExecMethod + Default + Sized {
+pub trait CustomScan: ExecMethod + Default + Sized + 'static {
     const NAME: &'static CStr;
     type State: CustomScanState;
     type PrivateData: From<*mut pg_sys::List> + Into<*mut pg_sys::List> + Default;
@@ -62,18 +66,23 @@ pub trait CustomScan: ExecMethod + Default + Sized {
     // SAFETY:  We need to allocate the struct to define the functions once, however
     // all the methods are generic over this trait ([`CustomScan]).  Because Rust
     // monomorphizes these functions, they're actually at different addresses per CustomScan
-    // impl.  As such, we allocate them once, in Postgres "TopMemoryContext", which is **never**
-    // freed.  This ensures we don't waste any more memory than we need and more importantly,
-    // ensures the returned pointer holding the function pointers lives for the life of the
-    // process, which Postgres requires of these.
+    // impl.  As such, we allocate them once per impl, in Postgres "TopMemoryContext", which is
+    // **never** freed.  This ensures we don't waste any more memory than we need and more
+    // importantly, ensures the returned pointer holding the function pointers lives for the life
+    // of the process, which Postgres requires of these.
+    //
+    // Note that a `static` inside a generic function is shared by every monomorphization, so the
+    // allocations are keyed by the impl's type.
     //
 
     fn custom_path_methods() -> *const pg_sys::CustomPathMethods {
         unsafe {
-            static mut METHODS: *mut pg_sys::CustomPathMethods = std::ptr::null_mut();
+            static mut METHODS: Lazy<HashMap<TypeId, *mut pg_sys::CustomPathMethods>> =
+                Lazy::new(Default::default);
 
-            if METHODS.is_null() {
-                METHODS = PgMemoryContexts::TopMemoryContext.leak_and_drop_on_delete(
+            #[allow(static_mut_refs)]
+            *METHODS.entry(TypeId::of::<Self>()).or_insert_with(|| {
+                PgMemoryContexts::TopMemoryContext.leak_and_drop_on_delete(
                     pg_sys::CustomPathMethods {
                         CustomName: Self::NAME.as_ptr(),
                         PlanCustomPath: Some(plan_custom_path::<Self>),
@@ -81,9 +90,8 @@ pub trait CustomScan: ExecMethod + Default + Sized {
                             reparameterize_custom_path_by_child::<Self>,
                         ),
                     },
-                );
-            }
-            METHODS
+                )
+            })
         }
     }
 
@@ -132,6 +140,17 @@ pub trait CustomScan: ExecMethod + Default + Sized {
     fn end_custom_scan(state: &mut CustomScanStateWrapper<Self>);
 }
 
+/// A [`CustomScan`] that provides paths for the "upper" relations which Postgres plans after
+/// scans and joins, such as grouping and aggregation.
+pub trait CustomUpperScan: CustomScan {
+    fn create_upper_paths_callback(
+        builder: CustomPathBuilder<Self::PrivateData>,
+        stage: pg_sys::UpperRelationKind::Type,
+        input_rel: *mut pg_sys::RelOptInfo,
+        extra: *mut std::ffi::c_void,
+    ) -> Option<pg_sys::CustomPath>;
+}
+
 pub trait ExecMethod {
     fn exec_methods() -> *const pg_sys::CustomExecMethods;
 }
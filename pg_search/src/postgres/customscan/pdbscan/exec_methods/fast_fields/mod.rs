@@ -224,7 +224,10 @@ fn collect_fast_field_try_for_attno(
             // Get attribute info - use if let to handle missing attributes gracefully
             if let Some(att) = tupdesc.get((attno - 1) as usize) {
                 if let Some(search_field) = schema.search_field(att.name()) {
-                    if search_field.is_fast() {
+                    // points are stored as JSON objects and queries as serialized JSON, neither
+                    // of which can be read back as a column
+                    if search_field.is_fast() && !search_field.is_geo() && !search_field.is_query()
+                    {
                         let ff_type = if att.type_oid().value() == pg_sys::TEXTOID
                             || att.type_oid().value() == pg_sys::VARCHAROID
                             || att.type_oid().value() == pg_sys::UUIDOID
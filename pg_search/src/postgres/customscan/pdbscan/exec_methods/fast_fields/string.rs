@@ -346,7 +346,10 @@ mod term_ord_collector {
 
         fn collect(&mut self, doc: DocId, score: Score) {
             let doc_address = DocAddress::new(self.segment_ord, doc);
-            let ctid = self.ctid_ff.as_u64(doc).expect("ctid should be present");
+            // the child documents of nested fields have no ctid, as they aren't rows
+            let Some(ctid) = self.ctid_ff.as_u64(doc) else {
+                return;
+            };
             let scored = SearchIndexScore::new(ctid, score);
             self.results.push((
                 self.ff.term_ords(doc).next().unwrap_or(NULL_TERM_ORDINAL),
@@ -20,11 +20,13 @@ use std::iter::Peekable;
 
 use crate::api::FieldName;
 use crate::index::reader::index::{SearchIndexReader, SearchResults};
+use crate::index::reader::order_by::OrderByFeature;
 use crate::postgres::customscan::builders::custom_path::SortDirection;
 use crate::postgres::customscan::pdbscan::exec_methods::{ExecMethod, ExecState};
 use crate::postgres::customscan::pdbscan::parallel::checkout_segment;
 use crate::postgres::customscan::pdbscan::scan_state::PdbScanState;
 use crate::postgres::ParallelScanState;
+use crate::query::rescore::Rescorer;
 use crate::query::SearchQueryInput;
 use pgrx::{check_for_interrupts, direct_function_call, pg_sys, IntoDatum};
 use tantivy::index::SegmentId;
@@ -43,6 +45,8 @@ pub struct TopNScanExecState {
     search_query_input: Option<SearchQueryInput>,
     search_reader: Option<SearchIndexReader>,
     sort_field: Option<FieldName>,
+    sort_keys: Option<Vec<(OrderByFeature, SortDirection)>>,
+    rescorer: Option<Rescorer>,
 
     // state tracking
     search_results: Peekable<SearchResults>,
@@ -64,6 +68,8 @@ impl TopNScanExecState {
             search_query_input: None,
             search_reader: None,
             sort_field: None,
+            sort_keys: None,
+            rescorer: None,
             search_results: SearchResults::None.peekable(),
             nresults: 0,
             did_query: false,
@@ -135,7 +141,13 @@ impl ExecMethod for TopNScanExecState {
 
         self.search_query_input = Some(state.search_query_input().clone());
         self.sort_field = sort_field;
+        self.sort_keys = state.sort_keys.clone();
         self.search_reader = state.search_reader.clone();
+        self.rescorer = self.search_reader.as_ref().and_then(|search_reader| {
+            search_reader
+                .rescorer(state.search_query_input())
+                .unwrap_or_else(|err| pgrx::error!("{err}"))
+        });
     }
 
     ///
@@ -160,18 +172,56 @@ impl ExecMethod for TopNScanExecState {
         let local_limit = self.limit.max(self.chunk_size);
         let next_offset = self.offset + local_limit;
 
-        self.search_results = state
-            .search_reader
-            .as_ref()
-            .unwrap()
-            .search_top_n_in_segments(
-                self.segments_to_query(state.search_reader.as_ref().unwrap(), state.parallel_state),
+        // any window aggregations are computed in the same pass as the first query's "top N".
+        // retries see the same matching documents, so they don't compute them again
+        let window_agg_collector = state.window_agg_collector();
+
+        let search_reader = state.search_reader.as_ref().unwrap();
+        let segment_ids = self.segments_to_query(search_reader, state.parallel_state);
+        let (search_results, window_aggs) = match (&self.sort_keys, &self.rescorer) {
+            // rescoring only applies when the results are ordered by score, most relevant first
+            (None, Some(rescorer))
+                if self.sort_field.is_none()
+                    && matches!(self.sort_direction, SortDirection::Desc) =>
+            {
+                search_reader
+                    .search_top_n_rescored_in_segments(
+                        segment_ids,
+                        rescorer,
+                        local_limit,
+                        self.offset,
+                        window_agg_collector,
+                    )
+                    .unwrap_or_else(|err| pgrx::error!("{err}"))
+            }
+            (Some(sort_keys), _) => search_reader
+                .search_top_n_compound_in_segments(
+                    segment_ids,
+                    &sort_keys
+                        .iter()
+                        .map(|(feature, sort_direction)| {
+                            (feature.clone(), (*sort_direction).into())
+                        })
+                        .collect::<Vec<_>>(),
+                    local_limit,
+                    self.offset,
+                    window_agg_collector,
+                )
+                .unwrap_or_else(|err| pgrx::error!("{err}")),
+            (None, _) => search_reader.search_top_n_in_segments(
+                segment_ids,
                 self.sort_field.clone(),
                 self.sort_direction.into(),
                 local_limit,
                 self.offset,
-            )
-            .peekable();
+                window_agg_collector,
+            ),
+        };
+        self.search_results = search_results.peekable();
+
+        if let Some(window_aggs) = window_aggs {
+            state.set_window_agg_results(window_aggs);
+        }
 
         // Record the offset to start from for the next query.
         self.offset = next_offset;
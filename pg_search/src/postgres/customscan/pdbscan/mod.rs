@@ -20,19 +20,20 @@ mod exec_methods;
 mod opexpr;
 pub mod parallel;
 mod privdat;
-mod projections;
+pub mod projections;
 mod pushdown;
-mod qual_inspect;
+pub mod qual_inspect;
 mod scan_state;
 mod solve_expr;
 
 use crate::api::operator::{anyelement_query_input_opoid, estimate_selectivity};
 use crate::api::Cardinality;
-use crate::api::{HashMap, HashSet};
+use crate::api::{FieldName, HashMap, HashSet};
 use crate::gucs;
 use crate::index::fast_fields_helper::WhichFastField;
 use crate::index::mvcc::MvccSatisfies;
 use crate::index::reader::index::SearchIndexReader;
+use crate::index::reader::order_by::OrderByFeature;
 use crate::postgres::customscan::builders::custom_path::{
     CustomPathBuilder, ExecMethodType, Flags, OrderByStyle, RestrictInfoType, SortDirection,
 };
@@ -47,11 +48,17 @@ use crate::postgres::customscan::pdbscan::exec_methods::{
 };
 use crate::postgres::customscan::pdbscan::parallel::{compute_nworkers, list_segment_ids};
 use crate::postgres::customscan::pdbscan::privdat::PrivateData;
+use crate::postgres::customscan::pdbscan::projections::matched_queries::{
+    matched_queries_funcoid, uses_matched_queries,
+};
 use crate::postgres::customscan::pdbscan::projections::score::{
     is_score_func, score_funcoid, uses_scores,
 };
 use crate::postgres::customscan::pdbscan::projections::snippet::{
-    snippet_funcoid, snippet_positions_funcoid, uses_snippets, SnippetType,
+    snippet_funcoid, snippet_positions_funcoid, snippets_funcoid, uses_snippets, SnippetType,
+};
+use crate::postgres::customscan::pdbscan::projections::window_agg::{
+    uses_window_aggs, window_agg_funcoid,
 };
 use crate::postgres::customscan::pdbscan::projections::{
     inject_placeholders, maybe_needs_const_projections, pullout_funcexprs,
@@ -70,7 +77,7 @@ use crate::schema::SearchIndexSchema;
 use crate::{nodecast, DEFAULT_STARTUP_COST, PARAMETERIZED_SELECTIVITY, UNKNOWN_SELECTIVITY};
 use crate::{FULL_RELATION_SELECTIVITY, UNASSIGNED_SELECTIVITY};
 use pgrx::pg_sys::CustomExecMethods;
-use pgrx::{direct_function_call, pg_sys, IntoDatum, PgList, PgMemoryContexts, PgRelation};
+use pgrx::{direct_function_call, pg_sys, IntoDatum, JsonB, PgList, PgMemoryContexts, PgRelation};
 use std::ffi::CStr;
 use std::ptr::addr_of_mut;
 use std::sync::atomic::Ordering;
@@ -122,6 +129,16 @@ impl PdbScan {
         let csstate = addr_of_mut!(state.csstate);
         state.custom_state_mut().init_exec_method(csstate);
 
+        if state.custom_state().need_matched_queries() {
+            let named_query_matcher = state
+                .custom_state()
+                .search_reader
+                .as_ref()
+                .unwrap()
+                .named_query_matcher(state.custom_state().search_query_input());
+            state.custom_state_mut().named_query_matcher = Some(named_query_matcher);
+        }
+
         if state.custom_state().need_snippets() {
             let mut snippet_generators: HashMap<
                 SnippetType,
@@ -146,12 +163,28 @@ impl PdbScan {
                     None
                 };
 
+            let mut fragment_generators = HashMap::default();
             for (snippet_type, generator) in &mut snippet_generators {
                 // Use enhanced query if available, otherwise use base query
                 let query_to_use = enhanced_query_for_snippets
                     .as_ref()
                     .unwrap_or_else(|| state.custom_state().search_query_input());
 
+                if let SnippetType::Fragments(_, _, config) = snippet_type {
+                    let fragment_generator = state
+                        .custom_state()
+                        .search_reader
+                        .as_ref()
+                        .unwrap()
+                        .fragment_generator(
+                            snippet_type.field(),
+                            query_to_use.clone(),
+                            config.fragment_size,
+                        );
+                    fragment_generators.insert(snippet_type.clone(), fragment_generator);
+                    continue;
+                }
+
                 let mut new_generator = state
                     .custom_state()
                     .search_reader
@@ -164,6 +197,9 @@ impl PdbScan {
                 let max_num_chars = match snippet_type {
                     SnippetType::Text(_, _, config) => config.max_num_chars,
                     SnippetType::Positions(_, _) => u32::MAX as usize,
+                    SnippetType::Fragments(..) => {
+                        unreachable!("fragments have their own generator")
+                    }
                 };
                 new_generator.1.set_max_num_chars(max_num_chars);
 
@@ -171,6 +207,7 @@ impl PdbScan {
             }
 
             state.custom_state_mut().snippet_generators = snippet_generators;
+            state.custom_state_mut().fragment_generators = fragment_generators;
         }
 
         unsafe {
@@ -178,7 +215,7 @@ impl PdbScan {
         }
     }
 
-    fn cleanup_varibilities_from_tantivy_query(json_value: &mut serde_json::Value) {
+    pub fn cleanup_varibilities_from_tantivy_query(json_value: &mut serde_json::Value) {
         match json_value {
             serde_json::Value::Object(obj) => {
                 // Check if this is a "with_index" object and remove its "oid" if present
@@ -345,7 +382,8 @@ impl CustomScan for PdbScan {
             let schema = bm25_index
                 .schema()
                 .expect("custom_scan: should have a schema");
-            let pathkey = pullup_orderby_pathkey(&mut builder, rti, &schema, root);
+            let pathkeys = pullup_orderby_pathkeys(&mut builder, rti, &schema, root);
+            let pathkey = pathkeys.first().cloned();
 
             #[cfg(any(feature = "pg14", feature = "pg15"))]
             let baserels = (*builder.args().root).all_baserels;
@@ -372,6 +410,8 @@ impl CustomScan for PdbScan {
             // quick look at the target list to see if we might need to do our const projections
             let target_list = (*(*builder.args().root).parse).targetList;
             let maybe_needs_const_projections = maybe_needs_const_projections(target_list.cast());
+            let needs_window_aggs =
+                !uses_window_aggs(target_list.cast(), window_agg_funcoid()).is_empty();
 
             // Get all columns referenced by this RTE throughout the entire query
             let referenced_columns = collect_maybe_fast_field_referenced_columns(rti, rel);
@@ -383,6 +423,14 @@ impl CustomScan for PdbScan {
 
             let is_topn = limit.is_some() && pathkey.is_some();
 
+            // we can only sort by more than one key if we can sort by all of them, otherwise
+            // TopN would cut off rows which are tied on the keys we do support
+            let is_compound_topn = is_topn
+                && pathkeys.len() > 1
+                && pathkeys.len()
+                    == PgList::<pg_sys::PathKey>::from_pg((*builder.args().root).query_pathkeys)
+                        .len();
+
             // When collecting which_fast_fields, analyze the entire set of referenced columns,
             // not just those in the target list. To avoid execution-time surprises, the "planned"
             // fast fields must be a superset of the fast fields which are extracted from the
@@ -430,7 +478,23 @@ impl CustomScan for PdbScan {
                 }
             }
 
+            // window aggregations are computed over every document the index matches, which are
+            // only the rows of the result if the index answers the entire WHERE clause of a query
+            // over this one relation.  otherwise the `paradedb.window_agg()` placeholder is left
+            // for Postgres to evaluate, and it raises an error
+            if needs_window_aggs
+                && (!matches!(ri_type, RestrictInfoType::BaseRelation)
+                    || !pg_sys::bms_equal((*rel).relids, baserels)
+                    || quals.contains_heap_expr()
+                    || quals.contains_external_var()
+                    || quals.contains_exec_param())
+            {
+                return None;
+            }
+
             let query = SearchQueryInput::from(&quals);
+            check_search_after_order(root, is_topn, &pathkeys, &query, &schema.key_field_name());
+            check_rescore_order(root, is_topn, &pathkeys, &query);
             let norm_selec = if restrict_info.len() == 1 {
                 (*restrict_info.get_ptr(0).unwrap()).norm_selec
             } else {
@@ -470,7 +534,11 @@ impl CustomScan for PdbScan {
             builder.custom_private().set_limit(limit);
             builder.custom_private().set_segment_count(segment_count);
 
-            if is_topn && pathkey.is_some() {
+            if is_compound_topn {
+                // unlike a single field, compound sorts collect scores alongside the fast field
+                // values, so they work regardless of whether we're doing const projections
+                builder.custom_private().set_compound_sort_info(&pathkeys);
+            } else if is_topn && pathkey.is_some() {
                 let pathkey = pathkey.as_ref().unwrap();
                 // sorting by a field only works if we're not doing const projections
                 // the reason for this is that tantivy can't do both scoring and ordering by
@@ -498,7 +566,9 @@ impl CustomScan for PdbScan {
                     .set_sort_direction(Some(SortDirection::None));
             }
 
-            let nworkers = if (*builder.args().rel).consider_parallel {
+            // window aggregations are computed over every matching document, which a parallel
+            // worker doesn't see
+            let nworkers = if (*builder.args().rel).consider_parallel && !needs_window_aggs {
                 compute_nworkers(limit, segment_count, builder.custom_private().is_sorted())
             } else {
                 0
@@ -558,7 +628,11 @@ impl CustomScan for PdbScan {
             // Once we have chosen an execution method type, we have a final determination of the
             // properties of the output, and can make claims about whether it is sorted.
             if builder.custom_private().exec_method_type().is_sorted() {
-                if let Some(pathkey) = pathkey.as_ref() {
+                if builder.custom_private().sort_keys().is_some() {
+                    for pathkey in &pathkeys {
+                        builder = builder.add_path_key(pathkey);
+                    }
+                } else if let Some(pathkey) = pathkey.as_ref() {
                     builder = builder.add_path_key(pathkey);
                 }
             }
@@ -626,12 +700,20 @@ impl CustomScan for PdbScan {
 
             let mut attname_lookup = HashMap::default();
             let score_funcoid = score_funcoid();
+            let matched_queries_funcoid = matched_queries_funcoid();
             let snippet_funcoid = snippet_funcoid();
             let snippet_positions_funcoid = snippet_positions_funcoid();
+            let snippets_funcoid = snippets_funcoid();
             for te in processed_tlist.iter_ptr() {
                 let func_vars_at_level = pullout_funcexprs(
                     te.cast(),
-                    &[score_funcoid, snippet_funcoid, snippet_positions_funcoid],
+                    &[
+                        score_funcoid,
+                        matched_queries_funcoid,
+                        snippet_funcoid,
+                        snippet_positions_funcoid,
+                        snippets_funcoid,
+                    ],
                     rti,
                     builder.args().root,
                 );
@@ -721,6 +803,7 @@ impl CustomScan for PdbScan {
             builder.custom_state().limit = builder.custom_private().limit();
             builder.custom_state().sort_field = builder.custom_private().sort_field();
             builder.custom_state().sort_direction = builder.custom_private().sort_direction();
+            builder.custom_state().sort_keys = builder.custom_private().sort_keys();
 
             builder.custom_state().segment_count = builder.custom_private().segment_count();
             builder.custom_state().var_attname_lookup = builder
@@ -733,16 +816,31 @@ impl CustomScan for PdbScan {
             let score_funcoid = score_funcoid();
             let snippet_funcoid = snippet_funcoid();
             let snippet_positions_funcoid = snippet_positions_funcoid();
+            let snippets_funcoid = snippets_funcoid();
 
             builder.custom_state().score_funcoid = score_funcoid;
             builder.custom_state().snippet_funcoid = snippet_funcoid;
             builder.custom_state().snippet_positions_funcoid = snippet_positions_funcoid;
+            builder.custom_state().snippets_funcoid = snippets_funcoid;
             builder.custom_state().need_scores = uses_scores(
                 builder.target_list().as_ptr().cast(),
                 score_funcoid,
                 builder.custom_state().execution_rti,
             );
 
+            let matched_queries_funcoid = matched_queries_funcoid();
+            builder.custom_state().matched_queries_funcoid = matched_queries_funcoid;
+            builder.custom_state().need_matched_queries = uses_matched_queries(
+                builder.target_list().as_ptr().cast(),
+                matched_queries_funcoid,
+                builder.custom_state().execution_rti,
+            );
+
+            let window_agg_funcoid = window_agg_funcoid();
+            builder.custom_state().window_agg_funcoid = window_agg_funcoid;
+            builder.custom_state().window_aggs =
+                uses_window_aggs(builder.target_list().as_ptr().cast(), window_agg_funcoid);
+
             // Store join snippet predicates in the scan state
             builder.custom_state().join_predicates =
                 builder.custom_private().join_predicates().clone();
@@ -800,6 +898,7 @@ impl CustomScan for PdbScan {
                 node,
                 snippet_funcoid,
                 snippet_positions_funcoid,
+                snippets_funcoid,
             )
             .into_iter()
             .map(|field| (field, None))
@@ -859,18 +958,32 @@ impl CustomScan for PdbScan {
 
         explainer.add_bool("Scores", state.custom_state().need_scores());
         if state.custom_state().is_sorted() {
-            if let Some(sort_field) = &state.custom_state().sort_field {
-                explainer.add_text("   Sort Field", sort_field);
+            if let Some(sort_keys) = &state.custom_state().sort_keys {
+                explainer.add_text(
+                    "   Sort Keys",
+                    sort_keys
+                        .iter()
+                        .map(|(feature, sort_direction)| match feature {
+                            OrderByFeature::Score => format!("paradedb.score() {sort_direction}"),
+                            OrderByFeature::Field(field) => format!("{field} {sort_direction}"),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
             } else {
-                explainer.add_text("   Sort Field", "paradedb.score()");
+                if let Some(sort_field) = &state.custom_state().sort_field {
+                    explainer.add_text("   Sort Field", sort_field);
+                } else {
+                    explainer.add_text("   Sort Field", "paradedb.score()");
+                }
+                explainer.add_text(
+                    "   Sort Direction",
+                    state
+                        .custom_state()
+                        .sort_direction
+                        .unwrap_or(SortDirection::Asc),
+                );
             }
-            explainer.add_text(
-                "   Sort Direction",
-                state
-                    .custom_state()
-                    .sort_direction
-                    .unwrap_or(SortDirection::Asc),
-            );
         }
 
         if let Some(limit) = state.custom_state().limit {
@@ -884,6 +997,14 @@ impl CustomScan for PdbScan {
             }
         }
 
+        if state.custom_state().need_window_aggs() {
+            explainer.add_text(
+                "Window Aggregates",
+                serde_json::to_string(&state.custom_state().window_aggs)
+                    .expect("window aggregates should serialize to json"),
+            );
+        }
+
         let mut json_value = state
             .custom_state()
             .query_to_json()
@@ -1009,23 +1130,29 @@ impl CustomScan for PdbScan {
                         };
 
                         if !state.custom_state().need_scores()
+                            && !state.custom_state().need_matched_queries()
                             && !state.custom_state().need_snippets()
+                            && !state.custom_state().need_window_aggs()
                         {
                             //
-                            // we don't need scores or snippets
-                            // do the projection and return
+                            // we don't need scores, matched queries, snippets, or window
+                            // aggregations do the projection and return
                             //
 
                             (*(*state.projection_info()).pi_exprContext).ecxt_scantuple = slot;
                             return pg_sys::ExecProject(state.projection_info());
                         } else {
                             //
-                            // we do need scores or snippets
+                            // we do need scores, matched queries, snippets, or window aggregations
                             //
                             // replace their placeholder values and then rebuild the ProjectionInfo
                             // and project it
                             //
 
+                            // the window aggregations are the same for every row, so their
+                            // placeholders are filled in once, outside the per-tuple context
+                            state.custom_state_mut().ensure_window_aggs();
+
                             let mut per_tuple_context = PgMemoryContexts::For(
                                 (*(*state.projection_info()).pi_exprContext).ecxt_per_tuple_memory,
                             );
@@ -1040,6 +1167,18 @@ impl CustomScan for PdbScan {
                                 (*const_score_node).constisnull = false;
                             }
 
+                            if state.custom_state().need_matched_queries() {
+                                let matched = state.custom_state_mut().matched_queries(doc_address);
+                                per_tuple_context.switch_to(|_| {
+                                    for const_ in &state.custom_state().const_matched_queries_nodes
+                                    {
+                                        (**const_).constvalue =
+                                            matched.clone().into_datum().unwrap();
+                                        (**const_).constisnull = false;
+                                    }
+                                });
+                            }
+
                             if state.custom_state().need_snippets() {
                                 per_tuple_context.switch_to(|_| {
                                     for (snippet_type, const_snippet_nodes) in
@@ -1088,6 +1227,28 @@ impl CustomScan for PdbScan {
                                                     }
                                                 }
                                             }
+                                            SnippetType::Fragments(..) => {
+                                                let fragments = state
+                                                    .custom_state()
+                                                    .make_fragments(ctid, snippet_type);
+
+                                                for const_ in const_snippet_nodes {
+                                                    match &fragments {
+                                                        Some(fragments) => {
+                                                            (**const_).constvalue =
+                                                                JsonB(fragments.clone())
+                                                                    .into_datum()
+                                                                    .unwrap();
+                                                            (**const_).constisnull = false;
+                                                        }
+                                                        None => {
+                                                            (**const_).constvalue =
+                                                                pg_sys::Datum::null();
+                                                            (**const_).constisnull = true;
+                                                        }
+                                                    }
+                                                }
+                                            }
                                         }
                                     }
                                 });
@@ -1131,6 +1292,10 @@ impl CustomScan for PdbScan {
         drop(std::mem::take(
             &mut state.custom_state_mut().snippet_generators,
         ));
+        drop(std::mem::take(
+            &mut state.custom_state_mut().fragment_generators,
+        ));
+        drop(state.custom_state_mut().named_query_matcher.take());
         drop(std::mem::take(&mut state.custom_state_mut().search_results));
 
         state.custom_state_mut().heaprel.take();
@@ -1338,87 +1503,203 @@ fn check_visibility(
 }
 
 unsafe fn inject_score_and_snippet_placeholders(state: &mut CustomScanStateWrapper<PdbScan>) {
-    if !state.custom_state().need_scores() && !state.custom_state().need_snippets() {
-        // scores/snippets aren't necessary so we use whatever we originally setup as our ProjectionInfo
+    if !state.custom_state().need_scores()
+        && !state.custom_state().need_matched_queries()
+        && !state.custom_state().need_snippets()
+        && !state.custom_state().need_window_aggs()
+    {
+        // scores/matched queries/snippets/window aggregations aren't necessary so we use whatever
+        // we originally setup as our ProjectionInfo
         return;
     }
 
-    // inject score and/or snippet placeholder [`pg_sys::Const`] nodes into what is a copy of the Plan's
-    // targetlist.  We store this in our custom state's "placeholder_targetlist" for use during the
-    // forced projection we must do later.
+    // inject score, matched queries, snippet, and/or window aggregation placeholder
+    // [`pg_sys::Const`] nodes into what is a copy of the Plan's targetlist.  We store this in our
+    // custom state's "placeholder_targetlist" for use during the forced projection we must do later.
     let planstate = state.planstate();
 
-    let (targetlist, const_score_node, const_snippet_nodes) = inject_placeholders(
+    let (
+        targetlist,
+        const_score_node,
+        const_matched_queries_nodes,
+        const_snippet_nodes,
+        const_window_agg_nodes,
+    ) = inject_placeholders(
         (*(*planstate).plan).targetlist,
         state.custom_state().planning_rti,
         state.custom_state().score_funcoid,
+        state.custom_state().matched_queries_funcoid,
         state.custom_state().snippet_funcoid,
         state.custom_state().snippet_positions_funcoid,
+        state.custom_state().snippets_funcoid,
+        state.custom_state().window_agg_funcoid,
         &state.custom_state().var_attname_lookup,
         &state.custom_state().snippet_generators,
     );
 
     state.custom_state_mut().placeholder_targetlist = Some(targetlist);
     state.custom_state_mut().const_score_node = Some(const_score_node);
+    state.custom_state_mut().const_matched_queries_nodes = const_matched_queries_nodes;
     state.custom_state_mut().const_snippet_nodes = const_snippet_nodes;
+    state.custom_state_mut().const_window_agg_nodes = const_window_agg_nodes;
 }
 
-unsafe fn pullup_orderby_pathkey<P: Into<*mut pg_sys::List> + Default>(
+/// Pull up the longest prefix of the query's `ORDER BY` whose keys we're able to sort by.
+///
+/// Keys after the first are only considered when every key uses Postgres' default NULLS ordering,
+/// as that's the only ordering a compound sort knows how to produce.
+unsafe fn pullup_orderby_pathkeys<P: Into<*mut pg_sys::List> + Default>(
     builder: &mut CustomPathBuilder<P>,
     rti: pg_sys::Index,
     schema: &SearchIndexSchema,
     root: *mut pg_sys::PlannerInfo,
-) -> Option<OrderByStyle> {
+) -> Vec<OrderByStyle> {
     let pathkeys = PgList::<pg_sys::PathKey>::from_pg((*builder.args().root).query_pathkeys);
+    let mut styles: Vec<OrderByStyle> = Vec::new();
 
-    if let Some(first_pathkey) = pathkeys.get_ptr(0) {
-        let equivclass = (*first_pathkey).pk_eclass;
-        let members = PgList::<pg_sys::EquivalenceMember>::from_pg((*equivclass).ec_members);
+    for pathkey in pathkeys.iter_ptr() {
+        let Some(style) = orderby_style(pathkey, rti, schema, root) else {
+            break;
+        };
+        if let Some(first) = styles.first() {
+            if !has_default_nulls_order(first) || !has_default_nulls_order(&style) {
+                break;
+            }
+        }
+        styles.push(style);
+    }
+    styles
+}
 
-        for member in members.iter_ptr() {
-            let expr = (*member).em_expr;
+/// A `search_after` cursor is only meaningful in a TopN scan in the order it was taken from:
+/// `paradedb.score()` descending and then the key field ascending, or the key field ascending
+/// alone when it has no score.  In any other plan it would skip the wrong rows, so that's an
+/// error rather than wrong results.
+unsafe fn check_search_after_order(
+    root: *mut pg_sys::PlannerInfo,
+    is_topn: bool,
+    pathkeys: &[OrderByStyle],
+    query: &SearchQueryInput,
+    key_field: &FieldName,
+) {
+    let Some(SearchQueryInput::SearchAfter { last_score, .. }) = query
+        .search_after()
+        .unwrap_or_else(|err| pgrx::error!("{err}"))
+    else {
+        return;
+    };
 
-            if is_score_func(expr.cast(), rti as _) {
-                return Some(OrderByStyle::Score(first_pathkey));
-            } else if let Some(var) = is_lower_func(expr.cast(), rti as _) {
-                let (heaprelid, attno, _) = find_var_relation(var, root);
-                let heaprel = PgRelation::with_lock(heaprelid, pg_sys::AccessShareLock as _);
-                let tupdesc = heaprel.tuple_desc();
-                if let Some(att) = tupdesc.get(attno as usize - 1) {
-                    if let Some(search_field) = schema.search_field(att.name()) {
-                        if search_field.is_lower_sortable() {
-                            return Some(OrderByStyle::Field(first_pathkey, att.name().into()));
-                        }
-                    }
-                }
-            } else if let Some(relabel) = nodecast!(RelabelType, T_RelabelType, expr) {
-                if let Some(var) = nodecast!(Var, T_Var, (*relabel).arg) {
-                    let (heaprelid, attno, _) = find_var_relation(var, root);
-                    let heaprel = PgRelation::with_lock(heaprelid, pg_sys::AccessShareLock as _);
-                    let tupdesc = heaprel.tuple_desc();
-                    if let Some(att) = tupdesc.get(attno as usize - 1) {
-                        if let Some(search_field) = schema.search_field(att.name()) {
-                            if search_field.is_raw_sortable() {
-                                return Some(OrderByStyle::Field(first_pathkey, att.name().into()));
-                            }
-                        }
+    let is_key_asc = |style: &OrderByStyle| {
+        style.feature() == OrderByFeature::Field(key_field.clone())
+            && matches!(style.direction(), SortDirection::Asc)
+    };
+    let is_expected_order = match (last_score, pathkeys) {
+        (Some(_), [score, key]) => {
+            matches!(score, OrderByStyle::Score(_))
+                && matches!(score.direction(), SortDirection::Desc)
+                && is_key_asc(key)
+        }
+        (None, [key]) => is_key_asc(key),
+        _ => false,
+    };
+    let query_pathkeys = PgList::<pg_sys::PathKey>::from_pg((*root).query_pathkeys);
+
+    if !is_topn || !is_expected_order || query_pathkeys.len() != pathkeys.len() {
+        match last_score {
+            Some(_) => pgrx::error!(
+                "search_after with a last_score requires ORDER BY paradedb.score() DESC, {key_field} and a LIMIT"
+            ),
+            None => pgrx::error!(
+                "search_after without a last_score requires ORDER BY {key_field} and a LIMIT"
+            ),
+        }
+    }
+}
+
+/// A `rescore` query only rescores the results of a TopN scan ordered by `paradedb.score()`
+/// descending.  In any other plan it would quietly score rows like its first-phase query, so
+/// that's an error instead.
+unsafe fn check_rescore_order(
+    root: *mut pg_sys::PlannerInfo,
+    is_topn: bool,
+    pathkeys: &[OrderByStyle],
+    query: &SearchQueryInput,
+) {
+    if query
+        .rescore()
+        .unwrap_or_else(|err| pgrx::error!("{err}"))
+        .is_none()
+    {
+        return;
+    }
+
+    let is_expected_order = matches!(
+        pathkeys,
+        [score @ OrderByStyle::Score(_)] if matches!(score.direction(), SortDirection::Desc)
+    );
+    let query_pathkeys = PgList::<pg_sys::PathKey>::from_pg((*root).query_pathkeys);
+
+    if !is_topn || !is_expected_order || query_pathkeys.len() != pathkeys.len() {
+        pgrx::error!("rescore requires ORDER BY paradedb.score() DESC and a LIMIT");
+    }
+}
+
+unsafe fn has_default_nulls_order(style: &OrderByStyle) -> bool {
+    (*style.pathkey()).pk_nulls_first == matches!(style.direction(), SortDirection::Desc)
+}
+
+unsafe fn orderby_style(
+    pathkey: *mut pg_sys::PathKey,
+    rti: pg_sys::Index,
+    schema: &SearchIndexSchema,
+    root: *mut pg_sys::PlannerInfo,
+) -> Option<OrderByStyle> {
+    let equivclass = (*pathkey).pk_eclass;
+    let members = PgList::<pg_sys::EquivalenceMember>::from_pg((*equivclass).ec_members);
+
+    for member in members.iter_ptr() {
+        let expr = (*member).em_expr;
+
+        if is_score_func(expr.cast(), rti as _) {
+            return Some(OrderByStyle::Score(pathkey));
+        } else if let Some(var) = is_lower_func(expr.cast(), rti as _) {
+            let (heaprelid, attno, _) = find_var_relation(var, root);
+            let heaprel = PgRelation::with_lock(heaprelid, pg_sys::AccessShareLock as _);
+            let tupdesc = heaprel.tuple_desc();
+            if let Some(att) = tupdesc.get(attno as usize - 1) {
+                if let Some(search_field) = schema.search_field(att.name()) {
+                    if search_field.is_lower_sortable() {
+                        return Some(OrderByStyle::Field(pathkey, att.name().into()));
                     }
                 }
-            } else if let Some(var) = nodecast!(Var, T_Var, expr) {
+            }
+        } else if let Some(relabel) = nodecast!(RelabelType, T_RelabelType, expr) {
+            if let Some(var) = nodecast!(Var, T_Var, (*relabel).arg) {
                 let (heaprelid, attno, _) = find_var_relation(var, root);
-                if heaprelid == pg_sys::Oid::INVALID {
-                    return None;
-                }
                 let heaprel = PgRelation::with_lock(heaprelid, pg_sys::AccessShareLock as _);
                 let tupdesc = heaprel.tuple_desc();
                 if let Some(att) = tupdesc.get(attno as usize - 1) {
                     if let Some(search_field) = schema.search_field(att.name()) {
                         if search_field.is_raw_sortable() {
-                            return Some(OrderByStyle::Field(first_pathkey, att.name().into()));
+                            return Some(OrderByStyle::Field(pathkey, att.name().into()));
                         }
                     }
                 }
             }
+        } else if let Some(var) = nodecast!(Var, T_Var, expr) {
+            let (heaprelid, attno, _) = find_var_relation(var, root);
+            if heaprelid == pg_sys::Oid::INVALID {
+                return None;
+            }
+            let heaprel = PgRelation::with_lock(heaprelid, pg_sys::AccessShareLock as _);
+            let tupdesc = heaprel.tuple_desc();
+            if let Some(att) = tupdesc.get(attno as usize - 1) {
+                if let Some(search_field) = schema.search_field(att.name()) {
+                    if search_field.is_raw_sortable() {
+                        return Some(OrderByStyle::Field(pathkey, att.name().into()));
+                    }
+                }
+            }
         }
     }
     None
@@ -1618,6 +1899,12 @@ fn base_query_has_search_predicates(
         SearchQueryInput::ConstScore { query, .. } => {
             base_query_has_search_predicates(query, current_index_oid)
         }
+        SearchQueryInput::Named { query, .. } => {
+            base_query_has_search_predicates(query, current_index_oid)
+        }
+        SearchQueryInput::Nested { query, .. } => {
+            base_query_has_search_predicates(query, current_index_oid)
+        }
         SearchQueryInput::ScoreFilter {
             query: Some(query), ..
         } => base_query_has_search_predicates(query, current_index_oid),
@@ -1625,6 +1912,25 @@ fn base_query_has_search_predicates(
         SearchQueryInput::DisjunctionMax { disjuncts, .. } => disjuncts
             .iter()
             .any(|q| base_query_has_search_predicates(q, current_index_oid)),
+        SearchQueryInput::Rrf {
+            query,
+            ranked_query,
+            ..
+        } => {
+            base_query_has_search_predicates(query, current_index_oid)
+                || ranked_query
+                    .as_ref()
+                    .is_some_and(|q| base_query_has_search_predicates(q, current_index_oid))
+        }
+        SearchQueryInput::SearchAfter { query, .. } => {
+            base_query_has_search_predicates(query, current_index_oid)
+        }
+        SearchQueryInput::Rescore { query, .. } => {
+            base_query_has_search_predicates(query, current_index_oid)
+        }
+        SearchQueryInput::FunctionScore { query, .. } => {
+            base_query_has_search_predicates(query, current_index_oid)
+        }
 
         // These are NOT search predicates (they're range/exists/other predicates)
         SearchQueryInput::Range { .. }
@@ -1634,6 +1940,9 @@ fn base_query_has_search_predicates(
         | SearchQueryInput::RangeWithin { .. }
         | SearchQueryInput::Exists { .. }
         | SearchQueryInput::FastFieldRangeWeight { .. }
+        | SearchQueryInput::GeoBoundingBox { .. }
+        | SearchQueryInput::GeoDistance { .. }
+        | SearchQueryInput::GeoPolygon { .. }
         | SearchQueryInput::MoreLikeThis { .. } => false,
 
         // These are search predicates that use the @@@ operator
@@ -1649,7 +1958,12 @@ fn base_query_has_search_predicates(
         | SearchQueryInput::FuzzyTerm { .. }
         | SearchQueryInput::Match { .. }
         | SearchQueryInput::Regex { .. }
-        | SearchQueryInput::RegexPhrase { .. } => true,
+        | SearchQueryInput::RegexPhrase { .. }
+        | SearchQueryInput::SpanTerm { .. }
+        | SearchQueryInput::SpanNear { .. }
+        | SearchQueryInput::SpanOr { .. }
+        | SearchQueryInput::SpanNot { .. }
+        | SearchQueryInput::SpanFirst { .. } => true,
 
         // Term with no field is not a search predicate
         SearchQueryInput::Term { field: None, .. } => false,
@@ -19,6 +19,7 @@ use crate::api::FieldName;
 use crate::api::{AsCStr, Cardinality, Varno};
 use crate::api::{HashMap, HashSet};
 use crate::index::fast_fields_helper::WhichFastField;
+use crate::index::reader::order_by::OrderByFeature;
 use crate::postgres::customscan::builders::custom_path::{OrderByStyle, SortDirection};
 use crate::postgres::customscan::pdbscan::ExecMethodType;
 use crate::query::SearchQueryInput;
@@ -35,6 +36,9 @@ pub struct PrivateData {
     limit: Option<usize>,
     sort_field: Option<FieldName>,
     sort_direction: Option<SortDirection>,
+    // Only set when sorting by more than one key, in which case `sort_field` and `sort_direction`
+    // describe the first of them
+    sort_keys: Option<Vec<(OrderByFeature, SortDirection)>>,
     #[serde(with = "var_attname_lookup_serializer")]
     var_attname_lookup: Option<HashMap<(Varno, pg_sys::AttrNumber), FieldName>>,
     segment_count: usize,
@@ -186,6 +190,18 @@ impl PrivateData {
         self.sort_direction = Some(style.direction())
     }
 
+    pub fn set_compound_sort_info(&mut self, styles: &[OrderByStyle]) {
+        if let Some(first) = styles.first() {
+            self.set_sort_info(first);
+        }
+        self.sort_keys = Some(
+            styles
+                .iter()
+                .map(|style| (style.feature(), style.direction()))
+                .collect(),
+        );
+    }
+
     pub fn set_var_attname_lookup(
         &mut self,
         var_attname_lookup: HashMap<(Varno, pg_sys::AttrNumber), FieldName>,
@@ -258,6 +274,10 @@ impl PrivateData {
         self.sort_direction
     }
 
+    pub fn sort_keys(&self) -> Option<Vec<(OrderByFeature, SortDirection)>> {
+        self.sort_keys.clone()
+    }
+
     pub fn is_sorted(&self) -> bool {
         matches!(
             self.sort_direction,
@@ -0,0 +1,58 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Support for `paradedb.matched_queries()`, which returns the names of the clauses of the query,
+//! given with `paradedb.named()`, that matched each row.
+//!
+//! Like `paradedb.score()`, the function is only a placeholder that the custom scan replaces
+//! with a [`pg_sys::Const`] as it returns each row, so the clauses are checked in the same pass
+//! over the index that finds the rows.
+
+use crate::postgres::customscan::pdbscan::projections::score::uses_scores;
+use pgrx::{direct_function_call, extension_sql, pg_extern, pg_sys, AnyElement, IntoDatum};
+
+#[pg_extern(name = "matched_queries", stable, parallel_safe, cost = 1)]
+fn matched_queries_from_relation(_relation_reference: AnyElement) -> Option<Vec<String>> {
+    None
+}
+
+extension_sql!(
+    r#"
+ALTER FUNCTION matched_queries SUPPORT placeholder_support;
+"#,
+    name = "matched_queries_placeholder",
+    requires = [matched_queries_from_relation, placeholder_support]
+);
+
+pub fn matched_queries_funcoid() -> pg_sys::Oid {
+    unsafe {
+        direct_function_call::<pg_sys::Oid>(
+            pg_sys::regprocedurein,
+            &[c"paradedb.matched_queries(anyelement)".into_datum()],
+        )
+        .expect("the `paradedb.matched_queries(anyelement)` function should exist")
+    }
+}
+
+pub unsafe fn uses_matched_queries(
+    node: *mut pg_sys::Node,
+    matched_queries_funcoid: pg_sys::Oid,
+    rti: pg_sys::Index,
+) -> bool {
+    // both functions take a single reference to the relation, so finding their calls is the same
+    uses_scores(node, matched_queries_funcoid, rti)
+}
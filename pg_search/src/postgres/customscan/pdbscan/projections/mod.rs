@@ -15,19 +15,23 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+pub mod matched_queries;
 pub mod score;
 pub mod snippet;
+pub mod window_agg;
 
 use crate::api::operator::ReturnedNodePointer;
 use crate::api::FieldName;
 use crate::api::HashMap;
 use crate::api::Varno;
 use crate::nodecast;
+use crate::postgres::customscan::pdbscan::projections::matched_queries::matched_queries_funcoid;
 use crate::postgres::customscan::pdbscan::projections::score::score_funcoid;
 use crate::postgres::customscan::pdbscan::projections::snippet::{
-    snippet_funcoid, snippet_positions_funcoid, SnippetType,
+    snippet_funcoid, snippet_positions_funcoid, snippets_funcoid, uses_snippets, SnippetType,
 };
-use crate::postgres::var::{find_one_var, find_one_var_and_fieldname, find_vars};
+use crate::postgres::customscan::pdbscan::projections::window_agg::window_agg_funcoid;
+use crate::postgres::var::{find_one_var_and_fieldname, find_vars};
 use pgrx::pg_sys::expression_tree_walker;
 use pgrx::{pg_extern, pg_guard, pg_sys, Internal, PgList};
 use std::ptr::{addr_of_mut, NonNull};
@@ -98,8 +102,11 @@ pub unsafe fn maybe_needs_const_projections(node: *mut pg_sys::Node) -> bool {
         if let Some(funcexpr) = nodecast!(FuncExpr, T_FuncExpr, node) {
             let data = &*data.cast::<Data>();
             if (*funcexpr).funcid == data.score_funcoid
+                || (*funcexpr).funcid == data.matched_queries_funcoid
                 || (*funcexpr).funcid == data.snipped_funcoid
                 || (*funcexpr).funcid == data.snippet_positions_funcoid
+                || (*funcexpr).funcid == data.snippets_funcoid
+                || (*funcexpr).funcid == data.window_agg_funcoid
             {
                 return true;
             }
@@ -110,14 +117,20 @@ pub unsafe fn maybe_needs_const_projections(node: *mut pg_sys::Node) -> bool {
 
     struct Data {
         score_funcoid: pg_sys::Oid,
+        matched_queries_funcoid: pg_sys::Oid,
         snipped_funcoid: pg_sys::Oid,
         snippet_positions_funcoid: pg_sys::Oid,
+        snippets_funcoid: pg_sys::Oid,
+        window_agg_funcoid: pg_sys::Oid,
     }
 
     let mut data = Data {
         score_funcoid: score_funcoid(),
+        matched_queries_funcoid: matched_queries_funcoid(),
         snipped_funcoid: snippet_funcoid(),
         snippet_positions_funcoid: snippet_positions_funcoid(),
+        snippets_funcoid: snippets_funcoid(),
+        window_agg_funcoid: window_agg_funcoid(),
     };
 
     let data = addr_of_mut!(data).cast();
@@ -187,14 +200,19 @@ pub unsafe fn inject_placeholders(
     targetlist: *mut pg_sys::List,
     rti: pg_sys::Index,
     score_funcoid: pg_sys::Oid,
+    matched_queries_funcoid: pg_sys::Oid,
     snippet_funcoid: pg_sys::Oid,
     snippet_positions_funcoid: pg_sys::Oid,
+    snippets_funcoid: pg_sys::Oid,
+    window_agg_funcoid: pg_sys::Oid,
     attname_lookup: &HashMap<(Varno, pg_sys::AttrNumber), FieldName>,
     snippet_generators: &HashMap<SnippetType, Option<(tantivy::schema::Field, SnippetGenerator)>>,
 ) -> (
     *mut pg_sys::List,
     *mut pg_sys::Const,
+    Vec<*mut pg_sys::Const>,
     HashMap<SnippetType, Vec<*mut pg_sys::Const>>,
+    Vec<*mut pg_sys::Const>,
 ) {
     #[pg_guard]
     unsafe extern "C-unwind" fn walker(
@@ -208,41 +226,73 @@ pub unsafe fn inject_placeholders(
         #[inline(always)]
         unsafe fn inner(node: *mut pg_sys::Node, data: &mut Data) -> Option<*mut pg_sys::Node> {
             let funcexpr = nodecast!(FuncExpr, T_FuncExpr, node)?;
-            let args = PgList::<pg_sys::Node>::from_pg((*funcexpr).args);
 
             if (*funcexpr).funcid == data.score_funcoid {
                 return Some(data.const_score_node.cast());
             }
 
+            if (*funcexpr).funcid == data.matched_queries_funcoid {
+                let const_ = pg_sys::makeConst(
+                    pg_sys::TEXTARRAYOID,
+                    -1,
+                    pg_sys::DEFAULT_COLLATION_OID,
+                    -1,
+                    pg_sys::Datum::null(),
+                    true,
+                    false,
+                );
+                data.const_matched_queries_nodes.push(const_);
+                return Some(const_.cast());
+            }
+
+            if (*funcexpr).funcid == data.window_agg_funcoid {
+                let const_ = pg_sys::makeConst(
+                    pg_sys::JSONBOID,
+                    -1,
+                    pg_sys::Oid::INVALID,
+                    -1,
+                    pg_sys::Datum::null(),
+                    true,
+                    false,
+                );
+                data.const_window_agg_nodes.push(const_);
+                return Some(const_.cast());
+            }
+
             if (*funcexpr).funcid == data.snippet_funcoid
                 || (*funcexpr).funcid == data.snippet_positions_funcoid
+                || (*funcexpr).funcid == data.snippets_funcoid
             {
-                let var = find_one_var(args.get_ptr(0)?)?;
-                let key = (data.rti as Varno, (*var).varattno);
-
-                if let Some(attname) = data.attname_lookup.get(&key) {
-                    for snippet_type in data.snippet_generators.keys() {
-                        if snippet_type.field() == attname
-                            && snippet_type.funcoid() == (*funcexpr).funcid
-                        {
-                            let const_ = pg_sys::makeConst(
-                                snippet_type.nodeoid(),
-                                -1,
-                                pg_sys::DEFAULT_COLLATION_OID,
-                                -1,
-                                pg_sys::Datum::null(),
-                                true,
-                                false,
-                            );
-
-                            data.const_snippet_nodes
-                                .entry(snippet_type.clone())
-                                .or_default()
-                                .push(const_);
-
-                            return Some(const_.cast());
-                        }
-                    }
+                // resolve the call the same way `uses_snippets` did, so that calls on different
+                // JSON paths of the same column, or with different arguments, stay distinct
+                let snippet_type = uses_snippets(
+                    data.rti,
+                    data.attname_lookup,
+                    funcexpr.cast(),
+                    data.snippet_funcoid,
+                    data.snippet_positions_funcoid,
+                    data.snippets_funcoid,
+                )
+                .into_iter()
+                .next()?;
+
+                if data.snippet_generators.contains_key(&snippet_type) {
+                    let const_ = pg_sys::makeConst(
+                        snippet_type.nodeoid(),
+                        -1,
+                        pg_sys::DEFAULT_COLLATION_OID,
+                        -1,
+                        pg_sys::Datum::null(),
+                        true,
+                        false,
+                    );
+
+                    data.const_snippet_nodes
+                        .entry(snippet_type)
+                        .or_default()
+                        .push(const_);
+
+                    return Some(const_.cast());
                 }
             }
 
@@ -274,13 +324,20 @@ pub unsafe fn inject_placeholders(
         score_funcoid: pg_sys::Oid,
         const_score_node: *mut pg_sys::Const,
 
+        matched_queries_funcoid: pg_sys::Oid,
+        const_matched_queries_nodes: Vec<*mut pg_sys::Const>,
+
         snippet_funcoid: pg_sys::Oid,
         snippet_positions_funcoid: pg_sys::Oid,
+        snippets_funcoid: pg_sys::Oid,
         attname_lookup: &'a HashMap<(Varno, pg_sys::AttrNumber), FieldName>,
 
         snippet_generators:
             &'a HashMap<SnippetType, Option<(tantivy::schema::Field, SnippetGenerator)>>,
         const_snippet_nodes: HashMap<SnippetType, Vec<*mut pg_sys::Const>>,
+
+        window_agg_funcoid: pg_sys::Oid,
+        const_window_agg_nodes: Vec<*mut pg_sys::Const>,
     }
 
     let mut data = Data {
@@ -297,16 +354,25 @@ pub unsafe fn inject_placeholders(
             true,
         ),
 
+        matched_queries_funcoid,
+        const_matched_queries_nodes: Default::default(),
+
         snippet_funcoid,
         snippet_positions_funcoid,
+        snippets_funcoid,
         attname_lookup,
         snippet_generators,
         const_snippet_nodes: Default::default(),
+
+        window_agg_funcoid,
+        const_window_agg_nodes: Default::default(),
     };
     let targetlist = walker(targetlist.cast(), addr_of_mut!(data).cast());
     (
         targetlist.cast(),
         data.const_score_node,
+        data.const_matched_queries_nodes,
         data.const_snippet_nodes,
+        data.const_window_agg_nodes,
     )
 }
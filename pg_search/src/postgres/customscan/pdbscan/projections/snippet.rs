@@ -18,18 +18,20 @@
 use crate::api::FieldName;
 use crate::api::HashMap;
 use crate::api::Varno;
+use crate::index::reader::snippet::FragmentOrder;
 use crate::nodecast;
-use crate::postgres::var::find_one_var;
+use crate::postgres::var::{find_one_var, find_one_var_and_fieldname_with};
 use pgrx::pg_sys::expression_tree_walker;
 use pgrx::{
     default, direct_function_call, extension_sql, pg_extern, pg_guard, pg_sys, AnyElement,
-    FromDatum, IntoDatum, PgList,
+    FromDatum, IntoDatum, JsonB, PgList,
 };
 use std::ptr::addr_of_mut;
 
 const DEFAULT_SNIPPET_PREFIX: &str = "<b>";
 const DEFAULT_SNIPPET_POSTFIX: &str = "</b>";
 const DEFAULT_SNIPPET_MAX_NUM_CHARS: i32 = 150;
+const DEFAULT_SNIPPET_MAX_FRAGMENTS: i32 = 3;
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub struct SnippetConfig {
@@ -38,10 +40,20 @@ pub struct SnippetConfig {
     pub max_num_chars: usize,
 }
 
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct FragmentsConfig {
+    pub start_tag: String,
+    pub end_tag: String,
+    pub max_fragments: usize,
+    pub fragment_size: usize,
+    pub order: FragmentOrder,
+}
+
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub enum SnippetType {
     Text(FieldName, pg_sys::Oid, SnippetConfig),
     Positions(FieldName, pg_sys::Oid),
+    Fragments(FieldName, pg_sys::Oid, FragmentsConfig),
 }
 
 impl SnippetType {
@@ -49,6 +61,7 @@ impl SnippetType {
         match self {
             SnippetType::Text(field, _, _) => field,
             SnippetType::Positions(field, _) => field,
+            SnippetType::Fragments(field, _, _) => field,
         }
     }
 
@@ -56,6 +69,7 @@ impl SnippetType {
         match self {
             SnippetType::Text(_, funcoid, _) => *funcoid,
             SnippetType::Positions(_, funcoid) => *funcoid,
+            SnippetType::Fragments(_, funcoid, _) => *funcoid,
         }
     }
 
@@ -63,6 +77,7 @@ impl SnippetType {
         match self {
             SnippetType::Text(_, _, _) => pg_sys::TEXTOID,
             SnippetType::Positions(_, _) => pg_sys::INT4ARRAYOID,
+            SnippetType::Fragments(_, _, _) => pg_sys::JSONBOID,
         }
     }
 }
@@ -72,6 +87,7 @@ struct Context<'a> {
     attname_lookup: &'a HashMap<(Varno, pg_sys::AttrNumber), FieldName>,
     snippet_funcoid: pg_sys::Oid,
     snippet_positions_funcoid: pg_sys::Oid,
+    snippets_funcoid: pg_sys::Oid,
     snippet_type: Vec<SnippetType>,
 }
 
@@ -90,6 +106,18 @@ fn snippet_positions_from_relation(field: AnyElement) -> Option<Vec<Vec<i32>>> {
     None
 }
 
+#[pg_extern(name = "snippets", stable, parallel_safe)]
+fn snippets_from_relation(
+    field: AnyElement,
+    max_fragments: default!(i32, "3"),
+    fragment_size: default!(i32, "150"),
+    order_by: default!(String, "'score'"),
+    start_tag: default!(String, "'<b>'"),
+    end_tag: default!(String, "'</b>'"),
+) -> Option<JsonB> {
+    None
+}
+
 extension_sql!(
     r#"
 ALTER FUNCTION snippet SUPPORT placeholder_support;
@@ -106,6 +134,14 @@ ALTER FUNCTION snippet_positions SUPPORT placeholder_support;
     requires = [snippet_positions_from_relation, placeholder_support]
 );
 
+extension_sql!(
+    r#"
+ALTER FUNCTION snippets SUPPORT placeholder_support;
+"#,
+    name = "snippets_placeholder",
+    requires = [snippets_from_relation, placeholder_support]
+);
+
 pub fn snippet_funcoid() -> pg_sys::Oid {
     unsafe {
         direct_function_call::<pg_sys::Oid>(
@@ -126,12 +162,23 @@ pub fn snippet_positions_funcoid() -> pg_sys::Oid {
     }
 }
 
+pub fn snippets_funcoid() -> pg_sys::Oid {
+    unsafe {
+        direct_function_call::<pg_sys::Oid>(
+            pg_sys::regprocedurein,
+            &[c"paradedb.snippets(anyelement, int, int, text, text, text)".into_datum()],
+        )
+        .expect("the `paradedb.snippets(anyelement, int, int, text, text, text) type should exist")
+    }
+}
+
 pub unsafe fn uses_snippets(
     planning_rti: pg_sys::Index,
     attname_lookup: &HashMap<(Varno, pg_sys::AttrNumber), FieldName>,
     node: *mut pg_sys::Node,
     snippet_funcoid: pg_sys::Oid,
     snippet_positions_funcoid: pg_sys::Oid,
+    snippets_funcoid: pg_sys::Oid,
 ) -> Vec<SnippetType> {
     #[pg_guard]
     unsafe extern "C-unwind" fn walker(
@@ -162,6 +209,15 @@ pub unsafe fn uses_snippets(
                     panic!("`paradedb.snippet_positions()`'s arguments must be literals")
                 }
             }
+
+            if (*funcexpr).funcid == (*context).snippets_funcoid {
+                let args = PgList::<pg_sys::Node>::from_pg((*funcexpr).args);
+                if let Some(snippet_type) = extract_snippet_fragments(args, context) {
+                    (*context).snippet_type.push(snippet_type);
+                } else {
+                    panic!("`paradedb.snippets()`'s arguments must be literals")
+                }
+            }
         }
 
         expression_tree_walker(node, Some(walker), data)
@@ -172,6 +228,7 @@ pub unsafe fn uses_snippets(
         attname_lookup,
         snippet_funcoid,
         snippet_positions_funcoid,
+        snippets_funcoid,
         snippet_type: vec![],
     };
 
@@ -179,6 +236,25 @@ pub unsafe fn uses_snippets(
     context.snippet_type
 }
 
+/// The field that a snippet function's first argument refers to, including its JSON path when the
+/// argument is an expression like `metadata->'details'->>'description'`.  Different paths into the
+/// same JSON column are different fields.
+unsafe fn snippet_fieldname(arg: *mut pg_sys::Node, context: *mut Context) -> Option<FieldName> {
+    let var_fieldname = |var: *mut pg_sys::Var| {
+        (*context)
+            .attname_lookup
+            .get(&((*context).planning_rti as _, (*var).varattno as _))
+            .map(|attname| FieldName::from(attname.root()))
+    };
+
+    if let Some((_, fieldname)) = find_one_var_and_fieldname_with(arg, &var_fieldname) {
+        return Some(fieldname);
+    }
+
+    let var = find_one_var(arg)?;
+    Some(var_fieldname(var).expect("Var attname should be in lookup"))
+}
+
 #[inline(always)]
 unsafe fn extract_snippet_text(
     args: PgList<pg_sys::Node>,
@@ -186,19 +262,14 @@ unsafe fn extract_snippet_text(
 ) -> Option<SnippetType> {
     assert!(args.len() == 4);
 
-    let field_arg = find_one_var(args.get_ptr(0).unwrap());
+    let attname = snippet_fieldname(args.get_ptr(0).unwrap(), context);
     let start_arg = nodecast!(Const, T_Const, args.get_ptr(1).unwrap());
     let end_arg = nodecast!(Const, T_Const, args.get_ptr(2).unwrap());
     let max_num_chars_arg = nodecast!(Const, T_Const, args.get_ptr(3).unwrap());
 
-    if let (Some(field_arg), Some(start_arg), Some(end_arg), Some(max_num_chars_arg)) =
-        (field_arg, start_arg, end_arg, max_num_chars_arg)
+    if let (Some(attname), Some(start_arg), Some(end_arg), Some(max_num_chars_arg)) =
+        (attname, start_arg, end_arg, max_num_chars_arg)
     {
-        let attname = (*context)
-            .attname_lookup
-            .get(&((*context).planning_rti as _, (*field_arg).varattno as _))
-            .cloned()
-            .expect("Var attname should be in lookup");
         let start_tag = String::from_datum((*start_arg).constvalue, (*start_arg).constisnull);
         let end_tag = String::from_datum((*end_arg).constvalue, (*end_arg).constisnull);
         let max_num_chars = i32::from_datum(
@@ -227,14 +298,9 @@ unsafe fn extract_snippet_positions(
 ) -> Option<SnippetType> {
     assert!(args.len() == 1);
 
-    let field_arg = find_one_var(args.get_ptr(0).unwrap());
+    let attname = snippet_fieldname(args.get_ptr(0).unwrap(), context);
 
-    if let Some(field_arg) = field_arg {
-        let attname = (*context)
-            .attname_lookup
-            .get(&((*context).planning_rti as _, (*field_arg).varattno as _))
-            .cloned()
-            .expect("Var attname should be in lookup");
+    if let Some(attname) = attname {
 
         Some(SnippetType::Positions(
             attname,
@@ -244,3 +310,72 @@ unsafe fn extract_snippet_positions(
         None
     }
 }
+
+#[inline(always)]
+unsafe fn extract_snippet_fragments(
+    args: PgList<pg_sys::Node>,
+    context: *mut Context,
+) -> Option<SnippetType> {
+    assert!(args.len() == 6);
+
+    let attname = snippet_fieldname(args.get_ptr(0).unwrap(), context);
+    let max_fragments_arg = nodecast!(Const, T_Const, args.get_ptr(1).unwrap());
+    let fragment_size_arg = nodecast!(Const, T_Const, args.get_ptr(2).unwrap());
+    let order_by_arg = nodecast!(Const, T_Const, args.get_ptr(3).unwrap());
+    let start_arg = nodecast!(Const, T_Const, args.get_ptr(4).unwrap());
+    let end_arg = nodecast!(Const, T_Const, args.get_ptr(5).unwrap());
+
+    if let (
+        Some(attname),
+        Some(max_fragments_arg),
+        Some(fragment_size_arg),
+        Some(order_by_arg),
+        Some(start_arg),
+        Some(end_arg),
+    ) = (
+        attname,
+        max_fragments_arg,
+        fragment_size_arg,
+        order_by_arg,
+        start_arg,
+        end_arg,
+    ) {
+        let max_fragments = i32::from_datum(
+            (*max_fragments_arg).constvalue,
+            (*max_fragments_arg).constisnull,
+        );
+        let fragment_size = i32::from_datum(
+            (*fragment_size_arg).constvalue,
+            (*fragment_size_arg).constisnull,
+        );
+        let order_by = String::from_datum((*order_by_arg).constvalue, (*order_by_arg).constisnull);
+        let start_tag = String::from_datum((*start_arg).constvalue, (*start_arg).constisnull);
+        let end_tag = String::from_datum((*end_arg).constvalue, (*end_arg).constisnull);
+
+        let order = match order_by.as_deref() {
+            None | Some("score") => FragmentOrder::Score,
+            Some("position") => FragmentOrder::Position,
+            Some(other) => panic!(
+                "`paradedb.snippets()`'s order_by must be 'score' or 'position', not '{other}'"
+            ),
+        };
+
+        Some(SnippetType::Fragments(
+            attname,
+            (*context).snippets_funcoid,
+            FragmentsConfig {
+                start_tag: start_tag.unwrap_or_else(|| DEFAULT_SNIPPET_PREFIX.to_string()),
+                end_tag: end_tag.unwrap_or_else(|| DEFAULT_SNIPPET_POSTFIX.to_string()),
+                max_fragments: max_fragments
+                    .unwrap_or(DEFAULT_SNIPPET_MAX_FRAGMENTS)
+                    .max(0) as usize,
+                fragment_size: fragment_size
+                    .unwrap_or(DEFAULT_SNIPPET_MAX_NUM_CHARS)
+                    .max(0) as usize,
+                order,
+            },
+        ))
+    } else {
+        None
+    }
+}
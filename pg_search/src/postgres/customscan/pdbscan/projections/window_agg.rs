@@ -0,0 +1,373 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Support for `paradedb.agg(...) OVER ()`, which computes a tantivy aggregation (terms,
+//! histogram, etc) over every document matching the query, in the same pass over the index that
+//! finds the rows being returned.
+//!
+//! Postgres would evaluate a window function over every row of the result, so before planning we
+//! rewrite each `paradedb.agg(...) OVER ()` into a call to the `paradedb.window_agg()` placeholder
+//! function.  The custom scan then replaces the placeholder with a [`pg_sys::Const`] holding the
+//! aggregation results, the same way it does for `paradedb.score()`.
+#![allow(static_mut_refs)]
+
+use crate::api::aggregate::mvcc_collector::MVCCFilterCollector;
+use crate::api::aggregate::vischeck::TSVisibilityChecker;
+use crate::nodecast;
+use crate::postgres::customscan::pdbscan::PdbScan;
+use crate::postgres::customscan::CustomScan;
+use pgrx::pg_sys::expression_tree_walker;
+use pgrx::{
+    direct_function_call, extension_sql, pg_extern, pg_guard, pg_sys, FromDatum, IntoDatum, JsonB,
+    PgList,
+};
+use std::ffi::CStr;
+use std::ptr::addr_of_mut;
+use tantivy::aggregation::agg_req::Aggregations;
+use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+use tantivy::aggregation::{AggregationLimitsGuard, DistributedAggregationCollector};
+
+/// The most memory, in bytes, the aggregations of a single query may use
+const MEMORY_LIMIT: u64 = 500_000_000;
+
+/// The most buckets the aggregations of a single query may produce
+const BUCKET_LIMIT: u32 = 65000;
+
+pub type WindowAggCollector = MVCCFilterCollector<DistributedAggregationCollector>;
+
+/// The state transition function of the `paradedb.agg(jsonb)` aggregate.  It's never meant to
+/// run, as the aggregate is always rewritten into a `paradedb.window_agg()` placeholder, but
+/// Postgres will call it if that rewrite wasn't possible.
+#[pg_extern(stable, parallel_safe)]
+fn agg_sfunc(_state: Option<JsonB>, _definition: JsonB) -> Option<JsonB> {
+    pgrx::error!(
+        "paradedb.agg() must be used as a window function over the entire result, such as `paradedb.agg('{{...}}') OVER ()`, in a query without GROUP BY, DISTINCT, or other aggregates"
+    )
+}
+
+extension_sql!(
+    r#"
+CREATE AGGREGATE agg(jsonb) (
+    SFUNC = agg_sfunc,
+    STYPE = jsonb
+);
+"#,
+    name = "agg",
+    requires = [agg_sfunc]
+);
+
+/// The error for a `paradedb.window_agg()` placeholder that the custom scan can't compute
+const UNSUPPORTED_WINDOW_AGG: &str = "paradedb.agg() OVER () is only supported in queries over a single table that use the ParadeDB custom scan, where the index can answer the entire WHERE clause";
+
+#[pg_extern(name = "window_agg", stable, parallel_safe, cost = 1)]
+fn window_agg_placeholder(_definition: JsonB) -> Option<JsonB> {
+    pgrx::error!("{UNSUPPORTED_WINDOW_AGG}")
+}
+
+/// The `paradedb.agg(jsonb)` aggregate, if pg_search is installed in the current database.  The
+/// planner hook runs for every database, so this can't assume it is.
+pub fn agg_funcoid() -> Option<pg_sys::Oid> {
+    unsafe {
+        direct_function_call::<pg_sys::Oid>(
+            pg_sys::to_regprocedure,
+            &["paradedb.agg(jsonb)".into_datum()],
+        )
+    }
+}
+
+pub fn window_agg_funcoid() -> pg_sys::Oid {
+    unsafe {
+        direct_function_call::<pg_sys::Oid>(
+            pg_sys::regprocedurein,
+            &[c"paradedb.window_agg(jsonb)".into_datum()],
+        )
+        .expect("the `paradedb.window_agg(jsonb)` function should exist")
+    }
+}
+
+pub unsafe fn register() {
+    static mut PREV_PLANNER_HOOK: pg_sys::planner_hook_type = None;
+
+    PREV_PLANNER_HOOK = pg_sys::planner_hook;
+    pg_sys::planner_hook = Some(planner_hook);
+
+    #[pg_guard]
+    unsafe extern "C-unwind" fn planner_hook(
+        parse: *mut pg_sys::Query,
+        query_string: *const std::os::raw::c_char,
+        cursor_options: std::os::raw::c_int,
+        bound_params: pg_sys::ParamListInfo,
+    ) -> *mut pg_sys::PlannedStmt {
+        let rewrote_window_aggs = rewrite_window_aggs(parse);
+
+        let stmt = match PREV_PLANNER_HOOK {
+            Some(prev_hook) => prev_hook(parse, query_string, cursor_options, bound_params),
+            None => pg_sys::standard_planner(parse, query_string, cursor_options, bound_params),
+        };
+
+        // the placeholders are only replaced by our custom scan, so if any other plan won, it's
+        // better to say so now than to fail once the query is executed
+        if rewrote_window_aggs && is_left_to_postgres((*stmt).planTree, window_agg_funcoid()) {
+            pgrx::error!("{UNSUPPORTED_WINDOW_AGG}");
+        }
+        stmt
+    }
+}
+
+/// Is a `paradedb.window_agg()` placeholder computed by any node of `plan` other than our custom
+/// scan, which replaces them with the aggregation results?
+unsafe fn is_left_to_postgres(plan: *mut pg_sys::Plan, window_agg_funcoid: pg_sys::Oid) -> bool {
+    if plan.is_null() {
+        return false;
+    }
+
+    let is_pdbscan = nodecast!(CustomScan, T_CustomScan, plan).is_some_and(|cscan| {
+        CStr::from_ptr((*(*cscan).methods).CustomName) == <PdbScan as CustomScan>::NAME
+    });
+    if !is_pdbscan
+        && (!uses_window_aggs((*plan).targetlist.cast(), window_agg_funcoid).is_empty()
+            || !uses_window_aggs((*plan).qual.cast(), window_agg_funcoid).is_empty())
+    {
+        return true;
+    }
+
+    let mut children = vec![(*plan).lefttree, (*plan).righttree];
+    if let Some(append) = nodecast!(Append, T_Append, plan) {
+        children.extend(PgList::<pg_sys::Plan>::from_pg((*append).appendplans).iter_ptr());
+    } else if let Some(merge_append) = nodecast!(MergeAppend, T_MergeAppend, plan) {
+        children.extend(PgList::<pg_sys::Plan>::from_pg((*merge_append).mergeplans).iter_ptr());
+    } else if let Some(subquery_scan) = nodecast!(SubqueryScan, T_SubqueryScan, plan) {
+        children.push((*subquery_scan).subplan);
+    } else if let Some(cscan) = nodecast!(CustomScan, T_CustomScan, plan) {
+        children.extend(PgList::<pg_sys::Plan>::from_pg((*cscan).custom_plans).iter_ptr());
+    }
+    children
+        .into_iter()
+        .any(|child| is_left_to_postgres(child, window_agg_funcoid))
+}
+
+/// Rewrite each `paradedb.agg(...) OVER ()` in the target list of `parse` into a call to the
+/// `paradedb.window_agg()` placeholder.
+///
+/// This is only done when the window is the entire result and nothing is computed over the rows
+/// after they're scanned, as the aggregations are computed over the documents matched by the
+/// scan itself.  Returns whether anything was rewritten.
+unsafe fn rewrite_window_aggs(parse: *mut pg_sys::Query) -> bool {
+    if (*parse).commandType != pg_sys::CmdType::CMD_SELECT
+        || !(*parse).hasWindowFuncs
+        || (*parse).hasAggs
+        || !(*parse).groupClause.is_null()
+        || !(*parse).groupingSets.is_null()
+        || !(*parse).havingQual.is_null()
+        || !(*parse).distinctClause.is_null()
+        || !(*parse).setOperations.is_null()
+    {
+        return false;
+    }
+
+    let Some(agg_funcoid) = agg_funcoid() else {
+        return false;
+    };
+
+    #[pg_guard]
+    unsafe extern "C-unwind" fn mutator(
+        node: *mut pg_sys::Node,
+        context: *mut std::ffi::c_void,
+    ) -> *mut pg_sys::Node {
+        if node.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let data = &mut *context.cast::<Data>();
+        if let Some(wfunc) = nodecast!(WindowFunc, T_WindowFunc, node) {
+            if (*wfunc).winfnoid == data.agg_funcoid
+                && (*wfunc).aggfilter.is_null()
+                && is_entire_result(data.window_clauses, (*wfunc).winref)
+            {
+                data.rewritten_window_funcs += 1;
+                return pg_sys::makeFuncExpr(
+                    data.window_agg_funcoid,
+                    pg_sys::JSONBOID,
+                    (*wfunc).args,
+                    pg_sys::Oid::INVALID,
+                    pg_sys::Oid::INVALID,
+                    pg_sys::CoercionForm::COERCE_EXPLICIT_CALL,
+                )
+                .cast();
+            }
+
+            data.remaining_window_funcs += 1;
+            return node;
+        }
+
+        #[cfg(not(any(feature = "pg16", feature = "pg17")))]
+        {
+            let fnptr = mutator as usize as *const ();
+            let mutator: unsafe extern "C-unwind" fn() -> *mut pg_sys::Node =
+                std::mem::transmute(fnptr);
+            pg_sys::expression_tree_mutator(node, Some(mutator), context)
+        }
+
+        #[cfg(any(feature = "pg16", feature = "pg17"))]
+        {
+            pg_sys::expression_tree_mutator_impl(node, Some(mutator), context)
+        }
+    }
+
+    struct Data {
+        agg_funcoid: pg_sys::Oid,
+        window_agg_funcoid: pg_sys::Oid,
+        window_clauses: *mut pg_sys::List,
+        rewritten_window_funcs: usize,
+        remaining_window_funcs: usize,
+    }
+
+    let mut data = Data {
+        agg_funcoid,
+        window_agg_funcoid: window_agg_funcoid(),
+        window_clauses: (*parse).windowClause,
+        rewritten_window_funcs: 0,
+        remaining_window_funcs: 0,
+    };
+
+    (*parse).targetList = mutator((*parse).targetList.cast(), addr_of_mut!(data).cast()).cast();
+    if data.remaining_window_funcs == 0 {
+        // there's nothing left for a WindowAgg node to compute
+        (*parse).hasWindowFuncs = false;
+        (*parse).windowClause = std::ptr::null_mut();
+    }
+    data.rewritten_window_funcs > 0
+}
+
+/// Is the window `winref` simply `OVER ()`, with no partitioning, ordering, or framing?
+unsafe fn is_entire_result(window_clauses: *mut pg_sys::List, winref: pg_sys::Index) -> bool {
+    PgList::<pg_sys::WindowClause>::from_pg(window_clauses)
+        .iter_ptr()
+        .find(|wc| (**wc).winref == winref)
+        .map(|wc| {
+            (*wc).partitionClause.is_null()
+                && (*wc).orderClause.is_null()
+                && (*wc).frameOptions & pg_sys::FRAMEOPTION_NONDEFAULT as i32 == 0
+        })
+        .unwrap_or(false)
+}
+
+/// Find the aggregation definitions of every `paradedb.window_agg()` placeholder in `node`, in the
+/// order they're found
+pub unsafe fn uses_window_aggs(
+    node: *mut pg_sys::Node,
+    window_agg_funcoid: pg_sys::Oid,
+) -> Vec<serde_json::Value> {
+    #[pg_guard]
+    unsafe extern "C-unwind" fn walker(
+        node: *mut pg_sys::Node,
+        data: *mut core::ffi::c_void,
+    ) -> bool {
+        if node.is_null() {
+            return false;
+        }
+
+        if let Some(funcexpr) = nodecast!(FuncExpr, T_FuncExpr, node) {
+            let data = &mut *data.cast::<Data>();
+            if (*funcexpr).funcid == data.window_agg_funcoid {
+                let args = PgList::<pg_sys::Node>::from_pg((*funcexpr).args);
+                let Some(const_) = args
+                    .get_ptr(0)
+                    .and_then(|arg| nodecast!(Const, T_Const, arg))
+                    .filter(|const_| !(**const_).constisnull)
+                else {
+                    pgrx::error!("the argument to paradedb.agg() must be a non-NULL constant");
+                };
+
+                let definition =
+                    JsonB::from_polymorphic_datum((*const_).constvalue, false, pg_sys::JSONBOID)
+                        .expect("paradedb.agg() definition should not be NULL");
+                data.definitions.push(definition.0);
+                return false;
+            }
+        }
+
+        expression_tree_walker(node, Some(walker), data)
+    }
+
+    struct Data {
+        window_agg_funcoid: pg_sys::Oid,
+        definitions: Vec<serde_json::Value>,
+    }
+
+    let mut data = Data {
+        window_agg_funcoid,
+        definitions: vec![],
+    };
+
+    walker(node, addr_of_mut!(data).cast());
+    data.definitions
+}
+
+/// The tantivy aggregation request that computes every one of `definitions`, where each is named
+/// by its position
+pub fn window_agg_request(definitions: &[serde_json::Value]) -> Aggregations {
+    let request = definitions
+        .iter()
+        .enumerate()
+        .map(|(i, definition)| (i.to_string(), definition.clone()))
+        .collect::<serde_json::Map<_, _>>();
+
+    serde_json::from_value(serde_json::Value::Object(request))
+        .unwrap_or_else(|e| pgrx::error!("invalid paradedb.agg() definition: {e}"))
+}
+
+/// A [`tantivy::collector::Collector`] for `definitions` that only sees the documents which are
+/// visible to the current snapshot
+pub fn window_agg_collector(
+    definitions: &[serde_json::Value],
+    heaprel: pg_sys::Relation,
+) -> WindowAggCollector {
+    MVCCFilterCollector::new(
+        DistributedAggregationCollector::from_aggs(
+            window_agg_request(definitions),
+            AggregationLimitsGuard::new(Some(MEMORY_LIMIT), Some(BUCKET_LIMIT)),
+        ),
+        TSVisibilityChecker::with_rel_and_snap(heaprel, unsafe { pg_sys::GetActiveSnapshot() }),
+    )
+}
+
+/// Turn the collected aggregations into the result of each of `definitions`, in order
+pub fn window_agg_results(
+    definitions: &[serde_json::Value],
+    intermediate: IntermediateAggregationResults,
+) -> Vec<serde_json::Value> {
+    let results = intermediate
+        .into_final_result(
+            window_agg_request(definitions),
+            AggregationLimitsGuard::new(Some(MEMORY_LIMIT), Some(BUCKET_LIMIT)),
+        )
+        .unwrap_or_else(|e| pgrx::error!("failed to compute paradedb.agg(): {e}"));
+    let mut results = serde_json::to_value(results)
+        .expect("aggregation results should serialize to json")
+        .as_object_mut()
+        .map(std::mem::take)
+        .unwrap_or_default();
+
+    (0..definitions.len())
+        .map(|i| {
+            results
+                .remove(&i.to_string())
+                .unwrap_or(serde_json::Value::Null)
+        })
+        .collect()
+}
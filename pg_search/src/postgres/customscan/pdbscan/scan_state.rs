@@ -19,9 +19,15 @@ use crate::api::FieldName;
 use crate::api::HashMap;
 use crate::api::Varno;
 use crate::index::reader::index::{SearchIndexReader, SearchResults};
+use crate::index::reader::named_queries::NamedQueryMatcher;
+use crate::index::reader::order_by::OrderByFeature;
+use crate::index::reader::snippet::FragmentGenerator;
 use crate::postgres::customscan::builders::custom_path::{ExecMethodType, SortDirection};
 use crate::postgres::customscan::pdbscan::exec_methods::ExecMethod;
 use crate::postgres::customscan::pdbscan::projections::snippet::SnippetType;
+use crate::postgres::customscan::pdbscan::projections::window_agg::{
+    window_agg_collector, window_agg_results, WindowAggCollector,
+};
 use crate::postgres::customscan::pdbscan::qual_inspect::Qual;
 use crate::postgres::customscan::CustomScanState;
 use crate::postgres::rel::PgSearchRelation;
@@ -30,10 +36,11 @@ use crate::postgres::visibility_checker::VisibilityChecker;
 use crate::postgres::ParallelScanState;
 use crate::query::{AsHumanReadable, SearchQueryInput};
 use pgrx::heap_tuple::PgHeapTuple;
-use pgrx::{name_data_to_str, pg_sys, PgTupleDesc};
+use pgrx::{name_data_to_str, pg_sys, IntoDatum, JsonB, PgTupleDesc};
 use std::cell::UnsafeCell;
+use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use tantivy::snippet::SnippetGenerator;
-use tantivy::SegmentReader;
+use tantivy::{DocAddress, SegmentReader};
 
 #[derive(Default)]
 pub struct PdbScanState {
@@ -57,6 +64,7 @@ pub struct PdbScanState {
     pub limit: Option<usize>,
     pub sort_field: Option<FieldName>,
     pub sort_direction: Option<SortDirection>,
+    pub sort_keys: Option<Vec<(OrderByFeature, SortDirection)>>,
 
     pub query_count: usize,
     pub heap_tuple_check_count: usize,
@@ -77,13 +85,29 @@ pub struct PdbScanState {
     pub const_score_node: Option<*mut pg_sys::Const>,
     pub score_funcoid: pg_sys::Oid,
 
+    // `paradedb.matched_queries()` placeholders, and what finds the named clauses that match
+    pub need_matched_queries: bool,
+    pub matched_queries_funcoid: pg_sys::Oid,
+    pub const_matched_queries_nodes: Vec<*mut pg_sys::Const>,
+    pub named_query_matcher: Option<NamedQueryMatcher>,
+
     pub const_snippet_nodes: HashMap<SnippetType, Vec<*mut pg_sys::Const>>,
 
     pub snippet_funcoid: pg_sys::Oid,
     pub snippet_positions_funcoid: pg_sys::Oid,
+    pub snippets_funcoid: pg_sys::Oid,
 
     pub snippet_generators:
         HashMap<SnippetType, Option<(tantivy::schema::Field, SnippetGenerator)>>,
+    // `paradedb.snippets()` returns more than tantivy's snippet generator can, so it has its own
+    pub fragment_generators: HashMap<SnippetType, FragmentGenerator>,
+
+    // The definitions of the `paradedb.agg(...) OVER ()` aggregations in our target list, and
+    // their results once they've been computed
+    pub window_agg_funcoid: pg_sys::Oid,
+    pub window_aggs: Vec<serde_json::Value>,
+    pub const_window_agg_nodes: Vec<*mut pg_sys::Const>,
+    window_agg_results: Option<Vec<serde_json::Value>>,
 
     pub var_attname_lookup: HashMap<(Varno, pg_sys::AttrNumber), FieldName>,
     pub placeholder_targetlist: Option<*mut pg_sys::List>,
@@ -226,6 +250,58 @@ impl PdbScanState {
         !self.snippet_generators.is_empty()
     }
 
+    #[inline(always)]
+    pub fn need_matched_queries(&self) -> bool {
+        self.need_matched_queries
+    }
+
+    /// The names of the clauses of the query that match the document at `doc_address`
+    pub fn matched_queries(&mut self, doc_address: DocAddress) -> Vec<String> {
+        let Some(named_query_matcher) = self.named_query_matcher.as_mut() else {
+            pgrx::error!("named_query_matcher should be initialized");
+        };
+        named_query_matcher
+            .matched(doc_address)
+            .unwrap_or_else(|err| pgrx::error!("failed to match named queries: {err}"))
+    }
+
+    #[inline(always)]
+    pub fn need_window_aggs(&self) -> bool {
+        !self.window_aggs.is_empty()
+    }
+
+    /// A collector for our window aggregations, if they still need to be computed
+    pub fn window_agg_collector(&self) -> Option<WindowAggCollector> {
+        (self.need_window_aggs() && self.window_agg_results.is_none())
+            .then(|| window_agg_collector(&self.window_aggs, self.heaprel().as_ptr()))
+    }
+
+    /// Record the results of our window aggregations and fill in their placeholders, which are
+    /// the same for every row
+    pub fn set_window_agg_results(&mut self, intermediate: IntermediateAggregationResults) {
+        let results = window_agg_results(&self.window_aggs, intermediate);
+        for (const_, result) in self.const_window_agg_nodes.iter().zip(&results) {
+            unsafe {
+                (**const_).constvalue = JsonB(result.clone()).into_datum().unwrap();
+                (**const_).constisnull = false;
+            }
+        }
+        self.window_agg_results = Some(results);
+    }
+
+    /// Make sure our window aggregations have been computed.  If the exec method didn't do so as
+    /// part of its search, this does a pass over the index of its own.
+    pub fn ensure_window_aggs(&mut self) {
+        if let Some(collector) = self.window_agg_collector() {
+            let intermediate = self
+                .search_reader
+                .as_ref()
+                .expect("search reader should be initialized")
+                .collect(collector);
+            self.set_window_agg_results(intermediate);
+        }
+    }
+
     #[track_caller]
     #[inline(always)]
     pub fn heaprel(&self) -> &PgSearchRelation {
@@ -299,6 +375,43 @@ impl PdbScanState {
         }
     }
 
+    pub fn make_fragments(
+        &self,
+        ctid: u64,
+        snippet_type: &SnippetType,
+    ) -> Option<serde_json::Value> {
+        let SnippetType::Fragments(_, _, config) = snippet_type else {
+            return None;
+        };
+        let text = unsafe { self.text_from_heap(ctid, snippet_type.field())? };
+        let generator = self.fragment_generators.get(snippet_type)?;
+        let fragments = match &text {
+            HeapText::Single(text) => generator.fragments(
+                text,
+                config.max_fragments,
+                config.order,
+                &config.start_tag,
+                &config.end_tag,
+            ),
+            HeapText::Multi(elements) => generator.element_fragments(
+                &elements
+                    .iter()
+                    .map(|element| element.as_deref().unwrap_or_default())
+                    .collect::<Vec<_>>(),
+                config.max_fragments,
+                config.order,
+                &config.start_tag,
+                &config.end_tag,
+            ),
+        };
+
+        if fragments.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_value(fragments).expect("fragments should serialize to json"))
+        }
+    }
+
     pub fn is_sorted(&self) -> bool {
         matches!(
             self.sort_direction,
@@ -317,6 +430,7 @@ impl PdbScanState {
             }
         }
         self.search_results = SearchResults::None;
+        self.window_agg_results = None;
         self.query_count = 0;
         self.heap_tuple_check_count = 0;
         self.virtual_tuple_count = 0;
@@ -328,6 +442,10 @@ impl PdbScanState {
     ///
     /// This function supports text, text[], and json/jsonb fields
     unsafe fn doc_from_heap(&self, ctid: u64, field: &FieldName) -> Option<String> {
+        self.text_from_heap(ctid, field).map(HeapText::joined)
+    }
+
+    unsafe fn text_from_heap(&self, ctid: u64, field: &FieldName) -> Option<HeapText> {
         let heaprel = self.heaprel();
         let mut ipd = pg_sys::ItemPointerData::default();
         u64_to_item_pointer(ctid, &mut ipd);
@@ -370,20 +488,14 @@ impl PdbScanState {
         let (index, attribute) = heap_tuple.get_attribute_by_name(&field.root()).unwrap();
 
         if pg_sys::type_is_array(attribute.type_oid().value()) {
-            // varchar[] and text[] are flattened into a single string
-            // to emulate Tantivy's default behavior for highlighting text arrays
-            Some(
+            Some(HeapText::Multi(
                 pgrx::htup::heap_getattr::<Vec<Option<String>>, _>(
                     &pgrx::pgbox::PgBox::from_pg(&mut htup),
                     index,
                     &tuple_desc,
                 )
-                .unwrap_or_default()
-                .into_iter()
-                .flatten()
-                .collect::<Vec<_>>()
-                .join(" "),
-            )
+                .unwrap_or_default(),
+            ))
         } else {
             match (field.root(), field.path()) {
                 (root, Some(path)) => {
@@ -417,11 +529,16 @@ impl PdbScanState {
                     };
 
                     match field {
-                        serde_json::Value::String(val) => Some(val),
-                        serde_json::Value::Array(array) => Some(array.iter().filter_map(|v| match v {
-                            serde_json::Value::String(s) => Some(s.to_owned()),
-                            _ => None
-                        }).collect::<Vec<_>>().join(" ")),
+                        serde_json::Value::String(val) => Some(HeapText::Single(val)),
+                        serde_json::Value::Array(array) => Some(HeapText::Multi(
+                            array
+                                .into_iter()
+                                .map(|v| match v {
+                                    serde_json::Value::String(s) => Some(s),
+                                    _ => None,
+                                })
+                                .collect(),
+                        )),
                         val => unimplemented!(
                             "only text fields for json/jsonb are supported for snippets, found {:?}",
                             val
@@ -430,7 +547,29 @@ impl PdbScanState {
                 }
                 (root, None) => heap_tuple
                     .get_by_name(&root)
-                    .unwrap_or_else(|_| panic!("doc_from_heap: should be able to read {root}")),
+                    .unwrap_or_else(|_| panic!("doc_from_heap: should be able to read {root}"))
+                    .map(HeapText::Single),
+            }
+        }
+    }
+}
+
+/// The text of a field, as read from the heap, for highlighting
+enum HeapText {
+    Single(String),
+    /// The values of a `text[]`/`varchar[]` column or a JSON array, where a value that isn't text
+    /// is `None`
+    Multi(Vec<Option<String>>),
+}
+
+impl HeapText {
+    /// Multiple values are flattened into a single string to emulate Tantivy's default behavior
+    /// for highlighting text arrays
+    fn joined(self) -> String {
+        match self {
+            HeapText::Single(text) => text,
+            HeapText::Multi(elements) => {
+                elements.into_iter().flatten().collect::<Vec<_>>().join(" ")
             }
         }
     }
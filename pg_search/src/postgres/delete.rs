@@ -94,6 +94,9 @@ pub unsafe extern "C-unwind" fn ambulkdelete(
             .expect("ambulkdelete: should be able to open a SegmentDeleter");
         let ctid_ff = FFType::new_ctid(segment_reader.fast_fields());
         let mut needs_commit = false;
+        // the first document of the current row's block, which starts with the child documents
+        // of its nested fields, if any, and ends with the row's own document
+        let mut block_start = 0;
 
         for doc_id in 0..segment_reader.max_doc() {
             if doc_id % 100 == 0 {
@@ -101,12 +104,18 @@ pub unsafe extern "C-unwind" fn ambulkdelete(
                 pg_sys::vacuum_delay_point();
             }
 
-            let ctid = ctid_ff.as_u64(doc_id).expect("ctid should be present");
+            // only a row's own document has a ctid
+            let Some(ctid) = ctid_ff.as_u64(doc_id) else {
+                continue;
+            };
             if callback(ctid) {
                 did_delete = true;
                 needs_commit = true;
-                deleter.delete_document(doc_id);
+                for block_doc_id in block_start..=doc_id {
+                    deleter.delete_document(block_doc_id);
+                }
             }
+            block_start = doc_id + 1;
         }
 
         if needs_commit {
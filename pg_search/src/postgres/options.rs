@@ -799,8 +799,11 @@ fn key_field_config(field_type: SearchFieldType) -> SearchFieldConfig {
             record: IndexRecordOption::Basic,
             normalizer: SearchNormalizer::Raw,
             column: None,
+            nested: false,
         },
         SearchFieldType::Range(_) => SearchFieldConfig::Range { fast: true },
+        SearchFieldType::Geo(_) => SearchFieldConfig::default_geo(),
+        SearchFieldType::Query(_) => SearchFieldConfig::default_query(),
         SearchFieldType::Bool(_) => SearchFieldConfig::Boolean {
             indexed: true,
             fast: true,
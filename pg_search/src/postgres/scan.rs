@@ -102,6 +102,13 @@ pub extern "C-unwind" fn amrescan(
         };
     }
 
+    search_query_input
+        .check_no_search_after()
+        .unwrap_or_else(|err| pgrx::error!("{err}"));
+    search_query_input
+        .check_no_rescore()
+        .unwrap_or_else(|err| pgrx::error!("{err}"));
+
     // Create the index and scan state
     let search_reader = SearchIndexReader::open(&indexrel, search_query_input, false, unsafe {
         if pg_sys::ParallelWorkerNumber == -1 || (*scan).parallel_scan.is_null() {
@@ -60,6 +60,9 @@ pub struct MetaPageData {
 
     /// The block where our FSM starts
     fsm: pg_sys::BlockNumber,
+
+    /// The header block for a [`LinkedBytesList`] of the tokenizers resolved when the index was built
+    tokenizers_list: pg_sys::BlockNumber,
 }
 
 /// Provides read access to the metadata page
@@ -222,6 +225,17 @@ impl MetaPage {
             .collect()
     }
 
+    pub fn tokenizers_bytes(&self) -> Option<LinkedBytesList> {
+        if !block_number_is_valid(self.data.tokenizers_list) {
+            return None;
+        }
+
+        Some(LinkedBytesList::open(
+            self.bman.buffer_access().rel(),
+            self.data.tokenizers_list,
+        ))
+    }
+
     pub fn fsm(&self) -> pg_sys::BlockNumber {
         assert!(block_number_is_valid(self.data.fsm));
         self.data.fsm
@@ -323,4 +337,20 @@ impl MetaPage {
 
         Ok(())
     }
+
+    pub fn record_tokenizers(&mut self, tokenizers: &[u8]) -> anyhow::Result<()> {
+        let tokenizers_list = LinkedBytesList::create_with_fsm(self.bman.buffer_access().rel());
+        let mut writer = tokenizers_list.writer();
+        unsafe {
+            writer.write(tokenizers)?;
+        }
+        let tokenizers_list = writer.into_inner()?;
+
+        let mut buffer = self.bman.get_buffer_mut(METAPAGE);
+        let mut page = buffer.page_mut();
+        let metadata = page.contents_mut::<MetaPageData>();
+        metadata.tokenizers_list = tokenizers_list.get_header_blockno();
+
+        Ok(())
+    }
 }
@@ -15,8 +15,11 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::operator::searchqueryinput_typoid;
 use crate::postgres::datetime::{datetime_components_to_tantivy_date, MICROSECONDS_IN_SECOND};
 use crate::postgres::range::RangeToTantivyValue;
+use crate::query::geo::{LAT_KEY, LON_KEY};
+use crate::query::SearchQueryInput;
 use crate::schema::AnyEnum;
 use ordered_float::OrderedFloat;
 use pgrx::datum::datetime_support::DateTimeConversionError;
@@ -235,6 +238,10 @@ impl TantivyValue {
                     pgrx::datum::Inet::from_datum(datum, false)
                         .ok_or(TantivyValueError::DatumDeref)?,
                 ),
+                PgBuiltInOids::POINTOID => TantivyValue::try_from(
+                    pgrx::pg_sys::Point::from_datum(datum, false)
+                        .ok_or(TantivyValueError::DatumDeref)?,
+                ),
                 PgBuiltInOids::INT4RANGEOID => TantivyValue::from_range(
                     pgrx::datum::Range::<i32>::from_datum(datum, false)
                         .ok_or(TantivyValueError::DatumDeref)?,
@@ -270,6 +277,11 @@ impl TantivyValue {
                             .ok_or(TantivyValueError::DatumDeref)?,
                     );
                     TantivyValue::try_from(ordinal)
+                } else if *custom == searchqueryinput_typoid() {
+                    TantivyValue::try_from(
+                        SearchQueryInput::from_datum(datum, false)
+                            .ok_or(TantivyValueError::DatumDeref)?,
+                    )
                 } else {
                     Err(TantivyValueError::UnsupportedOid(oid.value()))
                 }
@@ -959,10 +971,25 @@ impl TryFrom<pgrx::pg_sys::BOX> for TantivyValue {
 impl TryFrom<pgrx::pg_sys::Point> for TantivyValue {
     type Error = TantivyValueError;
 
-    fn try_from(_val: pgrx::pg_sys::Point) -> Result<Self, Self::Error> {
-        Err(TantivyValueError::UnsupportedFromConversion(
-            "point".to_string(),
-        ))
+    fn try_from(val: pgrx::pg_sys::Point) -> Result<Self, Self::Error> {
+        // points are (x, y), which for geo fields is (longitude, latitude)
+        if !((-90.0..=90.0).contains(&val.y) && (-180.0..=180.0).contains(&val.x)) {
+            return Err(TantivyValueError::InvalidGeoPoint(val.x, val.y));
+        }
+        Ok(TantivyValue(tantivy::schema::OwnedValue::Object(vec![
+            (LAT_KEY.to_string(), tantivy::schema::OwnedValue::F64(val.y)),
+            (LON_KEY.to_string(), tantivy::schema::OwnedValue::F64(val.x)),
+        ])))
+    }
+}
+
+impl TryFrom<SearchQueryInput> for TantivyValue {
+    type Error = TantivyValueError;
+
+    fn try_from(val: SearchQueryInput) -> Result<Self, Self::Error> {
+        Ok(TantivyValue(tantivy::schema::OwnedValue::Str(
+            serde_json::to_string(&val)?,
+        )))
     }
 }
 
@@ -1049,4 +1076,7 @@ pub enum TantivyValueError {
 
     #[error("Cannot convert TantivyValue to type {0}")]
     UnsupportedIntoConversion(String),
+
+    #[error("Point ({0}, {1}) is not a valid longitude and latitude")]
+    InvalidGeoPoint(f64, f64),
 }
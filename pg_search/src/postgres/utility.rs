@@ -0,0 +1,89 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! A "process utility" hook that schema-qualifies the word list tables named in the options of
+//! `CREATE INDEX ... USING bm25`, while it still has the `search_path` of whoever ran it.
+//! Postgres builds the index, and rebuilds it on `REINDEX`, with a restricted `search_path`.
+
+use crate::api::analyzer::qualify_field_word_list_tables;
+use pgrx::pg_sys::AsPgCStr;
+use pgrx::{is_a, pg_guard, pg_sys, PgList};
+use std::ffi::CStr;
+
+pub unsafe fn register() {
+    static mut PREV_PROCESS_UTILITY_HOOK: pg_sys::ProcessUtility_hook_type = None;
+
+    PREV_PROCESS_UTILITY_HOOK = pg_sys::ProcessUtility_hook;
+    pg_sys::ProcessUtility_hook = Some(process_utility_hook);
+
+    #[allow(clippy::too_many_arguments)]
+    #[rustfmt::skip]
+    #[pg_guard]
+    unsafe extern "C-unwind" fn process_utility_hook(
+        mut pstmt: *mut pg_sys::PlannedStmt,
+        query_string: *const ::core::ffi::c_char,
+        mut read_only_tree: bool,
+        context: pg_sys::ProcessUtilityContext::Type,
+        params: pg_sys::ParamListInfo,
+        query_env: *mut pg_sys::QueryEnvironment,
+        dest: *mut pg_sys::DestReceiver,
+        qc: *mut pg_sys::QueryCompletion,
+    ) {
+        if is_bm25_index_stmt((*pstmt).utilityStmt) {
+            if read_only_tree {
+                pstmt = pg_sys::copyObjectImpl(pstmt.cast()).cast();
+                read_only_tree = false;
+            }
+            qualify_word_list_tables((*pstmt).utilityStmt.cast());
+        }
+
+        if let Some(prev_hook) = PREV_PROCESS_UTILITY_HOOK {
+            prev_hook(pstmt, query_string, read_only_tree, context, params, query_env, dest, qc);
+        } else {
+            pg_sys::standard_ProcessUtility(pstmt, query_string, read_only_tree, context, params, query_env, dest, qc)
+        }
+    }
+}
+
+unsafe fn is_bm25_index_stmt(node: *mut pg_sys::Node) -> bool {
+    if !is_a(node, pg_sys::NodeTag::T_IndexStmt) {
+        return false;
+    }
+    let access_method = (*node.cast::<pg_sys::IndexStmt>()).accessMethod;
+    !access_method.is_null() && CStr::from_ptr(access_method) == c"bm25"
+}
+
+/// Rewrite the `text_fields` and `json_fields` options of `stmt` with their word list tables
+/// schema-qualified
+unsafe fn qualify_word_list_tables(stmt: *mut pg_sys::IndexStmt) {
+    let options = PgList::<pg_sys::DefElem>::from_pg((*stmt).options);
+    for option in options.iter_ptr() {
+        let name = CStr::from_ptr((*option).defname);
+        if name != c"text_fields" && name != c"json_fields" {
+            continue;
+        }
+
+        let value = CStr::from_ptr(pg_sys::defGetString(option)).to_string_lossy();
+        let Ok(mut configs) = serde_json::from_str::<serde_json::Value>(&value) else {
+            // the options are validated, and any errors reported, once the index is created
+            continue;
+        };
+        if qualify_field_word_list_tables(&mut configs).unwrap_or_else(|e| pgrx::error!("{e}")) {
+            (*option).arg = pg_sys::makeString(configs.to_string().as_pg_cstr()).cast();
+        }
+    }
+}
@@ -179,6 +179,18 @@ pub unsafe fn find_one_var(node: *mut pg_sys::Node) -> Option<*mut pg_sys::Var>
 pub unsafe fn find_one_var_and_fieldname(
     root: *mut pg_sys::PlannerInfo,
     node: *mut pg_sys::Node,
+) -> Option<(*mut pg_sys::Var, FieldName)> {
+    find_one_var_and_fieldname_with(node, &|var| {
+        let (heaprelid, varattno, _) = find_var_relation(var, root);
+        fieldname_from_var(heaprelid, var, varattno)
+    })
+}
+
+/// Like [`find_one_var_and_fieldname`], but for when there's no [`pg_sys::PlannerInfo`] to look
+/// the [`pg_sys::Var`] up in, such as during execution.  `var_fieldname` is used instead.
+pub unsafe fn find_one_var_and_fieldname_with(
+    node: *mut pg_sys::Node,
+    var_fieldname: &dyn Fn(*mut pg_sys::Var) -> Option<FieldName>,
 ) -> Option<(*mut pg_sys::Var, FieldName)> {
     if is_a(node, T_OpExpr) {
         let opexpr = node.cast::<OpExpr>();
@@ -188,32 +200,31 @@ pub unsafe fn find_one_var_and_fieldname(
             .contains(&(*opexpr).opno)
         {
             let var = find_one_var(node)?;
-            let path = find_json_path(root, node);
+            let path = find_json_path(var_fieldname, node);
             return Some((var, path.join(".").into()));
         }
         None
     } else if is_a(node, T_Var) {
         let var = node.cast::<Var>();
-        let (heaprelid, varattno, _) = find_var_relation(var, root);
-        Some((var, fieldname_from_var(heaprelid, var, varattno)?))
+        Some((var, var_fieldname(var)?))
     } else {
         None
     }
 }
 
-/// Given a [`pg_sys::Node`] and a [`pg_sys::PlannerInfo`], attempt to find the JSON path that the
-/// node references.
+/// Given a [`pg_sys::Node`], attempt to find the JSON path that the node references.
 ///
 /// It is the caller's responsibility to ensure that the node is a JSON path expression.
 #[inline(always)]
-unsafe fn find_json_path(root: *mut pg_sys::PlannerInfo, node: *mut pg_sys::Node) -> Vec<String> {
+unsafe fn find_json_path(
+    var_fieldname: &dyn Fn(*mut pg_sys::Var) -> Option<FieldName>,
+    node: *mut pg_sys::Node,
+) -> Vec<String> {
     let mut path = Vec::new();
 
     if is_a(node, T_Var) {
-        let node = node as *mut Var;
-        let (heaprelid, varattno, _) = find_var_relation(node, root);
-        let field_name = fieldname_from_var(heaprelid, node, varattno)
-            .expect("find_json_path: var should have a valid FieldName");
+        let field_name =
+            var_fieldname(node as *mut Var).expect("find_json_path: var should have a valid FieldName");
         path.push(field_name.root());
         return path;
     } else if is_a(node, T_Const) {
@@ -240,7 +251,7 @@ unsafe fn find_json_path(root: *mut pg_sys::PlannerInfo, node: *mut pg_sys::Node
     } else if is_a(node, T_OpExpr) {
         let node = node as *mut OpExpr;
         for expr in PgList::from_pg((*node).args).iter_ptr() {
-            path.extend(find_json_path(root, expr));
+            path.extend(find_json_path(var_fieldname, expr));
         }
     }
 
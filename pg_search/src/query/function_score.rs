@@ -0,0 +1,562 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use super::geo::{GeoColumns, GeoPoint};
+use super::{QueryError, TantivyDateTime};
+use crate::api::FieldName;
+use serde::{Deserialize, Serialize};
+use tantivy::columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+use tantivy::query::{EnableScoring, Explanation, Query, QueryClone, Scorer, Weight};
+use tantivy::schema::{Field, OwnedValue};
+use tantivy::{DateTime, DocId, DocSet, Score, SegmentReader, Term};
+
+/// How the values of a [`FunctionScoreQuery`]'s functions are combined with each other
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionScoreMode {
+    #[default]
+    Multiply,
+    Sum,
+    Avg,
+    First,
+    Max,
+    Min,
+}
+
+/// How the combined value of a [`FunctionScoreQuery`]'s functions is combined with the score of
+/// its query
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionBoostMode {
+    #[default]
+    Multiply,
+    Replace,
+    Sum,
+    Avg,
+    Max,
+    Min,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayFunction {
+    Gauss,
+    Exp,
+    Linear,
+}
+
+/// Applied to a field's value by [`ScoreFunction::FieldValueFactor`], after it's multiplied by
+/// the factor
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldValueModifier {
+    #[default]
+    None,
+    Log,
+    Log1p,
+    Log2p,
+    Ln,
+    Ln1p,
+    Ln2p,
+    Square,
+    Sqrt,
+    Reciprocal,
+}
+
+/// A function of a document's value for a numeric, date, or geo fast field, which adjusts the
+/// document's score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreFunction {
+    /// `1.0` for values within `offset` of `origin`, falling to `decay` at `scale` beyond that.
+    ///
+    /// For date fields, `origin` is a timestamp or `"now"`, and `scale` and `offset` are
+    /// durations like `"7d"` or `"12h"`, or numbers of seconds.
+    Decay {
+        field: FieldName,
+        function: DecayFunction,
+        origin: OwnedValue,
+        scale: OwnedValue,
+        offset: Option<OwnedValue>,
+        decay: Option<f64>,
+        weight: Option<f32>,
+    },
+    /// The field's value multiplied by `factor`, and then put through `modifier`.  Documents
+    /// without a value use `missing`, or aren't adjusted if it isn't set.
+    FieldValueFactor {
+        field: FieldName,
+        factor: Option<f64>,
+        #[serde(default)]
+        modifier: FieldValueModifier,
+        missing: Option<f64>,
+        weight: Option<f32>,
+    },
+    /// The distance in meters from `origin` to the field's point.  Documents without a point
+    /// are treated as infinitely far away.
+    GeoDistance { field: FieldName, origin: GeoPoint },
+}
+
+const DEFAULT_DECAY: f64 = 0.5;
+
+impl ScoreFunction {
+    pub fn field(&self) -> &FieldName {
+        match self {
+            ScoreFunction::Decay { field, .. }
+            | ScoreFunction::FieldValueFactor { field, .. }
+            | ScoreFunction::GeoDistance { field, .. } => field,
+        }
+    }
+
+    pub fn is_geo(&self) -> bool {
+        matches!(self, ScoreFunction::GeoDistance { .. })
+    }
+
+    /// Resolve the function's dates and durations into numbers.  Dates are measured in seconds.
+    pub fn resolve(self) -> Result<ResolvedFunction, QueryError> {
+        match self {
+            ScoreFunction::Decay {
+                field,
+                function,
+                origin,
+                scale,
+                offset,
+                decay,
+                weight,
+            } => {
+                let origin = match origin {
+                    OwnedValue::Str(text) if text == "now" => {
+                        chrono::Utc::now().timestamp_micros() as f64 / 1e6
+                    }
+                    OwnedValue::Str(text) => parse_date(&text)?,
+                    OwnedValue::Date(date) => date.into_timestamp_micros() as f64 / 1e6,
+                    other => number(&other).ok_or_else(|| {
+                        QueryError::InvalidScoreFunction(format!(
+                            "origin must be a number or a date, found {other:?}"
+                        ))
+                    })?,
+                };
+                let scale = distance(&scale)?;
+                let offset = offset.as_ref().map(distance).transpose()?.unwrap_or(0.0);
+                let decay = decay.unwrap_or(DEFAULT_DECAY);
+                if scale <= 0.0 {
+                    return Err(QueryError::InvalidScoreFunction(
+                        "scale must be greater than 0".into(),
+                    ));
+                }
+                if !(decay > 0.0 && decay < 1.0) {
+                    return Err(QueryError::InvalidScoreFunction(
+                        "decay must be between 0 and 1".into(),
+                    ));
+                }
+
+                Ok(ResolvedFunction {
+                    field,
+                    kind: FunctionKind::Decay {
+                        function,
+                        origin,
+                        scale,
+                        offset,
+                        decay,
+                    },
+                    weight: weight.unwrap_or(1.0),
+                })
+            }
+            ScoreFunction::FieldValueFactor {
+                field,
+                factor,
+                modifier,
+                missing,
+                weight,
+            } => Ok(ResolvedFunction {
+                field,
+                kind: FunctionKind::FieldValueFactor {
+                    factor: factor.unwrap_or(1.0),
+                    modifier,
+                    missing,
+                },
+                weight: weight.unwrap_or(1.0),
+            }),
+            ScoreFunction::GeoDistance { field, origin } => {
+                origin.validate()?;
+                Ok(ResolvedFunction {
+                    field,
+                    kind: FunctionKind::GeoDistance { origin },
+                    weight: 1.0,
+                })
+            }
+        }
+    }
+}
+
+/// Seconds since the epoch of an ISO 8601 timestamp, or of midnight UTC on a date
+fn parse_date(text: &str) -> Result<f64, QueryError> {
+    if let Ok(TantivyDateTime(date)) = TantivyDateTime::try_from(text) {
+        return Ok(date.into_timestamp_micros() as f64 / 1e6);
+    }
+    let date = chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map_err(|_| QueryError::FieldTypeMismatch)?;
+    Ok(date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp() as f64)
+}
+
+fn number(value: &OwnedValue) -> Option<f64> {
+    match value {
+        OwnedValue::I64(i) => Some(*i as f64),
+        OwnedValue::U64(u) => Some(*u as f64),
+        OwnedValue::F64(f) => Some(*f),
+        _ => None,
+    }
+}
+
+/// A `scale` or `offset`, which is a number, or a duration in seconds like `"7d"`
+fn distance(value: &OwnedValue) -> Result<f64, QueryError> {
+    let invalid = || {
+        QueryError::InvalidScoreFunction(format!(
+            "expected a number or a duration like '7d', found {value:?}"
+        ))
+    };
+    match value {
+        OwnedValue::Str(text) => {
+            let text = text.trim();
+            let unit_start = text
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .ok_or_else(invalid)?;
+            let amount = text[..unit_start].parse::<f64>().map_err(|_| invalid())?;
+            let seconds = match text[unit_start..].trim() {
+                "ms" => 0.001,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 3_600.0,
+                "d" => 86_400.0,
+                "w" => 604_800.0,
+                _ => return Err(invalid()),
+            };
+            Ok(amount * seconds)
+        }
+        other => number(other).ok_or_else(invalid),
+    }
+}
+
+/// A [`ScoreFunction`] with its dates and durations resolved to numbers
+#[derive(Debug, Clone)]
+pub struct ResolvedFunction {
+    field: FieldName,
+    kind: FunctionKind,
+    weight: Score,
+}
+
+#[derive(Debug, Clone)]
+enum FunctionKind {
+    Decay {
+        function: DecayFunction,
+        origin: f64,
+        scale: f64,
+        offset: f64,
+        decay: f64,
+    },
+    FieldValueFactor {
+        factor: f64,
+        modifier: FieldValueModifier,
+        missing: Option<f64>,
+    },
+    GeoDistance {
+        origin: GeoPoint,
+    },
+}
+
+impl ResolvedFunction {
+    /// The function's value for a document whose field has `value`, which is the distance from
+    /// the origin for geo functions
+    fn value(&self, value: Option<f64>) -> Score {
+        let value = match &self.kind {
+            FunctionKind::Decay {
+                function,
+                origin,
+                scale,
+                offset,
+                decay,
+            } => {
+                // like Elasticsearch, documents without a value aren't decayed
+                let Some(value) = value else {
+                    return self.weight;
+                };
+                let distance = ((value - origin).abs() - offset).max(0.0);
+                match function {
+                    DecayFunction::Gauss => (distance.powi(2) * decay.ln() / scale.powi(2)).exp(),
+                    DecayFunction::Exp => (distance * decay.ln() / scale).exp(),
+                    DecayFunction::Linear => {
+                        let zero_at = scale / (1.0 - decay);
+                        ((zero_at - distance) / zero_at).max(0.0)
+                    }
+                }
+            }
+            FunctionKind::FieldValueFactor {
+                factor,
+                modifier,
+                missing,
+            } => {
+                let Some(value) = value.or(*missing) else {
+                    return self.weight;
+                };
+                let value = value * factor;
+                let modified = match modifier {
+                    FieldValueModifier::None => value,
+                    FieldValueModifier::Log => value.log10(),
+                    FieldValueModifier::Log1p => (value + 1.0).log10(),
+                    FieldValueModifier::Log2p => (value + 2.0).log10(),
+                    FieldValueModifier::Ln => value.ln(),
+                    FieldValueModifier::Ln1p => value.ln_1p(),
+                    FieldValueModifier::Ln2p => (value + 2.0).ln(),
+                    FieldValueModifier::Square => value.powi(2),
+                    FieldValueModifier::Sqrt => value.sqrt(),
+                    FieldValueModifier::Reciprocal => value.recip(),
+                };
+                // negative and undefined scores would upset the ranking, so treat them as 0
+                if modified.is_finite() {
+                    modified.max(0.0)
+                } else {
+                    0.0
+                }
+            }
+            FunctionKind::GeoDistance { .. } => value.unwrap_or(f64::MAX),
+        };
+        value as Score * self.weight
+    }
+}
+
+/// Adjusts the scores of `query`'s documents with functions of their fast field values
+#[derive(Debug)]
+pub struct FunctionScoreQuery {
+    query: Box<dyn Query>,
+    functions: Vec<ResolvedFunction>,
+    score_mode: FunctionScoreMode,
+    boost_mode: FunctionBoostMode,
+}
+
+impl QueryClone for FunctionScoreQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            query: self.query.box_clone(),
+            functions: self.functions.clone(),
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        })
+    }
+}
+
+impl FunctionScoreQuery {
+    pub fn new(
+        query: Box<dyn Query>,
+        functions: Vec<ResolvedFunction>,
+        score_mode: FunctionScoreMode,
+        boost_mode: FunctionBoostMode,
+    ) -> Self {
+        Self {
+            query,
+            functions,
+            score_mode,
+            boost_mode,
+        }
+    }
+}
+
+impl Query for FunctionScoreQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(FunctionScoreWeight {
+            weight: self.query.weight(enable_scoring)?,
+            functions: self.functions.clone(),
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor)
+    }
+}
+
+struct FunctionScoreWeight {
+    weight: Box<dyn Weight>,
+    functions: Vec<ResolvedFunction>,
+    score_mode: FunctionScoreMode,
+    boost_mode: FunctionBoostMode,
+}
+
+impl FunctionScoreWeight {
+    fn function_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> tantivy::Result<FunctionScoreScorer> {
+        let columns = self
+            .functions
+            .iter()
+            .map(|function| FieldColumn::open(reader, function))
+            .collect::<tantivy::Result<_>>()?;
+
+        Ok(FunctionScoreScorer {
+            scorer: self.weight.scorer(reader, boost)?,
+            functions: self.functions.clone(),
+            columns,
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        })
+    }
+}
+
+impl Weight for FunctionScoreWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        Ok(Box::new(self.function_scorer(reader, boost)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let query_explanation = self.weight.explain(reader, doc)?;
+        let mut scorer = self.function_scorer(reader, 1.0)?;
+        scorer.seek(doc);
+
+        let mut explanation = Explanation::new("FunctionScore", scorer.score());
+        explanation.add_detail(query_explanation);
+        for (function, value) in self.functions.iter().zip(scorer.function_values()) {
+            explanation.add_detail(Explanation::new(
+                format!("function of {}", function.field),
+                value,
+            ));
+        }
+        Ok(explanation)
+    }
+}
+
+/// The numeric values of a fast field, where dates are in seconds, or the distances of a geo
+/// field's points from an origin
+enum FieldColumn {
+    Numeric(Option<(Column<u64>, ColumnType)>),
+    Distance(Option<GeoColumns>, GeoPoint),
+}
+
+impl FieldColumn {
+    fn open(reader: &SegmentReader, function: &ResolvedFunction) -> tantivy::Result<Self> {
+        const NUMERIC_TYPES: [ColumnType; 5] = [
+            ColumnType::I64,
+            ColumnType::U64,
+            ColumnType::F64,
+            ColumnType::Bool,
+            ColumnType::DateTime,
+        ];
+        if let FunctionKind::GeoDistance { origin } = &function.kind {
+            return Ok(FieldColumn::Distance(
+                GeoColumns::open(reader, &function.field)?,
+                *origin,
+            ));
+        }
+        Ok(FieldColumn::Numeric(
+            reader
+                .fast_fields()
+                .u64_lenient_for_type(Some(&NUMERIC_TYPES), &function.field)?,
+        ))
+    }
+
+    fn value(&self, doc: DocId) -> Option<f64> {
+        match self {
+            FieldColumn::Numeric(column) => {
+                let (column, column_type) = column.as_ref()?;
+                let value = column.first(doc)?;
+                Some(match column_type {
+                    ColumnType::I64 => i64::from_u64(value) as f64,
+                    ColumnType::F64 => f64::from_u64(value),
+                    ColumnType::Bool => bool::from_u64(value) as u8 as f64,
+                    ColumnType::DateTime => {
+                        DateTime::from_u64(value).into_timestamp_micros() as f64 / 1e6
+                    }
+                    _ => value as f64,
+                })
+            }
+            FieldColumn::Distance(columns, origin) => {
+                let point = columns.as_ref()?.point(doc)?;
+                Some(origin.distance(&point))
+            }
+        }
+    }
+}
+
+struct FunctionScoreScorer {
+    scorer: Box<dyn Scorer>,
+    functions: Vec<ResolvedFunction>,
+    columns: Vec<FieldColumn>,
+    score_mode: FunctionScoreMode,
+    boost_mode: FunctionBoostMode,
+}
+
+impl FunctionScoreScorer {
+    fn function_values(&self) -> Vec<Score> {
+        let doc = self.scorer.doc();
+        self.functions
+            .iter()
+            .zip(&self.columns)
+            .map(|(function, column)| function.value(column.value(doc)))
+            .collect()
+    }
+}
+
+impl Scorer for FunctionScoreScorer {
+    fn score(&mut self) -> Score {
+        let values = self.function_values();
+        let function_score = if values.is_empty() {
+            1.0
+        } else {
+            match self.score_mode {
+                FunctionScoreMode::Multiply => values.iter().product(),
+                FunctionScoreMode::Sum => values.iter().sum(),
+                FunctionScoreMode::Avg => values.iter().sum::<Score>() / values.len() as Score,
+                FunctionScoreMode::First => values[0],
+                FunctionScoreMode::Max => values.iter().copied().fold(Score::MIN, Score::max),
+                FunctionScoreMode::Min => values.iter().copied().fold(Score::MAX, Score::min),
+            }
+        };
+
+        let query_score = self.scorer.score();
+        match self.boost_mode {
+            FunctionBoostMode::Multiply => query_score * function_score,
+            FunctionBoostMode::Replace => function_score,
+            FunctionBoostMode::Sum => query_score + function_score,
+            FunctionBoostMode::Avg => (query_score + function_score) / 2.0,
+            FunctionBoostMode::Max => query_score.max(function_score),
+            FunctionBoostMode::Min => query_score.min(function_score),
+        }
+    }
+}
+
+impl DocSet for FunctionScoreScorer {
+    fn advance(&mut self) -> DocId {
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use super::QueryError;
+use crate::api::FieldName;
+use serde::{Deserialize, Serialize};
+use tantivy::columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+use tantivy::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::schema::OwnedValue;
+use tantivy::{DocId, DocSet, Score, SegmentReader, TantivyError, TERMINATED};
+
+/// The mean radius of the Earth, in meters
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// The keys that geo fields store their coordinates under.  Postgres `point`s are indexed as
+/// `{"lat": y, "lon": x}`, and JSON objects with these keys can be queried the same way.
+pub const LAT_KEY: &str = "lat";
+pub const LON_KEY: &str = "lon";
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    pub fn validate(&self) -> Result<(), QueryError> {
+        if !((-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lon)) {
+            return Err(QueryError::InvalidGeoQuery(format!(
+                "({}, {}) is not a valid latitude and longitude",
+                self.lat, self.lon
+            )));
+        }
+        Ok(())
+    }
+
+    /// The great-circle distance to `other`, in meters
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let half_dlat = (lat2 - lat1) / 2.0;
+        let half_dlon = (other.lon - self.lon).to_radians() / 2.0;
+        let a = half_dlat.sin().powi(2) + lat1.cos() * lat2.cos() * half_dlon.sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// A distance in meters, given as a number of meters or as a length like `"5km"`
+pub fn meters(value: &OwnedValue) -> Result<f64, QueryError> {
+    let invalid = || {
+        QueryError::InvalidGeoQuery(format!(
+            "expected a number of meters or a distance like '5km', found {value:?}"
+        ))
+    };
+    let meters = match value {
+        OwnedValue::I64(i) => *i as f64,
+        OwnedValue::U64(u) => *u as f64,
+        OwnedValue::F64(f) => *f,
+        OwnedValue::Str(text) => {
+            let text = text.trim();
+            let unit_start = text
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(text.len());
+            let amount = text[..unit_start].parse::<f64>().map_err(|_| invalid())?;
+            let unit = match text[unit_start..].trim() {
+                "" | "m" => 1.0,
+                "km" => 1_000.0,
+                "mi" => 1_609.344,
+                "yd" => 0.9144,
+                "ft" => 0.3048,
+                "nmi" => 1_852.0,
+                _ => return Err(invalid()),
+            };
+            amount * unit
+        }
+        _ => return Err(invalid()),
+    };
+    if !(meters.is_finite() && meters >= 0.0) {
+        return Err(invalid());
+    }
+    Ok(meters)
+}
+
+/// The area a [`GeoQuery`] matches points within
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoShape {
+    Distance {
+        origin: GeoPoint,
+        meters: f64,
+    },
+    /// A box that crosses the antimeridian when its left edge is east of its right edge
+    BoundingBox {
+        top_left: GeoPoint,
+        bottom_right: GeoPoint,
+    },
+    Polygon(Vec<GeoPoint>),
+}
+
+impl GeoShape {
+    pub fn validate(&self) -> Result<(), QueryError> {
+        match self {
+            GeoShape::Distance { origin, .. } => origin.validate(),
+            GeoShape::BoundingBox {
+                top_left,
+                bottom_right,
+            } => {
+                top_left.validate()?;
+                bottom_right.validate()?;
+                if top_left.lat < bottom_right.lat {
+                    return Err(QueryError::InvalidGeoQuery(
+                        "the top of a bounding box must not be below its bottom".into(),
+                    ));
+                }
+                Ok(())
+            }
+            GeoShape::Polygon(points) => {
+                if points.len() < 3 {
+                    return Err(QueryError::InvalidGeoQuery(
+                        "a polygon needs at least 3 points".into(),
+                    ));
+                }
+                points.iter().try_for_each(GeoPoint::validate)
+            }
+        }
+    }
+
+    fn contains(&self, point: &GeoPoint) -> bool {
+        match self {
+            GeoShape::Distance { origin, meters } => origin.distance(point) <= *meters,
+            GeoShape::BoundingBox {
+                top_left,
+                bottom_right,
+            } => {
+                let within_lat = point.lat <= top_left.lat && point.lat >= bottom_right.lat;
+                let within_lon = if top_left.lon <= bottom_right.lon {
+                    point.lon >= top_left.lon && point.lon <= bottom_right.lon
+                } else {
+                    point.lon >= top_left.lon || point.lon <= bottom_right.lon
+                };
+                within_lat && within_lon
+            }
+            GeoShape::Polygon(points) => {
+                // even-odd ray casting, treating longitude and latitude as planar coordinates
+                let mut inside = false;
+                let mut previous = points[points.len() - 1];
+                for current in points {
+                    if (current.lat > point.lat) != (previous.lat > point.lat) {
+                        let crossing = (previous.lon - current.lon) * (point.lat - current.lat)
+                            / (previous.lat - current.lat)
+                            + current.lon;
+                        if point.lon < crossing {
+                            inside = !inside;
+                        }
+                    }
+                    previous = *current;
+                }
+                inside
+            }
+        }
+    }
+}
+
+/// The latitude and longitude fast field columns of a geo field in one segment
+pub struct GeoColumns {
+    lat: (Column<u64>, ColumnType),
+    lon: (Column<u64>, ColumnType),
+}
+
+impl GeoColumns {
+    /// Returns `None` if no document in the segment has a point for `field`
+    pub fn open(reader: &SegmentReader, field: &FieldName) -> tantivy::Result<Option<Self>> {
+        const NUMERIC_TYPES: [ColumnType; 3] = [ColumnType::F64, ColumnType::I64, ColumnType::U64];
+        let open = |key: &str| {
+            reader
+                .fast_fields()
+                .u64_lenient_for_type(Some(&NUMERIC_TYPES), &format!("{field}.{key}"))
+        };
+
+        Ok(match (open(LAT_KEY)?, open(LON_KEY)?) {
+            (Some(lat), Some(lon)) => Some(GeoColumns { lat, lon }),
+            _ => None,
+        })
+    }
+
+    pub fn point(&self, doc: DocId) -> Option<GeoPoint> {
+        fn coordinate(
+            (column, column_type): &(Column<u64>, ColumnType),
+            doc: DocId,
+        ) -> Option<f64> {
+            let value = column.first(doc)?;
+            Some(match column_type {
+                ColumnType::F64 => f64::from_u64(value),
+                ColumnType::I64 => i64::from_u64(value) as f64,
+                _ => value as f64,
+            })
+        }
+
+        Some(GeoPoint {
+            lat: coordinate(&self.lat, doc)?,
+            lon: coordinate(&self.lon, doc)?,
+        })
+    }
+}
+
+/// Matches the documents whose point for `field` is within `shape`, with a constant score
+#[derive(Debug, Clone)]
+pub struct GeoQuery {
+    field: FieldName,
+    shape: GeoShape,
+}
+
+impl GeoQuery {
+    pub fn new(field: FieldName, shape: GeoShape) -> Self {
+        Self { field, shape }
+    }
+}
+
+impl Query for GeoQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(GeoWeight {
+            field: self.field.clone(),
+            shape: self.shape.clone(),
+        }))
+    }
+}
+
+struct GeoWeight {
+    field: FieldName,
+    shape: GeoShape,
+}
+
+impl Weight for GeoWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        match GeoColumns::open(reader, &self.field)? {
+            Some(columns) => Ok(Box::new(GeoScorer::new(
+                columns,
+                self.shape.clone(),
+                reader.max_doc(),
+                boost,
+            ))),
+            None => Ok(Box::new(EmptyScorer)),
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("GeoQuery", scorer.score()))
+    }
+}
+
+/// Walks a segment's documents, checking each one's point against the shape
+struct GeoScorer {
+    columns: GeoColumns,
+    shape: GeoShape,
+    doc: DocId,
+    max_doc: DocId,
+    score: Score,
+}
+
+impl GeoScorer {
+    fn new(columns: GeoColumns, shape: GeoShape, max_doc: DocId, score: Score) -> Self {
+        let mut scorer = Self {
+            columns,
+            shape,
+            doc: 0,
+            max_doc,
+            score,
+        };
+        if max_doc == 0 {
+            scorer.doc = TERMINATED;
+        } else if !scorer.matches(0) {
+            scorer.advance();
+        }
+        scorer
+    }
+
+    fn matches(&self, doc: DocId) -> bool {
+        self.columns
+            .point(doc)
+            .is_some_and(|point| self.shape.contains(&point))
+    }
+}
+
+impl Scorer for GeoScorer {
+    fn score(&mut self) -> Score {
+        self.score
+    }
+}
+
+impl DocSet for GeoScorer {
+    fn advance(&mut self) -> DocId {
+        while self.doc != TERMINATED {
+            self.doc += 1;
+            if self.doc >= self.max_doc {
+                self.doc = TERMINATED;
+            } else if self.matches(self.doc) {
+                break;
+            }
+        }
+        self.doc
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc >= target {
+            return self.doc;
+        }
+        if target >= self.max_doc {
+            self.doc = TERMINATED;
+            return TERMINATED;
+        }
+        if self.matches(target) {
+            self.doc = target;
+            return target;
+        }
+        self.doc = target;
+        self.advance()
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.max_doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const NEW_YORK: GeoPoint = GeoPoint {
+        lat: 40.7128,
+        lon: -74.0060,
+    };
+    const LONDON: GeoPoint = GeoPoint {
+        lat: 51.5074,
+        lon: -0.1278,
+    };
+
+    #[rstest]
+    fn test_distance() {
+        let distance = NEW_YORK.distance(&LONDON);
+        assert!((distance - 5_570_000.0).abs() < 10_000.0, "{distance}");
+        assert_eq!(LONDON.distance(&LONDON), 0.0);
+    }
+
+    #[rstest]
+    #[case(OwnedValue::F64(250.0), 250.0)]
+    #[case(OwnedValue::U64(10), 10.0)]
+    #[case(OwnedValue::Str("5km".into()), 5_000.0)]
+    #[case(OwnedValue::Str("1.5 mi".into()), 2_414.016)]
+    #[case(OwnedValue::Str("300".into()), 300.0)]
+    fn test_meters(#[case] value: OwnedValue, #[case] expected: f64) {
+        assert!((meters(&value).unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[rstest]
+    #[case(OwnedValue::Str("5 parsecs".into()))]
+    #[case(OwnedValue::Str("km".into()))]
+    #[case(OwnedValue::F64(-1.0))]
+    #[case(OwnedValue::Bool(true))]
+    fn test_invalid_meters(#[case] value: OwnedValue) {
+        assert!(meters(&value).is_err());
+    }
+
+    #[rstest]
+    fn test_bounding_box() {
+        let shape = GeoShape::BoundingBox {
+            top_left: GeoPoint {
+                lat: 45.0,
+                lon: -80.0,
+            },
+            bottom_right: GeoPoint {
+                lat: 35.0,
+                lon: -70.0,
+            },
+        };
+        assert!(shape.contains(&NEW_YORK));
+        assert!(!shape.contains(&LONDON));
+
+        // crosses the antimeridian
+        let shape = GeoShape::BoundingBox {
+            top_left: GeoPoint {
+                lat: 10.0,
+                lon: 170.0,
+            },
+            bottom_right: GeoPoint {
+                lat: -10.0,
+                lon: -170.0,
+            },
+        };
+        assert!(shape.contains(&GeoPoint {
+            lat: 0.0,
+            lon: 179.0
+        }));
+        assert!(shape.contains(&GeoPoint {
+            lat: 0.0,
+            lon: -179.0
+        }));
+        assert!(!shape.contains(&GeoPoint { lat: 0.0, lon: 0.0 }));
+    }
+
+    #[rstest]
+    fn test_polygon() {
+        // a triangle around New York
+        let shape = GeoShape::Polygon(vec![
+            GeoPoint {
+                lat: 42.0,
+                lon: -76.0,
+            },
+            GeoPoint {
+                lat: 42.0,
+                lon: -72.0,
+            },
+            GeoPoint {
+                lat: 39.0,
+                lon: -74.0,
+            },
+        ]);
+        assert!(shape.validate().is_ok());
+        assert!(shape.contains(&NEW_YORK));
+        assert!(!shape.contains(&LONDON));
+
+        assert!(GeoShape::Polygon(vec![NEW_YORK, LONDON])
+            .validate()
+            .is_err());
+    }
+}
@@ -287,12 +287,11 @@ impl HeapFilterScorer {
     }
 
     fn passes_heap_filters(&mut self, doc_id: DocId) -> bool {
-        // Extract ctid from the current document
-        let ctid_value = self.ctid_ff.as_u64(doc_id);
-        if ctid_value.is_none() {
-            panic!("Could not get ctid for doc_id: {doc_id}");
-        }
-        let ctid_value = ctid_value.unwrap();
+        // Extract ctid from the current document, which the child documents of nested fields
+        // don't have, as they aren't rows of their own
+        let Some(ctid_value) = self.ctid_ff.as_u64(doc_id) else {
+            return false;
+        };
         // Convert u64 ctid back to ItemPointer
         let mut item_pointer = pg_sys::ItemPointerData::default();
         crate::postgres::utils::u64_to_item_pointer(ctid_value, &mut item_pointer);
@@ -30,6 +30,14 @@ impl<'a> Iterator for SearchQueryInputIter<'a> {
                     self.stack.push(query);
                     continue;
                 }
+                SearchQueryInput::Named { query, .. } => {
+                    self.stack.push(query);
+                    continue;
+                }
+                SearchQueryInput::Nested { query, .. } => {
+                    self.stack.push(query);
+                    continue;
+                }
                 SearchQueryInput::ScoreFilter { query, .. } => {
                     self.stack.push(
                         query
@@ -42,6 +50,34 @@ impl<'a> Iterator for SearchQueryInputIter<'a> {
                     self.stack.extend(disjuncts.iter_mut().rev());
                     continue;
                 }
+                SearchQueryInput::Rrf {
+                    query,
+                    ranked_query,
+                    ..
+                } => {
+                    if let Some(ranked_query) = ranked_query {
+                        self.stack.push(ranked_query);
+                    }
+                    self.stack.push(query);
+                    continue;
+                }
+                SearchQueryInput::Rescore {
+                    query,
+                    rescore_query,
+                    ..
+                } => {
+                    self.stack.push(rescore_query);
+                    self.stack.push(query);
+                    continue;
+                }
+                SearchQueryInput::FunctionScore { query, .. } => {
+                    self.stack.push(query);
+                    continue;
+                }
+                SearchQueryInput::SearchAfter { query, .. } => {
+                    self.stack.push(query);
+                    continue;
+                }
                 SearchQueryInput::WithIndex { query, .. } => {
                     self.stack.push(query);
                     continue;
@@ -15,20 +15,36 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+pub mod function_score;
+pub mod geo;
 pub mod heap_field_filter;
 pub mod iter_mut;
 mod more_like_this;
+pub mod nested;
 mod range;
+pub mod rescore;
+mod rrf;
 mod score;
+mod search_after;
+mod span;
 
 use heap_field_filter::HeapFieldFilter;
 
+use crate::api::analyzer::{qualify_word_list_table, resolve_analyzer};
 use crate::api::FieldName;
 use crate::api::HashMap;
 use crate::postgres::utils::convert_pg_date_string;
+use crate::query::function_score::{
+    FunctionBoostMode, FunctionScoreMode, FunctionScoreQuery, ScoreFunction,
+};
+use crate::query::geo::{meters, GeoPoint, GeoQuery, GeoShape};
 use crate::query::more_like_this::MoreLikeThisQuery;
+use crate::query::nested::{NestedQuery, NestedScoreMode, ParentsQuery, NESTED_MARKER};
 use crate::query::range::{Comparison, RangeField};
+use crate::query::rrf::{RankedSource, RrfQuery, DEFAULT_RRF_K, DEFAULT_RRF_WINDOW_SIZE};
 use crate::query::score::ScoreFilter;
+use crate::query::search_after::{KeyCursor, SearchAfterQuery};
+use crate::query::span::{rewrite_near, SpanClause, SpanQuery, NEAR_SEPARATOR};
 use crate::schema::{IndexRecordOption, SearchIndexSchema};
 use anyhow::Result;
 use core::panic;
@@ -44,11 +60,12 @@ use tantivy::{
     query::{
         AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, DisjunctionMaxQuery, EmptyQuery,
         ExistsQuery, FastFieldRangeQuery, FuzzyTermQuery, PhrasePrefixQuery, PhraseQuery, Query,
-        QueryParser, RangeQuery, RegexPhraseQuery, RegexQuery, TermQuery, TermSetQuery,
+        QueryParser, QueryParserError, RangeQuery, RegexPhraseQuery, RegexQuery, TermQuery,
+        TermSetQuery,
     },
-    query_grammar::Occur,
+    query_grammar::{Occur, UserInputAst, UserInputLeaf},
     schema::{Field, FieldType, OwnedValue, DATE_TIME_PRECISION_INDEXED},
-    Searcher, Term,
+    Score, Searcher, Term,
 };
 use thiserror::Error;
 use tokenizers::SearchTokenizer;
@@ -133,6 +150,15 @@ pub enum SearchQueryInput {
         )]
         upper_bound: std::ops::Bound<u64>,
     },
+    /// `query`, with its scores adjusted by functions of its documents' fast field values
+    FunctionScore {
+        query: Box<SearchQueryInput>,
+        functions: Vec<ScoreFunction>,
+        #[serde(default)]
+        score_mode: FunctionScoreMode,
+        #[serde(default)]
+        boost_mode: FunctionBoostMode,
+    },
     FuzzyTerm {
         field: FieldName,
         value: String,
@@ -140,6 +166,23 @@ pub enum SearchQueryInput {
         transposition_cost_one: Option<bool>,
         prefix: Option<bool>,
     },
+    /// Documents whose point for `field` is within the box between `top_left` and `bottom_right`
+    GeoBoundingBox {
+        field: FieldName,
+        top_left: GeoPoint,
+        bottom_right: GeoPoint,
+    },
+    /// Documents whose point for `field` is within `distance` of `origin`, where `distance` is
+    /// a number of meters or a length like `"5km"`
+    GeoDistance {
+        field: FieldName,
+        origin: GeoPoint,
+        distance: tantivy::schema::OwnedValue,
+    },
+    GeoPolygon {
+        field: FieldName,
+        points: Vec<GeoPoint>,
+    },
     Match {
         field: FieldName,
         value: String,
@@ -161,6 +204,19 @@ pub enum SearchQueryInput {
         document_fields: Option<Vec<(String, tantivy::schema::OwnedValue)>>,
         document_id: Option<OwnedValue>,
     },
+    /// `query`, with a name that `paradedb.matched_queries()` reports for the rows it matches
+    Named {
+        name: String,
+        query: Box<SearchQueryInput>,
+    },
+    /// Matches rows with an element of the array at `path`, in a JSON field with `nested`
+    /// enabled, that `query` matches on its own
+    Nested {
+        path: FieldName,
+        query: Box<SearchQueryInput>,
+        #[serde(default)]
+        score_mode: NestedScoreMode,
+    },
     Parse {
         query_string: String,
         lenient: Option<bool>,
@@ -258,6 +314,66 @@ pub enum SearchQueryInput {
         slop: Option<u32>,
         max_expansions: Option<u32>,
     },
+    /// `query`, whose best `window_size` matches in each segment are scored again with
+    /// `rescore_query` when the results are ordered by score and limited
+    Rescore {
+        query: Box<SearchQueryInput>,
+        rescore_query: Box<SearchQueryInput>,
+        window_size: Option<u32>,
+        query_weight: Option<f32>,
+        rescore_weight: Option<f32>,
+    },
+    /// Reciprocal Rank Fusion of `query` with either an externally ranked list of key field
+    /// values or the ranking of a second query
+    Rrf {
+        query: Box<SearchQueryInput>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        ranked_keys: Vec<tantivy::schema::OwnedValue>,
+        ranked_query: Option<Box<SearchQueryInput>>,
+        k: Option<u32>,
+        window_size: Option<u32>,
+    },
+    /// The documents of `query` that come after the last row of the previous page, for keyset
+    /// pagination over results ordered by score descending, then by the key field ascending
+    SearchAfter {
+        query: Box<SearchQueryInput>,
+        last_key: tantivy::schema::OwnedValue,
+        last_score: Option<f32>,
+    },
+    /// `value`, analyzed with the field's tokenizer, as the building block of the other span
+    /// queries.  If it has several tokens, they must appear as a phrase.
+    SpanTerm {
+        field: FieldName,
+        value: String,
+    },
+    /// Documents where every clause matches with at most `slop` other words between them, and
+    /// in the order they're given if `in_order` is set
+    SpanNear {
+        clauses: Vec<SearchQueryInput>,
+        #[serde(default)]
+        slop: u32,
+        #[serde(default)]
+        in_order: bool,
+    },
+    SpanOr {
+        clauses: Vec<SearchQueryInput>,
+    },
+    /// Matches of `include` that aren't within `pre` words before or `post` words after a match
+    /// of `exclude`
+    SpanNot {
+        include: Box<SearchQueryInput>,
+        exclude: Box<SearchQueryInput>,
+        #[serde(default)]
+        pre: u32,
+        #[serde(default)]
+        post: u32,
+    },
+    /// Matches of `query` that end within the first `end` words of the field
+    SpanFirst {
+        query: Box<SearchQueryInput>,
+        end: u32,
+    },
     Term {
         field: Option<FieldName>,
         value: tantivy::schema::OwnedValue,
@@ -304,6 +420,8 @@ impl SearchQueryInput {
                 .any(Self::need_scores),
             SearchQueryInput::Boost { query, .. } => Self::need_scores(query),
             SearchQueryInput::ConstScore { query, .. } => Self::need_scores(query),
+            SearchQueryInput::Named { query, .. } => Self::need_scores(query),
+            SearchQueryInput::Nested { query, .. } => Self::need_scores(query),
             SearchQueryInput::DisjunctionMax { disjuncts, .. } => {
                 disjuncts.iter().any(Self::need_scores)
             }
@@ -311,10 +429,206 @@ impl SearchQueryInput {
             SearchQueryInput::HeapFilter { indexed_query, .. } => Self::need_scores(indexed_query),
             SearchQueryInput::MoreLikeThis { .. } => true,
             SearchQueryInput::ScoreFilter { .. } => true,
+            SearchQueryInput::FunctionScore { .. } => true,
+            SearchQueryInput::Rescore { .. } => true,
+            SearchQueryInput::Rrf { .. } => true,
+            SearchQueryInput::SearchAfter {
+                query, last_score, ..
+            } => last_score.is_some() || Self::need_scores(query),
             _ => false,
         }
     }
 
+    /// The names of the `Named` clauses of this query, along with their queries, in the order
+    /// they appear
+    pub fn named_queries(&self) -> Vec<(&str, &SearchQueryInput)> {
+        let mut named = vec![];
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                SearchQueryInput::Named { name, query } => {
+                    named.push((name.as_str(), query.as_ref()));
+                    stack.push(query);
+                }
+                SearchQueryInput::Boolean {
+                    must,
+                    should,
+                    must_not,
+                } => {
+                    stack.extend(must_not.iter().rev());
+                    stack.extend(should.iter().rev());
+                    stack.extend(must.iter().rev());
+                }
+                SearchQueryInput::DisjunctionMax { disjuncts, .. } => {
+                    stack.extend(disjuncts.iter().rev())
+                }
+                SearchQueryInput::Boost { query, .. }
+                | SearchQueryInput::ConstScore { query, .. }
+                | SearchQueryInput::FunctionScore { query, .. }
+                | SearchQueryInput::SearchAfter { query, .. }
+                | SearchQueryInput::WithIndex { query, .. } => stack.push(query),
+                SearchQueryInput::ScoreFilter {
+                    query: Some(query), ..
+                } => stack.push(query),
+                SearchQueryInput::Rrf {
+                    query,
+                    ranked_query,
+                    ..
+                } => {
+                    stack.extend(ranked_query.as_deref());
+                    stack.push(query);
+                }
+                SearchQueryInput::Rescore { query, .. } => stack.push(query),
+                SearchQueryInput::HeapFilter { indexed_query, .. } => stack.push(indexed_query),
+                _ => {}
+            }
+        }
+        named
+    }
+
+    /// The `Rescore` clause at the root of this query, possibly under `Boost`, `Named` and
+    /// `WithIndex` clauses, which are the only places its rescoring can apply to the results of
+    /// the whole query.  It's an error for a `Rescore` clause to be anywhere else.
+    pub fn rescore(&self) -> anyhow::Result<Option<&SearchQueryInput>> {
+        let mut root = self;
+        let rescore = loop {
+            match root {
+                SearchQueryInput::Rescore { .. } => break Some(root),
+                SearchQueryInput::Boost { query, .. }
+                | SearchQueryInput::Named { query, .. }
+                | SearchQueryInput::WithIndex { query, .. } => root = query,
+                _ => break None,
+            }
+        };
+
+        let mut stack = match root {
+            SearchQueryInput::Rescore {
+                query,
+                rescore_query,
+                ..
+            } => vec![query.as_ref(), rescore_query.as_ref()],
+            other => vec![other],
+        };
+        while let Some(node) = stack.pop() {
+            match node {
+                SearchQueryInput::Rescore { .. } => {
+                    anyhow::bail!("rescore must be the outermost query")
+                }
+                SearchQueryInput::Boolean {
+                    must,
+                    should,
+                    must_not,
+                } => stack.extend(must.iter().chain(should).chain(must_not)),
+                SearchQueryInput::DisjunctionMax { disjuncts, .. } => stack.extend(disjuncts),
+                SearchQueryInput::Boost { query, .. }
+                | SearchQueryInput::ConstScore { query, .. }
+                | SearchQueryInput::FunctionScore { query, .. }
+                | SearchQueryInput::Named { query, .. }
+                | SearchQueryInput::Nested { query, .. }
+                | SearchQueryInput::SearchAfter { query, .. }
+                | SearchQueryInput::WithIndex { query, .. } => stack.push(query),
+                SearchQueryInput::ScoreFilter {
+                    query: Some(query), ..
+                } => stack.push(query),
+                SearchQueryInput::Rrf {
+                    query,
+                    ranked_query,
+                    ..
+                } => {
+                    stack.extend(ranked_query.as_deref());
+                    stack.push(query);
+                }
+                SearchQueryInput::HeapFilter { indexed_query, .. } => stack.push(indexed_query),
+                _ => {}
+            }
+        }
+        Ok(rescore)
+    }
+
+    /// The `SearchAfter` clause at the root of this query, possibly under `Boost`, `Named` and
+    /// `WithIndex` clauses, which are the only places its cursor can page through the results of
+    /// the whole query.  It's an error for a `SearchAfter` clause to be anywhere else.
+    pub fn search_after(&self) -> anyhow::Result<Option<&SearchQueryInput>> {
+        let mut root = self;
+        let search_after = loop {
+            match root {
+                SearchQueryInput::SearchAfter { .. } => break Some(root),
+                SearchQueryInput::Boost { query, .. }
+                | SearchQueryInput::Named { query, .. }
+                | SearchQueryInput::WithIndex { query, .. } => root = query,
+                _ => break None,
+            }
+        };
+
+        let mut stack = match root {
+            SearchQueryInput::SearchAfter { query, .. } => vec![query.as_ref()],
+            other => vec![other],
+        };
+        while let Some(node) = stack.pop() {
+            match node {
+                SearchQueryInput::SearchAfter { .. } => {
+                    anyhow::bail!("search_after must be the outermost query")
+                }
+                SearchQueryInput::Boolean {
+                    must,
+                    should,
+                    must_not,
+                } => stack.extend(must.iter().chain(should).chain(must_not)),
+                SearchQueryInput::DisjunctionMax { disjuncts, .. } => stack.extend(disjuncts),
+                SearchQueryInput::Boost { query, .. }
+                | SearchQueryInput::ConstScore { query, .. }
+                | SearchQueryInput::FunctionScore { query, .. }
+                | SearchQueryInput::Named { query, .. }
+                | SearchQueryInput::Nested { query, .. }
+                | SearchQueryInput::WithIndex { query, .. } => stack.push(query),
+                SearchQueryInput::ScoreFilter {
+                    query: Some(query), ..
+                } => stack.push(query),
+                SearchQueryInput::Rrf {
+                    query,
+                    ranked_query,
+                    ..
+                } => {
+                    stack.extend(ranked_query.as_deref());
+                    stack.push(query);
+                }
+                SearchQueryInput::Rescore {
+                    query,
+                    rescore_query,
+                    ..
+                } => {
+                    stack.push(rescore_query);
+                    stack.push(query);
+                }
+                SearchQueryInput::HeapFilter { indexed_query, .. } => stack.push(indexed_query),
+                _ => {}
+            }
+        }
+        Ok(search_after)
+    }
+
+    /// A `Rescore` clause only rescores the results of a TopN scan ordered by score.  Anywhere
+    /// else it would quietly score documents like its first-phase query, so that's an error.
+    pub fn check_no_rescore(&self) -> anyhow::Result<()> {
+        if self.rescore()?.is_some() {
+            anyhow::bail!(
+                "rescore requires a ParadeDB scan with ORDER BY paradedb.score() DESC and a LIMIT"
+            );
+        }
+        Ok(())
+    }
+
+    /// A `SearchAfter` cursor only pages through the results of a TopN scan in the order it was
+    /// taken from.  Anywhere else it would silently filter rows out, so that's an error.
+    pub fn check_no_search_after(&self) -> anyhow::Result<()> {
+        if self.search_after()?.is_some() {
+            anyhow::bail!(
+                "search_after requires a ParadeDB scan with ORDER BY on the key field and a LIMIT"
+            );
+        }
+        Ok(())
+    }
+
     pub fn index_oid(&self) -> Option<pg_sys::Oid> {
         match self {
             SearchQueryInput::WithIndex { oid, .. } => Some(*oid),
@@ -390,6 +704,13 @@ impl AsHumanReadable for SearchQueryInput {
             SearchQueryInput::Empty => s.push_str("<EMPTY>"),
             SearchQueryInput::Exists { field } => s.push_str(&format!("<EXISTS:{field}>")),
             SearchQueryInput::FastFieldRangeWeight { .. } => {}
+            SearchQueryInput::FunctionScore {
+                query, functions, ..
+            } => s.push_str(&format!(
+                "FUNCTION_SCORE({}, {} FUNCTIONS)",
+                query.as_human_readable(),
+                functions.len()
+            )),
             SearchQueryInput::FuzzyTerm {
                 field,
                 value,
@@ -399,10 +720,37 @@ impl AsHumanReadable for SearchQueryInput {
                 Some(distance) => s.push_str(&format!("{field}:{value}~{distance}")),
                 None => s.push_str(&format!("{field}:{value}~")),
             },
+            SearchQueryInput::GeoBoundingBox {
+                field,
+                top_left,
+                bottom_right,
+            } => s.push_str(&format!(
+                "{field}:GEO_BOUNDING_BOX(({}, {}), ({}, {}))",
+                top_left.lat, top_left.lon, bottom_right.lat, bottom_right.lon
+            )),
+            SearchQueryInput::GeoDistance {
+                field,
+                origin,
+                distance,
+            } => s.push_str(&format!(
+                "{field}:GEO_DISTANCE(({}, {}), {})",
+                origin.lat,
+                origin.lon,
+                distance.as_human_readable()
+            )),
+            SearchQueryInput::GeoPolygon { field, points } => {
+                s.push_str(&format!("{field}:GEO_POLYGON({} POINTS)", points.len()))
+            }
             SearchQueryInput::Match { field, value, .. } => {
                 s.push_str(&format!("{field}:\"{value}\""))
             }
             SearchQueryInput::MoreLikeThis { .. } => s.push_str("<MLT>"),
+            SearchQueryInput::Named { name, query } => {
+                s.push_str(&format!("NAMED:{name}({})", query.as_human_readable()))
+            }
+            SearchQueryInput::Nested { path, query, .. } => {
+                s.push_str(&format!("NESTED:{path}({})", query.as_human_readable()))
+            }
             SearchQueryInput::Parse { query_string, .. } => {
                 s.push('(');
                 s.push_str(query_string);
@@ -439,6 +787,74 @@ impl AsHumanReadable for SearchQueryInput {
                 }
                 s.push(')');
             }
+            SearchQueryInput::Rescore {
+                query,
+                rescore_query,
+                ..
+            } => s.push_str(&format!(
+                "RESCORE({}, {})",
+                query.as_human_readable(),
+                rescore_query.as_human_readable()
+            )),
+            SearchQueryInput::Rrf {
+                query,
+                ranked_keys,
+                ranked_query,
+                ..
+            } => {
+                s.push_str(&format!("RRF({}, ", query.as_human_readable()));
+                match ranked_query {
+                    Some(ranked_query) => s.push_str(&ranked_query.as_human_readable()),
+                    None => s.push_str(&format!("<{} KEYS>", ranked_keys.len())),
+                }
+                s.push(')');
+            }
+            SearchQueryInput::SearchAfter {
+                query,
+                last_key,
+                last_score,
+            } => {
+                s.push_str(&format!("SEARCH_AFTER({}, ", query.as_human_readable()));
+                if let Some(last_score) = last_score {
+                    s.push_str(&format!("{last_score}, "));
+                }
+                s.push_str(&last_key.as_human_readable());
+                s.push(')');
+            }
+            SearchQueryInput::SpanTerm { field, value } => {
+                s.push_str(&format!("{field}:\"{value}\""))
+            }
+            SearchQueryInput::SpanNear {
+                clauses,
+                slop,
+                in_order,
+            } => {
+                s.push_str(if *in_order { "ORDERED_NEAR/" } else { "NEAR/" });
+                s.push_str(&format!("{slop}("));
+                for (i, clause) in clauses.iter().enumerate() {
+                    (i > 0).then(|| s.push_str(", "));
+                    s.push_str(&clause.as_human_readable());
+                }
+                s.push(')');
+            }
+            SearchQueryInput::SpanOr { clauses } => {
+                s.push_str("SPAN_OR(");
+                for (i, clause) in clauses.iter().enumerate() {
+                    (i > 0).then(|| s.push_str(", "));
+                    s.push_str(&clause.as_human_readable());
+                }
+                s.push(')');
+            }
+            SearchQueryInput::SpanNot {
+                include, exclude, ..
+            } => s.push_str(&format!(
+                "SPAN_NOT({}, {})",
+                include.as_human_readable(),
+                exclude.as_human_readable()
+            )),
+            SearchQueryInput::SpanFirst { query, end } => {
+                s.push_str(&format!("SPAN_FIRST({}, {end})", query.as_human_readable()))
+            }
             SearchQueryInput::Term { field, value, .. } => match field {
                 Some(field) => s.push_str(&format!("{field}:{}", value.as_human_readable())),
                 None => s.push_str(&value.as_human_readable()),
@@ -739,6 +1155,35 @@ impl SearchQueryInput {
                     new_upper_bound,
                 )))
             }
+            Self::FunctionScore {
+                query,
+                functions,
+                score_mode,
+                boost_mode,
+            } => {
+                let mut resolved = vec![];
+                for function in functions {
+                    let field = function.field();
+                    if function.is_geo() {
+                        validate_geo_field(schema, field)?;
+                    } else {
+                        let search_field = schema
+                            .search_field(field.root())
+                            .ok_or(QueryError::NonIndexedField(field.clone()))?;
+                        if !search_field.is_fast() {
+                            return Err(Box::new(QueryError::NotFastField(field.clone())));
+                        }
+                    }
+                    resolved.push(function.resolve()?);
+                }
+
+                Ok(Box::new(FunctionScoreQuery::new(
+                    query.into_tantivy_query(schema, parser, searcher, index_oid, relation_oid)?,
+                    resolved,
+                    score_mode,
+                    boost_mode,
+                )))
+            }
             Self::FuzzyTerm {
                 field,
                 value,
@@ -773,6 +1218,29 @@ impl SearchQueryInput {
                     )))
                 }
             }
+            Self::GeoBoundingBox {
+                field,
+                top_left,
+                bottom_right,
+            } => geo_query(
+                schema,
+                field,
+                GeoShape::BoundingBox {
+                    top_left,
+                    bottom_right,
+                },
+            ),
+            Self::GeoDistance {
+                field,
+                origin,
+                distance,
+            } => {
+                let meters = meters(&distance)?;
+                geo_query(schema, field, GeoShape::Distance { origin, meters })
+            }
+            Self::GeoPolygon { field, points } => {
+                geo_query(schema, field, GeoShape::Polygon(points))
+            }
             Self::Match {
                 field,
                 value,
@@ -792,8 +1260,10 @@ impl SearchQueryInput {
                     .ok_or(QueryError::NonIndexedField(field.clone()))?;
                 let field_type = search_field.field_entry().field_type();
                 let mut analyzer = match tokenizer {
-                    Some(tokenizer) => {
-                        let tokenizer = SearchTokenizer::from_json_value(&tokenizer)
+                    Some(mut tokenizer) => {
+                        let tokenizer = qualify_word_list_table(&mut tokenizer)
+                            .and_then(|_| SearchTokenizer::from_json_value(&tokenizer))
+                            .and_then(resolve_analyzer)
                             .map_err(|_| QueryError::InvalidTokenizer)?;
                         tokenizer
                             .to_tantivy_tokenizer()
@@ -909,6 +1379,46 @@ impl SearchQueryInput {
                     }
                 }
             }
+            Self::Named { query, .. } => {
+                query.into_tantivy_query(schema, parser, searcher, index_oid, relation_oid)
+            }
+            Self::Nested {
+                path,
+                query,
+                score_mode,
+            } => {
+                let search_field = schema
+                    .search_field(path.root())
+                    .filter(|search_field| search_field.is_nested())
+                    .ok_or(QueryError::NotNestedField(path.clone()))?;
+                let query =
+                    query.into_tantivy_query(schema, parser, searcher, index_oid, relation_oid)?;
+                // only the child documents of elements at `path` have its marker
+                let marker_path = match path.path() {
+                    Some(path) => format!("{path}.{NESTED_MARKER}"),
+                    None => NESTED_MARKER.to_string(),
+                };
+                let marker = value_to_term(
+                    search_field.field(),
+                    &OwnedValue::Bool(true),
+                    search_field.field_entry().field_type(),
+                    Some(marker_path.as_str()),
+                    false,
+                )?;
+                let marker = TermQuery::new(marker, tantivy::schema::IndexRecordOption::Basic);
+                let children = BooleanQuery::new(vec![
+                    (Occur::Must, query),
+                    (
+                        Occur::Must,
+                        Box::new(ConstScoreQuery::new(Box::new(marker), 0.0)),
+                    ),
+                ]);
+                Ok(Box::new(NestedQuery::new(
+                    Box::new(children),
+                    score_mode,
+                    schema.nested_fields(),
+                )))
+            }
             Self::PhrasePrefix {
                 field,
                 phrases,
@@ -943,6 +1453,34 @@ impl SearchQueryInput {
                     parser.set_conjunction_by_default();
                 }
 
+                if let Some(rewritten) = rewrite_near(&query_string)
+                    .map_err(|err| QueryError::InvalidNear(err, query_string.clone()))?
+                {
+                    let lenient = lenient.unwrap_or(false);
+                    let ast = if lenient {
+                        tantivy::query_grammar::parse_query_lenient(&rewritten).0
+                    } else {
+                        tantivy::query_grammar::parse_query(&rewritten).map_err(|_| {
+                            QueryError::ParseError(
+                                QueryParserError::SyntaxError(rewritten.clone()),
+                                query_string.clone(),
+                            )
+                        })?
+                    };
+                    return near_query(
+                        ast,
+                        schema,
+                        parser,
+                        searcher,
+                        conjunction_mode.unwrap_or(false),
+                        lenient,
+                    )
+                    .map_err(|err| match err.downcast::<QueryParserError>() {
+                        Ok(err) => QueryError::ParseError(*err, query_string).into(),
+                        Err(err) => err,
+                    });
+                }
+
                 match lenient {
                     Some(true) => {
                         let (parsed_query, _) = parser.parse_query_lenient(&query_string);
@@ -1700,6 +2238,107 @@ impl SearchQueryInput {
                 }
                 Ok(Box::new(query))
             }
+            // the rescoring happens while collecting the "top N", so elsewhere it's just `query`
+            Self::Rescore { query, .. } => {
+                query.into_tantivy_query(schema, parser, searcher, index_oid, relation_oid)
+            }
+            Self::Rrf {
+                query,
+                ranked_keys,
+                ranked_query,
+                k,
+                window_size,
+            } => {
+                if k == Some(0) {
+                    return Err(Box::new(QueryError::NotPositive("k")));
+                }
+                if window_size == Some(0) {
+                    return Err(Box::new(QueryError::NotPositive("window_size")));
+                }
+
+                let query =
+                    query.into_tantivy_query(schema, parser, searcher, index_oid, relation_oid)?;
+                // each ranking is searched on its own, so it mustn't rank the child documents of
+                // nested fields, or count them in its scores
+                let query = ParentsQuery::wrap(schema, query);
+                let ranked = match ranked_query {
+                    Some(ranked_query) => RankedSource::Query(ParentsQuery::wrap(
+                        schema,
+                        ranked_query.into_tantivy_query(
+                            schema,
+                            parser,
+                            searcher,
+                            index_oid,
+                            relation_oid,
+                        )?,
+                    )),
+                    None => {
+                        let key_field = schema.key_field_name();
+                        let search_field = schema
+                            .search_field(key_field.root())
+                            .ok_or(QueryError::NonIndexedField(key_field.clone()))?;
+                        let field_type = search_field.field_entry().field_type();
+                        let mut terms = Vec::with_capacity(ranked_keys.len());
+                        for mut value in ranked_keys {
+                            search_field.try_coerce(&mut value)?;
+                            terms.push(value_to_term(
+                                search_field.field(),
+                                &value,
+                                field_type,
+                                None,
+                                false,
+                            )?);
+                        }
+                        RankedSource::Keys(terms)
+                    }
+                };
+
+                Ok(Box::new(RrfQuery::new(
+                    query,
+                    ranked,
+                    k.unwrap_or(DEFAULT_RRF_K),
+                    window_size
+                        .map(|window_size| window_size as usize)
+                        .unwrap_or(DEFAULT_RRF_WINDOW_SIZE),
+                    relation_oid,
+                )))
+            }
+            Self::SearchAfter {
+                query,
+                mut last_key,
+                last_score,
+            } => {
+                let key_field = schema.key_field_name();
+                let search_field = schema
+                    .search_field(key_field.root())
+                    .ok_or(QueryError::NonIndexedField(key_field.clone()))?;
+                search_field.try_coerce(&mut last_key)?;
+                let key_term = value_to_term(
+                    search_field.field(),
+                    &last_key,
+                    search_field.field_entry().field_type(),
+                    None,
+                    false,
+                )?;
+                let last_key = KeyCursor::from_value(&last_key)
+                    .ok_or_else(|| QueryError::WrongFieldType(key_field.to_string()))?;
+
+                Ok(Box::new(SearchAfterQuery::new(
+                    query.into_tantivy_query(schema, parser, searcher, index_oid, relation_oid)?,
+                    key_field,
+                    key_term,
+                    last_key,
+                    last_score,
+                )))
+            }
+            span @ (Self::SpanTerm { .. }
+            | Self::SpanNear { .. }
+            | Self::SpanOr { .. }
+            | Self::SpanNot { .. }
+            | Self::SpanFirst { .. }) => {
+                let (_, clause) = span.into_span_clause(schema, searcher)?;
+                Ok(Box::new(SpanQuery::new(clause)))
+            }
 
             Self::Term {
                 field,
@@ -1790,6 +2429,251 @@ impl SearchQueryInput {
     }
 }
 
+impl SearchQueryInput {
+    /// The field and span clause of a span query, whose sub-queries must also be span queries on
+    /// the same field
+    fn into_span_clause(
+        self,
+        schema: &SearchIndexSchema,
+        searcher: &Searcher,
+    ) -> Result<(Option<Field>, SpanClause), Box<dyn std::error::Error>> {
+        match self {
+            Self::SpanTerm { field, value } => {
+                let search_field = schema
+                    .search_field(field.root())
+                    .ok_or(QueryError::NonIndexedField(field.clone()))?;
+                let terms = analyze_span_term(
+                    searcher,
+                    search_field.field(),
+                    search_field.field_entry().field_type(),
+                    field.path().as_deref(),
+                    &value,
+                )?;
+                Ok((Some(search_field.field()), SpanClause::phrase(terms)))
+            }
+            Self::SpanNear {
+                clauses,
+                slop,
+                in_order,
+            } => {
+                let (field, clauses) = Self::into_span_clauses(clauses, schema, searcher)?;
+                Ok((
+                    field,
+                    SpanClause::Near {
+                        clauses,
+                        slop,
+                        in_order,
+                    },
+                ))
+            }
+            Self::SpanOr { clauses } => {
+                let (field, clauses) = Self::into_span_clauses(clauses, schema, searcher)?;
+                Ok((field, SpanClause::Or(clauses)))
+            }
+            Self::SpanNot {
+                include,
+                exclude,
+                pre,
+                post,
+            } => {
+                let (field, mut clauses) =
+                    Self::into_span_clauses(vec![*include, *exclude], schema, searcher)?;
+                let exclude = clauses.pop().expect("exclude clause should exist");
+                let include = clauses.pop().expect("include clause should exist");
+                Ok((
+                    field,
+                    SpanClause::Not {
+                        include: Box::new(include),
+                        exclude: Box::new(exclude),
+                        pre,
+                        post,
+                    },
+                ))
+            }
+            Self::SpanFirst { query, end } => {
+                let (field, clause) = query.into_span_clause(schema, searcher)?;
+                Ok((
+                    field,
+                    SpanClause::First {
+                        clause: Box::new(clause),
+                        end,
+                    },
+                ))
+            }
+            other => Err(Box::new(QueryError::NonSpanQuery(
+                other.as_human_readable(),
+            ))),
+        }
+    }
+
+    fn into_span_clauses(
+        queries: Vec<SearchQueryInput>,
+        schema: &SearchIndexSchema,
+        searcher: &Searcher,
+    ) -> Result<(Option<Field>, Vec<SpanClause>), Box<dyn std::error::Error>> {
+        let mut field = None;
+        let mut clauses = vec![];
+        for query in queries {
+            let (clause_field, clause) = query.into_span_clause(schema, searcher)?;
+            match (field, clause_field) {
+                (Some(field), Some(clause_field)) if field != clause_field => {
+                    return Err(Box::new(QueryError::SpanFieldMismatch));
+                }
+                (None, Some(_)) => field = clause_field,
+                _ => {}
+            }
+            clauses.push(clause);
+        }
+        Ok((field, clauses))
+    }
+}
+
+/// The terms of `value` when it's tokenized for `field`
+fn analyze_span_term(
+    searcher: &Searcher,
+    field: Field,
+    field_type: &FieldType,
+    path: Option<&str>,
+    value: &str,
+) -> Result<Vec<Term>, Box<dyn std::error::Error>> {
+    let mut analyzer = searcher.index().tokenizer_for_field(field)?;
+    let mut stream = analyzer.token_stream(value);
+    let mut terms = vec![];
+    while stream.advance() {
+        let token = stream.token().text.clone();
+        terms.push(value_to_term(
+            field,
+            &OwnedValue::Str(token),
+            field_type,
+            path,
+            false,
+        )?);
+    }
+    Ok(terms)
+}
+
+/// The query for a parsed query string, where each phrase of words separated by
+/// [`NEAR_SEPARATOR`] is a `NEAR/n` operator that [`rewrite_near`] rewrote, and becomes a span
+/// query.  Everything else is built by the query parser, as usual.
+fn near_query(
+    ast: UserInputAst,
+    schema: &SearchIndexSchema,
+    parser: &QueryParser,
+    searcher: &Searcher,
+    conjunction_mode: bool,
+    lenient: bool,
+) -> Result<Box<dyn Query>, Box<dyn std::error::Error>> {
+    fn has_near(ast: &UserInputAst) -> bool {
+        match ast {
+            UserInputAst::Clause(clauses) => clauses.iter().any(|(_, ast)| has_near(ast)),
+            UserInputAst::Boost(ast, _) => has_near(ast),
+            UserInputAst::Leaf(leaf) => matches!(
+                leaf.as_ref(),
+                UserInputLeaf::Literal(literal) if literal.phrase.contains(NEAR_SEPARATOR)
+            ),
+        }
+    }
+
+    if !has_near(&ast) {
+        return if lenient {
+            Ok(parser.build_query_from_user_input_ast_lenient(ast).0)
+        } else {
+            Ok(parser.build_query_from_user_input_ast(ast)?)
+        };
+    }
+
+    match ast {
+        UserInputAst::Clause(clauses) => {
+            // like the query parser, a clause of only negative terms excludes them from everything
+            let only_must_not = clauses
+                .iter()
+                .all(|(occur, _)| *occur == Some(Occur::MustNot));
+            let default_occur = if conjunction_mode {
+                Occur::Must
+            } else {
+                Occur::Should
+            };
+
+            let mut subqueries = vec![];
+            for (occur, ast) in clauses {
+                subqueries.push((
+                    occur.unwrap_or(default_occur),
+                    near_query(ast, schema, parser, searcher, conjunction_mode, lenient)?,
+                ));
+            }
+            if only_must_not {
+                subqueries.push((Occur::Must, Box::new(AllQuery)));
+            }
+            Ok(Box::new(BooleanQuery::new(subqueries)))
+        }
+        UserInputAst::Boost(ast, boost) => Ok(Box::new(BoostQuery::new(
+            near_query(*ast, schema, parser, searcher, conjunction_mode, lenient)?,
+            boost as Score,
+        ))),
+        UserInputAst::Leaf(leaf) => {
+            let UserInputLeaf::Literal(literal) = *leaf else {
+                unreachable!("only literals can contain NEAR operators")
+            };
+            let field: FieldName = literal
+                .field_name
+                .ok_or_else(|| {
+                    QueryError::InvalidNear(
+                        "NEAR/n needs a field, as in 'description:running NEAR/2 shoes'".into(),
+                        literal.phrase.replace(NEAR_SEPARATOR, " NEAR "),
+                    )
+                })?
+                .into();
+            let search_field = schema
+                .search_field(field.root())
+                .ok_or(QueryError::NonIndexedField(field.clone()))?;
+
+            let mut clauses = vec![];
+            for operand in literal.phrase.split(NEAR_SEPARATOR) {
+                let terms = analyze_span_term(
+                    searcher,
+                    search_field.field(),
+                    search_field.field_entry().field_type(),
+                    field.path().as_deref(),
+                    operand,
+                )?;
+                clauses.push(SpanClause::phrase(terms));
+            }
+            Ok(Box::new(SpanQuery::new(SpanClause::Near {
+                clauses,
+                slop: literal.slop,
+                in_order: false,
+            })))
+        }
+    }
+}
+
+/// A [`GeoQuery`] for `field`, which must be a point, or a path to a fast JSON object with
+/// `lat` and `lon` keys
+fn geo_query(
+    schema: &SearchIndexSchema,
+    field: FieldName,
+    shape: GeoShape,
+) -> Result<Box<dyn Query>, Box<dyn std::error::Error>> {
+    validate_geo_field(schema, &field)?;
+    shape.validate()?;
+    Ok(Box::new(GeoQuery::new(field, shape)))
+}
+
+fn validate_geo_field(schema: &SearchIndexSchema, field: &FieldName) -> Result<(), QueryError> {
+    let search_field = schema
+        .search_field(field.root())
+        .ok_or(QueryError::NonIndexedField(field.clone()))?;
+    let is_geo = if search_field.is_geo() {
+        field.path().is_none()
+    } else {
+        search_field.is_json() && search_field.is_fast() && field.path().is_some()
+    };
+    if !is_geo {
+        return Err(QueryError::NotGeoField(field.clone()));
+    }
+    Ok(())
+}
+
 fn value_to_json_term(
     field: Field,
     value: &OwnedValue,
@@ -1935,6 +2819,24 @@ enum QueryError {
            make sure to use column:term pairs, and to capitalize AND/OR."#
     )]
     ParseError(#[source] tantivy::query::QueryParserError, String),
+    #[error("could not parse query string '{1}': {0}")]
+    InvalidNear(String, String),
+    #[error("span queries can only contain other span queries, found: {0}")]
+    NonSpanQuery(String),
+    #[error("all the clauses of a span query must be on the same field")]
+    SpanFieldMismatch,
+    #[error("field '{0}' must be a fast field to be used in a score function")]
+    NotFastField(FieldName),
+    #[error("invalid score function: {0}")]
+    InvalidScoreFunction(String),
+    #[error("field '{0}' must be a point, or a path to a fast JSON object with lat and lon keys")]
+    NotGeoField(FieldName),
+    #[error("invalid geo query: {0}")]
+    InvalidGeoQuery(String),
+    #[error("field '{0}' must be a JSON field with `nested` enabled to be used in a nested query")]
+    NotNestedField(FieldName),
+    #[error("'{0}' must be greater than zero")]
+    NotPositive(&'static str),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -0,0 +1,550 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Nested queries match a query against each element of a JSON array on its own, rather than
+//! against the values of every element flattened together.
+//!
+//! Like Lucene's block join, the elements of fields with `nested` enabled are indexed as child
+//! documents, right before the document of the row they belong to.  A row's document is the only
+//! one of its block with a ctid, so the children of a row are the documents between it and the
+//! previous row's document.  Child documents are never rows themselves, and are excluded from
+//! every top-level query by [`ParentsQuery`].
+
+use crate::schema::SearchIndexSchema;
+use serde::{Deserialize, Serialize};
+use tantivy::columnar::ColumnIndex;
+use tantivy::query::{
+    Bm25StatisticsProvider, EnableScoring, Explanation, Query, QueryClone, Scorer, Weight,
+};
+use tantivy::schema::{Field, IndexRecordOption, OwnedValue};
+use tantivy::{DocId, DocSet, Score, Searcher, SegmentReader, TantivyDocument, Term, TERMINATED};
+
+/// The key, added to each element of a child document, that tells the elements at one path
+/// apart from those at another
+pub const NESTED_MARKER: &str = "_nested";
+
+/// How the scores of a row's matching elements are combined into the row's score
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NestedScoreMode {
+    #[default]
+    Avg,
+    Max,
+    Min,
+    Sum,
+    None,
+}
+
+impl NestedScoreMode {
+    fn combine(self, scores: &[Score]) -> Score {
+        match self {
+            NestedScoreMode::Avg => scores.iter().sum::<Score>() / scores.len() as Score,
+            NestedScoreMode::Max => scores.iter().copied().fold(Score::MIN, Score::max),
+            NestedScoreMode::Min => scores.iter().copied().fold(Score::MAX, Score::min),
+            NestedScoreMode::Sum => scores.iter().sum(),
+            NestedScoreMode::None => 0.0,
+        }
+    }
+}
+
+/// The child documents of `document`, one for each object that is a value of one of `fields`,
+/// or an element of an array within one.
+///
+/// A child's value is the element alone, back at its place under its path and marked with
+/// [`NESTED_MARKER`], so the inner query of a nested query is written against the same field
+/// names as any other query.
+pub fn child_documents(document: &TantivyDocument, fields: &[Field]) -> Vec<TantivyDocument> {
+    let mut children = Vec::new();
+    for &field in fields {
+        for value in document.get_all(field) {
+            let mut elements = Vec::new();
+            match OwnedValue::from(value) {
+                OwnedValue::Object(entries) => {
+                    collect_elements(entries, &mut vec![], &mut elements)
+                }
+                OwnedValue::Array(items) => {
+                    for item in items {
+                        if let OwnedValue::Object(entries) = item {
+                            collect_elements(entries, &mut vec![], &mut elements);
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            for (path, mut entries) in elements {
+                entries.push((NESTED_MARKER.to_string(), OwnedValue::Bool(true)));
+                let value = path
+                    .into_iter()
+                    .rev()
+                    .fold(OwnedValue::Object(entries), |value, key| {
+                        OwnedValue::Object(vec![(key, value)])
+                    });
+                let mut child = TantivyDocument::new();
+                child.add_field_value(field, &value);
+                children.push(child);
+            }
+        }
+    }
+    children
+}
+
+/// An element of a nested field, by its path and its entries
+type Element = (Vec<String>, Vec<(String, OwnedValue)>);
+
+/// Add the element with `entries`, at `path`, and every object in an array within it, to
+/// `elements`
+fn collect_elements(
+    entries: Vec<(String, OwnedValue)>,
+    path: &mut Vec<String>,
+    elements: &mut Vec<Element>,
+) {
+    collect_arrays(&entries, path, elements);
+    elements.push((path.clone(), entries));
+}
+
+fn collect_arrays(
+    entries: &[(String, OwnedValue)],
+    path: &mut Vec<String>,
+    elements: &mut Vec<Element>,
+) {
+    for (key, value) in entries {
+        path.push(key.clone());
+        match value {
+            OwnedValue::Object(entries) => collect_arrays(entries, path, elements),
+            OwnedValue::Array(items) => {
+                for item in items {
+                    if let OwnedValue::Object(entries) = item {
+                        collect_elements(entries.clone(), path, elements);
+                    }
+                }
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
+/// The documents of `reader` that are rows, rather than the child documents of nested fields
+fn parents(reader: &SegmentReader) -> tantivy::Result<ColumnIndex> {
+    Ok(reader.fast_fields().u64("ctid")?.index)
+}
+
+/// The BM25 statistics of either the rows alone or the child documents alone, so that a field
+/// being nested doesn't change how rows are scored, and elements are scored among elements
+struct NestedStatistics<'a> {
+    searcher: &'a Searcher,
+    nested_fields: &'a [Field],
+    children: bool,
+}
+
+impl NestedStatistics<'_> {
+    fn is_counted(&self, parents: &ColumnIndex, doc: DocId) -> bool {
+        parents.has_value(doc) != self.children
+    }
+}
+
+impl Bm25StatisticsProvider for NestedStatistics<'_> {
+    fn total_num_tokens(&self, field: Field) -> tantivy::Result<u64> {
+        // only nested fields have tokens in child documents
+        if !self.nested_fields.contains(&field) {
+            return self.searcher.total_num_tokens(field);
+        }
+
+        let mut total_num_tokens = 0u64;
+        for segment_reader in self.searcher.segment_readers() {
+            let Some(fieldnorms) = segment_reader.fieldnorms_readers().get_field(field)? else {
+                return self.searcher.total_num_tokens(field);
+            };
+            let parents = parents(segment_reader)?;
+            total_num_tokens += (0..segment_reader.max_doc())
+                .filter(|doc| self.is_counted(&parents, *doc))
+                .map(|doc| u64::from(fieldnorms.fieldnorm(doc)))
+                .sum::<u64>();
+        }
+        Ok(total_num_tokens)
+    }
+
+    fn total_num_docs(&self) -> tantivy::Result<u64> {
+        let mut total_num_docs = 0u64;
+        for segment_reader in self.searcher.segment_readers() {
+            let num_rows = u64::from(segment_reader.fast_fields().u64("ctid")?.values.num_vals());
+            total_num_docs += if self.children {
+                u64::from(segment_reader.max_doc()) - num_rows
+            } else {
+                num_rows
+            };
+        }
+        Ok(total_num_docs)
+    }
+
+    fn doc_freq(&self, term: &Term) -> tantivy::Result<u64> {
+        // only nested fields have terms in child documents
+        if !self.nested_fields.contains(&term.field()) {
+            return if self.children {
+                Ok(0)
+            } else {
+                self.searcher.doc_freq(term)
+            };
+        }
+
+        let mut doc_freq = 0u64;
+        for segment_reader in self.searcher.segment_readers() {
+            let Some(mut postings) = segment_reader
+                .inverted_index(term.field())?
+                .read_postings(term, IndexRecordOption::Basic)?
+            else {
+                continue;
+            };
+            let parents = parents(segment_reader)?;
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if self.is_counted(&parents, doc) {
+                    doc_freq += 1;
+                }
+                doc = postings.advance();
+            }
+        }
+        Ok(doc_freq)
+    }
+}
+
+/// `enable_scoring`, with its statistics limited to the rows or to the child documents, and a
+/// call to `weight` with it
+fn nested_weight(
+    enable_scoring: EnableScoring<'_>,
+    nested_fields: &[Field],
+    children: bool,
+    weight: impl FnOnce(EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>>,
+) -> tantivy::Result<Box<dyn Weight>> {
+    match enable_scoring {
+        EnableScoring::Enabled { searcher, .. } => {
+            let statistics = NestedStatistics {
+                searcher,
+                nested_fields,
+                children,
+            };
+            weight(EnableScoring::enabled_from_statistics_provider(
+                &statistics,
+                searcher,
+            ))
+        }
+        disabled => weight(disabled),
+    }
+}
+
+/// Matches the rows with at least one element that `query`, which only matches child documents,
+/// matches, and scores them by combining the scores of their matching elements.
+#[derive(Debug)]
+pub struct NestedQuery {
+    query: Box<dyn Query>,
+    score_mode: NestedScoreMode,
+    nested_fields: Vec<Field>,
+}
+
+impl QueryClone for NestedQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            query: self.query.box_clone(),
+            score_mode: self.score_mode,
+            nested_fields: self.nested_fields.clone(),
+        })
+    }
+}
+
+impl NestedQuery {
+    /// `nested_fields` are all of the index's nested fields, whose child documents `query` is
+    /// scored among
+    pub fn new(
+        query: Box<dyn Query>,
+        score_mode: NestedScoreMode,
+        nested_fields: Vec<Field>,
+    ) -> Self {
+        Self {
+            query,
+            score_mode,
+            nested_fields,
+        }
+    }
+}
+
+impl Query for NestedQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let weight = nested_weight(
+            enable_scoring,
+            &self.nested_fields,
+            true,
+            |enable_scoring| self.query.weight(enable_scoring),
+        )?;
+        Ok(Box::new(NestedWeight {
+            weight,
+            score_mode: self.score_mode,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor);
+    }
+}
+
+struct NestedWeight {
+    weight: Box<dyn Weight>,
+    score_mode: NestedScoreMode,
+}
+
+impl NestedWeight {
+    fn nested_scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<NestedScorer> {
+        let mut scorer = NestedScorer {
+            children: self.weight.scorer(reader, boost)?,
+            parents: parents(reader)?,
+            max_doc: reader.max_doc(),
+            score_mode: self.score_mode,
+            doc: 0,
+            matches: Vec::new(),
+            scores: Vec::new(),
+        };
+        scorer.next_parent();
+        Ok(scorer)
+    }
+}
+
+impl Weight for NestedWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        Ok(Box::new(self.nested_scorer(reader, boost)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.nested_scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(tantivy::TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+
+        let mut explanation = Explanation::new_with_string(
+            format!("Nested, score_mode={:?}", scorer.score_mode),
+            scorer.score(),
+        );
+        for &child in &scorer.matches {
+            explanation.add_detail(self.weight.explain(reader, child)?);
+        }
+        Ok(explanation)
+    }
+}
+
+/// Joins the matching children of each row to the row, whose document comes right after them
+struct NestedScorer {
+    children: Box<dyn Scorer>,
+    parents: ColumnIndex,
+    max_doc: DocId,
+    score_mode: NestedScoreMode,
+    doc: DocId,
+    /// The matching children of the current row, and their scores
+    matches: Vec<DocId>,
+    scores: Vec<Score>,
+}
+
+impl NestedScorer {
+    /// Move to the row of the next matching child, gathering the row's matching children
+    fn next_parent(&mut self) -> DocId {
+        self.matches.clear();
+        self.scores.clear();
+
+        let child = self.children.doc();
+        self.doc = (child..self.max_doc)
+            .find(|doc| self.parents.has_value(*doc))
+            .unwrap_or(TERMINATED);
+        while self.children.doc() <= self.doc && self.children.doc() != TERMINATED {
+            // the child query requires the nested marker, which rows don't have, but a row is
+            // never one of its own children all the same
+            if self.children.doc() != self.doc {
+                self.matches.push(self.children.doc());
+                self.scores.push(self.children.score());
+            }
+            self.children.advance();
+        }
+        self.doc
+    }
+}
+
+impl Scorer for NestedScorer {
+    fn score(&mut self) -> Score {
+        if self.scores.is_empty() {
+            return 0.0;
+        }
+        self.score_mode.combine(&self.scores)
+    }
+}
+
+impl DocSet for NestedScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        self.next_parent()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc >= target {
+            return self.doc;
+        }
+        // seeking to TERMINATED is how a docset is consumed
+        let target = target.min(self.max_doc);
+
+        // the children of the first row at or after `target` come right after the row before it
+        let block_start = (self.doc + 1..target)
+            .rev()
+            .find(|doc| self.parents.has_value(*doc))
+            .map_or(self.doc + 1, |doc| doc + 1);
+        if self.children.doc() < block_start {
+            self.children.seek(block_start);
+        }
+        self.next_parent()
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.children.size_hint()
+    }
+}
+
+/// Matches the documents that `query` matches which are rows, rather than the child documents of
+/// nested fields
+#[derive(Debug)]
+pub struct ParentsQuery {
+    query: Box<dyn Query>,
+    nested_fields: Vec<Field>,
+}
+
+impl QueryClone for ParentsQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            query: self.query.box_clone(),
+            nested_fields: self.nested_fields.clone(),
+        })
+    }
+}
+
+impl ParentsQuery {
+    /// `query`, as it is when the index has no nested fields, and otherwise without the child
+    /// documents it matches, and scored as if they weren't in the index
+    pub fn wrap(schema: &SearchIndexSchema, query: Box<dyn Query>) -> Box<dyn Query> {
+        let nested_fields = schema.nested_fields();
+        if nested_fields.is_empty() {
+            query
+        } else {
+            Box::new(Self {
+                query,
+                nested_fields,
+            })
+        }
+    }
+}
+
+impl Query for ParentsQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let weight = nested_weight(
+            enable_scoring,
+            &self.nested_fields,
+            false,
+            |enable_scoring| self.query.weight(enable_scoring),
+        )?;
+        Ok(Box::new(ParentsWeight { weight }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor);
+    }
+}
+
+struct ParentsWeight {
+    weight: Box<dyn Weight>,
+}
+
+impl Weight for ParentsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let mut scorer = ParentsScorer {
+            scorer: self.weight.scorer(reader, boost)?,
+            parents: parents(reader)?,
+        };
+        scorer.skip_children();
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        if !parents(reader)?.has_value(doc) {
+            return Err(tantivy::TantivyError::InvalidArgument(format!(
+                "Document #({doc}) is not a row"
+            )));
+        }
+        self.weight.explain(reader, doc)
+    }
+}
+
+struct ParentsScorer {
+    scorer: Box<dyn Scorer>,
+    parents: ColumnIndex,
+}
+
+impl ParentsScorer {
+    fn skip_children(&mut self) -> DocId {
+        let mut doc = self.scorer.doc();
+        while doc != TERMINATED && !self.parents.has_value(doc) {
+            doc = self.scorer.advance();
+        }
+        doc
+    }
+}
+
+impl Scorer for ParentsScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
+
+impl DocSet for ParentsScorer {
+    fn advance(&mut self) -> DocId {
+        self.scorer.advance();
+        self.skip_children()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.scorer.seek(target);
+        self.skip_children()
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
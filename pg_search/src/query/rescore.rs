@@ -0,0 +1,82 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Second-phase rescoring of the best first-phase matches of a "top N" search.
+
+use tantivy::query::{EnableScoring, Query, Weight};
+use tantivy::{DocAddress, DocSet, Score, Searcher, SegmentReader};
+
+/// How many of the best documents in each segment are rescored when none is specified.
+pub const DEFAULT_RESCORE_WINDOW_SIZE: u32 = 10;
+
+/// Scores the best first-phase matches of each segment again with a second, usually more
+/// expensive, query.
+///
+/// A rescored document's score is `query_weight` times its first-phase score, plus
+/// `rescore_weight` times its score from the rescore query, if that query matches it.  The
+/// rescored documents come before the rest of the matches, which keep their first-phase scores.
+pub struct Rescorer {
+    weight: Box<dyn Weight>,
+    window_size: usize,
+    query_weight: Score,
+    rescore_weight: Score,
+}
+
+impl Rescorer {
+    pub fn create(
+        searcher: &Searcher,
+        query: Box<dyn Query>,
+        window_size: usize,
+        query_weight: Score,
+        rescore_weight: Score,
+    ) -> tantivy::Result<Self> {
+        let weight = query.weight(EnableScoring::enabled_from_searcher(searcher))?;
+        Ok(Self {
+            weight,
+            window_size,
+            query_weight,
+            rescore_weight,
+        })
+    }
+
+    /// How many of the best documents in each segment are rescored
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Rescore the first `window_size` documents of `top_docs`, a segment's best matches in
+    /// descending score order, returning them with their new scores, in no particular order
+    pub fn rescore_segment(
+        &self,
+        segment_reader: &SegmentReader,
+        top_docs: &[(Score, DocAddress)],
+    ) -> tantivy::Result<Vec<(Score, DocAddress)>> {
+        let mut window = top_docs[..top_docs.len().min(self.window_size)].to_vec();
+        window.sort_unstable_by_key(|(_, doc_address)| doc_address.doc_id);
+
+        let mut scorer = self.weight.scorer(segment_reader, 1.0)?;
+        for (score, doc_address) in &mut window {
+            *score *= self.query_weight;
+            if scorer.doc() <= doc_address.doc_id
+                && scorer.seek(doc_address.doc_id) == doc_address.doc_id
+            {
+                *score += self.rescore_weight * scorer.score();
+            }
+        }
+        Ok(window)
+    }
+}
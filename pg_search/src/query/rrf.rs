@@ -0,0 +1,324 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::aggregate::vischeck::TSVisibilityChecker;
+use crate::api::HashMap;
+use crate::index::fast_fields_helper::FFType;
+use crate::postgres::rel::PgSearchRelation;
+use pgrx::pg_sys;
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::index::SegmentId;
+use tantivy::query::{EnableScoring, Explanation, Query, QueryClone, Scorer, TermQuery, Weight};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocAddress, DocId, DocSet, Score, Searcher, SegmentReader, Term, TERMINATED};
+
+/// The `k` constant from the original RRF paper (Cormack et al.), which dampens the
+/// influence of the very top ranks.
+pub const DEFAULT_RRF_K: u32 = 60;
+
+/// How many top documents are taken from each ranked query when none is specified.
+pub const DEFAULT_RRF_WINDOW_SIZE: usize = 100;
+
+/// How much more of a ranking is searched when too few of its top documents are visible
+const RETRY_SCALE_FACTOR: usize = 2;
+
+/// A ranking that is fused with the BM25 ranking of the base query.
+#[derive(Debug)]
+pub enum RankedSource {
+    /// Terms on the index's key field, in rank order.  Typically these come from an external
+    /// ranking, such as a pgvector nearest-neighbor search.
+    Keys(Vec<Term>),
+    /// A second query whose own scores determine the ranking.
+    Query(Box<dyn Query>),
+}
+
+impl Clone for RankedSource {
+    fn clone(&self) -> Self {
+        match self {
+            RankedSource::Keys(terms) => RankedSource::Keys(terms.clone()),
+            RankedSource::Query(query) => RankedSource::Query(query.box_clone()),
+        }
+    }
+}
+
+/// Combines the BM25 ranking of `query` with a second ranking using Reciprocal Rank Fusion.
+///
+/// Every document in the top `window_size` of either ranking is matched, and its score is the
+/// sum of `1 / (k + rank)` over the rankings it appears in.  Only documents visible to the
+/// current snapshot are ranked, so dead tuples that haven't been vacuumed yet don't take up
+/// ranks.  Because ranks are global, the fused scores are computed once across all segments when
+/// the [`Weight`] is created.
+#[derive(Debug)]
+pub struct RrfQuery {
+    query: Box<dyn Query>,
+    ranked: RankedSource,
+    k: u32,
+    window_size: usize,
+    heap_oid: Option<pg_sys::Oid>,
+}
+
+impl QueryClone for RrfQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            query: self.query.box_clone(),
+            ranked: self.ranked.clone(),
+            k: self.k,
+            window_size: self.window_size,
+            heap_oid: self.heap_oid,
+        })
+    }
+}
+
+impl RrfQuery {
+    /// `heap_oid` is the table whose visibility rules decide which documents are ranked
+    pub fn new(
+        query: Box<dyn Query>,
+        ranked: RankedSource,
+        k: u32,
+        window_size: usize,
+        heap_oid: Option<pg_sys::Oid>,
+    ) -> Self {
+        Self {
+            query,
+            ranked,
+            k,
+            window_size,
+            heap_oid,
+        }
+    }
+
+    /// The top `window_size` visible documents of `query`, by score.  Like a "top N" scan, more
+    /// of the ranking is searched until enough of its documents are visible, or it runs out.
+    fn ranked_docs(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        visibility: &mut Visibility,
+    ) -> tantivy::Result<Vec<Vec<DocAddress>>> {
+        let mut limit = self.window_size;
+        loop {
+            let top_docs = searcher.search(query, &TopDocs::with_limit(limit))?;
+            let exhausted = top_docs.len() < limit;
+            let ranked = top_docs
+                .into_iter()
+                .filter(|(_, doc_address)| visibility.is_visible(*doc_address))
+                .take(self.window_size)
+                .map(|(_, doc_address)| vec![doc_address])
+                .collect::<Vec<_>>();
+            if exhausted || ranked.len() == self.window_size {
+                return Ok(ranked);
+            }
+            limit = limit.saturating_mul(RETRY_SCALE_FACTOR);
+        }
+    }
+
+    fn ranked_keys(
+        &self,
+        searcher: &Searcher,
+        terms: &[Term],
+        visibility: &mut Visibility,
+    ) -> tantivy::Result<Vec<Vec<DocAddress>>> {
+        // the keys are ranked by the order they're given in, and a key's rank is kept even when
+        // none of its documents are visible
+        terms
+            .iter()
+            .take(self.window_size)
+            .map(|term| {
+                let query = TermQuery::new(term.clone(), IndexRecordOption::Basic);
+                Ok(searcher
+                    .search(&query, &DocSetCollector)?
+                    .into_iter()
+                    .filter(|doc_address| visibility.is_visible(*doc_address))
+                    .collect())
+            })
+            .collect()
+    }
+
+    fn fuse(
+        &self,
+        searcher: &Searcher,
+    ) -> tantivy::Result<HashMap<SegmentId, Vec<(DocId, Score)>>> {
+        let mut visibility = Visibility::new(searcher, self.heap_oid);
+        let rankings = [
+            self.ranked_docs(searcher, self.query.as_ref(), &mut visibility)?,
+            match &self.ranked {
+                RankedSource::Keys(terms) => self.ranked_keys(searcher, terms, &mut visibility)?,
+                RankedSource::Query(query) => {
+                    self.ranked_docs(searcher, query.as_ref(), &mut visibility)?
+                }
+            },
+        ];
+
+        let mut fused = HashMap::<DocAddress, Score>::default();
+        for ranking in rankings {
+            for (rank, doc_addresses) in ranking.into_iter().enumerate() {
+                let contribution = 1.0 / (self.k as Score + (rank + 1) as Score);
+                for doc_address in doc_addresses {
+                    *fused.entry(doc_address).or_default() += contribution;
+                }
+            }
+        }
+
+        let mut by_segment = HashMap::<SegmentId, Vec<(DocId, Score)>>::default();
+        for (doc_address, score) in fused {
+            let segment_id = searcher
+                .segment_reader(doc_address.segment_ord)
+                .segment_id();
+            by_segment
+                .entry(segment_id)
+                .or_default()
+                .push((doc_address.doc_id, score));
+        }
+        for docs in by_segment.values_mut() {
+            docs.sort_unstable_by_key(|(doc_id, _)| *doc_id);
+        }
+        Ok(by_segment)
+    }
+}
+
+/// Decides whether a document is a row visible to the current snapshot
+struct Visibility {
+    ctids: Vec<FFType>,
+    vischeck: Option<TSVisibilityChecker>,
+    // we hold onto this b/c `vischeck` points to it
+    _heaprel: Option<PgSearchRelation>,
+}
+
+impl Visibility {
+    fn new(searcher: &Searcher, heap_oid: Option<pg_sys::Oid>) -> Self {
+        let heaprel = heap_oid.map(PgSearchRelation::open);
+        let vischeck = heaprel.as_ref().map(|heaprel| {
+            TSVisibilityChecker::with_rel_and_snap(heaprel.as_ptr(), unsafe {
+                pg_sys::GetActiveSnapshot()
+            })
+        });
+        Self {
+            ctids: searcher
+                .segment_readers()
+                .iter()
+                .map(|segment_reader| FFType::new_ctid(segment_reader.fast_fields()))
+                .collect(),
+            vischeck,
+            _heaprel: heaprel,
+        }
+    }
+
+    fn is_visible(&mut self, doc_address: DocAddress) -> bool {
+        let Some(ctid) = self.ctids[doc_address.segment_ord as usize].as_u64(doc_address.doc_id)
+        else {
+            return false;
+        };
+        self.vischeck
+            .as_mut()
+            .is_none_or(|vischeck| vischeck.is_visible(ctid))
+    }
+}
+
+struct RrfWeight {
+    scores: HashMap<SegmentId, Vec<(DocId, Score)>>,
+}
+
+impl Weight for RrfWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let docs = self
+            .scores
+            .get(&reader.segment_id())
+            .cloned()
+            .unwrap_or_default();
+        Ok(Box::new(RrfScorer {
+            docs,
+            cursor: 0,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let score = self
+            .scores
+            .get(&reader.segment_id())
+            .and_then(|docs| {
+                docs.binary_search_by_key(&doc, |(doc_id, _)| *doc_id)
+                    .ok()
+                    .map(|idx| docs[idx].1)
+            })
+            .ok_or_else(|| {
+                tantivy::TantivyError::InvalidArgument(format!("Document #({doc}) does not match"))
+            })?;
+        Ok(Explanation::new("RRF", score))
+    }
+}
+
+struct RrfScorer {
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+    boost: Score,
+}
+
+impl Scorer for RrfScorer {
+    fn score(&mut self) -> Score {
+        self.docs
+            .get(self.cursor)
+            .map(|(_, score)| *score * self.boost)
+            .unwrap_or_default()
+    }
+}
+
+impl DocSet for RrfScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs
+            .get(self.cursor)
+            .map(|(doc_id, _)| *doc_id)
+            .unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        (self.docs.len() - self.cursor) as u32
+    }
+}
+
+impl Query for RrfQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let searcher = enable_scoring.searcher().ok_or_else(|| {
+            tantivy::TantivyError::InvalidArgument(
+                "RrfQuery requires a searcher to rank documents".to_string(),
+            )
+        })?;
+
+        Ok(Box::new(RrfWeight {
+            scores: self.fuse(searcher)?,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor);
+        if let RankedSource::Query(query) = &self.ranked {
+            query.query_terms(field, segment_reader, visitor);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::FieldName;
+use std::cmp::Ordering;
+use std::ops::Bound;
+use tantivy::columnar::{Column, MonotonicallyMappableToU64, StrColumn};
+use tantivy::query::{
+    intersect_scorers, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, QueryClone,
+    RangeQuery, Scorer, Weight,
+};
+use tantivy::schema::{Field, OwnedValue};
+use tantivy::{DocId, DocSet, Score, SegmentReader, Term, TERMINATED};
+
+/// The key field value of the last row of the previous page
+#[derive(Debug, Clone)]
+pub enum KeyCursor {
+    Numeric(u64),
+    Str(String),
+}
+
+impl KeyCursor {
+    /// Map `value` the same way the key field's fast field column does, so that cursors can be
+    /// compared against the column's values directly
+    pub fn from_value(value: &OwnedValue) -> Option<Self> {
+        match value {
+            OwnedValue::I64(i) => Some(KeyCursor::Numeric(i.to_u64())),
+            OwnedValue::U64(u) => Some(KeyCursor::Numeric(*u)),
+            OwnedValue::F64(f) => Some(KeyCursor::Numeric(f.to_u64())),
+            OwnedValue::Str(s) => Some(KeyCursor::Str(s.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Matches the documents of `query` that sort after the last row of the previous page, where
+/// rows are ordered by score descending and then by the index's key field ascending, or by the
+/// key field alone when there's no `last_score`.  The planner rejects `search_after` under any
+/// other order, as the cursor would skip the wrong rows.
+///
+/// Rather than collecting and discarding every document of the preceding pages, as `OFFSET`
+/// must, the "top N" collector only ever sees the documents that could be on the page being
+/// fetched.  When ordering by the key alone, those are found with a range over the key's fast
+/// field, so earlier pages cost nothing.  When ordering by score, every match of `query` still
+/// has to be scored to know whether it comes after the cursor, so a page costs as much as
+/// scoring all the matches, like the first page does, but without keeping the earlier pages'
+/// rows.
+#[derive(Debug)]
+pub struct SearchAfterQuery {
+    query: Box<dyn Query>,
+    key_field: FieldName,
+    key_term: Term,
+    last_key: KeyCursor,
+    last_score: Option<Score>,
+}
+
+impl QueryClone for SearchAfterQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            query: self.query.box_clone(),
+            key_field: self.key_field.clone(),
+            key_term: self.key_term.clone(),
+            last_key: self.last_key.clone(),
+            last_score: self.last_score,
+        })
+    }
+}
+
+impl SearchAfterQuery {
+    /// When `last_score` is `None` the rows are assumed to be ordered by the key field alone.
+    /// `key_term` is the last key as a term of the key field, and `last_key` is the same value as
+    /// its fast field column stores it.
+    pub fn new(
+        query: Box<dyn Query>,
+        key_field: FieldName,
+        key_term: Term,
+        last_key: KeyCursor,
+        last_score: Option<Score>,
+    ) -> Self {
+        Self {
+            query,
+            key_field,
+            key_term,
+            last_key,
+            last_score,
+        }
+    }
+}
+
+enum KeyColumn {
+    Numeric(Column<u64>, u64),
+    Str(StrColumn, String),
+    Missing,
+}
+
+impl KeyColumn {
+    fn open(reader: &SegmentReader, key_field: &FieldName, last_key: &KeyCursor) -> Self {
+        let fast_fields = reader.fast_fields();
+        let column = match last_key {
+            KeyCursor::Numeric(last_key) => fast_fields
+                .u64_lenient(key_field)
+                .ok()
+                .flatten()
+                .map(|(column, _)| KeyColumn::Numeric(column, *last_key)),
+            KeyCursor::Str(last_key) => fast_fields
+                .str(key_field)
+                .ok()
+                .flatten()
+                .map(|column| KeyColumn::Str(column, last_key.clone())),
+        };
+        column.unwrap_or(KeyColumn::Missing)
+    }
+
+    /// Is the key of `doc` after the cursor's key?
+    fn is_after(&self, doc: DocId, buffer: &mut String) -> bool {
+        match self {
+            KeyColumn::Numeric(column, last_key) => column
+                .first(doc)
+                .map(|key| key > *last_key)
+                .unwrap_or(false),
+            KeyColumn::Str(column, last_key) => {
+                let Some(ordinal) = column.ords().first(doc) else {
+                    return false;
+                };
+                buffer.clear();
+                column.ord_to_str(ordinal, buffer).unwrap_or(false)
+                    && buffer.as_str().cmp(last_key.as_str()) == Ordering::Greater
+            }
+            // every document has a key, so this can only be a segment without any documents
+            KeyColumn::Missing => false,
+        }
+    }
+}
+
+struct SearchAfterWeight {
+    weight: Box<dyn Weight>,
+    /// The keys after the last key, when the rows are ordered by the key alone
+    key_range: Option<Box<dyn Weight>>,
+    key_field: FieldName,
+    last_key: KeyCursor,
+    last_score: Option<Score>,
+}
+
+impl Weight for SearchAfterWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader, boost)?;
+        if let Some(key_range) = &self.key_range {
+            // the range doesn't contribute to the score
+            let key_range = ConstScorer::new(key_range.scorer(reader, 1.0)?, 0.0);
+            return Ok(intersect_scorers(vec![scorer, Box::new(key_range)]));
+        }
+
+        let mut scorer = SearchAfterScorer {
+            key_column: KeyColumn::open(reader, &self.key_field, &self.last_key),
+            last_score: self.last_score,
+            scorer,
+            buffer: String::new(),
+        };
+
+        if scorer.doc() != TERMINATED && !scorer.is_after() && scorer.advance() == TERMINATED {
+            return Ok(Box::new(EmptyScorer));
+        }
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        self.weight.explain(reader, doc)
+    }
+}
+
+struct SearchAfterScorer {
+    key_column: KeyColumn,
+    last_score: Option<Score>,
+    scorer: Box<dyn Scorer>,
+    buffer: String,
+}
+
+impl SearchAfterScorer {
+    fn is_after(&mut self) -> bool {
+        let doc = self.scorer.doc();
+        match self.last_score {
+            None => self.key_column.is_after(doc, &mut self.buffer),
+            Some(last_score) => match self.scorer.score().total_cmp(&last_score) {
+                Ordering::Less => true,
+                Ordering::Greater => false,
+                // only ties with the last row need to look at the key
+                Ordering::Equal => self.key_column.is_after(doc, &mut self.buffer),
+            },
+        }
+    }
+}
+
+impl Scorer for SearchAfterScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
+
+impl DocSet for SearchAfterScorer {
+    fn advance(&mut self) -> DocId {
+        loop {
+            let doc = self.scorer.advance();
+            if doc == TERMINATED || self.is_after() {
+                return doc;
+            }
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Query for SearchAfterQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let key_range = match self.last_score {
+            None => Some(
+                RangeQuery::new(Bound::Excluded(self.key_term.clone()), Bound::Unbounded)
+                    .weight(enable_scoring)?,
+            ),
+            Some(_) => None,
+        };
+        Ok(Box::new(SearchAfterWeight {
+            weight: self.query.weight(enable_scoring)?,
+            key_range,
+            key_field: self.key_field.clone(),
+            last_key: self.last_key.clone(),
+            last_score: self.last_score,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor)
+    }
+}
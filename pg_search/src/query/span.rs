@@ -0,0 +1,1036 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Span queries, which match documents by the positions of their terms relative to each other.
+//!
+//! A span is a range of token positions, `start` inclusive and `end` exclusive, so a single term
+//! at position `p` is the span `(p, p + 1)`.  Each kind of [`SpanClause`] turns the spans of its
+//! sub-clauses into new spans, and a document matches when the top clause has any span in it.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use tantivy::fieldnorm::FieldNormReader;
+use tantivy::postings::{Postings, SegmentPostings};
+use tantivy::query::{
+    Bm25Weight, EmptyScorer, EnableScoring, Explanation, Query, QueryClone, Scorer, Weight,
+};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TantivyError, Term, TERMINATED};
+
+type Span = (u32, u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpanClause {
+    Term(Term),
+    /// Spans of every clause, with at most `slop` positions between them, and in the order the
+    /// clauses are given if `in_order` is set
+    Near {
+        clauses: Vec<SpanClause>,
+        slop: u32,
+        in_order: bool,
+    },
+    /// Spans of any of the clauses
+    Or(Vec<SpanClause>),
+    /// Spans of `include` that don't overlap a span of `exclude`, where `include` spans are
+    /// extended by `pre` positions before and `post` positions after for the overlap check
+    Not {
+        include: Box<SpanClause>,
+        exclude: Box<SpanClause>,
+        pre: u32,
+        post: u32,
+    },
+    /// Spans of `clause` that end within the first `end` positions
+    First {
+        clause: Box<SpanClause>,
+        end: u32,
+    },
+}
+
+impl SpanClause {
+    /// A clause that matches `terms` as an exact phrase.  With no terms, it matches nothing.
+    pub fn phrase(mut terms: Vec<Term>) -> Self {
+        match terms.len() {
+            0 => SpanClause::Or(vec![]),
+            1 => SpanClause::Term(terms.remove(0)),
+            _ => SpanClause::Near {
+                clauses: terms.into_iter().map(SpanClause::Term).collect(),
+                slop: 0,
+                in_order: true,
+            },
+        }
+    }
+
+    fn terms<'a>(&'a self, terms: &mut Vec<&'a Term>) {
+        match self {
+            SpanClause::Term(term) => terms.push(term),
+            SpanClause::Near { clauses, .. } | SpanClause::Or(clauses) => {
+                clauses.iter().for_each(|clause| clause.terms(terms))
+            }
+            // the excluded terms don't contribute to the score, and shouldn't be highlighted
+            SpanClause::Not { include, .. } => include.terms(terms),
+            SpanClause::First { clause, .. } => clause.terms(terms),
+        }
+    }
+
+    fn spans(&self, reader: &SegmentReader) -> tantivy::Result<Box<dyn Spans>> {
+        Ok(match self {
+            SpanClause::Term(term) => {
+                let postings = reader
+                    .inverted_index(term.field())?
+                    .read_postings(term, IndexRecordOption::WithFreqsAndPositions)?;
+                match postings {
+                    Some(postings) => Box::new(TermSpans::new(postings)),
+                    None => Box::new(OrSpans::new(vec![])),
+                }
+            }
+            SpanClause::Near {
+                clauses,
+                slop,
+                in_order,
+            } => Box::new(NearSpans::new(
+                clauses
+                    .iter()
+                    .map(|clause| clause.spans(reader))
+                    .collect::<tantivy::Result<_>>()?,
+                *slop,
+                *in_order,
+            )),
+            SpanClause::Or(clauses) => Box::new(OrSpans::new(
+                clauses
+                    .iter()
+                    .map(|clause| clause.spans(reader))
+                    .collect::<tantivy::Result<_>>()?,
+            )),
+            SpanClause::Not {
+                include,
+                exclude,
+                pre,
+                post,
+            } => Box::new(NotSpans::new(
+                include.spans(reader)?,
+                exclude.spans(reader)?,
+                *pre,
+                *post,
+            )),
+            SpanClause::First { clause, end } => {
+                Box::new(FirstSpans::new(clause.spans(reader)?, *end))
+            }
+        })
+    }
+}
+
+/// Matches the documents where `clause` has at least one span.  Documents are scored with BM25,
+/// using the number of matching spans as the term frequency.
+#[derive(Debug, Clone)]
+pub struct SpanQuery {
+    clause: SpanClause,
+}
+
+impl SpanQuery {
+    pub fn new(clause: SpanClause) -> Self {
+        Self { clause }
+    }
+
+    fn field(&self) -> Option<Field> {
+        let mut terms = vec![];
+        self.clause.terms(&mut terms);
+        terms.first().map(|term| term.field())
+    }
+}
+
+impl Query for SpanQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let mut terms = vec![];
+        self.clause.terms(&mut terms);
+        let terms = terms.into_iter().cloned().collect::<Vec<_>>();
+
+        let similarity = match enable_scoring {
+            EnableScoring::Enabled {
+                statistics_provider,
+                ..
+            } if !terms.is_empty() => Some(Bm25Weight::for_terms(statistics_provider, &terms)?),
+            _ => None,
+        };
+
+        Ok(Box::new(SpanWeight {
+            clause: self.clause.clone(),
+            field: self.field(),
+            similarity,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        _field: Field,
+        _segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        let mut terms = vec![];
+        self.clause.terms(&mut terms);
+        for term in terms {
+            visitor(term, true);
+        }
+    }
+}
+
+impl QueryClone for SpanQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(self.clone())
+    }
+}
+
+struct SpanWeight {
+    clause: SpanClause,
+    field: Option<Field>,
+    similarity: Option<Bm25Weight>,
+}
+
+impl SpanWeight {
+    fn span_scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<SpanScorer> {
+        let fieldnorm_reader = match self.field {
+            Some(field) => reader.fieldnorms_readers().get_field(field)?,
+            None => None,
+        }
+        .unwrap_or_else(|| FieldNormReader::constant(reader.max_doc(), 1));
+
+        Ok(SpanScorer {
+            spans: self.clause.spans(reader)?,
+            similarity: self
+                .similarity
+                .as_ref()
+                .map(|similarity| similarity.boost_by(boost)),
+            fieldnorm_reader,
+        })
+    }
+}
+
+impl Weight for SpanWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        if self.field.is_none() {
+            return Ok(Box::new(EmptyScorer));
+        }
+        Ok(Box::new(self.span_scorer(reader, boost)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.span_scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(scorer.explain())
+    }
+}
+
+struct SpanScorer {
+    spans: Box<dyn Spans>,
+    similarity: Option<Bm25Weight>,
+    fieldnorm_reader: FieldNormReader,
+}
+
+impl SpanScorer {
+    fn explain(&self) -> Explanation {
+        let freq = self.spans.spans().len() as u32;
+        match &self.similarity {
+            Some(similarity) => {
+                let fieldnorm_id = self.fieldnorm_reader.fieldnorm_id(self.doc());
+                let mut explanation = Explanation::new("SpanQuery", self.score_of(freq));
+                explanation.add_detail(similarity.explain(fieldnorm_id, freq));
+                explanation
+            }
+            None => Explanation::new("SpanQuery", 1.0),
+        }
+    }
+
+    fn score_of(&self, freq: u32) -> Score {
+        match &self.similarity {
+            Some(similarity) => {
+                similarity.score(self.fieldnorm_reader.fieldnorm_id(self.doc()), freq)
+            }
+            None => 1.0,
+        }
+    }
+}
+
+impl Scorer for SpanScorer {
+    fn score(&mut self) -> Score {
+        self.score_of(self.spans.spans().len() as u32)
+    }
+}
+
+impl DocSet for SpanScorer {
+    fn advance(&mut self) -> DocId {
+        self.spans.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.spans.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.spans.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.spans.size_hint()
+    }
+}
+
+/// The documents that have spans for a clause, along with their spans.
+///
+/// Unless it's [`TERMINATED`], the current document always has at least one span.
+trait Spans: Send {
+    fn doc(&self) -> DocId;
+
+    /// Move to the next document with spans
+    fn advance(&mut self) -> DocId;
+
+    /// Move to the first document at or after `target` with spans
+    fn seek(&mut self, target: DocId) -> DocId;
+
+    /// The spans in the current document, sorted and without duplicates
+    fn spans(&self) -> &[Span];
+
+    fn size_hint(&self) -> u32;
+}
+
+struct TermSpans {
+    postings: SegmentPostings,
+    positions: Vec<u32>,
+    spans: Vec<Span>,
+}
+
+impl TermSpans {
+    fn new(postings: SegmentPostings) -> Self {
+        let mut spans = Self {
+            postings,
+            positions: vec![],
+            spans: vec![],
+        };
+        spans.settle();
+        spans
+    }
+
+    /// Skip any documents without positions, which are from fields that don't record them
+    fn settle(&mut self) -> DocId {
+        while self.postings.doc() != TERMINATED {
+            self.postings.positions(&mut self.positions);
+            if !self.positions.is_empty() {
+                self.spans.clear();
+                self.spans.extend(
+                    self.positions
+                        .iter()
+                        .map(|&position| (position, position + 1)),
+                );
+                break;
+            }
+            self.postings.advance();
+        }
+        self.postings.doc()
+    }
+}
+
+impl Spans for TermSpans {
+    fn doc(&self) -> DocId {
+        self.postings.doc()
+    }
+
+    fn advance(&mut self) -> DocId {
+        self.postings.advance();
+        self.settle()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.postings.doc() >= target {
+            return self.postings.doc();
+        }
+        self.postings.seek(target);
+        self.settle()
+    }
+
+    fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.postings.size_hint()
+    }
+}
+
+struct OrSpans {
+    clauses: Vec<Box<dyn Spans>>,
+    doc: DocId,
+    spans: Vec<Span>,
+}
+
+impl OrSpans {
+    fn new(clauses: Vec<Box<dyn Spans>>) -> Self {
+        let mut spans = Self {
+            clauses,
+            doc: TERMINATED,
+            spans: vec![],
+        };
+        spans.settle();
+        spans
+    }
+
+    fn settle(&mut self) -> DocId {
+        self.doc = self
+            .clauses
+            .iter()
+            .map(|clause| clause.doc())
+            .min()
+            .unwrap_or(TERMINATED);
+        self.spans.clear();
+        if self.doc != TERMINATED {
+            for clause in self
+                .clauses
+                .iter()
+                .filter(|clause| clause.doc() == self.doc)
+            {
+                self.spans.extend_from_slice(clause.spans());
+            }
+            self.spans.sort_unstable();
+            self.spans.dedup();
+        }
+        self.doc
+    }
+}
+
+impl Spans for OrSpans {
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn advance(&mut self) -> DocId {
+        let doc = self.doc;
+        for clause in self.clauses.iter_mut().filter(|clause| clause.doc() == doc) {
+            clause.advance();
+        }
+        self.settle()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc >= target {
+            return self.doc;
+        }
+        for clause in &mut self.clauses {
+            clause.seek(target);
+        }
+        self.settle()
+    }
+
+    fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.clauses
+            .iter()
+            .map(|clause| clause.size_hint())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+struct NearSpans {
+    clauses: Vec<Box<dyn Spans>>,
+    slop: u32,
+    in_order: bool,
+    doc: DocId,
+    spans: Vec<Span>,
+}
+
+impl NearSpans {
+    fn new(clauses: Vec<Box<dyn Spans>>, slop: u32, in_order: bool) -> Self {
+        let mut spans = Self {
+            clauses,
+            slop,
+            in_order,
+            doc: TERMINATED,
+            spans: vec![],
+        };
+        spans.settle();
+        spans
+    }
+
+    /// Move all the clauses to the first document they have in common where their spans are
+    /// near enough to each other
+    fn settle(&mut self) -> DocId {
+        loop {
+            let Some(target) = self.clauses.iter().map(|clause| clause.doc()).max() else {
+                self.doc = TERMINATED;
+                return self.doc;
+            };
+            if target == TERMINATED {
+                self.doc = TERMINATED;
+                return self.doc;
+            }
+
+            for clause in &mut self.clauses {
+                clause.seek(target);
+            }
+            if self.clauses.iter().any(|clause| clause.doc() != target) {
+                continue;
+            }
+
+            self.spans.clear();
+            let clauses = self
+                .clauses
+                .iter()
+                .map(|clause| clause.spans())
+                .collect::<Vec<_>>();
+            if self.in_order {
+                near_in_order(&clauses, self.slop, &mut self.spans);
+            } else {
+                near_any_order(&clauses, self.slop, &mut self.spans);
+            }
+            if !self.spans.is_empty() {
+                self.spans.sort_unstable();
+                self.spans.dedup();
+                self.doc = target;
+                return self.doc;
+            }
+
+            self.clauses[0].advance();
+        }
+    }
+}
+
+impl Spans for NearSpans {
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        self.clauses[0].advance();
+        self.settle()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc >= target {
+            return self.doc;
+        }
+        for clause in &mut self.clauses {
+            clause.seek(target);
+        }
+        self.settle()
+    }
+
+    fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.clauses
+            .iter()
+            .map(|clause| clause.size_hint())
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// For each span of the first clause, the span from it to the earliest spans of the other
+/// clauses that follow it in order, if the positions between them add up to at most `slop`.
+///
+/// Like Lucene's `NearSpansOrdered`, each start has at most one match, which is enough to know
+/// whether a document matches and how often, and the other clauses' cursors only move forward,
+/// so this is linear in the number of spans rather than enumerating every combination.
+fn near_in_order(clauses: &[&[Span]], slop: u32, matches: &mut Vec<Span>) {
+    let Some((first, rest)) = clauses.split_first() else {
+        return;
+    };
+    let mut cursors = vec![0; rest.len()];
+    'first: for &(start, end) in first.iter() {
+        let mut end = end;
+        let mut gaps = 0;
+        for (spans, cursor) in rest.iter().zip(cursors.iter_mut()) {
+            while *cursor < spans.len() && spans[*cursor].0 < end {
+                *cursor += 1;
+            }
+            let Some(&(next_start, next_end)) = spans.get(*cursor) else {
+                // no later span of this clause follows any later start either
+                return;
+            };
+            gaps += next_start - end;
+            // spans are sorted by start, so the gap only grows from here
+            if gaps > slop {
+                continue 'first;
+            }
+            end = next_end;
+        }
+        matches.push((start, end));
+    }
+}
+
+/// The spans covering one span from each clause, in any order and without overlapping, where
+/// the positions between them add up to at most `slop`.
+///
+/// Like Lucene's `NearSpansUnordered`, this slides a window over the clauses' spans, always
+/// advancing the clause whose current span starts first, so each span is visited once rather
+/// than enumerating every combination.
+fn near_any_order(clauses: &[&[Span]], slop: u32, matches: &mut Vec<Span>) {
+    if clauses.iter().any(|spans| spans.is_empty()) {
+        return;
+    }
+
+    let mut cursors = vec![0; clauses.len()];
+    // the clauses ordered by the start of their current span, earliest first
+    let mut queue = clauses
+        .iter()
+        .enumerate()
+        .map(|(idx, spans)| Reverse((spans[0], idx)))
+        .collect::<BinaryHeap<_>>();
+    let mut window = Vec::with_capacity(clauses.len());
+    loop {
+        window.clear();
+        window.extend(
+            clauses
+                .iter()
+                .zip(&cursors)
+                .map(|(spans, &cursor)| spans[cursor]),
+        );
+        window.sort_unstable();
+
+        // the window's spans don't overlap, so whatever they don't cover is a gap between them
+        let overlaps = window.windows(2).any(|pair| pair[1].0 < pair[0].1);
+        if !overlaps {
+            let start = window[0].0;
+            let end = window.iter().map(|span| span.1).max().unwrap_or(start);
+            let length = window.iter().map(|span| span.1 - span.0).sum::<u32>();
+            if (end - start).saturating_sub(length) <= slop {
+                matches.push((start, end));
+            }
+        }
+
+        let Some(Reverse((_, idx))) = queue.pop() else {
+            return;
+        };
+        cursors[idx] += 1;
+        match clauses[idx].get(cursors[idx]) {
+            Some(&span) => queue.push(Reverse((span, idx))),
+            // every window from here on would be missing this clause
+            None => return,
+        }
+    }
+}
+
+struct NotSpans {
+    include: Box<dyn Spans>,
+    exclude: Box<dyn Spans>,
+    pre: u32,
+    post: u32,
+    spans: Vec<Span>,
+}
+
+impl NotSpans {
+    fn new(include: Box<dyn Spans>, exclude: Box<dyn Spans>, pre: u32, post: u32) -> Self {
+        let mut spans = Self {
+            include,
+            exclude,
+            pre,
+            post,
+            spans: vec![],
+        };
+        spans.settle();
+        spans
+    }
+
+    fn settle(&mut self) -> DocId {
+        loop {
+            let doc = self.include.doc();
+            if doc == TERMINATED {
+                return doc;
+            }
+
+            let excluded: &[Span] = if self.exclude.seek(doc) == doc {
+                self.exclude.spans()
+            } else {
+                &[]
+            };
+            self.spans.clear();
+            self.spans.extend(
+                self.include
+                    .spans()
+                    .iter()
+                    .copied()
+                    .filter(|&(start, end)| {
+                        let (start, end) = (start.saturating_sub(self.pre), end + self.post);
+                        !excluded.iter().any(|&(excluded_start, excluded_end)| {
+                            excluded_start < end && start < excluded_end
+                        })
+                    }),
+            );
+            if !self.spans.is_empty() {
+                return doc;
+            }
+
+            self.include.advance();
+        }
+    }
+}
+
+impl Spans for NotSpans {
+    fn doc(&self) -> DocId {
+        self.include.doc()
+    }
+
+    fn advance(&mut self) -> DocId {
+        self.include.advance();
+        self.settle()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.include.doc() >= target {
+            return self.include.doc();
+        }
+        self.include.seek(target);
+        self.settle()
+    }
+
+    fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.include.size_hint()
+    }
+}
+
+struct FirstSpans {
+    clause: Box<dyn Spans>,
+    end: u32,
+    spans: Vec<Span>,
+}
+
+impl FirstSpans {
+    fn new(clause: Box<dyn Spans>, end: u32) -> Self {
+        let mut spans = Self {
+            clause,
+            end,
+            spans: vec![],
+        };
+        spans.settle();
+        spans
+    }
+
+    fn settle(&mut self) -> DocId {
+        loop {
+            let doc = self.clause.doc();
+            if doc == TERMINATED {
+                return doc;
+            }
+
+            self.spans.clear();
+            self.spans.extend(
+                self.clause
+                    .spans()
+                    .iter()
+                    .copied()
+                    .filter(|&(_, end)| end <= self.end),
+            );
+            if !self.spans.is_empty() {
+                return doc;
+            }
+
+            self.clause.advance();
+        }
+    }
+}
+
+impl Spans for FirstSpans {
+    fn doc(&self) -> DocId {
+        self.clause.doc()
+    }
+
+    fn advance(&mut self) -> DocId {
+        self.clause.advance();
+        self.settle()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.clause.doc() >= target {
+            return self.clause.doc();
+        }
+        self.clause.seek(target);
+        self.settle()
+    }
+
+    fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.clause.size_hint()
+    }
+}
+
+/// Separates the operands of a `NEAR/n` operator once it's been rewritten into a phrase
+pub const NEAR_SEPARATOR: char = '\u{1f}';
+
+/// Rewrite each `NEAR/n` operator in `query_string`, and the words or phrases on either side of
+/// it, into a phrase with a slop of `n` whose words are separated by [`NEAR_SEPARATOR`].  The
+/// query string grammar doesn't know about `NEAR/n`, but it can parse those phrases, and they're
+/// turned into span queries once it has.
+///
+/// Chains like `a NEAR/3 b NEAR/3 c` become a single phrase, so all their distances must be the
+/// same.  Returns `None` if there's no `NEAR/n` in `query_string`.
+pub fn rewrite_near(query_string: &str) -> Result<Option<String>, String> {
+    let tokens = query_tokens(query_string);
+    let text = |i: usize| &query_string[tokens[i].0..tokens[i].1];
+    let near_distance = |i: usize| -> Result<Option<u32>, String> {
+        if i < tokens.len() {
+            near_operator(text(i))
+        } else {
+            Ok(None)
+        }
+    };
+    let is_operand = |i: usize| i < tokens.len() && is_near_operand(text(i));
+
+    let mut rewritten = String::new();
+    let mut copied = 0;
+    let mut found = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        if near_distance(i)?.is_some() {
+            return Err(format!(
+                "'{}' must be between two words or phrases",
+                text(i)
+            ));
+        }
+        let Some(distance) = near_distance(i + 1)?.filter(|_| is_operand(i)) else {
+            i += 1;
+            continue;
+        };
+
+        let mut operands = vec![text(i)];
+        let mut next = i + 1;
+        while let Some(next_distance) = near_distance(next)? {
+            if next_distance != distance {
+                return Err(format!(
+                    "'{}' follows NEAR/{distance}, but a chain of NEAR operators must all have the same distance",
+                    text(next)
+                ));
+            }
+            if !is_operand(next + 1) {
+                return Err(format!(
+                    "'{}' must be between two words or phrases",
+                    text(next)
+                ));
+            }
+            operands.push(text(next + 1));
+            next += 2;
+        }
+
+        rewritten.push_str(&query_string[copied..tokens[i].0]);
+        rewritten.push_str(&near_phrase(&operands, distance)?);
+        copied = tokens[next - 1].1;
+        found = true;
+        i = next;
+    }
+
+    if !found {
+        return Ok(None);
+    }
+    rewritten.push_str(&query_string[copied..]);
+    Ok(Some(rewritten))
+}
+
+/// The byte ranges of the whitespace-separated tokens of a query string, where quoted phrases are
+/// never split, and parentheses are tokens of their own
+fn query_tokens(query_string: &str) -> Vec<(usize, usize)> {
+    let mut tokens = vec![];
+    let mut start = None;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in query_string.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => {
+                escaped = true;
+                start.get_or_insert(i);
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                start.get_or_insert(i);
+            }
+            _ if in_quotes => {}
+            '(' | ')' => {
+                if let Some(start) = start.take() {
+                    tokens.push((start, i));
+                }
+                tokens.push((i, i + 1));
+            }
+            c if c.is_whitespace() => {
+                if let Some(start) = start.take() {
+                    tokens.push((start, i));
+                }
+            }
+            _ => {
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(start) = start {
+        tokens.push((start, query_string.len()));
+    }
+    tokens
+}
+
+/// `n` if `token` is a `NEAR/n` operator
+fn near_operator(token: &str) -> Result<Option<u32>, String> {
+    match token.strip_prefix("NEAR/") {
+        Some(distance) => distance
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("'{token}' must be NEAR/ followed by a number of words")),
+        None => Ok(None),
+    }
+}
+
+fn is_near_operand(token: &str) -> bool {
+    !matches!(token, "(" | ")" | "AND" | "OR" | "NOT" | "&&" | "||")
+        && !token.starts_with("NEAR/")
+        && !token.ends_with(':')
+}
+
+/// The phrase that replaces a chain of `NEAR/distance` operators and their `operands`
+fn near_phrase(operands: &[&str], distance: u32) -> Result<String, String> {
+    let mut occur = "";
+    let mut field = None;
+    let mut words = vec![];
+    for (i, operand) in operands.iter().enumerate() {
+        let mut operand = *operand;
+        if i == 0 && (operand.starts_with('+') || operand.starts_with('-')) {
+            (occur, operand) = operand.split_at(1);
+        }
+
+        let (operand_field, value) = match operand.split_once(':') {
+            Some((operand_field, value))
+                if !operand_field.is_empty() && !operand_field.contains('"') =>
+            {
+                (Some(operand_field), value)
+            }
+            _ => (None, operand),
+        };
+        match (field, operand_field) {
+            (Some(field), Some(operand_field)) if field != operand_field => {
+                return Err(format!(
+                    "the words around NEAR/{distance} must be in the same field, found '{field}' and '{operand_field}'"
+                ));
+            }
+            (None, Some(_)) => field = operand_field,
+            _ => {}
+        }
+
+        let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(phrase) => phrase.replace("\\\"", "\"").replace("\\\\", "\\"),
+            None => value.to_string(),
+        };
+        words.push(value.replace('\\', "\\\\").replace('"', "\\\""));
+    }
+
+    let field = field.map(|field| format!("{field}:")).unwrap_or_default();
+    let separator = NEAR_SEPARATOR.to_string();
+    Ok(format!(
+        "{occur}{field}\"{}\"~{distance}",
+        words.join(&separator)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn rewrite(query_string: &str) -> Option<String> {
+        rewrite_near(query_string)
+            .unwrap()
+            .map(|rewritten| rewritten.replace(NEAR_SEPARATOR, "|"))
+    }
+
+    #[rstest]
+    fn test_rewrite_near() {
+        assert_eq!(rewrite("description:running shoes"), None);
+        assert_eq!(
+            rewrite("description:running NEAR/2 shoes"),
+            Some(r#"description:"running|shoes"~2"#.into())
+        );
+        assert_eq!(
+            rewrite(r#"-description:"running shoes" NEAR/3 white OR keyboard"#),
+            Some(r#"-description:"running shoes|white"~3 OR keyboard"#.into())
+        );
+        assert_eq!(
+            rewrite("description:(a NEAR/1 b NEAR/1 c) AND category:footwear"),
+            Some(r#"description:("a|b|c"~1) AND category:footwear"#.into())
+        );
+        assert_eq!(
+            rewrite("metadata.color:white NEAR/0 metadata.color:shoes"),
+            Some(r#"metadata.color:"white|shoes"~0"#.into())
+        );
+    }
+
+    fn terms(positions: &[u32]) -> Vec<Span> {
+        positions
+            .iter()
+            .map(|&position| (position, position + 1))
+            .collect()
+    }
+
+    #[rstest]
+    fn test_near_in_order() {
+        let (a, b, c) = (terms(&[0, 4, 10]), terms(&[1, 5, 7]), terms(&[2, 7]));
+        let mut matches = vec![];
+        near_in_order(&[&a, &b, &c], 1, &mut matches);
+        assert_eq!(matches, vec![(0, 3), (4, 8)]);
+
+        matches.clear();
+        near_in_order(&[&c, &a], 10, &mut matches);
+        assert_eq!(matches, vec![(2, 5), (7, 11)]);
+    }
+
+    #[rstest]
+    fn test_near_any_order() {
+        let (a, b) = (terms(&[3, 9]), terms(&[1, 10, 20]));
+        let mut matches = vec![];
+        near_any_order(&[&a, &b], 1, &mut matches);
+        assert_eq!(matches, vec![(1, 4), (9, 11)]);
+
+        // the same term can't match twice at one position
+        let mut matches = vec![];
+        near_any_order(&[&a, &a], 6, &mut matches);
+        assert_eq!(matches, vec![(3, 10)]);
+    }
+
+    #[rstest]
+    #[case::no_left_operand("NEAR/2 shoes")]
+    #[case::no_right_operand("running NEAR/2")]
+    #[case::not_a_number("running NEAR/two shoes")]
+    #[case::mixed_distances("a NEAR/1 b NEAR/2 c")]
+    #[case::mixed_fields("description:a NEAR/1 category:b")]
+    #[case::operator_operand("a NEAR/1 AND b")]
+    fn test_rewrite_near_errors(#[case] query_string: &str) {
+        assert!(rewrite_near(query_string).is_err());
+    }
+}
@@ -68,6 +68,10 @@ pub enum SearchFieldConfig {
         normalizer: SearchNormalizer,
         #[serde(default)]
         column: Option<String>,
+        /// Keep each value of the field, which for a JSON array column is each of its elements,
+        /// as a child document of its row that nested queries can match one at a time
+        #[serde(default)]
+        nested: bool,
     },
     Range {
         #[serde(default = "default_as_true")]
@@ -242,6 +246,18 @@ impl SearchFieldConfig {
     pub fn default_range() -> Self {
         Self::from_json(json!({"Json": {"fast": true}}))
     }
+
+    /// Points are indexed as JSON objects with `lat` and `lon` keys, which geo queries read
+    /// from fast fields
+    pub fn default_geo() -> Self {
+        Self::from_json(json!({"Json": {"fast": true}}))
+    }
+
+    /// Stored queries are kept whole in a fast field for `paradedb.percolate()` to read back,
+    /// and aren't searchable themselves
+    pub fn default_query() -> Self {
+        Self::from_json(json!({"Text": {"indexed": false, "fast": true, "fieldnorms": false}}))
+    }
 }
 
 impl From<SearchFieldConfig> for TextOptions {
@@ -19,6 +19,7 @@ mod anyenum;
 mod config;
 pub mod range;
 
+use crate::api::operator::searchqueryinput_typoid;
 use crate::api::FieldName;
 use crate::api::HashMap;
 use crate::postgres::options::BM25IndexOptions;
@@ -56,6 +57,8 @@ pub enum SearchFieldType {
     Json(pg_sys::Oid),
     Date(pg_sys::Oid),
     Range(pg_sys::Oid),
+    Geo(pg_sys::Oid),
+    Query(pg_sys::Oid),
 }
 
 impl SearchFieldType {
@@ -71,6 +74,8 @@ impl SearchFieldType {
             SearchFieldType::Json(_) => SearchFieldConfig::default_json(),
             SearchFieldType::Date(_) => SearchFieldConfig::default_date(),
             SearchFieldType::Range(_) => SearchFieldConfig::default_range(),
+            SearchFieldType::Geo(_) => SearchFieldConfig::default_geo(),
+            SearchFieldType::Query(_) => SearchFieldConfig::default_query(),
         }
     }
 
@@ -86,6 +91,8 @@ impl SearchFieldType {
             SearchFieldType::Json(oid) => *oid,
             SearchFieldType::Date(oid) => *oid,
             SearchFieldType::Range(oid) => *oid,
+            SearchFieldType::Geo(oid) => *oid,
+            SearchFieldType::Query(oid) => *oid,
         }
         .into()
     }
@@ -134,11 +141,14 @@ impl TryFrom<PgOid> for SearchFieldType {
                 | PgBuiltInOids::TIMESTAMPTZOID
                 | PgBuiltInOids::TIMEOID
                 | PgBuiltInOids::TIMETZOID => Ok(SearchFieldType::Date((*builtin).into())),
+                PgBuiltInOids::POINTOID => Ok(SearchFieldType::Geo((*builtin).into())),
                 _ => Err(SearchIndexSchemaError::InvalidPgOid(pg_oid)),
             },
             PgOid::Custom(custom) => {
                 if unsafe { pgrx::pg_sys::type_is_enum(*custom) } {
                     Ok(SearchFieldType::F64(*custom))
+                } else if *custom == searchqueryinput_typoid() {
+                    Ok(SearchFieldType::Query(*custom))
                 } else {
                     Err(SearchIndexSchemaError::InvalidPgOid(pg_oid))
                 }
@@ -213,6 +223,17 @@ impl SearchIndexSchema {
         self.schema.fields()
     }
 
+    /// The fields with `nested` enabled, whose elements are indexed as child documents
+    pub fn nested_fields(&self) -> Vec<Field> {
+        self.fields()
+            .filter(|(_, entry)| {
+                self.search_field(entry.name())
+                    .is_some_and(|search_field| search_field.is_nested())
+            })
+            .map(|(field, _)| field)
+            .collect()
+    }
+
     /// A lookup from a Postgres column name to search fields that have
     /// marked it as their source column with the 'column' key.
     pub fn alias_lookup(&self) -> HashMap<String, Vec<SearchField>> {
@@ -417,6 +438,21 @@ impl SearchField {
         matches!(self.field_type, SearchFieldType::Json(_))
     }
 
+    pub fn is_nested(&self) -> bool {
+        matches!(
+            self.field_config,
+            SearchFieldConfig::Json { nested: true, .. }
+        )
+    }
+
+    pub fn is_geo(&self) -> bool {
+        matches!(self.field_type, SearchFieldType::Geo(_))
+    }
+
+    pub fn is_query(&self) -> bool {
+        matches!(self.field_type, SearchFieldType::Query(_))
+    }
+
     #[allow(deprecated)]
     pub fn is_keyword(&self) -> bool {
         self.field_config
@@ -572,4 +608,14 @@ mod tests {
         let text_options = json_object_options.set_fast(Some("index"));
         assert_ne!(expected.is_fast(), text_options.is_fast());
     }
+
+    #[rstest]
+    fn test_search_jsonobject_nested_options() {
+        let search_json_option: SearchFieldConfig =
+            serde_json::from_value(serde_json::json!({"Json": {"nested": true}})).unwrap();
+        // the elements are written as child documents, so the field itself is indexed as usual
+        let json_object_options: JsonObjectOptions = search_json_option.into();
+        let expected: JsonObjectOptions = SearchFieldConfig::default_json().into();
+        assert_eq!(json_object_options, expected);
+    }
 }
@@ -20,6 +20,7 @@ mod fixtures;
 use fixtures::db::Query;
 use fixtures::*;
 use rstest::*;
+use serde_json::Value;
 use sqlx::PgConnection;
 
 #[rstest]
@@ -128,3 +129,324 @@ fn test_aggregate_without_mvcc(mut conn: PgConnection) {
         .unwrap();
     assert_eq!(buckets.len(), 2); // Should have 2 categories
 }
+
+#[rstest]
+fn test_aggregate_ascii_folding_normalizer(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+    CREATE INDEX idxbm25_search ON paradedb.bm25_search
+    USING bm25 (id, description, category)
+    WITH (
+        key_field='id',
+        text_fields='{
+            "description": {},
+            "category": {"fast": true, "normalizer": "ascii_folding"}
+        }'
+    );
+    INSERT INTO paradedb.bm25_search (description, category) VALUES
+        ('espresso', 'Café'),
+        ('espresso', 'cafe'),
+        ('espresso', 'CAFÉ');
+    "#
+    .execute(&mut conn);
+
+    let result = r#"
+    SELECT paradedb.aggregate(
+        'paradedb.idxbm25_search',
+        paradedb.parse('description:espresso'),
+        '{
+            "category": {
+                "terms": {
+                    "field": "category",
+                    "size": 10
+                }
+            }
+        }'::json
+    )
+    "#
+    .fetch_one::<(serde_json::Value,)>(&mut conn);
+
+    // every spelling of the category is normalized to the same value
+    let buckets = result
+        .0
+        .pointer("/category/buckets")
+        .unwrap()
+        .as_array()
+        .unwrap();
+    assert_eq!(buckets.len(), 1);
+    assert_eq!(buckets[0]["key"], "cafe");
+    assert_eq!(buckets[0]["doc_count"], 3);
+}
+
+fn aggregate_scan_setup(conn: &mut PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+    CREATE INDEX idxbm25_search ON paradedb.bm25_search
+    USING bm25 (id, description, category, rating)
+    WITH (
+        key_field='id',
+        text_fields='{
+            "description": {},
+            "category": {"fast": true, "normalizer": "raw"}
+        }',
+        numeric_fields='{"rating": {"fast": true}}'
+    );
+    INSERT INTO paradedb.bm25_search (description, category, rating) VALUES
+        ('keyboard', NULL, 2),
+        ('keyboard', 'Accessories', 1);
+
+    DELETE FROM paradedb.bm25_search WHERE category = 'Accessories' AND rating = 1;
+    SET paradedb.enable_aggregate_custom_scan TO on;
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn aggregate_custom_scan_group_by(mut conn: PgConnection) {
+    aggregate_scan_setup(&mut conn);
+
+    let query = r#"
+    SELECT category, COUNT(*), SUM(rating), MIN(rating), MAX(rating)
+    FROM paradedb.bm25_search
+    WHERE description @@@ 'keyboard OR shoes OR plastic'
+    GROUP BY category
+    ORDER BY category
+    "#;
+
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+    eprintln!("{plan:#?}");
+    assert_eq!(
+        plan.pointer("/0/Plan/Plans/0/Custom Plan Provider"),
+        Some(&Value::String(String::from("ParadeDB Aggregate Scan")))
+    );
+
+    type Row = (Option<String>, i64, Option<i64>, Option<i32>, Option<i32>);
+    let with_custom_scan: Vec<Row> = query.fetch(&mut conn);
+    "SET paradedb.enable_aggregate_custom_scan TO off".execute(&mut conn);
+    let without_custom_scan: Vec<Row> = query.fetch(&mut conn);
+    assert_eq!(with_custom_scan, without_custom_scan);
+    assert!(with_custom_scan
+        .iter()
+        .any(|(category, ..)| category.is_none()));
+}
+
+#[rstest]
+fn aggregate_custom_scan_null_group(mut conn: PgConnection) {
+    aggregate_scan_setup(&mut conn);
+    r#"
+    INSERT INTO paradedb.bm25_search (description, category, rating) VALUES
+        ('keyboard', 'NULL', 1),
+        ('keyboard', '0', NULL),
+        ('keyboard', '__paradedb_null_group__', 1);
+    "#
+    .execute(&mut conn);
+
+    // real values that look like the `NULL` group's bucket key are still their own groups
+    let by_category = "SELECT category, COUNT(*) FROM paradedb.bm25_search
+        WHERE description @@@ 'keyboard' GROUP BY category ORDER BY category";
+    let by_rating = "SELECT rating, COUNT(*) FROM paradedb.bm25_search
+        WHERE description @@@ 'keyboard' GROUP BY rating ORDER BY rating";
+    for query in [by_category, by_rating] {
+        let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+        assert_eq!(
+            plan.pointer("/0/Plan/Plans/0/Custom Plan Provider"),
+            Some(&Value::String(String::from("ParadeDB Aggregate Scan"))),
+            "{query}"
+        );
+    }
+
+    let categories: Vec<(Option<String>, i64)> = by_category.fetch(&mut conn);
+    let ratings: Vec<(Option<i32>, i64)> = by_rating.fetch(&mut conn);
+    "SET paradedb.enable_aggregate_custom_scan TO off".execute(&mut conn);
+    assert_eq!(categories, by_category.fetch(&mut conn));
+    assert_eq!(ratings, by_rating.fetch(&mut conn));
+    assert!(categories.contains(&(Some("NULL".into()), 1)));
+    assert!(categories.contains(&(Some("__paradedb_null_group__".into()), 1)));
+    assert!(ratings.iter().any(|(rating, _)| rating.is_none()));
+}
+
+#[rstest]
+fn aggregate_custom_scan_without_group_by(mut conn: PgConnection) {
+    aggregate_scan_setup(&mut conn);
+
+    let query = r#"
+    SELECT COUNT(*), COUNT(rating), SUM(rating), MIN(rating), MAX(rating)
+    FROM paradedb.bm25_search
+    WHERE description @@@ 'keyboard'
+    "#;
+
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+    assert_eq!(
+        plan.pointer("/0/Plan/Custom Plan Provider"),
+        Some(&Value::String(String::from("ParadeDB Aggregate Scan")))
+    );
+
+    type Row = (i64, i64, Option<i64>, Option<i32>, Option<i32>);
+    let with_custom_scan: Row = query.fetch_one(&mut conn);
+    "SET paradedb.enable_aggregate_custom_scan TO off".execute(&mut conn);
+    let without_custom_scan: Row = query.fetch_one(&mut conn);
+    assert_eq!(with_custom_scan, without_custom_scan);
+
+    // nothing matches, but there's still a row of aggregates
+    "SET paradedb.enable_aggregate_custom_scan TO on".execute(&mut conn);
+    let empty: Row = r#"
+    SELECT COUNT(*), COUNT(rating), SUM(rating), MIN(rating), MAX(rating)
+    FROM paradedb.bm25_search
+    WHERE description @@@ 'does_not_exist'
+    "#
+    .fetch_one(&mut conn);
+    assert_eq!(empty, (0, 0, None, None, None));
+}
+
+#[rstest]
+fn aggregate_custom_scan_declines_bigint_min_max(mut conn: PgConnection) {
+    r#"
+    CREATE TABLE big_numbers (id SERIAL PRIMARY KEY, description TEXT, value BIGINT);
+    INSERT INTO big_numbers (description, value) VALUES
+        ('big', 9007199254740993),
+        ('big', 9007199254740995);
+    CREATE INDEX big_numbers_idx ON big_numbers
+    USING bm25 (id, description, value)
+    WITH (key_field='id', numeric_fields='{"value": {"fast": true}}');
+    SET paradedb.enable_aggregate_custom_scan TO on;
+    "#
+    .execute(&mut conn);
+
+    // values above 2^53 can't be aggregated exactly as f64s
+    let query = "SELECT MIN(value), MAX(value) FROM big_numbers WHERE description @@@ 'big'";
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+    assert_ne!(
+        plan.pointer("/0/Plan/Custom Plan Provider"),
+        Some(&Value::String(String::from("ParadeDB Aggregate Scan")))
+    );
+    let (min, max): (i64, i64) = query.fetch_one(&mut conn);
+    assert_eq!((min, max), (9007199254740993, 9007199254740995));
+
+    // but they can be counted
+    let query = "SELECT COUNT(value) FROM big_numbers WHERE description @@@ 'big'";
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+    assert_eq!(
+        plan.pointer("/0/Plan/Custom Plan Provider"),
+        Some(&Value::String(String::from("ParadeDB Aggregate Scan")))
+    );
+}
+
+#[rstest]
+fn window_agg_alongside_top_n(mut conn: PgConnection) {
+    aggregate_scan_setup(&mut conn);
+    "SET paradedb.enable_aggregate_custom_scan TO off".execute(&mut conn);
+
+    let query = r#"
+    SELECT id, paradedb.agg('{"terms": {"field": "category"}}') OVER ()
+    FROM paradedb.bm25_search
+    WHERE description @@@ 'keyboard OR shoes'
+    ORDER BY paradedb.score(id) DESC
+    LIMIT 3
+    "#;
+
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+    let scan = plan.pointer("/0/Plan/Plans/0").unwrap();
+    assert_eq!(
+        scan.get("Custom Plan Provider"),
+        Some(&Value::String(String::from("ParadeDB Scan")))
+    );
+    assert_eq!(
+        scan.get("Exec Method"),
+        Some(&Value::String(String::from("TopNScanExecState")))
+    );
+
+    let rows: Vec<(i32, Value)> = query.fetch(&mut conn);
+    assert_eq!(rows.len(), 3);
+    assert!(rows.iter().all(|(_, facets)| facets == &rows[0].1));
+
+    // the facets are over every visible match, not just the top 3
+    let expected: Vec<(String, i64)> = r#"
+    SELECT category, COUNT(*) FROM paradedb.bm25_search
+    WHERE description @@@ 'keyboard OR shoes' AND category IS NOT NULL
+    GROUP BY category
+    ORDER BY category
+    "#
+    .fetch(&mut conn);
+    let mut buckets: Vec<(String, i64)> = rows[0].1["buckets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|bucket| {
+            (
+                bucket["key"].as_str().unwrap().to_string(),
+                bucket["doc_count"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    buckets.sort();
+    assert_eq!(buckets, expected);
+}
+
+#[rstest]
+fn window_agg_without_limit(mut conn: PgConnection) {
+    aggregate_scan_setup(&mut conn);
+    "SET paradedb.enable_aggregate_custom_scan TO off".execute(&mut conn);
+
+    let rows: Vec<(i32, Value)> = r#"
+    SELECT id, paradedb.agg('{"value_count": {"field": "rating"}}') OVER ()
+    FROM paradedb.bm25_search
+    WHERE description @@@ 'keyboard'
+    "#
+    .fetch(&mut conn);
+
+    let (count,) =
+        "SELECT COUNT(rating) FROM paradedb.bm25_search WHERE description @@@ 'keyboard'"
+            .fetch_one::<(i64,)>(&mut conn);
+    assert_eq!(rows.len() as i64, count);
+    for (_, facets) in rows {
+        assert_eq!(facets["value"].as_f64(), Some(count as f64));
+    }
+}
+
+#[rstest]
+fn window_agg_requires_whole_where_clause_in_index(mut conn: PgConnection) {
+    aggregate_scan_setup(&mut conn);
+    "SET paradedb.enable_aggregate_custom_scan TO off".execute(&mut conn);
+    "SET paradedb.enable_filter_pushdown TO on".execute(&mut conn);
+
+    let expected_error = "error returned from database: paradedb.agg() OVER () is only supported in queries over a single table that use the ParadeDB custom scan, where the index can answer the entire WHERE clause";
+    let assert_planning_error = |conn: &mut PgConnection, query: &str| {
+        // it's an error as soon as the query is planned, even if nothing would be returned
+        for query in [format!("EXPLAIN {query}"), query.to_string()] {
+            let result = query.as_str().execute_result(conn);
+            assert_eq!(result.unwrap_err().to_string(), expected_error, "{query}");
+        }
+    };
+
+    // `in_stock` isn't indexed, so the index can't tell which of its matches are in the result
+    assert_planning_error(
+        &mut conn,
+        r#"
+        SELECT id, paradedb.agg('{"value_count": {"field": "rating"}}') OVER ()
+        FROM paradedb.bm25_search
+        WHERE description @@@ 'keyboard' AND in_stock
+        "#,
+    );
+
+    // nor which of them a join keeps
+    assert_planning_error(
+        &mut conn,
+        r#"
+        SELECT a.id, paradedb.agg('{"value_count": {"field": "rating"}}') OVER ()
+        FROM paradedb.bm25_search a
+        JOIN paradedb.bm25_search b ON a.id = b.id AND b.in_stock
+        WHERE a.description @@@ 'keyboard'
+        "#,
+    );
+
+    // and without the custom scan, nothing computes it
+    "SET paradedb.enable_custom_scan TO off".execute(&mut conn);
+    assert_planning_error(
+        &mut conn,
+        r#"
+        SELECT id, paradedb.agg('{"value_count": {"field": "rating"}}') OVER ()
+        FROM paradedb.bm25_search
+        WHERE description @@@ 'keyboard'
+        "#,
+    );
+}
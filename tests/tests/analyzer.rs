@@ -0,0 +1,170 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for named analyzers created with paradedb.create_analyzer
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+#[rstest]
+fn named_analyzer_in_field_configs(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+    SELECT paradedb.create_analyzer(
+        'english_folded',
+        paradedb.tokenizer('default'),
+        ARRAY['{"stemmer": "English"}', '{"ascii_folding": true}']::jsonb[]
+    );
+
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description, metadata)
+        WITH (
+            key_field='id',
+            text_fields='{"description": {"tokenizer": {"type": "analyzer", "name": "english_folded"}}}',
+            json_fields='{"metadata": {"tokenizer": {"type": "analyzer", "name": "english_folded"}}}'
+        );
+    "#
+    .execute(&mut conn);
+
+    // "keyboards" is stemmed to "keyboard"
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:keyboards'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 2);
+
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'metadata.color:whites'"
+        .fetch_one(&mut conn);
+    assert!(count.0 > 0);
+
+    // the analyzer is also usable on its own
+    let rows: Vec<(String, i32)> = "
+    SELECT * FROM paradedb.tokenize(paradedb.analyzer('english_folded'), 'Running Cafés')"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("run".into(), 0), ("cafe".into(), 1)]);
+}
+
+#[rstest]
+fn replace_and_drop_analyzer(mut conn: PgConnection) {
+    "SELECT paradedb.create_analyzer('simple', paradedb.tokenizer('whitespace'))"
+        .execute(&mut conn);
+    let rows: Vec<(String, i32)> =
+        "SELECT * FROM paradedb.tokenize(paradedb.analyzer('simple'), 'Hello World')"
+            .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("hello".into(), 0), ("world".into(), 1)]);
+
+    "SELECT paradedb.create_analyzer('simple', paradedb.tokenizer('whitespace', lowercase => false))"
+        .execute(&mut conn);
+    let rows: Vec<(String, i32)> =
+        "SELECT * FROM paradedb.tokenize(paradedb.analyzer('simple'), 'Hello World')"
+            .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("Hello".into(), 0), ("World".into(), 1)]);
+
+    "SELECT paradedb.drop_analyzer('simple')".execute(&mut conn);
+    let result = "SELECT * FROM paradedb.tokenize(paradedb.analyzer('simple'), 'Hello World')"
+        .execute_result(&mut conn);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "error returned from database: analyzer 'simple' does not exist"
+    );
+
+    let result = "SELECT paradedb.drop_analyzer('simple')".execute_result(&mut conn);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "error returned from database: analyzer 'simple' does not exist"
+    );
+}
+
+#[rstest]
+fn analyzer_used_by_an_index(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+    SELECT paradedb.create_analyzer('simple', paradedb.tokenizer('whitespace'));
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "analyzer", "name": "simple"}}}');
+    "#
+    .execute(&mut conn);
+
+    // the index keeps the definition it was built with when the analyzer is replaced or dropped
+    "SELECT paradedb.create_analyzer('simple', paradedb.tokenizer('whitespace', lowercase => false))"
+        .execute(&mut conn);
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:keyboard'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 2);
+
+    // until it's reindexed, and "Plastic Keyboard" is no longer lowercased
+    "REINDEX INDEX paradedb.bm25_search_idx".execute(&mut conn);
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:keyboard'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 1);
+
+    "SELECT paradedb.drop_analyzer('simple')".execute(&mut conn);
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:keyboard'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 1);
+
+    let result = "REINDEX INDEX paradedb.bm25_search_idx".execute_result(&mut conn);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "error returned from database: analyzer 'simple' does not exist"
+    );
+}
+
+#[rstest]
+fn invalid_analyzers(mut conn: PgConnection) {
+    // unknown tokenizer
+    let result =
+        r#"SELECT paradedb.create_analyzer('bad', '{"type": "nope"}')"#.execute_result(&mut conn);
+    assert!(result.is_err());
+
+    // filters must be objects
+    let result =
+        r#"SELECT paradedb.create_analyzer('bad', paradedb.tokenizer('default'), ARRAY['"stemmer"']::jsonb[])"#
+            .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    // analyzers can't refer to other analyzers
+    let result = "SELECT paradedb.create_analyzer('bad', paradedb.analyzer('other'))"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    // indexes can't use analyzers that don't exist
+    let result = r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "analyzer", "name": "missing"}}}');
+    "#
+    .execute_result(&mut conn);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "error returned from database: analyzer 'missing' does not exist"
+    );
+}
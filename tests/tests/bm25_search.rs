@@ -427,6 +427,114 @@ fn snippet_text_array(mut conn: PgConnection) {
     );
 }
 
+#[rstest]
+fn snippets(mut conn: PgConnection) {
+    r#"
+    CREATE TABLE reviews (
+        id SERIAL PRIMARY KEY,
+        body TEXT
+    );
+    INSERT INTO reviews (body) VALUES
+    ('Red shoes are nice. Blue socks are warm. Red socks and shoes are best.'),
+    ('Nothing to see here');
+    CREATE INDEX reviews_idx ON reviews USING bm25 (id, body) WITH (key_field='id');
+    "#
+    .execute(&mut conn);
+
+    let (fragments,): (serde_json::Value,) = "
+        SELECT paradedb.snippets(body, max_fragments => 2, fragment_size => 20)
+        FROM reviews WHERE body @@@ 'shoes OR socks'"
+        .fetch_one(&mut conn);
+    assert_eq!(
+        fragments,
+        serde_json::json!([
+            {
+                "text": "Red <b>socks</b> and <b>shoes</b>",
+                "start": 41,
+                "end": 60,
+                "score": 1.0,
+                "positions": [[45, 50], [55, 60]]
+            },
+            {
+                "text": "Red <b>shoes</b> are nice",
+                "start": 0,
+                "end": 18,
+                "score": 0.5,
+                "positions": [[4, 9]]
+            }
+        ])
+    );
+
+    let (texts,): (serde_json::Value,) = "
+        SELECT jsonb_path_query_array(
+            paradedb.snippets(body, 2, 20, order_by => 'position', start_tag => '<i>', end_tag => '</i>'),
+            '$[*].text'
+        )
+        FROM reviews WHERE body @@@ 'shoes OR socks'"
+        .fetch_one(&mut conn);
+    assert_eq!(
+        texts,
+        serde_json::json!([
+            "Red <i>shoes</i> are nice",
+            "Red <i>socks</i> and <i>shoes</i>"
+        ])
+    );
+
+    let error = "
+        SELECT paradedb.snippets(body, order_by => 'length')
+        FROM reviews WHERE body @@@ 'shoes'"
+        .execute_result(&mut conn)
+        .unwrap_err();
+    assert!(error.to_string().contains("must be 'score' or 'position'"));
+}
+
+#[rstest]
+fn snippets_json_and_arrays(mut conn: PgConnection) {
+    r#"
+    CREATE TABLE products (
+        id SERIAL PRIMARY KEY,
+        metadata JSONB,
+        tags TEXT[]
+    );
+    INSERT INTO products (metadata, tags) VALUES
+    ('{"title": "red shoes", "description": "shoes for running", "colors": ["red", "blue shoes"]}', '{"running", "trail shoes"}');
+    CREATE INDEX products_idx ON products USING bm25 (id, metadata, tags) WITH (key_field='id');
+    "#
+    .execute(&mut conn);
+
+    // each JSON path is highlighted on its own, with only the query terms for that path
+    let (title, description): (serde_json::Value, serde_json::Value) = "
+        SELECT jsonb_path_query_array(paradedb.snippets(metadata->>'title'), '$[*].text'),
+               jsonb_path_query_array(paradedb.snippets(metadata->>'description'), '$[*].text')
+        FROM products
+        WHERE id @@@ paradedb.parse('metadata.title:shoes OR metadata.description:running')"
+        .fetch_one(&mut conn);
+    assert_eq!(title, serde_json::json!(["red <b>shoes</b>"]));
+    assert_eq!(description, serde_json::json!(["shoes for <b>running</b>"]));
+
+    // the values of arrays are highlighted individually
+    let (fragments,): (serde_json::Value,) = "
+        SELECT paradedb.snippets(tags) FROM products WHERE tags @@@ 'shoes'"
+        .fetch_one(&mut conn);
+    assert_eq!(
+        fragments,
+        serde_json::json!([{
+            "element": 1,
+            "text": "trail <b>shoes</b>",
+            "start": 0,
+            "end": 11,
+            "score": 0.5,
+            "positions": [[6, 11]]
+        }])
+    );
+
+    let (fragments,): (serde_json::Value,) = "
+        SELECT jsonb_path_query_array(paradedb.snippets(metadata->'colors'), '$[*] ? (@.element == 1).text')
+        FROM products WHERE id @@@ paradedb.parse('metadata.colors:shoes')"
+        .fetch_one(&mut conn);
+    assert_eq!(fragments, serde_json::json!(["blue <b>shoes</b>"]));
+}
+
 #[rstest]
 fn hybrid_with_single_result(mut conn: PgConnection) {
     r#"
@@ -0,0 +1,123 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for paradedb.complete
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CREATE TABLE products (
+        id SERIAL PRIMARY KEY,
+        name TEXT,
+        brand TEXT,
+        popularity INTEGER
+    );
+    INSERT INTO products (name, brand, popularity) VALUES
+        ('Ergonomic keyboard', 'Keychron', 10),
+        ('Leather keychain', 'Kelty', 50),
+        ('Electric kettle', 'Keurig', 5),
+        ('Keyboard stand', 'Keychron', 1);
+    CREATE INDEX products_idx ON products
+    USING bm25 (id, name, brand, popularity)
+    WITH (
+        key_field = 'id',
+        text_fields = '{"brand": {"tokenizer": {"type": "keyword"}}}'
+    );
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn complete(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // ranked by the number of rows that contain each term
+    let rows: Vec<(String, f64)> =
+        "SELECT * FROM paradedb.complete('products_idx', 'name', 'Ke')".fetch_collect(&mut conn);
+    assert_eq!(
+        rows,
+        vec![
+            ("keyboard".into(), 2.0),
+            ("kettle".into(), 1.0),
+            ("keychain".into(), 1.0)
+        ]
+    );
+
+    // only the last token is completed
+    let rows: Vec<(String,)> =
+        "SELECT completion FROM paradedb.complete('products_idx', 'name', 'Leather ke', 1)"
+            .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("Leather keyboard".into(),)]);
+
+    // keyword fields complete whole values
+    let rows: Vec<(String, f64)> =
+        "SELECT * FROM paradedb.complete('products_idx', 'brand', 'Key')".fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("Keychron".into(), 2.0), ("Keurig".into(), 1.0)]);
+
+    let rows: Vec<(String, f64)> =
+        "SELECT * FROM paradedb.complete('products_idx', 'name', 'xyz')".fetch_collect(&mut conn);
+    assert_eq!(rows, vec![]);
+}
+
+#[rstest]
+fn complete_with_weight(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(String, f64)> = "
+    SELECT * FROM paradedb.complete('products_idx', 'name', 'ke', weight_field => 'popularity')"
+        .fetch_collect(&mut conn);
+    assert_eq!(
+        rows,
+        vec![
+            ("keychain".into(), 50.0),
+            ("keyboard".into(), 10.0),
+            ("kettle".into(), 5.0)
+        ]
+    );
+
+    let result =
+        "SELECT * FROM paradedb.complete('products_idx', 'name', 'ke', weight_field => 'brand')"
+            .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn complete_respects_visibility(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    "DELETE FROM products WHERE name = 'Leather keychain'".execute(&mut conn);
+    "UPDATE products SET name = 'Keyboard tray' WHERE name = 'Electric kettle'".execute(&mut conn);
+
+    let rows: Vec<(String, f64)> =
+        "SELECT * FROM paradedb.complete('products_idx', 'name', 'ke')".fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("keyboard".into(), 3.0)]);
+}
+
+#[rstest]
+fn invalid_complete(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let result = "SELECT * FROM paradedb.complete('products_idx', 'popularity', '1')"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
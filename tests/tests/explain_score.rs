@@ -0,0 +1,108 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for paradedb.explain_score
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use serde_json::Value;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'mock_items', schema_name => 'public');
+    CREATE INDEX search_idx ON mock_items
+    USING bm25 (id, description, category, rating)
+    WITH (key_field='id');
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn explain_score(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let (score,): (f32,) = "
+    SELECT paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.match('description', 'running shoes') AND id = 3"
+        .fetch_one(&mut conn);
+    let (explanation,): (Value,) = "
+    SELECT paradedb.explain_score('search_idx', paradedb.match('description', 'running shoes'), 3)"
+        .fetch_one(&mut conn);
+
+    let value = explanation["value"].as_f64().unwrap() as f32;
+    assert!((value - score).abs() < 1e-4, "{value} != {score}");
+
+    // each term's contribution is explained by its frequency and idf
+    let details = explanation["details"].as_array().unwrap();
+    assert_eq!(details.len(), 2);
+    let text = explanation.to_string();
+    assert!(text.contains("idf"), "{text}");
+    assert!(text.contains("freq"), "{text}");
+}
+
+#[rstest]
+fn explain_boosted_score(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let (explanation,): (Value,) = "
+    SELECT paradedb.explain_score(
+        'search_idx',
+        paradedb.boolean(
+            should => ARRAY[
+                paradedb.boost(2, paradedb.term('description', 'shoes')),
+                paradedb.term('category', 'footwear')
+            ]
+        ),
+        4
+    )"
+    .fetch_one(&mut conn);
+
+    let (score,): (f32,) = "
+    SELECT paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.boolean(
+        should => ARRAY[
+            paradedb.boost(2, paradedb.term('description', 'shoes')),
+            paradedb.term('category', 'footwear')
+        ]
+    ) AND id = 4"
+        .fetch_one(&mut conn);
+    let value = explanation["value"].as_f64().unwrap() as f32;
+    assert!((value - score).abs() < 1e-4, "{value} != {score}");
+    assert!(explanation.to_string().contains("Boost"), "{explanation}");
+}
+
+#[rstest]
+fn explain_score_without_match(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // the row doesn't match the query
+    let (explanation,): (Option<Value>,) = "
+    SELECT paradedb.explain_score('search_idx', paradedb.match('description', 'shoes'), 1)"
+        .fetch_one(&mut conn);
+    assert!(explanation.is_none());
+
+    // a deleted row can't be explained
+    "DELETE FROM mock_items WHERE id = 3".execute(&mut conn);
+    let result =
+        "SELECT paradedb.explain_score('search_idx', paradedb.match('description', 'shoes'), 3)"
+            .execute_result(&mut conn);
+    assert!(result.is_err());
+}
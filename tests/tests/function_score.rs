@@ -0,0 +1,154 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for paradedb.function_score and its decay and field value functions
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'mock_items', schema_name => 'public');
+    CREATE INDEX search_idx ON mock_items
+    USING bm25 (id, description, rating, created_at)
+    WITH (
+        key_field='id',
+        numeric_fields='{"rating": {"fast": true}}',
+        datetime_fields='{"created_at": {"fast": true}}'
+    );
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn field_value_factor(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // the shoes are rated 5, 3, and 4
+    let rows: Vec<(i32, f32)> = "
+    SELECT id, paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.function_score(
+        paradedb.match('description', 'shoes'),
+        ARRAY[paradedb.field_value_factor('rating')],
+        boost_mode => 'replace'
+    )
+    ORDER BY paradedb.score(id) DESC"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(3, 5.0), (5, 4.0), (4, 3.0)]);
+
+    // by default, the query's score is multiplied by the functions
+    let plain: Vec<(i32, f32)> = "
+    SELECT id, paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.match('description', 'shoes')
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    let multiplied: Vec<(i32, f32)> = "
+    SELECT id, paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.function_score(
+        paradedb.match('description', 'shoes'),
+        ARRAY[paradedb.field_value_factor('rating', factor => 2, modifier => 'log1p')]
+    )
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    let ratings = [5.0f32, 3.0, 4.0];
+    for (((id, score), (multiplied_id, multiplied)), rating) in
+        plain.iter().zip(&multiplied).zip(ratings)
+    {
+        assert_eq!(id, multiplied_id);
+        let expected = score * (2.0 * rating + 1.0).log10();
+        assert!(
+            (multiplied - expected).abs() < 1e-4,
+            "{multiplied} != {expected}"
+        );
+    }
+}
+
+#[rstest]
+fn decay(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // created on 2023-05-02, 2023-04-28, and 2023-04-20
+    let ids: Vec<(i32,)> = "
+    SELECT id FROM mock_items
+    WHERE id @@@ paradedb.function_score(
+        paradedb.match('description', 'shoes'),
+        ARRAY[paradedb.decay('created_at', 'gauss', origin => '2023-05-03', scale => '7d')],
+        boost_mode => 'replace'
+    )
+    ORDER BY paradedb.score(id) DESC"
+        .fetch_collect(&mut conn);
+    assert_eq!(ids, vec![(5,), (3,), (4,)]);
+
+    // within the offset, nothing decays
+    let scores: Vec<(f32,)> = "
+    SELECT paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.function_score(
+        paradedb.match('description', 'shoes'),
+        ARRAY[paradedb.decay('created_at', 'linear', origin => '2023-05-03', scale => '1d', origin_offset => '30d')],
+        boost_mode => 'replace'
+    )"
+    .fetch_collect(&mut conn);
+    assert_eq!(scores, vec![(1.0,), (1.0,), (1.0,)]);
+
+    // the decay is 0.5 at the scale, and the scores of the functions are summed
+    let scores: Vec<(i32, f32)> = r#"
+    SELECT id, paradedb.score(id) FROM mock_items
+    WHERE id @@@ '{"function_score": {
+        "query": {"term": {"field": "description", "value": "shoes"}},
+        "functions": [
+            {"decay": {"field": "rating", "function": "exp", "origin": 5, "scale": 1}},
+            {"field_value_factor": {"field": "rating", "weight": 0}}
+        ],
+        "score_mode": "sum",
+        "boost_mode": "replace"
+    }}'::jsonb
+    ORDER BY id"#
+        .fetch_collect(&mut conn);
+    assert_eq!(scores, vec![(3, 1.0), (4, 0.25), (5, 0.5)]);
+}
+
+#[rstest]
+fn invalid_function_score(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // functions need fast fields
+    let result = "
+    SELECT id FROM mock_items
+    WHERE id @@@ paradedb.function_score(
+        paradedb.match('description', 'shoes'),
+        ARRAY[paradedb.field_value_factor('description')]
+    )"
+    .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result = "SELECT paradedb.decay('created_at', 'cubic', origin => 'now', scale => '1d')"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result = "
+    SELECT id FROM mock_items
+    WHERE id @@@ paradedb.function_score(
+        paradedb.match('description', 'shoes'),
+        ARRAY[paradedb.decay('created_at', 'gauss', origin => 'now', scale => 'soon')]
+    )"
+    .execute_result(&mut conn);
+    assert!(result.is_err());
+}
@@ -0,0 +1,183 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for point fields and the geo distance, bounding box and polygon queries
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CREATE TABLE places (
+        id SERIAL PRIMARY KEY,
+        name TEXT,
+        location POINT,
+        metadata JSONB
+    );
+    INSERT INTO places (name, location, metadata) VALUES
+        ('Empire State Building', point(-73.9857, 40.7484), '{"location": {"lat": 40.7484, "lon": -73.9857}}'),
+        ('Statue of Liberty', point(-74.0445, 40.6892), '{"location": {"lat": 40.6892, "lon": -74.0445}}'),
+        ('Tower Bridge', point(-0.0754, 51.5055), '{"location": {"lat": 51.5055, "lon": -0.0754}}'),
+        ('Sydney Opera House', point(151.2153, -33.8568), '{"location": {"lat": -33.8568, "lon": 151.2153}}'),
+        ('Nowhere', NULL, '{}');
+    CREATE INDEX search_idx ON places
+    USING bm25 (id, name, location, metadata)
+    WITH (key_field = 'id', json_fields = '{"metadata": {"fast": true}}');
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn geo_distance(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // the Empire State Building is ~4.3km from City Hall, and the Statue of Liberty ~4.2km
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_distance('location', point(-74.0060, 40.7128), '10km')
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(1,), (2,)]);
+
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_distance('location', point(-74.0060, 40.7128), '4200')
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(2,)]);
+
+    let rows: Vec<(i32,)> = r#"
+    SELECT id FROM places
+    WHERE id @@@ '{"geo_distance": {
+        "field": "metadata.location",
+        "origin": {"lat": 40.7128, "lon": -74.0060},
+        "distance": "3500mi"
+    }}'::jsonb
+    ORDER BY id"#
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(1,), (2,), (3,)]);
+}
+
+#[rstest]
+fn geo_bounding_box(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_bounding_box('location', point(-74.0, 41.0), point(-73.0, 40.0))
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(1,)]);
+
+    // a box from east of Sydney to west of London crosses the antimeridian
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_bounding_box('metadata.location', point(150.0, 60.0), point(-1.0, -60.0))
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(1,), (2,), (4,)]);
+}
+
+#[rstest]
+fn geo_polygon(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // a triangle around lower Manhattan and the harbor
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_polygon(
+        'location',
+        ARRAY[point(-74.1, 40.6), point(-73.95, 40.6), point(-74.1, 40.8)]
+    )
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(2,)]);
+
+    // the polygon is combined with other queries
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.boolean(
+        must => ARRAY[
+            paradedb.geo_polygon(
+                'metadata.location',
+                ARRAY[point(-75, 41), point(-73, 41), point(-73, 40), point(-75, 40)]
+            ),
+            paradedb.match('name', 'building')
+        ]
+    )
+    ORDER BY id"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(1,)]);
+}
+
+#[rstest]
+fn geo_distance_score(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.function_score(
+        paradedb.all(),
+        ARRAY[paradedb.geo_distance_score('location', point(-74.0060, 40.7128))],
+        boost_mode => 'replace'
+    )
+    ORDER BY paradedb.score(id) ASC
+    LIMIT 3"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![(2,), (1,), (3,)]);
+
+    let (meters,): (f32,) = "
+    SELECT paradedb.score(id) FROM places
+    WHERE id @@@ paradedb.function_score(
+        paradedb.term('id', 3),
+        ARRAY[paradedb.geo_distance_score('metadata.location', point(-0.1276, 51.5072))],
+        boost_mode => 'replace'
+    )"
+    .fetch_one(&mut conn);
+    assert!((meters - 3_650.0).abs() < 100.0, "{meters}");
+}
+
+#[rstest]
+fn invalid_geo_query(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let result = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_distance('name', point(0, 0), '1km')"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_distance('location', point(0, 0), '1 parsec')"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result = "
+    SELECT id FROM places
+    WHERE id @@@ paradedb.geo_polygon('location', ARRAY[point(0, 0), point(1, 1)])"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result = "INSERT INTO places (name, location) VALUES ('Nowhere', point(0, 100))"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
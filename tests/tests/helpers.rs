@@ -80,6 +80,118 @@ fn tokenizer_filters(mut conn: PgConnection) {
     );
 }
 
+#[rstest]
+fn tokenizer_normalization_filters(mut conn: PgConnection) {
+    let rows: Vec<(String, i32)> = r#"
+    SELECT * FROM paradedb.tokenize(
+      paradedb.tokenizer('default', ascii_folding => true),
+      'Café Crème'
+    );
+    "#
+    .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("cafe".into(), 0), ("creme".into(), 1)]);
+
+    // "ﬁ" is a single ligature character, which compatibility normalization splits up
+    let rows: Vec<(String, i32)> = r#"
+    SELECT * FROM paradedb.tokenize(
+      paradedb.tokenizer('whitespace', unicode_normalization => 'nfkc'),
+      'ﬁle'
+    );
+    "#
+    .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("file".into(), 0)]);
+
+    let res = r#"
+    SELECT * FROM paradedb.tokenize(
+      paradedb.tokenizer('default', unicode_normalization => 'nfx'),
+      'ﬁle'
+    );
+    "#
+    .execute_result(&mut conn);
+    assert!(res.is_err());
+}
+
+#[rstest]
+#[case::phonetic(
+    "paradedb.tokenizer('default', phonetic => 'soundex')",
+    "Smyth Smith",
+    &[("S530", 0), ("S530", 1)]
+)]
+#[case::phonetic_inject(
+    "paradedb.tokenizer('default', phonetic => 'metaphone', phonetic_mode => 'inject')",
+    "Katherine",
+    &[("katherine", 0), ("K0RN", 0)]
+)]
+#[case::shingle(
+    "paradedb.tokenizer('default', shingle => '{}')",
+    "quick brown fox",
+    &[("quick", 0), ("quick brown", 0), ("brown", 1), ("brown fox", 1), ("fox", 2)]
+)]
+#[case::edge_ngram(
+    r#"paradedb.tokenizer('default', edge_ngram => '{"min_gram": 2, "max_gram": 4}')"#,
+    "Running Shoes",
+    &[("ru", 0), ("run", 0), ("runn", 0), ("sh", 1), ("sho", 1), ("shoe", 1)]
+)]
+#[case::char_filters(
+    r#"paradedb.tokenizer('default', char_filters => '[
+        {"type": "html_strip"},
+        {"type": "mapping", "mappings": [["&", " and "]]},
+        {"type": "pattern_replace", "pattern": "(\\d)-(\\d)", "replacement": "$1$2"}
+    ]')"#,
+    "<h1>Salt &amp; Pepper</h1><script>alert(1)</script><p>Serves 2-4</p>",
+    &[("salt", 0), ("and", 1), ("pepper", 2), ("serves", 3), ("24", 4)]
+)]
+#[case::word_delimiter(
+    r#"paradedb.tokenizer('whitespace', word_delimiter => '{"catenate_words": true}')"#,
+    "WiFi-6E router",
+    &[("wifi", 0), ("wi", 0), ("fi", 1), ("6", 2), ("e", 3), ("router", 4)]
+)]
+#[case::decompounder(
+    r#"paradedb.tokenizer('default', decompounder => '{"word_list": ["kühl", "schrank", "griff"]}')"#,
+    "Kühlschrankgriff aus Edelstahl",
+    &[
+        ("kühlschrankgriff", 0),
+        ("kühl", 0),
+        ("schrank", 0),
+        ("griff", 0),
+        ("aus", 1),
+        ("edelstahl", 2)
+    ]
+)]
+fn tokenizer_token_filters(
+    mut conn: PgConnection,
+    #[case] tokenizer: &str,
+    #[case] text: &str,
+    #[case] expected: &[(&str, i32)],
+) {
+    let rows: Vec<(String, i32)> =
+        format!("SELECT * FROM paradedb.tokenize({tokenizer}, '{text}')").fetch_collect(&mut conn);
+    assert_eq!(
+        rows,
+        expected
+            .iter()
+            .map(|(token, position)| (token.to_string(), *position))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[rstest]
+#[case::phonetic_mode_without_phonetic("paradedb.tokenizer('default', phonetic_mode => 'inject')")]
+#[case::edge_ngram_min_over_max(
+    r#"paradedb.tokenizer('default', edge_ngram => '{"min_gram": 4, "max_gram": 2}')"#
+)]
+#[case::invalid_pattern(
+    r#"paradedb.tokenizer('default', char_filters => '[{"type": "pattern_replace", "pattern": "("}]')"#
+)]
+#[case::decompounder_without_words(
+    r#"paradedb.tokenizer('default', decompounder => '{"min_word_size": 4}')"#
+)]
+fn tokenizer_invalid_token_filters(mut conn: PgConnection, #[case] tokenizer: &str) {
+    let res = format!("SELECT * FROM paradedb.tokenize({tokenizer}, 'Katherine')")
+        .execute_result(&mut conn);
+    assert!(res.is_err());
+}
+
 #[rstest]
 fn list_tokenizers(mut conn: PgConnection) {
     let rows: Vec<(String,)> = r#"
@@ -106,7 +218,8 @@ fn list_tokenizers(mut conn: PgConnection) {
                 ("japanese_lindera".into(),),
                 ("korean_lindera".into(),),
                 ("icu".into(),),
-                ("jieba".into(),)
+                ("jieba".into(),),
+                ("analyzer".into(),)
             ]
         );
     } else {
@@ -127,7 +240,8 @@ fn list_tokenizers(mut conn: PgConnection) {
                 ("chinese_lindera".into(),),
                 ("japanese_lindera".into(),),
                 ("korean_lindera".into(),),
-                ("jieba".into(),)
+                ("jieba".into(),),
+                ("analyzer".into(),)
             ]
         );
     }
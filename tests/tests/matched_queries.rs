@@ -0,0 +1,113 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for paradedb.named and paradedb.matched_queries
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'mock_items', schema_name => 'public');
+    CREATE INDEX search_idx ON mock_items
+    USING bm25 (id, description, category, rating)
+    WITH (key_field='id');
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn matched_queries_boolean(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(i32, Vec<String>)> = "
+    SELECT id, paradedb.matched_queries(id) FROM mock_items
+    WHERE id @@@ paradedb.boolean(
+        should => ARRAY[
+            paradedb.named('description', paradedb.match('description', 'shoes')),
+            paradedb.named('category', paradedb.match('category', 'footwear'))
+        ]
+    )
+    ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(
+        rows,
+        vec![
+            (3, vec!["description".into(), "category".into()]),
+            (4, vec!["description".into(), "category".into()]),
+            (5, vec!["description".into(), "category".into()]),
+            (13, vec!["category".into()]),
+        ]
+    );
+}
+
+#[rstest]
+fn matched_queries_disjunction_max_json(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(i32, Vec<String>)> = r#"
+    SELECT id, paradedb.matched_queries(id) FROM mock_items
+    WHERE id @@@ '{
+        "disjunction_max": {
+            "disjuncts": [
+                {"named": {"name": "keyboard", "query": {"term": {"field": "description", "value": "keyboard"}}}},
+                {"named": {"name": "plastic", "query": {"term": {"field": "description", "value": "plastic"}}}}
+            ]
+        }
+    }'::jsonb
+    ORDER BY id"#
+        .fetch(&mut conn);
+    assert_eq!(
+        rows,
+        vec![
+            (1, vec!["keyboard".into()]),
+            (2, vec!["keyboard".into(), "plastic".into()]),
+        ]
+    );
+}
+
+#[rstest]
+fn matched_queries_with_scores_and_limit(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(i32, Vec<String>)> = "
+    SELECT id, paradedb.matched_queries(id) FROM mock_items
+    WHERE id @@@ paradedb.boolean(
+        should => ARRAY[
+            paradedb.named('running', paradedb.term('description', 'running')),
+            paradedb.term('description', 'shoes'),
+            paradedb.named('boots', paradedb.term('description', 'boots'))
+        ]
+    )
+    ORDER BY paradedb.score(id) DESC, id
+    LIMIT 3"
+        .fetch(&mut conn);
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0], (3, vec!["running".into()]));
+
+    // rows matched only by the unnamed clause have no names
+    for (id, names) in &rows[1..] {
+        match id {
+            13 => assert_eq!(names, &vec!["boots".to_string()]),
+            _ => assert_eq!(names, &Vec::<String>::new()),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//! Tests for nested JSON fields and paradedb.nested
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CREATE TABLE products (id SERIAL PRIMARY KEY, name TEXT, variants JSONB);
+    INSERT INTO products (name, variants) VALUES
+        ('shirt', '[{"color": "red", "size": "large"}, {"color": "blue", "size": "small"}]'),
+        ('hoodie', '[{"color": "red", "size": "small"}, {"color": "red", "size": "medium"}]'),
+        ('jacket', '[{"color": "green", "size": "small"}]'),
+        ('scarf', NULL);
+    CREATE INDEX products_idx ON products
+    USING bm25 (id, name, variants)
+    WITH (key_field='id', json_fields='{"variants": {"nested": true}}');
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn nested_matches_within_one_element(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // flattened, the shirt's elements together have both a red color and a small size
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products
+    WHERE id @@@ paradedb.boolean(must => ARRAY[
+        paradedb.term('variants.color', 'red'),
+        paradedb.term('variants.size', 'small')
+    ])
+    ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (2,)]);
+
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products
+    WHERE id @@@ paradedb.nested('variants', paradedb.boolean(must => ARRAY[
+        paradedb.term('variants.color', 'red'),
+        paradedb.term('variants.size', 'small')
+    ]))
+    ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(2,)]);
+
+    let rows: Vec<(i32,)> = r#"
+    SELECT id FROM products
+    WHERE id @@@ '{
+        "nested": {
+            "path": "variants",
+            "query": {"boolean": {"must": [
+                {"term": {"field": "variants.color", "value": "green"}},
+                {"term": {"field": "variants.size", "value": "small"}}
+            ]}}
+        }
+    }'::jsonb
+    ORDER BY id"#
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(3,)]);
+}
+
+#[rstest]
+fn nested_must_not(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // flattened, neither would match, as both have a small variant besides their red ones
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products
+    WHERE id @@@ paradedb.nested('variants', paradedb.boolean(
+        must => ARRAY[paradedb.term('variants.color', 'red')],
+        must_not => ARRAY[paradedb.term('variants.size', 'small')]
+    ))
+    ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (2,)]);
+}
+
+#[rstest]
+fn nested_must_not_only(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // only rows with an element that isn't red, rather than every row
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products
+    WHERE id @@@ paradedb.nested('variants', paradedb.boolean(
+        must_not => ARRAY[paradedb.term('variants.color', 'red')]
+    ))
+    ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (3,)]);
+}
+
+#[rstest]
+fn nested_elements_are_not_rows(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let (count,): (i64,) =
+        "SELECT COUNT(*) FROM products WHERE id @@@ paradedb.all()".fetch_one(&mut conn);
+    assert_eq!(count, 4);
+
+    let (count,): (i64,) =
+        "SELECT COUNT(*) FROM products WHERE id @@@ 'variants.color:red'".fetch_one(&mut conn);
+    assert_eq!(count, 2);
+
+    // a deleted row's elements are vacuumed along with it
+    "DELETE FROM products WHERE id = 1".execute(&mut conn);
+    "VACUUM products".execute(&mut conn);
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products
+    WHERE id @@@ paradedb.nested('variants', paradedb.term('variants.color', 'red'))
+    ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(2,)]);
+
+    let (count,): (i64,) =
+        "SELECT COUNT(*) FROM products WHERE id @@@ paradedb.all()".fetch_one(&mut conn);
+    assert_eq!(count, 3);
+}
+
+#[rstest]
+fn nested_score_modes(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let score = |conn: &mut PgConnection, score_mode: &str| -> f32 {
+        let (score,): (f32,) = format!(
+            "
+            SELECT paradedb.score(id) FROM products
+            WHERE id @@@ paradedb.nested(
+                'variants',
+                paradedb.term('variants.color', 'red'),
+                score_mode => '{score_mode}'
+            )
+            AND id = 2"
+        )
+        .fetch_one(conn);
+        score
+    };
+
+    // both of the hoodie's elements are red, and score the same
+    let avg = score(&mut conn, "avg");
+    assert!(avg > 0.0);
+    assert_eq!(score(&mut conn, "max"), avg);
+    assert_eq!(score(&mut conn, "min"), avg);
+    assert!((score(&mut conn, "sum") - 2.0 * avg).abs() < 1e-5);
+    assert_eq!(score(&mut conn, "none"), 0.0);
+}
+
+#[rstest]
+fn nested_requires_nested_field(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let result = "
+    SELECT id FROM products
+    WHERE id @@@ paradedb.nested('name', paradedb.term('name', 'shirt'))"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result = "SELECT paradedb.nested('variants', paradedb.all(), score_mode => 'median')"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn nested_elements_do_not_change_row_scores(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    r#"
+    CREATE TABLE products_flat AS SELECT * FROM products;
+    CREATE INDEX products_flat_idx ON products_flat
+    USING bm25 (id, name, variants)
+    WITH (key_field='id', json_fields='{"variants": {}}');
+    "#
+    .execute(&mut conn);
+
+    let scores = |conn: &mut PgConnection, table: &str, query: &str| -> Vec<(i32, f32)> {
+        format!(
+            "
+            SELECT id, paradedb.score(id) FROM {table}
+            WHERE id @@@ {query}
+            ORDER BY paradedb.score(id) DESC, id LIMIT 10"
+        )
+        .fetch(conn)
+    };
+
+    // the elements are documents of their own, but they aren't counted as rows when scoring,
+    // fusing or rescoring, so the rows score as if the field weren't nested
+    for query in [
+        "paradedb.term('name', 'shirt')",
+        "paradedb.term('variants.color', 'red')",
+        "paradedb.rrf(paradedb.term('variants.color', 'red'), ARRAY[3, 1], window_size => 2)",
+        "paradedb.rrf(paradedb.term('variants.size', 'small'), paradedb.term('variants.color', 'red'))",
+        "paradedb.rescore(paradedb.term('variants.size', 'small'), paradedb.term('variants.color', 'red'), window_size => 2)",
+    ] {
+        let nested = scores(&mut conn, "products", query);
+        let flat = scores(&mut conn, "products_flat", query);
+        assert!(!nested.is_empty(), "{query}");
+        assert_eq!(
+            nested.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            flat.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            "{query}"
+        );
+        for ((_, nested), (_, flat)) in nested.iter().zip(&flat) {
+            assert!((nested - flat).abs() < 1e-5, "{query}: {nested} != {flat}");
+        }
+    }
+}
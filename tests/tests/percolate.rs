@@ -0,0 +1,139 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for stored queries and paradedb.percolate
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CREATE TABLE alerts (
+        id SERIAL PRIMARY KEY,
+        description TEXT,
+        rating INTEGER,
+        query paradedb.searchqueryinput
+    );
+    CREATE INDEX alerts_idx ON alerts
+    USING bm25 (id, description, rating, query)
+    WITH (
+        key_field = 'id',
+        text_fields = '{"description": {"tokenizer": {"type": "default", "stemmer": "English"}}}'
+    );
+    INSERT INTO alerts (query) VALUES
+        (paradedb.match('description', 'keyboard')),
+        (paradedb.term('rating', 5)),
+        (paradedb.boolean(
+            must => ARRAY[paradedb.match('description', 'shoes')],
+            must_not => ARRAY[paradedb.term('rating', 5)]
+        )),
+        (NULL);
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn percolate(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // "keyboards" is stemmed the same way as the stored query's "keyboard"
+    let rows: Vec<(String,)> = r#"
+    SELECT key FROM paradedb.percolate('alerts_idx', '{"description": "Ergonomic metal keyboards", "rating": 4}')
+    ORDER BY key"#
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("1".into(),)]);
+
+    let rows: Vec<(String,)> = r#"
+    SELECT key FROM paradedb.percolate('alerts_idx', '{"description": "Sleek running shoes", "rating": 5}')
+    ORDER BY key"#
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("2".into(),)]);
+
+    let rows: Vec<(String,)> = r#"
+    SELECT key FROM paradedb.percolate('alerts_idx', '{"description": "White jogging shoes", "rating": 3}')
+    ORDER BY key"#
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("3".into(),)]);
+
+    // fields that aren't in the document, or are null, match nothing
+    let rows: Vec<(String,)> = r#"
+    SELECT key FROM paradedb.percolate('alerts_idx', '{"description": null, "color": "white"}')"#
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![]);
+}
+
+#[rstest]
+fn percolate_sees_changes(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    "DELETE FROM alerts WHERE id = 1".execute(&mut conn);
+    "INSERT INTO alerts (query) VALUES (paradedb.match('description', 'metal'))".execute(&mut conn);
+
+    let rows: Vec<(String,)> = r#"
+    SELECT key FROM paradedb.percolate('alerts_idx', '{"description": "Ergonomic metal keyboard"}')
+    ORDER BY key"#
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("5".into(),)]);
+
+    // the stored query is returned with its key
+    let (key, query): (String, String) = r#"
+    SELECT key, query::text FROM paradedb.percolate('alerts_idx', '{"rating": 5}')"#
+        .fetch_one(&mut conn);
+    assert_eq!(key, "2");
+    assert!(query.contains("rating"), "{query}");
+}
+
+#[rstest]
+fn percolate_articles(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'mock_items', schema_name => 'public');
+    "#
+    .execute(&mut conn);
+
+    // match each new row against the stored queries
+    let rows: Vec<(i32, String)> = "
+    SELECT mock_items.id, alerts.key
+    FROM mock_items, paradedb.percolate('alerts_idx', to_jsonb(mock_items)) AS alerts
+    WHERE mock_items.id <= 5
+    ORDER BY mock_items.id, alerts.key"
+        .fetch_collect(&mut conn);
+    assert_eq!(
+        rows,
+        vec![
+            (1, "1".into()),
+            (2, "1".into()),
+            (3, "2".into()),
+            (4, "3".into()),
+            (5, "3".into())
+        ]
+    );
+}
+
+#[rstest]
+fn percolate_invalid_document(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let result =
+        "SELECT * FROM paradedb.percolate('alerts_idx', '[1, 2, 3]')".execute_result(&mut conn);
+    assert!(result.is_err());
+}
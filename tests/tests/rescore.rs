@@ -0,0 +1,176 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//! Tests for paradedb.rescore
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'mock_items', schema_name => 'public');
+    CREATE INDEX search_idx ON mock_items
+    USING bm25 (id, description, category, rating)
+    WITH (key_field='id');
+    "#
+    .execute(conn);
+}
+
+fn score_of(conn: &mut PgConnection, id: i32, query: &str) -> f32 {
+    let (score,): (f32,) =
+        format!("SELECT paradedb.score(id) FROM mock_items WHERE id @@@ {query} AND id = {id}")
+            .fetch_one(conn);
+    score
+}
+
+#[rstest]
+fn rescore_combines_scores(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(i32, f32)> = "
+    SELECT id, paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.rescore(
+        paradedb.match('description', 'shoes'),
+        paradedb.term('description', 'white'),
+        query_weight => 0.5,
+        rescore_weight => 2.0
+    )
+    ORDER BY paradedb.score(id) DESC LIMIT 3"
+        .fetch(&mut conn);
+    assert_eq!(rows.iter().map(|(id, _)| *id).collect::<Vec<_>>()[0], 4);
+
+    let first_phase = score_of(&mut conn, 4, "paradedb.match('description', 'shoes')");
+    let second_phase = score_of(&mut conn, 4, "paradedb.term('description', 'white')");
+    assert!((rows[0].1 - (0.5 * first_phase + 2.0 * second_phase)).abs() < 1e-4);
+
+    // rows that the rescore query doesn't match are only weighted
+    for (id, score) in &rows[1..] {
+        let first_phase = score_of(&mut conn, *id, "paradedb.match('description', 'shoes')");
+        assert!((score - 0.5 * first_phase).abs() < 1e-4);
+    }
+}
+
+#[rstest]
+fn rescore_window_size(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // "Generic shoes" is the best first-phase match, so it's the only one rescored
+    let rows: Vec<(i32, f32)> = "
+    SELECT id, paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.rescore(
+        paradedb.match('description', 'shoes'),
+        paradedb.term('description', 'white'),
+        window_size => 1,
+        rescore_weight => 10.0
+    )
+    ORDER BY paradedb.score(id) DESC LIMIT 3"
+        .fetch(&mut conn);
+    assert_eq!(rows[0].0, 5);
+
+    let (_, score) = rows.iter().find(|(id, _)| *id == 4).unwrap();
+    let first_phase = score_of(&mut conn, 4, "paradedb.match('description', 'shoes')");
+    assert!((score - first_phase).abs() < 1e-4);
+}
+
+#[rstest]
+fn rescore_keeps_matches(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let mut rows: Vec<(i32,)> = r#"
+    SELECT id FROM mock_items
+    WHERE id @@@ '{
+        "rescore": {
+            "query": {"match": {"field": "description", "value": "shoes"}},
+            "rescore_query": {"term": {"field": "description", "value": "white"}},
+            "window_size": 10
+        }
+    }'::jsonb
+    ORDER BY paradedb.score(id) DESC LIMIT 10"#
+        .fetch(&mut conn);
+    rows.sort();
+    assert_eq!(rows, vec![(3,), (4,), (5,)]);
+}
+
+#[rstest]
+fn rescore_requires_top_n_by_score(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    for order_by in [
+        "ORDER BY id LIMIT 3",
+        "ORDER BY paradedb.score(id) LIMIT 3",
+        "ORDER BY paradedb.score(id) DESC, id LIMIT 3",
+        "ORDER BY paradedb.score(id) DESC",
+        "",
+    ] {
+        let result = format!(
+            "SELECT id FROM mock_items
+            WHERE id @@@ paradedb.rescore(paradedb.match('description', 'shoes'), paradedb.term('description', 'white'))
+            {order_by}"
+        )
+        .execute_result(&mut conn);
+        assert!(result.is_err(), "{order_by}");
+    }
+
+    "SET paradedb.enable_custom_scan TO off".execute(&mut conn);
+    let result = "SELECT id FROM mock_items
+        WHERE id @@@ paradedb.rescore(paradedb.match('description', 'shoes'), paradedb.term('description', 'white'))
+        ORDER BY paradedb.score(id) DESC LIMIT 3"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn rescore_must_be_outermost(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // a boost around the rescore is fine
+    let rows: Vec<(i32,)> = r#"
+    SELECT id FROM mock_items
+    WHERE id @@@ paradedb.boost(2.0, paradedb.rescore(
+        paradedb.match('description', 'shoes'),
+        paradedb.term('description', 'white')
+    ))
+    ORDER BY paradedb.score(id) DESC LIMIT 3"#
+        .fetch(&mut conn);
+    assert_eq!(rows.len(), 3);
+
+    let result = r#"
+    SELECT id FROM mock_items
+    WHERE id @@@ paradedb.boolean(should => ARRAY[
+        paradedb.rescore(paradedb.match('description', 'shoes'), paradedb.term('description', 'white')),
+        paradedb.match('description', 'keyboard')
+    ])
+    ORDER BY paradedb.score(id) DESC LIMIT 3"#
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn rescore_invalid_window_size(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    for window_size in [0, -1] {
+        let result = format!(
+            "SELECT id FROM mock_items WHERE id @@@ paradedb.rescore(paradedb.match('description', 'shoes'), paradedb.term('description', 'white'), window_size => {window_size})"
+        )
+        .execute_result(&mut conn);
+        assert!(result.is_err(), "{window_size}");
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use serde_json::Value;
+use sqlx::PgConnection;
+
+#[rstest]
+fn rrf_with_ranked_keys(mut conn: PgConnection) {
+    SimpleProductsTable::setup().execute(&mut conn);
+
+    // 'keyboard' ranks id 2 ahead of id 1 by BM25, so id 2 is first in both rankings
+    let rows: Vec<(i32, f32)> = r#"
+    SELECT id, paradedb.score(id)
+    FROM paradedb.bm25_search
+    WHERE id @@@ paradedb.rrf(paradedb.match('description', 'keyboard'), ARRAY[2, 19, 9])
+    ORDER BY paradedb.score(id) DESC, id
+    LIMIT 5
+    "#
+    .fetch(&mut conn);
+
+    assert_eq!(
+        rows,
+        vec![
+            (2, 2.0 / 61.0),
+            (1, 1.0 / 62.0),
+            (19, 1.0 / 62.0),
+            (9, 1.0 / 63.0)
+        ]
+    );
+}
+
+#[rstest]
+fn rrf_with_ranked_query(mut conn: PgConnection) {
+    SimpleProductsTable::setup().execute(&mut conn);
+
+    let (fused,): (i64,) = r#"
+    SELECT COUNT(*)
+    FROM paradedb.bm25_search
+    WHERE id @@@ paradedb.rrf(
+        paradedb.match('description', 'keyboard'),
+        paradedb.term('category', 'electronics')
+    )
+    "#
+    .fetch_one(&mut conn);
+
+    let (union,): (i64,) = r#"
+    SELECT COUNT(*)
+    FROM paradedb.bm25_search
+    WHERE description @@@ 'keyboard' OR category @@@ 'electronics'
+    "#
+    .fetch_one(&mut conn);
+
+    assert_eq!(fused, union);
+
+    let top: Vec<(i32,)> = r#"
+    SELECT id FROM (
+        SELECT id
+        FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.rrf(
+            paradedb.match('description', 'keyboard'),
+            paradedb.term('category', 'electronics')
+        )
+        ORDER BY paradedb.score(id) DESC
+        LIMIT 2
+    ) top ORDER BY id
+    "#
+    .fetch(&mut conn);
+
+    // the two keyboards are electronics, so they're the only rows ranked by both queries
+    assert_eq!(top, vec![(1,), (2,)]);
+}
+
+#[rstest]
+fn rrf_invalid_arguments(mut conn: PgConnection) {
+    SimpleProductsTable::setup().execute(&mut conn);
+
+    for args in ["k => 0", "k => -1", "window_size => 0", "window_size => -1"] {
+        let result = format!(
+            "SELECT id FROM paradedb.bm25_search WHERE id @@@ paradedb.rrf(paradedb.match('description', 'keyboard'), ARRAY[2, 19, 9], {args})"
+        )
+        .execute_result(&mut conn);
+        assert!(result.is_err(), "{args}");
+
+        let result = format!(
+            "SELECT id FROM paradedb.bm25_search WHERE id @@@ paradedb.rrf(paradedb.match('description', 'keyboard'), paradedb.term('category', 'electronics'), {args})"
+        )
+        .execute_result(&mut conn);
+        assert!(result.is_err(), "{args}");
+    }
+
+    let result = r#"
+    SELECT id FROM paradedb.bm25_search
+    WHERE id @@@ '{"rrf": {"query": {"match": {"field": "description", "value": "keyboard"}}, "ranked_keys": [2, 19, 9], "window_size": 0}}'::jsonb
+    "#
+    .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn rrf_uses_top_n(mut conn: PgConnection) {
+    SimpleProductsTable::setup().execute(&mut conn);
+
+    let (plan,): (Value,) = r#"
+    EXPLAIN (FORMAT JSON)
+    SELECT id, paradedb.score(id)
+    FROM paradedb.bm25_search
+    WHERE id @@@ paradedb.rrf(paradedb.match('description', 'keyboard'), ARRAY[2, 19, 9])
+    ORDER BY paradedb.score(id) DESC
+    LIMIT 5
+    "#
+    .fetch_one(&mut conn);
+
+    let scan = plan.pointer("/0/Plan/Plans/0").unwrap();
+    assert_eq!(
+        scan.get("Custom Plan Provider"),
+        Some(&Value::String(String::from("ParadeDB Scan")))
+    );
+    assert_eq!(
+        scan.get("Exec Method"),
+        Some(&Value::String(String::from("TopNScanExecState")))
+    );
+}
+
+#[rstest]
+fn rrf_ranks_only_visible_rows(mut conn: PgConnection) {
+    SimpleProductsTable::setup().execute(&mut conn);
+
+    // 'keyboard' ranks id 2 first by BM25, but once it's deleted, id 1 takes its rank whether or
+    // not the dead tuple has been vacuumed yet
+    "DELETE FROM paradedb.bm25_search WHERE id = 2".execute(&mut conn);
+    let rows: Vec<(i32, f32)> = r#"
+    SELECT id, paradedb.score(id)
+    FROM paradedb.bm25_search
+    WHERE id @@@ paradedb.rrf(
+        paradedb.match('description', 'keyboard'),
+        ARRAY[1],
+        window_size => 1
+    )
+    ORDER BY paradedb.score(id) DESC
+    LIMIT 5
+    "#
+    .fetch(&mut conn);
+    assert_eq!(rows, vec![(1, 2.0 / 61.0)]);
+}
@@ -561,3 +561,313 @@ fn stopwords_tokenizer_config(mut conn: PgConnection) {
         .fetch_one(&mut conn);
     assert_eq!(count.0, 0);
 }
+
+#[rstest]
+fn synonyms_tokenizer_config(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "default", "synonyms": ["sneakers, shoes", "keyboard, input device", "kb => keyboard"]}}}');
+    "#
+    .execute(&mut conn);
+
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:sneakers'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 3);
+
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:kb'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 2);
+
+    // multi-word synonyms keep their positions, so phrases still match
+    let count: (i64,) = r#"
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:"metal input device"'"#
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 1);
+
+    let count: (i64,) = r#"
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:"running sneakers"'"#
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 1);
+}
+
+#[rstest]
+fn mixed_case_synonyms_tokenizer_config(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "default", "synonyms": ["Sneakers, Shoes", "KB => Keyboard"]}}}');
+    "#
+    .execute(&mut conn);
+
+    // the rules are lowercased like the tokens they're compared with
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:sneakers'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 3);
+
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:KB'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 2);
+}
+
+#[rstest]
+fn invalid_synonyms_tokenizer_config(mut conn: PgConnection) {
+    "CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb')"
+        .execute(&mut conn);
+
+    let result = r#"
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "default", "synonyms": ["tv =>"]}}}')"#
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn ascii_folding_tokenizer_config(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+    INSERT INTO paradedb.bm25_search (description, category, rating, in_stock)
+    VALUES ('Café au lait mug', 'Kitchen', 4, true), (U&'cafe\0301 table', 'Furniture', 3, true);
+
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "default", "ascii_folding": true, "unicode_normalization": "nfc"}}}');
+    "#
+    .execute(&mut conn);
+
+    // the decomposed "é" of the second row is composed, and then folded like the first
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:cafe'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 2);
+
+    let count: (i64,) = "
+    SELECT COUNT(*) FROM paradedb.bm25_search
+    WHERE bm25_search @@@ 'description:café'"
+        .fetch_one(&mut conn);
+    assert_eq!(count.0, 2);
+}
+
+#[rstest]
+#[case::phonetic(
+    &["Catherine Smith", "Katherine Smyth", "Karen Jones"],
+    r#"{"tokenizer": {"type": "default", "phonetic": {"encoder": "double_metaphone"}}}"#,
+    "paradedb.match('body', 'Katherine')",
+    &[1, 2]
+)]
+#[case::edge_ngram(
+    &["Sleek running shoes", "Generic shoes"],
+    r#"{"tokenizer": {"type": "default", "edge_ngram": {"min_gram": 2, "max_gram": 10}}}"#,
+    "paradedb.term('body', 'runn')",
+    &[1]
+)]
+#[case::shingle(
+    &["Sleek running shoes", "Shoes for running"],
+    r#"{"record": "freq", "tokenizer": {"type": "default", "shingle": {"output_unigrams": false}}}"#,
+    "paradedb.term('body', 'running shoes')",
+    &[1]
+)]
+#[case::html_strip(
+    &[
+        "<p>Fast &amp; <b>light</b> running shoes</p>",
+        "<script>var light = 1;</script><p>Heavy hiking boots</p>"
+    ],
+    r#"{"tokenizer": {"type": "default", "char_filters": [{"type": "html_strip"}]}}"#,
+    "paradedb.term('body', 'light')",
+    &[1]
+)]
+#[case::html_strip_entities(
+    &["<p>Fast &amp; <b>light</b> running shoes</p>"],
+    r#"{"tokenizer": {"type": "default", "char_filters": [{"type": "html_strip"}]}}"#,
+    "paradedb.term('body', 'amp')",
+    &[]
+)]
+#[case::html_strip_scripts(
+    &["<script>var light = 1;</script><p>Heavy hiking boots</p>"],
+    r#"{"tokenizer": {"type": "default", "char_filters": [{"type": "html_strip"}]}}"#,
+    "paradedb.term('body', 'script')",
+    &[]
+)]
+#[case::word_delimiter(
+    &["XPS13-9310", "PowerShot G7", "WiFi-6E Router"],
+    r#"{"tokenizer": {"type": "whitespace", "word_delimiter": {"preserve_original": true, "catenate_all": true}}}"#,
+    "paradedb.term('body', 'powershot')",
+    &[2]
+)]
+#[case::word_delimiter_phrase(
+    &["XPS13-9310", "PowerShot G7", "WiFi-6E Router"],
+    r#"{"tokenizer": {"type": "whitespace", "word_delimiter": {"preserve_original": true, "catenate_all": true}}}"#,
+    "paradedb.phrase('body', ARRAY['xps', '13', '9310'])",
+    &[1]
+)]
+fn token_filter_config(
+    mut conn: PgConnection,
+    #[case] rows: &[&str],
+    #[case] config: &str,
+    #[case] query: &str,
+    #[case] expected: &[i32],
+) {
+    let values = rows
+        .iter()
+        .map(|row| format!("('{row}')"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        r#"
+        CREATE TABLE filtered (id SERIAL PRIMARY KEY, body TEXT);
+        INSERT INTO filtered (body) VALUES {values};
+
+        CREATE INDEX filtered_idx ON filtered
+            USING bm25 (id, body)
+            WITH (key_field='id', text_fields='{{"body": {config}}}');
+        "#
+    )
+    .execute(&mut conn);
+
+    let ids: Vec<(i32,)> =
+        format!("SELECT id FROM filtered WHERE id @@@ {query} ORDER BY id").fetch(&mut conn);
+    assert_eq!(
+        ids,
+        expected.iter().map(|id| (*id,)).collect::<Vec<_>>(),
+        "{query}"
+    );
+}
+
+#[rstest]
+#[case::phonetic(r#"{"type": "default", "phonetic": {"encoder": "nysiis"}}"#)]
+#[case::shingle(r#"{"type": "default", "shingle": {"min_shingle_size": 1}}"#)]
+#[case::edge_ngram(r#"{"type": "default", "edge_ngram": {"min_gram": 4, "max_gram": 2}}"#)]
+#[case::pattern_replace(
+    r#"{"type": "default", "char_filters": [{"type": "pattern_replace", "pattern": "("}]}"#
+)]
+fn invalid_token_filter_config(mut conn: PgConnection, #[case] tokenizer: &str) {
+    "CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb')"
+        .execute(&mut conn);
+
+    let result = format!(
+        r#"
+        CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+            USING bm25 (id, description)
+            WITH (key_field='id', text_fields='{{"description": {{"tokenizer": {tokenizer}}}}}')"#
+    )
+    .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn decompounder_tokenizer_config(mut conn: PgConnection) {
+    r#"
+    CREATE TABLE compound_parts (word TEXT);
+    INSERT INTO compound_parts (word) VALUES ('kühl'), ('schrank'), ('griff'), ('tür'), (NULL);
+
+    CREATE TABLE products (id SERIAL PRIMARY KEY, description TEXT);
+    INSERT INTO products (description) VALUES ('Kühlschrankgriff aus Edelstahl'), ('Schranktür aus Holz'), ('Türgriff');
+
+    CREATE INDEX products_idx ON products
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "stem", "language": "German", "decompounder": {"word_list_table": "compound_parts"}}}}');
+    "#
+    .execute(&mut conn);
+
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products WHERE id @@@ paradedb.match('description', 'Griff') ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (3,)]);
+
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products WHERE id @@@ paradedb.match('description', 'Schrank') ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (2,)]);
+
+    // rows added later use the words the index was built with, even once the table changes
+    r#"
+    DELETE FROM compound_parts WHERE word = 'griff';
+    INSERT INTO products (description) VALUES ('Schrankgriff');
+    "#
+    .execute(&mut conn);
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products WHERE id @@@ paradedb.match('description', 'griff') ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (3,), (4,)]);
+
+    // the words are saved in the index, so readers don't need access to the table
+    r#"
+    DROP ROLE IF EXISTS decompounder_reader;
+    CREATE ROLE decompounder_reader;
+    GRANT SELECT ON products TO decompounder_reader;
+    SET ROLE decompounder_reader;
+    "#
+    .execute(&mut conn);
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products WHERE id @@@ paradedb.match('description', 'griff') ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (3,), (4,)]);
+    r#"
+    RESET ROLE;
+    DROP OWNED BY decompounder_reader;
+    DROP ROLE decompounder_reader;
+    "#
+    .execute(&mut conn);
+
+    // the table is schema-qualified when the index is created, so a temporary table with the same
+    // name can't take its place when the index is rebuilt
+    let (options,): (Vec<String>,) =
+        "SELECT reloptions FROM pg_class WHERE relname = 'products_idx'".fetch_one(&mut conn);
+    assert!(options
+        .iter()
+        .any(|option| option.contains(r#""word_list_table":"public.compound_parts""#)));
+    r#"
+    CREATE TEMP TABLE compound_parts (word TEXT);
+    INSERT INTO compound_parts (word) VALUES ('edelstahl');
+    REINDEX INDEX products_idx;
+    "#
+    .execute(&mut conn);
+    let rows: Vec<(i32,)> = "
+    SELECT id FROM products WHERE id @@@ paradedb.match('description', 'Schrank') ORDER BY id"
+        .fetch(&mut conn);
+    assert_eq!(rows, vec![(1,), (2,), (4,)]);
+}
+
+#[rstest]
+fn invalid_decompounder_tokenizer_config(mut conn: PgConnection) {
+    "CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb')"
+        .execute(&mut conn);
+
+    let result = r#"
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "default", "decompounder": {"word_list_table": "no_such_table"}}}}')"#
+        .execute_result(&mut conn);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "error returned from database: word list table 'no_such_table' does not exist"
+    );
+
+    // the words must be text, so reading them can't call a user-defined cast
+    let result = r#"
+    CREATE TABLE numeric_parts (word INT);
+    CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description)
+        WITH (key_field='id', text_fields='{"description": {"tokenizer": {"type": "default", "decompounder": {"word_list_table": "numeric_parts"}}}}')"#
+        .execute_result(&mut conn);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "error returned from database: the word list table 'public.numeric_parts' must have a text column named 'word'"
+    );
+}
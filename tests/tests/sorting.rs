@@ -147,6 +147,169 @@ fn sort_by_raw(mut conn: PgConnection) {
     );
 }
 
+#[rstest]
+fn sort_by_compound_keys(mut conn: PgConnection) {
+    // ensure our custom scan wins against our small test table
+    r#"
+        SET enable_indexscan TO off;
+        CALL paradedb.create_bm25_test_table(table_name => 'bm25_search', schema_name => 'paradedb');
+
+        CREATE INDEX bm25_search_idx ON paradedb.bm25_search
+        USING bm25 (id, description, category, rating)
+        WITH (
+            key_field = 'id',
+            text_fields = '{
+                "description": {},
+                "category": {
+                    "fast": true,
+                    "normalizer": "raw"
+                }
+            }',
+            numeric_fields = '{
+                "rating": {}
+            }'
+        );
+    "#.execute(&mut conn);
+
+    let query = "SELECT id FROM paradedb.bm25_search WHERE description @@@ 'keyboard OR shoes' ORDER BY category, rating DESC, id LIMIT 5";
+    let (plan,) =
+        format!("EXPLAIN (ANALYZE, FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+    eprintln!("{plan:#?}");
+    let plan = plan
+        .pointer("/0/Plan/Plans/0")
+        .unwrap()
+        .as_object()
+        .unwrap();
+    assert_eq!(
+        plan.get("   Sort Keys"),
+        Some(&Value::String(String::from(
+            "category asc, rating desc, id asc"
+        )))
+    );
+
+    let with_custom_scan: Vec<(i32,)> = query.fetch(&mut conn);
+
+    // the score can be one of the keys too
+    let scored = "SELECT id FROM paradedb.bm25_search WHERE description @@@ 'keyboard OR shoes' ORDER BY paradedb.score(id) DESC, category, id LIMIT 5";
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {scored}").fetch_one::<(Value,)>(&mut conn);
+    assert_eq!(
+        plan.pointer("/0/Plan/Plans/0/   Sort Keys"),
+        Some(&Value::String(String::from(
+            "paradedb.score() desc, category asc, id asc"
+        )))
+    );
+    let scored_rows: Vec<(i32,)> = scored.fetch(&mut conn);
+    assert_eq!(scored_rows.len(), 5);
+
+    "SET paradedb.enable_custom_scan TO off".execute(&mut conn);
+    let without_custom_scan: Vec<(i32,)> = query.fetch(&mut conn);
+    assert_eq!(with_custom_scan, without_custom_scan);
+}
+
+#[rstest]
+fn search_after_pagination(mut conn: PgConnection) {
+    SimpleProductsTable::setup().execute(&mut conn);
+
+    let expected: Vec<(i32, f32)> = r#"
+        SELECT id, paradedb.score(id) FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.parse('description:(keyboard OR shoes OR plastic)')
+        ORDER BY paradedb.score(id) DESC, id
+    "#
+    .fetch(&mut conn);
+    assert!(expected.len() > 3);
+
+    let mut pages: Vec<(i32, f32)> = r#"
+        SELECT id, paradedb.score(id) FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.parse('description:(keyboard OR shoes OR plastic)')
+        ORDER BY paradedb.score(id) DESC, id LIMIT 3
+    "#
+    .fetch(&mut conn);
+    loop {
+        let (last_key, last_score) = *pages.last().unwrap();
+        let page: Vec<(i32, f32)> = format!(
+            r#"
+            SELECT id, paradedb.score(id) FROM paradedb.bm25_search
+            WHERE id @@@ paradedb.search_after(
+                paradedb.parse('description:(keyboard OR shoes OR plastic)'),
+                {last_key},
+                '{last_score}'::real
+            )
+            ORDER BY paradedb.score(id) DESC, id LIMIT 3
+            "#
+        )
+        .fetch(&mut conn);
+        if page.is_empty() {
+            break;
+        }
+        pages.extend(page);
+    }
+    assert_eq!(pages, expected);
+
+    // without a score, pages are ordered by the key field alone
+    let rows: Vec<(i32,)> = r#"
+        SELECT id FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.search_after(paradedb.parse('description:(keyboard OR shoes OR plastic)'), 5)
+        ORDER BY id LIMIT 100
+    "#
+    .fetch(&mut conn);
+    let mut ids = expected
+        .into_iter()
+        .filter_map(|(id, _)| (id > 5).then_some((id,)))
+        .collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(rows, ids);
+}
+
+#[rstest]
+fn search_after_requires_its_order(mut conn: PgConnection) {
+    SimpleProductsTable::setup().execute(&mut conn);
+
+    for order_by in [
+        "ORDER BY id DESC",
+        "ORDER BY paradedb.score(id) DESC",
+        "ORDER BY paradedb.score(id) DESC, id DESC",
+        "ORDER BY rating, id",
+        "",
+    ] {
+        let result = format!(
+            "SELECT id FROM paradedb.bm25_search
+            WHERE id @@@ paradedb.search_after(paradedb.parse('description:keyboard'), 1, 1.0)
+            {order_by} LIMIT 3"
+        )
+        .execute_result(&mut conn);
+        assert!(result.is_err(), "{order_by}");
+    }
+
+    let result = "SELECT id FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.search_after(paradedb.parse('description:keyboard'), 1)
+        ORDER BY paradedb.score(id) DESC, id LIMIT 3"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    // without a LIMIT, or outside of our custom scan, it's not a TopN scan
+    let result = "SELECT id FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.search_after(paradedb.parse('description:keyboard'), 1)
+        ORDER BY id"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    // and it can't be nested inside another query
+    let result = "SELECT id FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.boolean(must => ARRAY[
+            paradedb.search_after(paradedb.parse('description:keyboard'), 1)
+        ])
+        ORDER BY id LIMIT 3"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    "SET paradedb.enable_custom_scan TO off".execute(&mut conn);
+    let result = "SELECT id FROM paradedb.bm25_search
+        WHERE id @@@ paradedb.search_after(paradedb.parse('description:keyboard'), 1)
+        ORDER BY id LIMIT 3"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
+
 #[rstest]
 fn sort_by_row_return_scores(mut conn: PgConnection) {
     // ensure our custom scan wins against our small test table
@@ -218,10 +381,6 @@ async fn test_incremental_sort_with_partial_order(mut conn: PgConnection) {
         .await
         .unwrap();
 
-    // Check Postgres version - Incremental Sort only exists in PG 16+
-    let pg_version = pg_major_version(&mut conn);
-    let pg_supports_incremental_sort = pg_version >= 16;
-
     // Test BM25 with ORDER BY ... LIMIT to confirm sort optimization works
     let (explain_bm25,) = sqlx::query_as::<_, (Value,)>(
         "EXPLAIN (ANALYZE, VERBOSE, FORMAT JSON) 
@@ -264,25 +423,24 @@ async fn test_incremental_sort_with_partial_order(mut conn: PgConnection) {
 
     println!("SIMPLE QUERY EXPLAIN OUTPUT: {explain_simple}");
 
-    // Instead of checking for specific node types, check that:
-    // 1. A Sort node exists to handle the sorting (either regular Sort or Incremental Sort)
+    // Check that:
+    // 1. The Custom Scans sort by every key of the ORDER BY themselves, as they're all fast
+    //    fields, so there's no Sort or Incremental Sort node on top of them
     // 2. Custom Scan nodes exist that support our search
     // 3. Scores are enabled in the Custom Scan
 
-    // Check that we have a Sort node somewhere in the plan
-    let has_sort_node = if pg_supports_incremental_sort {
-        plan_json.contains("\"Node Type\":\"Incremental Sort\"")
-            || explain_simple.contains("Incremental Sort")
-    } else {
-        plan_json.contains("\"Node Type\":\"Sort\"")
-            || plan_json.contains("\"Node Type\":\"Incremental Sort\"")
-            || explain_simple.contains("Sort")
-            || explain_simple.contains("Incremental Sort")
-    };
-
+    let has_sort_node = plan_json.contains("\"Node Type\":\"Sort\"")
+        || plan_json.contains("\"Node Type\":\"Incremental Sort\"");
+    assert!(
+        !has_sort_node,
+        "Plan should not need a Sort node to handle ORDER BY"
+    );
     assert!(
-        has_sort_node,
-        "Plan should include an Incremental Sort node to handle ORDER BY"
+        custom_scan_nodes.iter().all(|node| node.get("   Sort Keys")
+            == Some(&Value::String(String::from(
+                "paradedb.score() asc, sale_date asc, amount asc"
+            )))),
+        "Every Custom Scan node should sort by all of the ORDER BY keys"
     );
 
     // Check that we have Custom Scan nodes that handle our search
@@ -0,0 +1,215 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for span queries and the NEAR/n query string operator
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'mock_items', schema_name => 'public');
+    CREATE INDEX search_idx ON mock_items
+    USING bm25 (id, description, category)
+    WITH (key_field='id');
+    "#
+    .execute(conn);
+}
+
+fn ids(conn: &mut PgConnection, query: &str) -> Vec<i32> {
+    let rows: Vec<(i32,)> =
+        format!("SELECT id FROM mock_items WHERE {query} ORDER BY id").fetch_collect(conn);
+    rows.into_iter().map(|(id,)| id).collect()
+}
+
+#[rstest]
+fn span_near(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // "Sleek running shoes"
+    let unordered = ids(
+        &mut conn,
+        "id @@@ paradedb.span_near(ARRAY[
+            paradedb.span_term('description', 'shoes'),
+            paradedb.span_term('description', 'running')
+        ])",
+    );
+    assert_eq!(unordered, vec![3]);
+
+    let ordered = ids(
+        &mut conn,
+        "id @@@ paradedb.span_near(ARRAY[
+            paradedb.span_term('description', 'shoes'),
+            paradedb.span_term('description', 'running')
+        ], in_order => true)",
+    );
+    assert_eq!(ordered, Vec::<i32>::new());
+
+    let with_slop = ids(
+        &mut conn,
+        "id @@@ paradedb.span_near(ARRAY[
+            paradedb.span_term('description', 'sleek'),
+            paradedb.span_term('description', 'shoes')
+        ], slop => 1, in_order => true)",
+    );
+    assert_eq!(with_slop, vec![3]);
+
+    // a span term with several tokens is a phrase
+    let phrase = ids(
+        &mut conn,
+        "id @@@ paradedb.span_near(ARRAY[
+            paradedb.span_term('description', 'running shoes'),
+            paradedb.span_term('description', 'sleek')
+        ])",
+    );
+    assert_eq!(phrase, vec![3]);
+
+    let json = ids(
+        &mut conn,
+        r#"id @@@ '{"span_near": {"clauses": [
+            {"span_term": {"field": "description", "value": "sleek"}},
+            {"span_term": {"field": "description", "value": "shoes"}}
+        ], "slop": 1}}'::jsonb"#,
+    );
+    assert_eq!(json, vec![3]);
+}
+
+#[rstest]
+fn span_or_not_first(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let or = ids(
+        &mut conn,
+        "id @@@ paradedb.span_near(ARRAY[
+            paradedb.span_or(ARRAY[
+                paradedb.span_term('description', 'running'),
+                paradedb.span_term('description', 'jogging')
+            ]),
+            paradedb.span_term('description', 'shoes')
+        ], in_order => true)",
+    );
+    assert_eq!(or, vec![3, 4]);
+
+    // "shoes" that don't come right after "running"
+    let not = ids(
+        &mut conn,
+        "id @@@ paradedb.span_not(
+            paradedb.span_term('description', 'shoes'),
+            paradedb.span_term('description', 'running'),
+            pre => 1
+        )",
+    );
+    assert_eq!(not, vec![4, 5]);
+
+    // "Generic shoes" is the only one where "shoes" is one of the first two words
+    let first = ids(
+        &mut conn,
+        "id @@@ paradedb.span_first(paradedb.span_term('description', 'shoes'), end_position => 2)",
+    );
+    assert_eq!(first, vec![5]);
+}
+
+#[rstest]
+fn span_scores(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let scores: Vec<(i32, f32)> = "
+    SELECT id, paradedb.score(id) FROM mock_items
+    WHERE id @@@ paradedb.span_near(ARRAY[
+        paradedb.span_term('description', 'shoes'),
+        paradedb.span_term('description', 'running')
+    ])"
+    .fetch_collect(&mut conn);
+    assert_eq!(scores.len(), 1);
+    assert!(scores[0].1 > 0.0);
+}
+
+#[rstest]
+fn near_operator(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    assert_eq!(
+        ids(&mut conn, "description @@@ 'shoes NEAR/1 sleek'"),
+        vec![3]
+    );
+    assert_eq!(
+        ids(&mut conn, "description @@@ 'shoes NEAR/0 sleek'"),
+        Vec::<i32>::new()
+    );
+    assert_eq!(
+        ids(
+            &mut conn,
+            "description @@@ 'sleek NEAR/1 running NEAR/1 shoes'"
+        ),
+        vec![3]
+    );
+    assert_eq!(
+        ids(
+            &mut conn,
+            r#"description @@@ '"running shoes" NEAR/0 sleek'"#
+        ),
+        vec![3]
+    );
+    assert_eq!(
+        ids(
+            &mut conn,
+            "description @@@ 'keyboard OR shoes NEAR/0 running'"
+        ),
+        vec![1, 2, 3]
+    );
+    assert_eq!(
+        ids(
+            &mut conn,
+            "id @@@ paradedb.parse('description:jogging NEAR/1 description:white AND category:footwear')"
+        ),
+        vec![4]
+    );
+}
+
+#[rstest]
+fn invalid_span_queries(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    // clauses must be on the same field
+    let result = "SELECT id FROM mock_items WHERE id @@@ paradedb.span_near(ARRAY[
+        paradedb.span_term('description', 'shoes'),
+        paradedb.span_term('category', 'footwear')
+    ])"
+    .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    // clauses must be span queries
+    let result = "SELECT id FROM mock_items WHERE id @@@ paradedb.span_near(ARRAY[
+        paradedb.span_term('description', 'shoes'),
+        paradedb.term('description', 'running')
+    ])"
+    .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    // NEAR needs a field, and words on both sides
+    let result = "SELECT id FROM mock_items WHERE id @@@ paradedb.parse('shoes NEAR/1 running')"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result =
+        "SELECT id FROM mock_items WHERE description @@@ 'NEAR/1 shoes'".execute_result(&mut conn);
+    assert!(result.is_err());
+}
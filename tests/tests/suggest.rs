@@ -0,0 +1,110 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for paradedb.suggest
+
+mod fixtures;
+
+use fixtures::*;
+use pretty_assertions::assert_eq;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn setup(conn: &mut PgConnection) {
+    r#"
+    CALL paradedb.create_bm25_test_table(table_name => 'mock_items', schema_name => 'public');
+    CREATE INDEX search_idx ON mock_items
+    USING bm25 (id, description, category)
+    WITH (key_field='id');
+    "#
+    .execute(conn);
+}
+
+#[rstest]
+fn suggest(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(Option<String>, String, i32, Option<i64>)> =
+        "SELECT * FROM paradedb.suggest('search_idx', 'description', 'Keybaord')"
+            .fetch_collect(&mut conn);
+    assert_eq!(
+        rows,
+        vec![
+            (None, "keyboard".into(), 1, None),
+            (Some("keybaord".into()), "keyboard".into(), 1, Some(2)),
+        ]
+    );
+
+    // terms that are already in the index are suggested as they are
+    let (phrase, distance): (String, i32) = "
+    SELECT suggestion, distance FROM paradedb.suggest('search_idx', 'description', 'runing metal shoez')
+    WHERE token IS NULL"
+        .fetch_one(&mut conn);
+    assert_eq!(phrase, "running metal shoes");
+    assert_eq!(distance, 2);
+
+    // the phrase keeps what was typed around the corrections
+    let (phrase, distance): (String, i32) = "
+    SELECT suggestion, distance FROM paradedb.suggest('search_idx', 'description', 'Runing, Metal-shoez!')
+    WHERE token IS NULL"
+        .fetch_one(&mut conn);
+    assert_eq!(phrase, "running, Metal-shoes!");
+    assert_eq!(distance, 2);
+
+    // the most common terms are suggested first
+    let rows: Vec<(String, i32, i64)> = "
+    SELECT suggestion, distance, doc_freq FROM paradedb.suggest('search_idx', 'description', 'shoe', 1)
+    WHERE token IS NOT NULL"
+        .fetch_collect(&mut conn);
+    assert_eq!(rows, vec![("shoes".into(), 1, 3)]);
+}
+
+#[rstest]
+fn suggest_limit(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let rows: Vec<(String,)> = r#"
+    SELECT suggestion FROM paradedb.suggest('search_idx', 'description', 'wal', "limit" => 1)
+    WHERE token IS NOT NULL"#
+        .fetch_collect(&mut conn);
+    assert_eq!(rows.len(), 1);
+
+    // tokens without any suggestions are kept in the phrase
+    let rows: Vec<(Option<String>, String)> =
+        "SELECT token, suggestion FROM paradedb.suggest('search_idx', 'description', 'xyzzy keyboard', 0)"
+            .fetch_collect(&mut conn);
+    assert_eq!(
+        rows,
+        vec![
+            (None, "xyzzy keyboard".into()),
+            (Some("keyboard".into()), "keyboard".into())
+        ]
+    );
+}
+
+#[rstest]
+fn invalid_suggest(mut conn: PgConnection) {
+    setup(&mut conn);
+
+    let result =
+        "SELECT * FROM paradedb.suggest('search_idx', 'id', 'keyboard')".execute_result(&mut conn);
+    assert!(result.is_err());
+
+    let result = "SELECT * FROM paradedb.suggest('search_idx', 'description', 'keyboard', 3)"
+        .execute_result(&mut conn);
+    assert!(result.is_err());
+}
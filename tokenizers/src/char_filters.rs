@@ -0,0 +1,511 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::Range;
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// Tags whose content is never text, and is removed along with the tags themselves
+const HIDDEN_TAGS: &[&str] = &["script", "style"];
+
+/// Tags that separate blocks of text, and are replaced with a newline so the words on either side
+/// of them aren't joined together
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// The configuration of a [`CharFilter`], as it appears in the tokenizer JSON
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CharFilterOptions {
+    /// Removes HTML tags, comments, and the contents of `<script>` and `<style>` elements, and
+    /// decodes character references like `&amp;`
+    HtmlStrip,
+    /// Replaces each occurrence of a string with another, preferring the longest match
+    Mapping { mappings: Vec<(String, String)> },
+    /// Replaces each match of a regular expression, which may refer to its capture groups
+    PatternReplace {
+        pattern: String,
+        #[serde(default)]
+        replacement: String,
+    },
+}
+
+/// A filter that rewrites the text given to a tokenizer, before it is split into tokens
+#[derive(Clone, Debug)]
+pub enum CharFilter {
+    HtmlStrip,
+    Mapping(Vec<(String, String)>),
+    PatternReplace(Regex, String),
+}
+
+impl CharFilter {
+    pub fn new(options: &CharFilterOptions) -> Result<Self> {
+        match options {
+            CharFilterOptions::HtmlStrip => Ok(CharFilter::HtmlStrip),
+            CharFilterOptions::Mapping { mappings } => {
+                if mappings.iter().any(|(from, _)| from.is_empty()) {
+                    anyhow::bail!("a 'mapping' char filter cannot map from an empty string");
+                }
+                Ok(CharFilter::Mapping(mappings.clone()))
+            }
+            CharFilterOptions::PatternReplace {
+                pattern,
+                replacement,
+            } => {
+                let regex = Regex::new(pattern).map_err(|e| {
+                    anyhow::anyhow!("a 'pattern_replace' char filter has an invalid pattern: {e}")
+                })?;
+                Ok(CharFilter::PatternReplace(regex, replacement.clone()))
+            }
+        }
+    }
+
+    /// Rewrite `text`, returning the new text and the edits that were made to it
+    fn apply(&self, text: &str) -> (String, OffsetMap) {
+        let mut rewriter = Rewriter::new(text);
+        match self {
+            CharFilter::HtmlStrip => html_strip(text, &mut rewriter),
+            CharFilter::Mapping(mappings) => {
+                let mut position = 0;
+                while let Some(c) = text[position..].chars().next() {
+                    let longest = mappings
+                        .iter()
+                        .filter(|(from, _)| text[position..].starts_with(from.as_str()))
+                        .max_by_key(|(from, _)| from.len());
+                    match longest {
+                        Some((from, to)) => {
+                            rewriter.replace(position..position + from.len(), to);
+                            position += from.len();
+                        }
+                        None => position += c.len_utf8(),
+                    }
+                }
+            }
+            CharFilter::PatternReplace(regex, replacement) => {
+                for captures in regex.captures_iter(text) {
+                    let matched = captures.get(0).expect("group 0 is always present");
+                    let mut expanded = String::new();
+                    captures.expand(replacement, &mut expanded);
+                    rewriter.replace(matched.range(), &expanded);
+                }
+            }
+        }
+        rewriter.finish()
+    }
+}
+
+fn html_strip(text: &str, rewriter: &mut Rewriter) {
+    let bytes = text.as_bytes();
+    let mut position = 0;
+    while position < bytes.len() {
+        let end = match bytes[position] {
+            b'<' => strip_markup(text, position, rewriter),
+            b'&' => decode_reference(text, position, rewriter),
+            _ => None,
+        };
+        // everything we look for starts with an ASCII byte, so skipping a single byte never
+        // lands us in the middle of a character that we'd then mistake for markup
+        position = end.unwrap_or(position + 1);
+    }
+}
+
+/// Remove the comment or tag starting at `start`, returning where it ends
+fn strip_markup(text: &str, start: usize, rewriter: &mut Rewriter) -> Option<usize> {
+    let rest = &text[start..];
+    if rest.starts_with("<!--") {
+        let end = rest.find("-->").map_or(text.len(), |i| start + i + 3);
+        rewriter.replace(start..end, "");
+        return Some(end);
+    }
+
+    let after = *rest.as_bytes().get(1)?;
+    if !(after.is_ascii_alphabetic() || matches!(after, b'/' | b'!' | b'?')) {
+        // a lone "<", like in "1 < 2"
+        return None;
+    }
+    let mut end = start + rest.find('>')? + 1;
+
+    let name = rest[1..]
+        .trim_start_matches('/')
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if !rest[1..].starts_with('/') && HIDDEN_TAGS.contains(&name.as_str()) {
+        let closing = format!("</{name}");
+        end = text[end..]
+            .to_ascii_lowercase()
+            .find(&closing)
+            .and_then(|i| text[end + i..].find('>').map(|j| end + i + j + 1))
+            .unwrap_or(text.len());
+    }
+
+    let replacement = if HIDDEN_TAGS.contains(&name.as_str()) || BLOCK_TAGS.contains(&name.as_str())
+    {
+        "\n"
+    } else {
+        ""
+    };
+    rewriter.replace(start..end, replacement);
+    Some(end)
+}
+
+/// Decode the character reference starting at `start`, returning where it ends
+fn decode_reference(text: &str, start: usize, rewriter: &mut Rewriter) -> Option<usize> {
+    // the longest reference we decode is a hexadecimal one, like "&#x10FFFF;"
+    let semicolon = text[start..].bytes().take(10).position(|b| b == b';')?;
+    let end = start + semicolon + 1;
+    let name = &text[start + 1..end - 1];
+
+    let decoded = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        _ => {
+            let code =
+                if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    u32::from_str_radix(hex, 16).ok()?
+                } else {
+                    name.strip_prefix('#')?.parse().ok()?
+                };
+            char::from_u32(code)?
+        }
+    };
+    rewriter.replace(start..end, decoded.encode_utf8(&mut [0; 4]));
+    Some(end)
+}
+
+/// Builds the output of a [`CharFilter`] from the replacements it makes, in order, recording
+/// where each one was made.
+struct Rewriter<'a> {
+    text: &'a str,
+    output: String,
+    copied: usize,
+    edits: Vec<Edit>,
+}
+
+impl<'a> Rewriter<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            output: String::with_capacity(text.len()),
+            copied: 0,
+            edits: vec![],
+        }
+    }
+
+    fn replace(&mut self, range: Range<usize>, replacement: &str) {
+        if &self.text[range.clone()] == replacement {
+            return;
+        }
+        self.output.push_str(&self.text[self.copied..range.start]);
+        let output_start = self.output.len();
+        self.output.push_str(replacement);
+        self.edits.push(Edit {
+            output: output_start..self.output.len(),
+            original: range.clone(),
+        });
+        self.copied = range.end;
+    }
+
+    fn finish(mut self) -> (String, OffsetMap) {
+        self.output.push_str(&self.text[self.copied..]);
+        (self.output, OffsetMap { edits: self.edits })
+    }
+}
+
+/// A replacement of the `original` range of a [`CharFilter`]'s input with the `output` range of
+/// its output
+#[derive(Clone, Debug)]
+struct Edit {
+    output: Range<usize>,
+    original: Range<usize>,
+}
+
+/// Maps offsets in a [`CharFilter`]'s output back to offsets in its input
+#[derive(Clone, Debug, Default)]
+struct OffsetMap {
+    edits: Vec<Edit>,
+}
+
+impl OffsetMap {
+    /// The input offset of the output `offset`.  A token that starts right after removed text
+    /// starts after it too, while one that ends right before removed text ends before it, so that
+    /// neither includes the removed text.
+    fn correct(&self, offset: usize, is_end: bool) -> usize {
+        let before = |edit: &Edit| {
+            if is_end {
+                edit.output.end < offset || (edit.output.end == offset && !edit.output.is_empty())
+            } else {
+                edit.output.end <= offset
+            }
+        };
+        let index = self.edits.partition_point(before);
+
+        if let Some(edit) = self.edits.get(index) {
+            // a token that starts or ends within a replacement covers all of the text it replaced
+            if edit.output.start < offset || (!is_end && edit.output.start == offset) {
+                return if is_end {
+                    edit.original.end
+                } else {
+                    edit.original.start
+                };
+            }
+        }
+        match index.checked_sub(1).map(|i| &self.edits[i]) {
+            Some(edit) => offset - edit.output.end + edit.original.end,
+            None => offset,
+        }
+    }
+}
+
+/// A [`Tokenizer`] that runs its text through a series of [`CharFilter`]s before handing it to
+/// the `inner` tokenizer, and maps the offsets of the resulting tokens back to the original text,
+/// so that snippets still highlight the right words.
+#[derive(Clone)]
+pub struct CharFilterTokenizer<T> {
+    inner: T,
+    filters: Vec<CharFilter>,
+    /// The filtered text of the current token stream
+    text: String,
+    /// The offset maps of the current token stream, one per filter
+    maps: Vec<OffsetMap>,
+}
+
+impl<T> CharFilterTokenizer<T> {
+    pub fn new(inner: T, filters: Vec<CharFilter>) -> Self {
+        Self {
+            inner,
+            filters,
+            text: String::new(),
+            maps: vec![],
+        }
+    }
+}
+
+impl<T: Tokenizer> Tokenizer for CharFilterTokenizer<T> {
+    type TokenStream<'a> = CharFilterTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        if self.filters.is_empty() {
+            return CharFilterTokenStream {
+                tail: self.inner.token_stream(text),
+                maps: &[],
+            };
+        }
+
+        self.maps.clear();
+        let mut filtered = text.to_string();
+        for filter in &self.filters {
+            let (output, map) = filter.apply(&filtered);
+            filtered = output;
+            self.maps.push(map);
+        }
+        self.text = filtered;
+
+        CharFilterTokenStream {
+            tail: self.inner.token_stream(&self.text),
+            maps: &self.maps,
+        }
+    }
+}
+
+pub struct CharFilterTokenStream<'a, T> {
+    tail: T,
+    maps: &'a [OffsetMap],
+}
+
+impl<T: TokenStream> TokenStream for CharFilterTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let token = self.tail.token_mut();
+        // the last filter's output is what was tokenized, so its map is applied first
+        for map in self.maps.iter().rev() {
+            token.offset_from = map.correct(token.offset_from, false);
+            token.offset_to = map.correct(token.offset_to, true);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
+
+    fn tokens(filters: &[CharFilterOptions], text: &str) -> Vec<(String, usize, usize)> {
+        let filters = filters
+            .iter()
+            .map(|options| CharFilter::new(options).unwrap())
+            .collect();
+        let mut analyzer = TextAnalyzer::builder(CharFilterTokenizer::new(
+            SimpleTokenizer::default(),
+            filters,
+        ))
+        .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            // the offsets should always point at the token's text in the original
+            tokens.push((token.text.clone(), token.offset_from, token.offset_to));
+        }
+        tokens
+    }
+
+    #[rstest]
+    fn test_html_strip() {
+        let text = "<p>Fast&amp;<b>light</b></p><div>run&#8217;s</div><!-- hidden --><script>var x;</script>";
+        assert_eq!(
+            tokens(&[CharFilterOptions::HtmlStrip], text),
+            vec![
+                ("Fast".to_string(), 3, 7),
+                ("light".to_string(), 15, 20),
+                ("run".to_string(), 33, 36),
+                ("s".to_string(), 43, 44),
+            ]
+        );
+        assert_eq!(&text[15..20], "light");
+        assert_eq!(&text[43..44], "s");
+    }
+
+    #[rstest]
+    fn test_html_strip_leaves_plain_text_alone() {
+        assert_eq!(
+            tokens(&[CharFilterOptions::HtmlStrip], "1 < 2 & 3 > 2"),
+            vec![
+                ("1".to_string(), 0, 1),
+                ("2".to_string(), 4, 5),
+                ("3".to_string(), 8, 9),
+                ("2".to_string(), 12, 13),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_mapping() {
+        let mapping = CharFilterOptions::Mapping {
+            mappings: vec![("+".into(), " plus ".into()), ("c++".into(), "cpp".into())],
+        };
+        assert_eq!(
+            tokens(&[mapping], "c++ and c+d"),
+            vec![
+                ("cpp".to_string(), 0, 3),
+                ("and".to_string(), 4, 7),
+                ("c".to_string(), 8, 9),
+                ("plus".to_string(), 9, 10),
+                ("d".to_string(), 10, 11),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_pattern_replace() {
+        let pattern_replace = CharFilterOptions::PatternReplace {
+            pattern: r"(\d+)-(\d+)".into(),
+            replacement: "$1$2".into(),
+        };
+        assert_eq!(
+            tokens(&[pattern_replace], "call 555-1234 now"),
+            vec![
+                ("call".to_string(), 0, 4),
+                ("5551234".to_string(), 5, 13),
+                ("now".to_string(), 14, 17),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_chained() {
+        let filters = [
+            CharFilterOptions::HtmlStrip,
+            CharFilterOptions::Mapping {
+                mappings: vec![("&".into(), " and ".into())],
+            },
+        ];
+        assert_eq!(
+            tokens(&filters, "<i>salt</i>&amp;<i>pepper</i>"),
+            vec![
+                ("salt".to_string(), 3, 7),
+                ("and".to_string(), 11, 16),
+                ("pepper".to_string(), 19, 25),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_invalid_options() {
+        assert!(CharFilter::new(&CharFilterOptions::Mapping {
+            mappings: vec![("".into(), "x".into())]
+        })
+        .is_err());
+        assert!(CharFilter::new(&CharFilterOptions::PatternReplace {
+            pattern: "(".into(),
+            replacement: "".into()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// The configuration of a [`DecompounderFilter`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DecompounderOptions {
+    /// The words that compounds are made of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_list: Option<Vec<String>>,
+    /// A table to read the words from instead, which is loaded into `word_list` by whatever
+    /// creates the tokenizer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_list_table: Option<String>,
+    /// The column of `word_list_table` that has the words
+    #[serde(default = "default_word_list_column")]
+    pub word_list_column: String,
+    /// The fewest characters a token must have to be decompounded
+    #[serde(default = "default_min_word_size")]
+    pub min_word_size: usize,
+    /// The fewest characters in a subword
+    #[serde(default = "default_min_subword_size")]
+    pub min_subword_size: usize,
+    /// The most characters in a subword
+    #[serde(default = "default_max_subword_size")]
+    pub max_subword_size: usize,
+    /// Whether only the longest word starting at each character is used
+    #[serde(default)]
+    pub only_longest_match: bool,
+}
+
+fn default_word_list_column() -> String {
+    "word".to_string()
+}
+
+fn default_min_word_size() -> usize {
+    5
+}
+
+fn default_min_subword_size() -> usize {
+    2
+}
+
+fn default_max_subword_size() -> usize {
+    15
+}
+
+impl DecompounderOptions {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.word_list.is_some() == self.word_list_table.is_some() {
+            anyhow::bail!(
+                "a 'decompounder' filter needs either a 'word_list' or a 'word_list_table'"
+            );
+        }
+        if self.min_subword_size < 1 {
+            anyhow::bail!("'min_subword_size' must be at least 1");
+        }
+        if self.max_subword_size < self.min_subword_size {
+            anyhow::bail!("'max_subword_size' must be at least 'min_subword_size'");
+        }
+        Ok(())
+    }
+}
+
+/// A [`TokenFilter`] that splits compound words, like the German "kühlschrankgriff", into the
+/// words they're made of, "kühlschrank", "kühl", "schrank", and "griff", according to a word
+/// list.
+///
+/// The original token is kept, and the words found in it are given its position, so they can be
+/// searched for on their own without changing how phrases match.
+#[derive(Clone, Debug)]
+pub struct DecompounderFilter {
+    words: Arc<HashSet<String>>,
+    options: Arc<DecompounderOptions>,
+}
+
+impl DecompounderFilter {
+    pub fn new(options: DecompounderOptions) -> Self {
+        let words = options
+            .word_list
+            .iter()
+            .flatten()
+            .map(|word| word.to_lowercase())
+            .collect();
+        Self {
+            words: Arc::new(words),
+            options: Arc::new(options),
+        }
+    }
+}
+
+impl TokenFilter for DecompounderFilter {
+    type Tokenizer<T: Tokenizer> = DecompounderFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> DecompounderFilterWrapper<T> {
+        DecompounderFilterWrapper {
+            words: self.words,
+            options: self.options,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DecompounderFilterWrapper<T> {
+    words: Arc<HashSet<String>>,
+    options: Arc<DecompounderOptions>,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for DecompounderFilterWrapper<T> {
+    type TokenStream<'a> = DecompounderFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        DecompounderFilterStream {
+            words: &self.words,
+            options: &self.options,
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct DecompounderFilterStream<'a, T> {
+    words: &'a HashSet<String>,
+    options: &'a DecompounderOptions,
+    tail: T,
+    /// Subwords waiting to be returned
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> DecompounderFilterStream<'_, T> {
+    /// The subwords of the token that was just read from `tail` into `pending`
+    fn decompound(&mut self) {
+        let token = self.tail.token();
+        let text = token.text.to_lowercase();
+        // the byte offset of every character, and of the end of the text
+        let boundaries = text
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([text.len()])
+            .collect::<Vec<_>>();
+        let len = boundaries.len() - 1;
+        if len < self.options.min_word_size {
+            return;
+        }
+
+        for start in 0..len {
+            let lengths = self.options.min_subword_size..=self.options.max_subword_size;
+            let mut matches = lengths
+                .filter(|length| start + length <= len)
+                .map(|length| &text[boundaries[start]..boundaries[start + length]])
+                .filter(|subword| subword.len() < text.len() && self.words.contains(*subword))
+                .collect::<Vec<_>>();
+            if self.options.only_longest_match && matches.len() > 1 {
+                // the lengths are in ascending order, so the longest match is the last one
+                let shorter = matches.len() - 1;
+                matches.drain(..shorter);
+            }
+
+            for subword in matches {
+                if !self.pending.iter().any(|pending| pending.text == subword) {
+                    self.pending.push_back(Token {
+                        text: subword.to_string(),
+                        ..token.clone()
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<T: TokenStream> TokenStream for DecompounderFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if let Some(token) = self.pending.pop_front() {
+            self.token = token;
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+
+        self.token = self.tail.token().clone();
+        self.decompound();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+
+    fn options(words: &[&str]) -> DecompounderOptions {
+        serde_json::from_value(serde_json::json!({ "word_list": words })).unwrap()
+    }
+
+    fn tokens(options: DecompounderOptions, text: &str) -> Vec<(String, usize)> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(DecompounderFilter::new(options))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        tokens
+    }
+
+    fn expected(tokens: &[(&str, usize)]) -> Vec<(String, usize)> {
+        tokens
+            .iter()
+            .map(|(text, position)| (text.to_string(), *position))
+            .collect()
+    }
+
+    #[rstest]
+    fn test_decompound() {
+        let words = ["Kühl", "Schrank", "Kühlschrank", "Griff"];
+        assert_eq!(
+            tokens(options(&words), "Kühlschrankgriff aus Edelstahl"),
+            expected(&[
+                ("kühlschrankgriff", 0),
+                ("kühl", 0),
+                ("kühlschrank", 0),
+                ("schrank", 0),
+                ("griff", 0),
+                ("aus", 1),
+                ("edelstahl", 2),
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_only_longest_match() {
+        let options = DecompounderOptions {
+            only_longest_match: true,
+            ..options(&["kühl", "schrank", "kühlschrank", "griff"])
+        };
+        assert_eq!(
+            tokens(options, "Kühlschrankgriff"),
+            expected(&[
+                ("kühlschrankgriff", 0),
+                ("kühlschrank", 0),
+                ("schrank", 0),
+                ("griff", 0),
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_offsets() {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(DecompounderFilter::new(options(&["kühl", "schrank"])))
+            .build();
+        let mut stream = analyzer.token_stream("ein Kühlschrank");
+        let mut offsets = vec![];
+        while let Some(token) = stream.next() {
+            offsets.push((token.text.clone(), token.offset_from, token.offset_to));
+        }
+        // a subword points at the whole of its compound
+        assert_eq!(
+            offsets,
+            vec![
+                ("ein".to_string(), 0, 3),
+                ("kühlschrank".to_string(), 4, 16),
+                ("kühl".to_string(), 4, 16),
+                ("schrank".to_string(), 4, 16)
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_short_words_are_not_decompounded() {
+        assert_eq!(
+            tokens(options(&["ab", "cd"]), "abcd"),
+            expected(&[("abcd", 0)])
+        );
+    }
+
+    #[rstest]
+    fn test_invalid_options() {
+        let mut options = options(&["kühl"]);
+        assert!(options.validate().is_ok());
+        options.word_list_table = Some("words".into());
+        assert!(options.validate().is_err());
+        options.word_list = None;
+        assert!(options.validate().is_ok());
+        options.max_subword_size = 1;
+        assert!(options.validate().is_err());
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// The configuration of an [`EdgeNgramFilter`]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EdgeNgramOptions {
+    /// The fewest characters in a prefix, at least 1
+    #[serde(default = "default_min_gram")]
+    pub min_gram: usize,
+    /// The most characters in a prefix
+    #[serde(default = "default_max_gram")]
+    pub max_gram: usize,
+    /// Whether the whole token is kept when it's longer than `max_gram` or shorter than
+    /// `min_gram`
+    #[serde(default)]
+    pub preserve_original: bool,
+}
+
+fn default_min_gram() -> usize {
+    1
+}
+
+fn default_max_gram() -> usize {
+    2
+}
+
+impl EdgeNgramOptions {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.min_gram < 1 {
+            anyhow::bail!("'min_gram' must be at least 1");
+        }
+        if self.max_gram < self.min_gram {
+            anyhow::bail!("'max_gram' must be at least 'min_gram'");
+        }
+        Ok(())
+    }
+}
+
+/// A [`TokenFilter`] that replaces each token with its prefixes, from `min_gram` to `max_gram`
+/// characters long, for search-as-you-type.
+///
+/// Unlike the ngram tokenizer, the prefixes are of each word rather than of the whole text, and
+/// they're all given their word's position.
+#[derive(Clone, Copy, Debug)]
+pub struct EdgeNgramFilter {
+    options: EdgeNgramOptions,
+}
+
+impl EdgeNgramFilter {
+    pub fn new(options: EdgeNgramOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl TokenFilter for EdgeNgramFilter {
+    type Tokenizer<T: Tokenizer> = EdgeNgramFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> EdgeNgramFilterWrapper<T> {
+        EdgeNgramFilterWrapper {
+            options: self.options,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EdgeNgramFilterWrapper<T> {
+    options: EdgeNgramOptions,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for EdgeNgramFilterWrapper<T> {
+    type TokenStream<'a> = EdgeNgramFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        EdgeNgramFilterStream {
+            options: self.options,
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct EdgeNgramFilterStream<T> {
+    options: EdgeNgramOptions,
+    tail: T,
+    /// Prefixes waiting to be returned
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> EdgeNgramFilterStream<T> {
+    /// The prefixes of the next token from `tail` into `pending`
+    fn prefixes(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let token = self.tail.token();
+        let ends = token
+            .text
+            .char_indices()
+            .map(|(start, c)| start + c.len_utf8())
+            .skip(self.options.min_gram - 1)
+            .take(self.options.max_gram - self.options.min_gram + 1);
+        for end in ends {
+            self.pending.push_back(Token {
+                text: token.text[..end].to_string(),
+                ..token.clone()
+            });
+        }
+
+        let len = token.text.chars().count();
+        if self.options.preserve_original
+            && (len < self.options.min_gram || len > self.options.max_gram)
+        {
+            self.pending.push_back(token.clone());
+        }
+        true
+    }
+}
+
+impl<T: TokenStream> TokenStream for EdgeNgramFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.pending.is_empty() {
+            if !self.prefixes() {
+                return false;
+            }
+        }
+
+        match self.pending.pop_front() {
+            Some(token) => {
+                self.token = token;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
+
+    fn tokens(options: EdgeNgramOptions, text: &str) -> Vec<(String, usize)> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(EdgeNgramFilter::new(options))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        tokens
+    }
+
+    fn expected(tokens: &[(&str, usize)]) -> Vec<(String, usize)> {
+        tokens
+            .iter()
+            .map(|(text, position)| (text.to_string(), *position))
+            .collect()
+    }
+
+    #[rstest]
+    fn test_prefixes() {
+        let options = EdgeNgramOptions {
+            min_gram: 2,
+            max_gram: 4,
+            preserve_original: false,
+        };
+        assert_eq!(
+            tokens(options, "a café keyboard"),
+            expected(&[
+                ("ca", 1),
+                ("caf", 1),
+                ("café", 1),
+                ("ke", 2),
+                ("key", 2),
+                ("keyb", 2)
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_preserve_original() {
+        let options = EdgeNgramOptions {
+            min_gram: 2,
+            max_gram: 3,
+            preserve_original: true,
+        };
+        assert_eq!(
+            tokens(options, "a keyboard"),
+            expected(&[("a", 0), ("ke", 1), ("key", 1), ("keyboard", 1)])
+        );
+    }
+
+    #[rstest]
+    fn test_offsets() {
+        let options = EdgeNgramOptions {
+            min_gram: 2,
+            max_gram: 3,
+            preserve_original: false,
+        };
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(EdgeNgramFilter::new(options))
+            .build();
+        let mut stream = analyzer.token_stream("a keyboard");
+        let mut offsets = vec![];
+        while let Some(token) = stream.next() {
+            offsets.push((token.offset_from, token.offset_to));
+        }
+        // a prefix points at the whole of its word, so it's highlighted as the word
+        assert_eq!(offsets, vec![(2, 10), (2, 10)]);
+    }
+
+    #[rstest]
+    fn test_invalid_options() {
+        let options = EdgeNgramOptions {
+            min_gram: 3,
+            max_gram: 2,
+            preserve_original: false,
+        };
+        assert!(options.validate().is_err());
+    }
+}
@@ -15,15 +15,23 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+pub mod char_filters;
 pub mod cjk;
 pub mod code;
+pub mod decompounder;
+pub mod edge_ngram;
 #[cfg(feature = "icu")]
 pub mod icu;
 pub mod lindera;
 pub mod manager;
+pub mod normalization;
+pub mod phonetic;
+pub mod shingle;
+pub mod synonyms;
+pub mod word_delimiter;
 
 use tantivy::tokenizer::{
-    LowerCaser, RawTokenizer, RemoveLongFilter, TextAnalyzer, TokenizerManager,
+    AsciiFoldingFilter, LowerCaser, RawTokenizer, RemoveLongFilter, TextAnalyzer, TokenizerManager,
 };
 use tracing::debug;
 
@@ -31,18 +39,20 @@ pub use manager::{SearchNormalizer, SearchTokenizer};
 
 pub const DEFAULT_REMOVE_TOKEN_LENGTH: usize = 255;
 
-pub fn create_tokenizer_manager(search_tokenizers: Vec<SearchTokenizer>) -> TokenizerManager {
+/// Build a [`TokenizerManager`] with each of `search_tokenizers` registered under the name it's
+/// paired with, which is the name that fields refer to it by.
+///
+/// Any [`SearchTokenizer::Analyzer`] must already be replaced with the named analyzer's
+/// definition, as that lives in the database.
+pub fn create_tokenizer_manager(
+    search_tokenizers: Vec<(String, SearchTokenizer)>,
+) -> TokenizerManager {
     let tokenizer_manager = TokenizerManager::default();
 
-    for search_tokenizer in search_tokenizers {
-        let tokenizer_option = search_tokenizer.to_tantivy_tokenizer();
-
-        if let Some(text_analyzer) = tokenizer_option {
-            debug!(
-                tokenizer_name = &search_tokenizer.name(),
-                "registering tokenizer",
-            );
-            tokenizer_manager.register(&search_tokenizer.name(), text_analyzer);
+    for (name, search_tokenizer) in search_tokenizers {
+        if let Some(text_analyzer) = search_tokenizer.to_tantivy_tokenizer() {
+            debug!(tokenizer_name = &name, "registering tokenizer");
+            tokenizer_manager.register(&name, text_analyzer);
         }
     }
 
@@ -57,8 +67,14 @@ pub fn create_normalizer_manager() -> TokenizerManager {
         .filter(LowerCaser)
         .filter(RemoveLongFilter::limit(DEFAULT_REMOVE_TOKEN_LENGTH))
         .build();
+    let ascii_folding_tokenizer = TextAnalyzer::builder(RawTokenizer::default())
+        .filter(LowerCaser)
+        .filter(AsciiFoldingFilter)
+        .filter(RemoveLongFilter::limit(DEFAULT_REMOVE_TOKEN_LENGTH))
+        .build();
     let tokenizer_manager = TokenizerManager::new();
     tokenizer_manager.register("raw", raw_tokenizer);
     tokenizer_manager.register("lowercase", lower_case_tokenizer);
+    tokenizer_manager.register("ascii_folding", ascii_folding_tokenizer);
     tokenizer_manager
 }
@@ -21,19 +21,29 @@ use std::fmt::Write;
 #[cfg(feature = "icu")]
 use crate::icu::ICUTokenizer;
 use crate::{
+    char_filters::{CharFilter, CharFilterOptions, CharFilterTokenizer},
     cjk::ChineseTokenizer,
     code::CodeTokenizer,
+    decompounder::{DecompounderFilter, DecompounderOptions},
+    edge_ngram::{EdgeNgramFilter, EdgeNgramOptions},
     lindera::{LinderaChineseTokenizer, LinderaJapaneseTokenizer, LinderaKoreanTokenizer},
+    normalization::{UnicodeNormalizationFilter, UnicodeNormalizationForm},
+    phonetic::{PhoneticFilter, PhoneticOptions},
+    shingle::{ShingleFilter, ShingleOptions},
+    synonyms::SynonymFilter,
+    word_delimiter::{WordDelimiterFilter, WordDelimiterOptions},
     DEFAULT_REMOVE_TOKEN_LENGTH,
 };
 use anyhow::Result;
+use once_cell::sync::Lazy;
 use serde::de::{self, Deserializer};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use strum::AsRefStr;
 use tantivy::tokenizer::{
     AsciiFoldingFilter, Language, LowerCaser, NgramTokenizer, RawTokenizer, RegexTokenizer,
-    RemoveLongFilter, SimpleTokenizer, Stemmer, StopWordFilter, TextAnalyzer, WhitespaceTokenizer,
+    RemoveLongFilter, SimpleTokenizer, Stemmer, StopWordFilter, TextAnalyzer, TokenStream,
+    Tokenizer, WhitespaceTokenizer,
 };
 use tantivy_jieba;
 
@@ -44,6 +54,15 @@ pub struct SearchTokenizerFilters {
     pub stemmer: Option<Language>,
     pub stopwords_language: Option<Language>,
     pub stopwords: Option<Vec<String>>,
+    pub synonyms: Option<Vec<String>>,
+    pub ascii_folding: Option<bool>,
+    pub unicode_normalization: Option<UnicodeNormalizationForm>,
+    pub phonetic: Option<PhoneticOptions>,
+    pub shingle: Option<ShingleOptions>,
+    pub edge_ngram: Option<EdgeNgramOptions>,
+    pub char_filters: Option<Vec<CharFilterOptions>>,
+    pub word_delimiter: Option<WordDelimiterOptions>,
+    pub decompounder: Option<DecompounderOptions>,
 }
 
 impl SearchTokenizerFilters {
@@ -52,14 +71,21 @@ impl SearchTokenizerFilters {
     ///
     /// This should be used for declaring the "key field" in an index.  It can be used for other
     /// text types that don't want tokenization too.
-    pub const fn keyword() -> &'static Self {
-        &SearchTokenizerFilters {
+    pub fn keyword() -> &'static Self {
+        static KEYWORD: Lazy<SearchTokenizerFilters> = Lazy::new(|| SearchTokenizerFilters {
             remove_long: Some(usize::MAX),
             lowercase: Some(false),
-            stemmer: None,
-            stopwords_language: None,
-            stopwords: None,
-        }
+            ..Default::default()
+        });
+        &KEYWORD
+    }
+
+    /// Returns a [`SearchTokenizerFilter`] instance with no filters configured.
+    ///
+    /// This is used by tokenizers whose filters are defined elsewhere, such as named analyzers.
+    pub fn none() -> &'static Self {
+        static NONE: Lazy<SearchTokenizerFilters> = Lazy::new(SearchTokenizerFilters::default);
+        &NONE
     }
 
     fn from_json_value(value: &serde_json::Value) -> Result<Self, anyhow::Error> {
@@ -100,6 +126,103 @@ impl SearchTokenizerFilters {
                 anyhow::anyhow!("stopwords tokenizer requires a valid 'stopwords' field")
             })?);
         }
+        if let Some(synonyms) = value.get("synonyms") {
+            let synonyms: Vec<String> = serde_json::from_value(synonyms.clone()).map_err(|_| {
+                anyhow::anyhow!(
+                    "synonyms tokenizer requires a 'synonyms' field that is an array of rules"
+                )
+            })?;
+            // make sure the rules are valid now, rather than when the tokenizer is first used
+            SynonymFilter::new(&synonyms)?;
+            filters.synonyms = Some(synonyms);
+        }
+        if let Some(ascii_folding) = value.get("ascii_folding") {
+            filters.ascii_folding = Some(ascii_folding.as_bool().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "an 'ascii_folding' value passed to the pg_search tokenizer configuration \
+                     must be of type bool, found: {ascii_folding:#?}"
+                )
+            })?);
+        }
+        if let Some(unicode_normalization) = value.get("unicode_normalization") {
+            filters.unicode_normalization = Some(
+                serde_json::from_value(unicode_normalization.clone()).map_err(|_| {
+                    anyhow::anyhow!(
+                        "a 'unicode_normalization' value passed to the pg_search tokenizer \
+                         configuration must be one of 'nfc', 'nfkc', or 'nfkd', found: \
+                         {unicode_normalization:#?}"
+                    )
+                })?,
+            );
+        }
+        if let Some(phonetic) = value.get("phonetic") {
+            filters.phonetic = Some(serde_json::from_value(phonetic.clone()).map_err(|e| {
+                anyhow::anyhow!(
+                    "a 'phonetic' value passed to the pg_search tokenizer configuration must \
+                     be an object with an 'encoder' of 'soundex', 'metaphone', \
+                     'double_metaphone', or 'beider_morse', and an optional 'mode' of \
+                     'replace' or 'inject': {e}"
+                )
+            })?);
+        }
+        if let Some(shingle) = value.get("shingle") {
+            let shingle: ShingleOptions = serde_json::from_value(shingle.clone()).map_err(|e| {
+                anyhow::anyhow!(
+                    "a 'shingle' value passed to the pg_search tokenizer configuration must \
+                         be an object of shingle options: {e}"
+                )
+            })?;
+            shingle.validate()?;
+            filters.shingle = Some(shingle);
+        }
+        if let Some(edge_ngram) = value.get("edge_ngram") {
+            let edge_ngram: EdgeNgramOptions =
+                serde_json::from_value(edge_ngram.clone()).map_err(|e| {
+                    anyhow::anyhow!(
+                        "an 'edge_ngram' value passed to the pg_search tokenizer configuration \
+                         must be an object of edge ngram options: {e}"
+                    )
+                })?;
+            edge_ngram.validate()?;
+            filters.edge_ngram = Some(edge_ngram);
+        }
+        if let Some(char_filters) = value.get("char_filters") {
+            let char_filters: Vec<CharFilterOptions> = serde_json::from_value(char_filters.clone())
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "a 'char_filters' value passed to the pg_search tokenizer configuration \
+                         must be an array of objects with a 'type' of 'html_strip', 'mapping', \
+                         or 'pattern_replace': {e}"
+                    )
+                })?;
+            // make sure the patterns are valid now, rather than when the tokenizer is first used
+            for options in &char_filters {
+                CharFilter::new(options)?;
+            }
+            filters.char_filters = Some(char_filters);
+        }
+        if let Some(word_delimiter) = value.get("word_delimiter") {
+            let word_delimiter: WordDelimiterOptions =
+                serde_json::from_value(word_delimiter.clone()).map_err(|e| {
+                    anyhow::anyhow!(
+                        "a 'word_delimiter' value passed to the pg_search tokenizer configuration \
+                         must be an object of word delimiter options: {e}"
+                    )
+                })?;
+            word_delimiter.validate()?;
+            filters.word_delimiter = Some(word_delimiter);
+        }
+        if let Some(decompounder) = value.get("decompounder") {
+            let decompounder: DecompounderOptions = serde_json::from_value(decompounder.clone())
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "a 'decompounder' value passed to the pg_search tokenizer configuration \
+                         must be an object of decompounder options: {e}"
+                    )
+                })?;
+            decompounder.validate()?;
+            filters.decompounder = Some(decompounder);
+        }
 
         Ok(filters)
     }
@@ -124,6 +247,61 @@ impl SearchTokenizerFilters {
             );
             enclosing.insert("stopwords".to_string(), v);
         }
+
+        if let Some(synonyms) = self.synonyms.as_ref() {
+            let v = serde_json::Value::Array(
+                synonyms
+                    .iter()
+                    .map(|s| serde_json::Value::String(s.clone()))
+                    .collect(),
+            );
+            enclosing.insert("synonyms".to_string(), v);
+        }
+
+        if let Some(value) = self.ascii_folding {
+            let v = serde_json::Value::Bool(value);
+            enclosing.insert("ascii_folding".to_string(), v);
+        }
+
+        if let Some(value) = self.unicode_normalization {
+            let v = serde_json::to_value(value).expect("normalization form should serialize");
+            enclosing.insert("unicode_normalization".to_string(), v);
+        }
+
+        if let Some(value) = self.phonetic {
+            let v = serde_json::to_value(value).expect("phonetic options should serialize");
+            enclosing.insert("phonetic".to_string(), v);
+        }
+
+        if let Some(value) = self.shingle.as_ref() {
+            let v = serde_json::to_value(value).expect("shingle options should serialize");
+            enclosing.insert("shingle".to_string(), v);
+        }
+
+        if let Some(value) = self.edge_ngram {
+            let v = serde_json::to_value(value).expect("edge ngram options should serialize");
+            enclosing.insert("edge_ngram".to_string(), v);
+        }
+
+        if let Some(value) = self.char_filters.as_ref() {
+            let v = serde_json::to_value(value).expect("char filters should serialize");
+            enclosing.insert("char_filters".to_string(), v);
+        }
+
+        if let Some(value) = self.word_delimiter {
+            let v = serde_json::to_value(value).expect("word delimiter options should serialize");
+            enclosing.insert("word_delimiter".to_string(), v);
+        }
+
+        if let Some(value) = self.decompounder.as_ref() {
+            let mut value = value.clone();
+            if value.word_list_table.is_some() {
+                // the words were loaded from the table, and aren't part of the configuration
+                value.word_list = None;
+            }
+            let v = serde_json::to_value(value).expect("decompounder options should serialize");
+            enclosing.insert("decompounder".to_string(), v);
+        }
     }
 
     fn name_suffix(&self) -> String {
@@ -162,6 +340,89 @@ impl SearchTokenizerFilters {
             is_empty = false;
         }
 
+        if let Some(value) = self.synonyms.as_ref() {
+            write!(buffer, "{}synonyms={value:?}", sep(is_empty)).unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.ascii_folding {
+            write!(buffer, "{}ascii_folding={value}", sep(is_empty)).unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.unicode_normalization {
+            write!(buffer, "{}unicode_normalization={value:?}", sep(is_empty)).unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.phonetic {
+            write!(
+                buffer,
+                "{}phonetic={:?}:{:?}",
+                sep(is_empty),
+                value.encoder,
+                value.mode
+            )
+            .unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.shingle.as_ref() {
+            write!(
+                buffer,
+                "{}shingle={}..{}:{}:{:?}",
+                sep(is_empty),
+                value.min_shingle_size,
+                value.max_shingle_size,
+                value.output_unigrams,
+                value.token_separator
+            )
+            .unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.edge_ngram {
+            write!(
+                buffer,
+                "{}edge_ngram={}..{}:{}",
+                sep(is_empty),
+                value.min_gram,
+                value.max_gram,
+                value.preserve_original
+            )
+            .unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.char_filters.as_ref() {
+            write!(buffer, "{}char_filters={value:?}", sep(is_empty)).unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.word_delimiter {
+            write!(buffer, "{}word_delimiter={value:?}", sep(is_empty)).unwrap();
+            is_empty = false;
+        }
+
+        if let Some(value) = self.decompounder.as_ref() {
+            // a table's words aren't included, so the name doesn't change when they're loaded
+            let words = match value.word_list_table.as_ref() {
+                Some(table) => format!("{table}.{}", value.word_list_column),
+                None => format!("{:?}", value.word_list.as_deref().unwrap_or_default()),
+            };
+            write!(
+                buffer,
+                "{}decompounder={words}:{}..{}:{}:{}",
+                sep(is_empty),
+                value.min_subword_size,
+                value.max_subword_size,
+                value.min_word_size,
+                value.only_longest_match
+            )
+            .unwrap();
+            is_empty = false;
+        }
+
         if is_empty {
             "".into()
         } else {
@@ -197,6 +458,71 @@ impl SearchTokenizerFilters {
             .as_ref()
             .map(|stop_words| StopWordFilter::remove(stop_words.clone()))
     }
+
+    fn synonyms(&self) -> Option<SynonymFilter> {
+        self.synonyms.as_ref().map(|synonyms| {
+            // the words of the rules go through the same filters as the tokens they're compared
+            // with, so that a rule like "TV, Television" matches lowercased tokens
+            let mut normalizer = TextAnalyzer::builder(self.char_filtered(RawTokenizer::default()))
+                .filter(self.unicode_normalization())
+                .filter(self.lower_caser())
+                .filter(self.ascii_folding())
+                .build();
+            SynonymFilter::with_normalizer(synonyms, |word| {
+                let mut stream = normalizer.token_stream(word);
+                match stream.next() {
+                    Some(token) => token.text.clone(),
+                    None => word.to_string(),
+                }
+            })
+            .expect("synonym rules should have been validated")
+        })
+    }
+
+    fn ascii_folding(&self) -> Option<AsciiFoldingFilter> {
+        match self.ascii_folding {
+            Some(true) => Some(AsciiFoldingFilter),
+            _ => None,
+        }
+    }
+
+    fn unicode_normalization(&self) -> Option<UnicodeNormalizationFilter> {
+        self.unicode_normalization
+            .map(UnicodeNormalizationFilter::new)
+    }
+
+    fn phonetic(&self) -> Option<PhoneticFilter> {
+        self.phonetic.map(PhoneticFilter::new)
+    }
+
+    fn shingle(&self) -> Option<ShingleFilter> {
+        self.shingle.clone().map(ShingleFilter::new)
+    }
+
+    fn edge_ngram(&self) -> Option<EdgeNgramFilter> {
+        self.edge_ngram.map(EdgeNgramFilter::new)
+    }
+
+    fn decompounder(&self) -> Option<DecompounderFilter> {
+        self.decompounder.clone().map(DecompounderFilter::new)
+    }
+
+    fn word_delimiter(&self) -> Option<WordDelimiterFilter> {
+        self.word_delimiter.map(WordDelimiterFilter::new)
+    }
+
+    /// Wraps `tokenizer` so that the char filters rewrite its text before it's tokenized
+    fn char_filtered<T: Tokenizer>(&self, tokenizer: T) -> CharFilterTokenizer<T> {
+        let char_filters = self
+            .char_filters
+            .iter()
+            .flatten()
+            .map(|options| {
+                CharFilter::new(options).expect("char filters should have been validated")
+            })
+            .collect();
+        CharFilterTokenizer::new(tokenizer, char_filters)
+    }
 }
 
 // Serde will pick a SearchTokenizer variant based on the value of the
@@ -245,6 +571,11 @@ pub enum SearchTokenizer {
     #[strum(serialize = "icu")]
     ICUTokenizer(SearchTokenizerFilters),
     Jieba(SearchTokenizerFilters),
+
+    /// A reference to an analyzer created with `paradedb.create_analyzer()`.  Its definition
+    /// lives in the database, so pg_search copies it into each index that uses it when the index
+    /// is built, and [`SearchTokenizer::to_tantivy_tokenizer`] can't build it.
+    Analyzer(String),
 }
 
 impl Default for SearchTokenizer {
@@ -292,6 +623,7 @@ impl SearchTokenizer {
             #[cfg(feature = "icu")]
             SearchTokenizer::ICUTokenizer(_filters) => json!({ "type": "icu" }),
             SearchTokenizer::Jieba(_filters) => json!({ "type": "jieba" }),
+            SearchTokenizer::Analyzer(name) => json!({ "type": "analyzer", "name": name }),
         };
 
         // Serialize filters to the enclosing json object.
@@ -361,6 +693,17 @@ impl SearchTokenizer {
             #[cfg(feature = "icu")]
             "icu" => Ok(SearchTokenizer::ICUTokenizer(filters)),
             "jieba" => Ok(SearchTokenizer::Jieba(filters)),
+            "analyzer" => {
+                let name: String = serde_json::from_value(value["name"].clone()).map_err(|_| {
+                    anyhow::anyhow!("analyzer tokenizer requires a string 'name' field")
+                })?;
+                if filters != SearchTokenizerFilters::default() {
+                    anyhow::bail!(
+                        "filters cannot be added to analyzer '{name}', they must be part of its definition"
+                    );
+                }
+                Ok(SearchTokenizer::Analyzer(name))
+            }
             _ => Err(anyhow::anyhow!(
                 "unknown tokenizer type: {}",
                 tokenizer_type
@@ -371,12 +714,20 @@ impl SearchTokenizer {
     pub fn to_tantivy_tokenizer(&self) -> Option<tantivy::tokenizer::TextAnalyzer> {
         match self {
             SearchTokenizer::Default(filters) => Some(
-                TextAnalyzer::builder(SimpleTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(SimpleTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
 
@@ -392,144 +743,262 @@ impl SearchTokenizer {
             // that is, doesn't mutate the input tokens
             #[allow(deprecated)]
             SearchTokenizer::Raw(filters) => Some(
-                TextAnalyzer::builder(RawTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(RawTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             // Deprecated, use `raw` with `lowercase` filter instead
             SearchTokenizer::Lowercase(filters) => Some(
-                TextAnalyzer::builder(RawTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(RawTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             SearchTokenizer::WhiteSpace(filters) => Some(
-                TextAnalyzer::builder(WhitespaceTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(WhitespaceTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             SearchTokenizer::RegexTokenizer { pattern, filters } => Some(
-                TextAnalyzer::builder(RegexTokenizer::new(pattern.as_str()).unwrap())
-                    .filter(filters.remove_long_filter())
-                    .filter(filters.lower_caser())
-                    .filter(filters.stemmer())
-                    .filter(filters.stopwords_language())
-                    .filter(filters.stopwords())
-                    .build(),
-            ),
-            SearchTokenizer::Ngram {
-                min_gram,
-                max_gram,
-                prefix_only,
-                filters,
-            } => Some(
                 TextAnalyzer::builder(
-                    NgramTokenizer::new(*min_gram, *max_gram, *prefix_only)
-                        .expect("Ngram parameters should be valid parameters for NgramTokenizer"),
+                    filters.char_filtered(RegexTokenizer::new(pattern.as_str()).unwrap()),
                 )
+                .filter(filters.word_delimiter())
                 .filter(filters.remove_long_filter())
+                .filter(filters.unicode_normalization())
                 .filter(filters.lower_caser())
+                .filter(filters.decompounder())
+                .filter(filters.ascii_folding())
+                .filter(filters.synonyms())
                 .filter(filters.stemmer())
                 .filter(filters.stopwords_language())
                 .filter(filters.stopwords())
+                .filter(filters.phonetic())
+                .filter(filters.shingle())
+                .filter(filters.edge_ngram())
                 .build(),
             ),
+            SearchTokenizer::Ngram {
+                min_gram,
+                max_gram,
+                prefix_only,
+                filters,
+            } => {
+                Some(
+                    TextAnalyzer::builder(filters.char_filtered(
+                        NgramTokenizer::new(*min_gram, *max_gram, *prefix_only).expect(
+                            "Ngram parameters should be valid parameters for NgramTokenizer",
+                        ),
+                    ))
+                    .filter(filters.word_delimiter())
+                    .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
+                    .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
+                    .filter(filters.stemmer())
+                    .filter(filters.stopwords_language())
+                    .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
+                    .build(),
+                )
+            }
             SearchTokenizer::ChineseCompatible(filters) => Some(
-                TextAnalyzer::builder(ChineseTokenizer)
+                TextAnalyzer::builder(filters.char_filtered(ChineseTokenizer))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             SearchTokenizer::SourceCode(filters) => Some(
-                TextAnalyzer::builder(CodeTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(CodeTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.synonyms())
                     .filter(AsciiFoldingFilter)
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             SearchTokenizer::ChineseLindera(filters) => Some(
-                TextAnalyzer::builder(LinderaChineseTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(LinderaChineseTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             SearchTokenizer::JapaneseLindera(filters) => Some(
-                TextAnalyzer::builder(LinderaJapaneseTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(LinderaJapaneseTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             SearchTokenizer::KoreanLindera(filters) => Some(
-                TextAnalyzer::builder(LinderaKoreanTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(LinderaKoreanTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             // Deprecated, use `stemmer` filter instead
             SearchTokenizer::EnStem(filters) => Some(
-                TextAnalyzer::builder(SimpleTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(SimpleTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(Stemmer::new(Language::English))
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             // Deprecated, use `stemmer` filter instead
             SearchTokenizer::Stem { language, filters } => Some(
-                TextAnalyzer::builder(SimpleTokenizer::default())
+                TextAnalyzer::builder(filters.char_filtered(SimpleTokenizer::default()))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(Stemmer::new(*language))
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             #[cfg(feature = "icu")]
             SearchTokenizer::ICUTokenizer(filters) => Some(
-                TextAnalyzer::builder(ICUTokenizer)
+                TextAnalyzer::builder(filters.char_filtered(ICUTokenizer))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
             SearchTokenizer::Jieba(filters) => Some(
-                TextAnalyzer::builder(tantivy_jieba::JiebaTokenizer {})
+                TextAnalyzer::builder(filters.char_filtered(tantivy_jieba::JiebaTokenizer {}))
+                    .filter(filters.word_delimiter())
                     .filter(filters.remove_long_filter())
+                    .filter(filters.unicode_normalization())
                     .filter(filters.lower_caser())
+                    .filter(filters.decompounder())
+                    .filter(filters.ascii_folding())
+                    .filter(filters.synonyms())
                     .filter(filters.stemmer())
                     .filter(filters.stopwords_language())
                     .filter(filters.stopwords())
+                    .filter(filters.phonetic())
+                    .filter(filters.shingle())
+                    .filter(filters.edge_ngram())
                     .build(),
             ),
+            // the analyzer's definition isn't known here
+            SearchTokenizer::Analyzer(_) => None,
         }
     }
 
@@ -553,6 +1022,33 @@ impl SearchTokenizer {
             #[cfg(feature = "icu")]
             SearchTokenizer::ICUTokenizer(filters) => filters,
             SearchTokenizer::Jieba(filters) => filters,
+            SearchTokenizer::Analyzer(_) => SearchTokenizerFilters::none(),
+        }
+    }
+
+    /// This tokenizer's filters, so that pg_search can load the data that they read from tables,
+    /// like a decompounder's `word_list_table`.  Tokenizers without filters of their own have none.
+    pub fn filters_mut(&mut self) -> Option<&mut SearchTokenizerFilters> {
+        match self {
+            SearchTokenizer::Default(filters) => Some(filters),
+            SearchTokenizer::Keyword => None,
+            #[allow(deprecated)]
+            SearchTokenizer::Raw(filters) => Some(filters),
+            SearchTokenizer::EnStem(filters) => Some(filters),
+            SearchTokenizer::Stem { filters, .. } => Some(filters),
+            SearchTokenizer::Lowercase(filters) => Some(filters),
+            SearchTokenizer::WhiteSpace(filters) => Some(filters),
+            SearchTokenizer::RegexTokenizer { filters, .. } => Some(filters),
+            SearchTokenizer::ChineseCompatible(filters) => Some(filters),
+            SearchTokenizer::SourceCode(filters) => Some(filters),
+            SearchTokenizer::Ngram { filters, .. } => Some(filters),
+            SearchTokenizer::ChineseLindera(filters) => Some(filters),
+            SearchTokenizer::JapaneseLindera(filters) => Some(filters),
+            SearchTokenizer::KoreanLindera(filters) => Some(filters),
+            #[cfg(feature = "icu")]
+            SearchTokenizer::ICUTokenizer(filters) => Some(filters),
+            SearchTokenizer::Jieba(filters) => Some(filters),
+            SearchTokenizer::Analyzer(_) => None,
         }
     }
 }
@@ -617,6 +1113,7 @@ impl SearchTokenizer {
             #[cfg(feature = "icu")]
             SearchTokenizer::ICUTokenizer(_filters) => format!("icu{filters_suffix}"),
             SearchTokenizer::Jieba(_filters) => format!("jieba{filters_suffix}"),
+            SearchTokenizer::Analyzer(name) => format!("analyzer:{name}"),
         }
     }
 }
@@ -639,6 +1136,9 @@ pub enum SearchNormalizer {
     Raw,
     #[serde(rename = "lowercase")]
     Lowercase,
+    /// Lowercases, and folds characters such as "é" into their ASCII equivalents
+    #[serde(rename = "ascii_folding")]
+    AsciiFolding,
 }
 
 impl SearchNormalizer {
@@ -646,6 +1146,7 @@ impl SearchNormalizer {
         match self {
             SearchNormalizer::Raw => "raw",
             SearchNormalizer::Lowercase => "lowercase",
+            SearchNormalizer::AsciiFolding => "ascii_folding",
         }
     }
 }
@@ -666,6 +1167,15 @@ mod tests {
             stemmer: None,
             stopwords_language: None,
             stopwords: None,
+            synonyms: None,
+            ascii_folding: None,
+            unicode_normalization: None,
+            phonetic: None,
+            shingle: None,
+            edge_ngram: None,
+            char_filters: None,
+            word_delimiter: None,
+            decompounder: None,
         });
         assert_eq!(
             tokenizer.name(),
@@ -696,11 +1206,306 @@ mod tests {
                     stemmer: None,
                     stopwords_language: None,
                     stopwords: None,
+                    synonyms: None,
+                    ascii_folding: None,
+                    unicode_normalization: None,
+                    phonetic: None,
+                    shingle: None,
+                    edge_ngram: None,
+                    char_filters: None,
+                    word_delimiter: None,
+                    decompounder: None,
                 }
             }
         );
     }
 
+    #[rstest]
+    fn test_normalization_filters() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "default",
+            "ascii_folding": true,
+            "unicode_normalization": "nfkc"
+        }"#;
+
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            "default[ascii_folding=true,unicode_normalization=Nfkc]"
+        );
+        assert_eq!(
+            SearchTokenizer::from_json_value(&tokenizer.to_json_value()).unwrap(),
+            tokenizer
+        );
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut stream = analyzer.token_stream("Crème Brûlée");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push(token.text.clone());
+        }
+        assert_eq!(tokens, vec!["creme", "brulee"]);
+
+        let json = r#"{"type": "default", "unicode_normalization": "nfx"}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_synonym_rules_are_normalized() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "default",
+            "ascii_folding": true,
+            "synonyms": ["TV, Télévision"]
+        }"#;
+
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut stream = analyzer.token_stream("Big TV");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push(token.text.clone());
+        }
+        assert_eq!(tokens, vec!["big", "tv", "television"]);
+    }
+
+    #[rstest]
+    fn test_phonetic_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "whitespace",
+            "phonetic": {"encoder": "double_metaphone", "mode": "inject"}
+        }"#;
+
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            "whitespace[phonetic=DoubleMetaphone:Inject]"
+        );
+        assert_eq!(
+            SearchTokenizer::from_json_value(&tokenizer.to_json_value()).unwrap(),
+            tokenizer
+        );
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut stream = analyzer.token_stream("Smyth");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        assert_eq!(tokens[0], ("smyth".to_string(), 0));
+        assert!(tokens.contains(&("SM0".to_string(), 0)));
+
+        let json = r#"{"type": "default", "phonetic": {"encoder": "nysiis"}}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_shingle_and_edge_ngram_filters() {
+        use tantivy::tokenizer::TokenStream;
+
+        let tokens = |json: &str, text: &str| {
+            let tokenizer =
+                SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+            let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+            let mut stream = analyzer.token_stream(text);
+            let mut tokens = vec![];
+            while let Some(token) = stream.next() {
+                tokens.push((token.text.clone(), token.position));
+            }
+            tokens
+        };
+
+        // shingles are made after stemming and stopwords
+        let json = r#"{
+            "type": "default",
+            "stemmer": "English",
+            "stopwords_language": "English",
+            "shingle": {}
+        }"#;
+        assert_eq!(
+            tokens(json, "The Running Shoes"),
+            vec![
+                ("run".to_string(), 1),
+                ("run shoe".to_string(), 1),
+                ("shoe".to_string(), 2)
+            ]
+        );
+
+        let json = r#"{"type": "default", "edge_ngram": {"min_gram": 1, "max_gram": 3}}"#;
+        assert_eq!(
+            tokens(json, "Sleek Shoes"),
+            vec![
+                ("s".to_string(), 0),
+                ("sl".to_string(), 0),
+                ("sle".to_string(), 0),
+                ("s".to_string(), 1),
+                ("sh".to_string(), 1),
+                ("sho".to_string(), 1)
+            ]
+        );
+
+        let json = r#"{
+            "type": "whitespace",
+            "shingle": {"max_shingle_size": 3, "output_unigrams": false, "token_separator": "_"},
+            "edge_ngram": {"max_gram": 10, "preserve_original": true}
+        }"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            "whitespace[shingle=2..3:false:\"_\",edge_ngram=1..10:true]"
+        );
+        assert_eq!(
+            SearchTokenizer::from_json_value(&tokenizer.to_json_value()).unwrap(),
+            tokenizer
+        );
+
+        let json = r#"{"type": "default", "shingle": {"min_shingle_size": 1}}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+        let json = r#"{"type": "default", "edge_ngram": {"min_gram": 0}}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_char_filters() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "default",
+            "stemmer": "English",
+            "char_filters": [
+                {"type": "html_strip"},
+                {"type": "mapping", "mappings": [["&", " and "]]},
+                {"type": "pattern_replace", "pattern": "(\\d)-(\\d)", "replacement": "$1$2"}
+            ]
+        }"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            SearchTokenizer::from_json_value(&tokenizer.to_json_value()).unwrap(),
+            tokenizer
+        );
+
+        // offsets point into the original text, not the filtered one
+        let text = "<p>Running&amp;<b>hiking</b> model 55-12</p>";
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((
+                token.text.clone(),
+                text[token.offset_from..token.offset_to].to_string(),
+            ));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("run".to_string(), "Running".to_string()),
+                ("and".to_string(), "&amp;".to_string()),
+                ("hike".to_string(), "hiking".to_string()),
+                ("model".to_string(), "model".to_string()),
+                ("5512".to_string(), "55-12".to_string()),
+            ]
+        );
+
+        let json =
+            r#"{"type": "default", "char_filters": [{"type": "pattern_replace", "pattern": "("}]}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+        let json = r#"{"type": "default", "char_filters": [{"type": "unknown"}]}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_word_delimiter_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        // parts are split before lowercasing, and are then lowercased and stemmed like any other
+        let json = r#"{
+            "type": "whitespace",
+            "stemmer": "English",
+            "word_delimiter": {"preserve_original": true, "catenate_all": true}
+        }"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut stream = analyzer.token_stream("PowerShots XPS13-9310");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("powershot".to_string(), 0),
+                ("power".to_string(), 0),
+                ("shot".to_string(), 1),
+                ("xps13-9310".to_string(), 2),
+                ("xps139310".to_string(), 2),
+                ("xps".to_string(), 2),
+                ("13".to_string(), 3),
+                ("9310".to_string(), 4),
+            ]
+        );
+
+        let json = r#"{"type": "default", "word_delimiter": {"generate_word_parts": false, "generate_number_parts": false}}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_decompounder_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "stem",
+            "language": "German",
+            "decompounder": {"word_list": ["kühlschrank", "griff", "griffe"], "only_longest_match": true}
+        }"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut stream = analyzer.token_stream("Kühlschrankgriffe");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        // the subwords are stemmed like any other token
+        assert_eq!(
+            tokens,
+            vec![
+                ("kuhlschrankgriff".to_string(), 0),
+                ("kuhlschrank".to_string(), 0),
+                ("griff".to_string(), 0),
+            ]
+        );
+
+        // a table's words are loaded later, and don't change the tokenizer's name or config
+        let json = r#"{"type": "default", "decompounder": {"word_list_table": "public.words"}}"#;
+        let mut tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        let name = tokenizer.name();
+        let config = tokenizer.to_json_value();
+        tokenizer
+            .filters_mut()
+            .unwrap()
+            .decompounder
+            .as_mut()
+            .unwrap()
+            .word_list = Some(vec!["kühl".into()]);
+        assert_eq!(tokenizer.name(), name);
+        assert_eq!(tokenizer.to_json_value(), config);
+
+        let json = r#"{"type": "default", "decompounder": {"min_subword_size": 3}}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
     #[rstest]
     fn test_regexizer() {
         let json = r#"{
@@ -716,6 +1521,15 @@ mod tests {
                 stemmer: None,
                 stopwords_language: None,
                 stopwords: None,
+                synonyms: None,
+                ascii_folding: None,
+                unicode_normalization: None,
+                phonetic: None,
+                shingle: None,
+                edge_ngram: None,
+                char_filters: None,
+                word_delimiter: None,
+                decompounder: None,
             },
         };
 
@@ -729,6 +1543,11 @@ mod tests {
     fn test_search_normalizer() {
         assert_eq!(SearchNormalizer::Lowercase.name(), "lowercase");
         assert_ne!(SearchNormalizer::Raw, SearchNormalizer::Lowercase);
+        assert_eq!(SearchNormalizer::AsciiFolding.name(), "ascii_folding");
+        assert_eq!(
+            serde_json::from_value::<SearchNormalizer>("ascii_folding".into()).unwrap(),
+            SearchNormalizer::AsciiFolding
+        );
     }
 
     #[rstest]
@@ -756,6 +1575,15 @@ mod tests {
                     "花朵".to_string(),
                     "公园".to_string()
                 ]),
+                synonyms: None,
+                ascii_folding: None,
+                unicode_normalization: None,
+                phonetic: None,
+                shingle: None,
+                edge_ngram: None,
+                char_filters: None,
+                word_delimiter: None,
+                decompounder: None,
             })
         );
 
@@ -804,6 +1632,15 @@ mod tests {
                 stemmer: None,
                 stopwords_language: Some(Language::English),
                 stopwords: None,
+                synonyms: None,
+                ascii_folding: None,
+                unicode_normalization: None,
+                phonetic: None,
+                shingle: None,
+                edge_ngram: None,
+                char_filters: None,
+                word_delimiter: None,
+                decompounder: None,
             })
         );
 
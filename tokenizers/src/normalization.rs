@@ -0,0 +1,141 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+use unicode_normalization::{is_nfc, is_nfkc, is_nfkd, UnicodeNormalization};
+
+/// The Unicode normalization forms a [`UnicodeNormalizationFilter`] can produce
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum UnicodeNormalizationForm {
+    /// Canonical composition, so that "e" followed by a combining acute accent becomes "é"
+    Nfc,
+    /// Compatibility composition, which also folds characters such as "ﬁ" into "fi"
+    Nfkc,
+    /// Compatibility decomposition, which leaves accents as separate combining characters
+    Nfkd,
+}
+
+/// A [`TokenFilter`] that rewrites each token into a Unicode normalization form, so that text
+/// which looks the same but is encoded differently produces the same tokens.
+#[derive(Clone, Copy, Debug)]
+pub struct UnicodeNormalizationFilter {
+    form: UnicodeNormalizationForm,
+}
+
+impl UnicodeNormalizationFilter {
+    pub fn new(form: UnicodeNormalizationForm) -> Self {
+        Self { form }
+    }
+}
+
+impl TokenFilter for UnicodeNormalizationFilter {
+    type Tokenizer<T: Tokenizer> = UnicodeNormalizationFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> UnicodeNormalizationFilterWrapper<T> {
+        UnicodeNormalizationFilterWrapper {
+            form: self.form,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct UnicodeNormalizationFilterWrapper<T> {
+    form: UnicodeNormalizationForm,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for UnicodeNormalizationFilterWrapper<T> {
+    type TokenStream<'a> = UnicodeNormalizationFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        UnicodeNormalizationFilterStream {
+            form: self.form,
+            tail: self.inner.token_stream(text),
+        }
+    }
+}
+
+pub struct UnicodeNormalizationFilterStream<T> {
+    form: UnicodeNormalizationForm,
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for UnicodeNormalizationFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let text = &mut self.tail.token_mut().text;
+        // most text is already normalized, so avoid reallocating it
+        let normalized = match self.form {
+            UnicodeNormalizationForm::Nfc if !is_nfc(text) => text.nfc().collect(),
+            UnicodeNormalizationForm::Nfkc if !is_nfkc(text) => text.nfkc().collect(),
+            UnicodeNormalizationForm::Nfkd if !is_nfkd(text) => text.nfkd().collect(),
+            _ => return true,
+        };
+        *text = normalized;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{RawTokenizer, TextAnalyzer};
+
+    fn normalize(form: UnicodeNormalizationForm, text: &str) -> String {
+        let mut analyzer = TextAnalyzer::builder(RawTokenizer::default())
+            .filter(UnicodeNormalizationFilter::new(form))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        stream.next().expect("a token").text.clone()
+    }
+
+    #[rstest]
+    #[case::nfc(UnicodeNormalizationForm::Nfc, "cafe\u{301}", "caf\u{e9}")]
+    #[case::nfc_already_normalized(UnicodeNormalizationForm::Nfc, "caf\u{e9}", "caf\u{e9}")]
+    #[case::nfc_keeps_compatibility(UnicodeNormalizationForm::Nfc, "\u{fb01}le", "\u{fb01}le")]
+    #[case::nfkc(UnicodeNormalizationForm::Nfkc, "\u{fb01}le", "file")]
+    #[case::nfkd(UnicodeNormalizationForm::Nfkd, "caf\u{e9}", "cafe\u{301}")]
+    fn test_unicode_normalization(
+        #[case] form: UnicodeNormalizationForm,
+        #[case] text: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(normalize(form, text), expected);
+    }
+
+    #[rstest]
+    fn test_form_from_json() {
+        let form: UnicodeNormalizationForm = serde_json::from_value("nfkc".into()).unwrap();
+        assert_eq!(form, UnicodeNormalizationForm::Nfkc);
+        assert!(serde_json::from_value::<UnicodeNormalizationForm>("nfx".into()).is_err());
+    }
+}
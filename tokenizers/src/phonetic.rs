@@ -0,0 +1,290 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+use once_cell::sync::Lazy;
+use rphonetic::{BeiderMorse, BeiderMorseBuilder, ConfigFiles, DoubleMetaphone, Encoder};
+use rphonetic::{Metaphone, Soundex};
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// The Beider-Morse rules are large, so they're only loaded once, when first used
+static BEIDER_MORSE_RULES: Lazy<ConfigFiles> = Lazy::new(ConfigFiles::default);
+static BEIDER_MORSE: Lazy<BeiderMorse<'static>> =
+    Lazy::new(|| BeiderMorseBuilder::new(&BEIDER_MORSE_RULES).build());
+
+/// The algorithms a [`PhoneticFilter`] can encode tokens with
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneticEncoder {
+    /// A letter followed by three digits, such as "S530" for both "Smith" and "Smyth"
+    Soundex,
+    /// A code of up to four letters, with more English pronunciation rules than Soundex
+    Metaphone,
+    /// Metaphone for names from many languages, with an alternate code for ambiguous spellings
+    DoubleMetaphone,
+    /// Every plausible pronunciation of a name, across the languages it could be from
+    BeiderMorse,
+}
+
+impl PhoneticEncoder {
+    /// The codes for `text`, of which there may be none or several
+    fn encode(self, text: &str) -> Vec<String> {
+        let codes = match self {
+            PhoneticEncoder::Soundex => vec![Soundex::default().encode(text)],
+            PhoneticEncoder::Metaphone => vec![Metaphone::default().encode(text)],
+            PhoneticEncoder::DoubleMetaphone => {
+                let result = DoubleMetaphone::default().double_metaphone(text);
+                vec![result.primary(), result.alternate()]
+            }
+            PhoneticEncoder::BeiderMorse => BEIDER_MORSE
+                .encode(text)
+                .split('|')
+                .map(str::to_string)
+                .collect(),
+        };
+
+        let mut distinct: Vec<String> = Vec::with_capacity(codes.len());
+        for code in codes {
+            if !code.is_empty() && !distinct.contains(&code) {
+                distinct.push(code);
+            }
+        }
+        distinct
+    }
+}
+
+/// Whether a [`PhoneticFilter`]'s codes replace the original token, or are added alongside it
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneticMode {
+    #[default]
+    Replace,
+    Inject,
+}
+
+/// The configuration of a [`PhoneticFilter`]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PhoneticOptions {
+    pub encoder: PhoneticEncoder,
+    #[serde(default)]
+    pub mode: PhoneticMode,
+}
+
+/// A [`TokenFilter`] that encodes tokens by how they sound, so that names spelled differently but
+/// pronounced alike, like "Smith" and "Smyth", produce the same tokens.
+///
+/// An encoder that produces several codes for a token emits them all at the token's position.
+/// Tokens that can't be encoded, like numbers, are kept as they are.
+#[derive(Clone, Copy, Debug)]
+pub struct PhoneticFilter {
+    options: PhoneticOptions,
+}
+
+impl PhoneticFilter {
+    pub fn new(options: PhoneticOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl TokenFilter for PhoneticFilter {
+    type Tokenizer<T: Tokenizer> = PhoneticFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> PhoneticFilterWrapper<T> {
+        PhoneticFilterWrapper {
+            options: self.options,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PhoneticFilterWrapper<T> {
+    options: PhoneticOptions,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for PhoneticFilterWrapper<T> {
+    type TokenStream<'a> = PhoneticFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        PhoneticFilterStream {
+            options: self.options,
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct PhoneticFilterStream<T> {
+    options: PhoneticOptions,
+    tail: T,
+    /// Codes waiting to be returned
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> TokenStream for PhoneticFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        if let Some(token) = self.pending.pop_front() {
+            self.token = token;
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let original = self.tail.token();
+        let codes = self.options.encoder.encode(&original.text);
+        if codes.is_empty() {
+            self.token = original.clone();
+            return true;
+        }
+
+        if self.options.mode == PhoneticMode::Inject {
+            self.pending.push_back(original.clone());
+        }
+        for code in codes {
+            if code != original.text {
+                self.pending.push_back(Token {
+                    text: code,
+                    ..original.clone()
+                });
+            }
+        }
+        match self.pending.pop_front() {
+            Some(token) => self.token = token,
+            None => self.token = original.clone(),
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
+
+    fn tokens(encoder: PhoneticEncoder, mode: PhoneticMode, text: &str) -> Vec<(String, usize)> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(PhoneticFilter::new(PhoneticOptions { encoder, mode }))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        tokens
+    }
+
+    #[rstest]
+    fn test_soundex() {
+        assert_eq!(
+            tokens(
+                PhoneticEncoder::Soundex,
+                PhoneticMode::Replace,
+                "Smith Smyth"
+            ),
+            vec![("S530".to_string(), 0), ("S530".to_string(), 1)]
+        );
+    }
+
+    #[rstest]
+    #[case::soundex(PhoneticEncoder::Soundex)]
+    #[case::metaphone(PhoneticEncoder::Metaphone)]
+    #[case::double_metaphone(PhoneticEncoder::DoubleMetaphone)]
+    #[case::beider_morse(PhoneticEncoder::BeiderMorse)]
+    fn test_sounds_alike(#[case] encoder: PhoneticEncoder) {
+        let codes = |text| {
+            tokens(encoder, PhoneticMode::Replace, text)
+                .into_iter()
+                .map(|(code, _)| code)
+                .collect::<Vec<_>>()
+        };
+        assert!(!codes("Smith").is_empty());
+        assert!(codes("Smith")
+            .iter()
+            .any(|code| codes("Smyth").contains(code)));
+
+        // Soundex keeps the first letter as it is
+        if encoder != PhoneticEncoder::Soundex {
+            assert!(codes("Katherine")
+                .iter()
+                .any(|code| codes("Catherine").contains(code)));
+        }
+    }
+
+    #[rstest]
+    fn test_inject() {
+        assert_eq!(
+            tokens(PhoneticEncoder::Soundex, PhoneticMode::Inject, "Smith 42"),
+            vec![
+                ("Smith".to_string(), 0),
+                ("S530".to_string(), 0),
+                ("42".to_string(), 1)
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_offsets() {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(PhoneticFilter::new(PhoneticOptions {
+                encoder: PhoneticEncoder::Soundex,
+                mode: PhoneticMode::Inject,
+            }))
+            .build();
+        let mut stream = analyzer.token_stream("Dr Smith");
+        let mut offsets = vec![];
+        while let Some(token) = stream.next() {
+            offsets.push((token.text.clone(), token.offset_from, token.offset_to));
+        }
+        // a code points at the word it encodes
+        assert_eq!(
+            offsets,
+            vec![
+                ("Dr".to_string(), 0, 2),
+                ("D600".to_string(), 0, 2),
+                ("Smith".to_string(), 3, 8),
+                ("S530".to_string(), 3, 8)
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_double_metaphone_alternate() {
+        // "Schmidt" could be German or anglicized, so it has two codes at the same position
+        let tokens = tokens(
+            PhoneticEncoder::DoubleMetaphone,
+            PhoneticMode::Replace,
+            "Schmidt",
+        );
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens.iter().all(|(_, position)| *position == 0));
+    }
+}
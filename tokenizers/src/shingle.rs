@@ -0,0 +1,288 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// The configuration of a [`ShingleFilter`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShingleOptions {
+    /// The fewest words in a shingle, at least 2
+    #[serde(default = "default_shingle_size")]
+    pub min_shingle_size: usize,
+    /// The most words in a shingle
+    #[serde(default = "default_shingle_size")]
+    pub max_shingle_size: usize,
+    /// Whether the single words are kept alongside the shingles
+    #[serde(default = "default_output_unigrams")]
+    pub output_unigrams: bool,
+    /// What the words of a shingle are joined with
+    #[serde(default = "default_token_separator")]
+    pub token_separator: String,
+}
+
+fn default_shingle_size() -> usize {
+    2
+}
+
+fn default_output_unigrams() -> bool {
+    true
+}
+
+fn default_token_separator() -> String {
+    " ".to_string()
+}
+
+impl ShingleOptions {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.min_shingle_size < 2 {
+            anyhow::bail!("'min_shingle_size' must be at least 2");
+        }
+        if self.max_shingle_size < self.min_shingle_size {
+            anyhow::bail!("'max_shingle_size' must be at least 'min_shingle_size'");
+        }
+        Ok(())
+    }
+}
+
+/// A [`TokenFilter`] that joins runs of consecutive tokens into "shingles", or word n-grams.
+///
+/// With the default options, "quick brown fox" produces `quick`, `quick brown`, `brown`,
+/// `brown fox`, and `fox`.  A shingle has the position of its first word, and spans as many
+/// positions as it has words, so a search for a shingle scores like a phrase search without
+/// needing positions to be recorded.
+#[derive(Clone, Debug)]
+pub struct ShingleFilter {
+    options: ShingleOptions,
+}
+
+impl ShingleFilter {
+    pub fn new(options: ShingleOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl TokenFilter for ShingleFilter {
+    type Tokenizer<T: Tokenizer> = ShingleFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> ShingleFilterWrapper<T> {
+        ShingleFilterWrapper {
+            options: self.options,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ShingleFilterWrapper<T> {
+    options: ShingleOptions,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for ShingleFilterWrapper<T> {
+    type TokenStream<'a> = ShingleFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        ShingleFilterStream {
+            options: &self.options,
+            tail: self.inner.token_stream(text),
+            window: VecDeque::new(),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct ShingleFilterStream<'a, T> {
+    options: &'a ShingleOptions,
+    tail: T,
+    /// The tokens from `tail` that the next shingles are made of
+    window: VecDeque<Token>,
+    /// Tokens and shingles waiting to be returned
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> ShingleFilterStream<'_, T> {
+    /// The unigram and shingles that start with the first token of `window` into `pending`
+    fn shingle(&mut self) -> bool {
+        while self.window.len() < self.options.max_shingle_size && self.tail.advance() {
+            self.window.push_back(self.tail.token().clone());
+        }
+        let Some(first) = self.window.front() else {
+            return false;
+        };
+
+        if self.options.output_unigrams {
+            self.pending.push_back(first.clone());
+        }
+        let longest = self.options.max_shingle_size.min(self.window.len());
+        for len in self.options.min_shingle_size..=longest {
+            let last = &self.window[len - 1];
+            let text = self
+                .window
+                .iter()
+                .take(len)
+                .map(|token| token.text.as_str())
+                .collect::<Vec<_>>()
+                .join(&self.options.token_separator);
+            self.pending.push_back(Token {
+                offset_from: first.offset_from,
+                offset_to: last.offset_to,
+                position: first.position,
+                text,
+                position_length: len,
+            });
+        }
+
+        self.window.pop_front();
+        true
+    }
+}
+
+impl<T: TokenStream> TokenStream for ShingleFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        while self.pending.is_empty() {
+            if !self.shingle() {
+                return false;
+            }
+        }
+
+        match self.pending.pop_front() {
+            Some(token) => {
+                self.token = token;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{SimpleTokenizer, StopWordFilter, TextAnalyzer};
+
+    fn tokens(options: ShingleOptions, text: &str) -> Vec<(String, usize)> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(ShingleFilter::new(options))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        tokens
+    }
+
+    fn expected(tokens: &[(&str, usize)]) -> Vec<(String, usize)> {
+        tokens
+            .iter()
+            .map(|(text, position)| (text.to_string(), *position))
+            .collect()
+    }
+
+    fn options(min: usize, max: usize, output_unigrams: bool) -> ShingleOptions {
+        ShingleOptions {
+            min_shingle_size: min,
+            max_shingle_size: max,
+            output_unigrams,
+            token_separator: " ".to_string(),
+        }
+    }
+
+    #[rstest]
+    fn test_bigrams() {
+        assert_eq!(
+            tokens(options(2, 2, true), "quick brown fox"),
+            expected(&[
+                ("quick", 0),
+                ("quick brown", 0),
+                ("brown", 1),
+                ("brown fox", 1),
+                ("fox", 2)
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_bigrams_and_trigrams_without_unigrams() {
+        assert_eq!(
+            tokens(options(2, 3, false), "quick brown fox"),
+            expected(&[("quick brown", 0), ("quick brown fox", 0), ("brown fox", 1)])
+        );
+    }
+
+    #[rstest]
+    fn test_position_gaps() {
+        // a removed stopword leaves a gap, which the shingles around it span
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(StopWordFilter::remove(vec!["the".to_string()]))
+            .filter(ShingleFilter::new(options(2, 2, true)))
+            .build();
+        let mut stream = analyzer.token_stream("quick the brown fox");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        assert_eq!(
+            tokens,
+            expected(&[
+                ("quick", 0),
+                ("quick brown", 0),
+                ("brown", 2),
+                ("brown fox", 2),
+                ("fox", 3)
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_offsets() {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(ShingleFilter::new(options(2, 3, false)))
+            .build();
+        let text = "quick  brown fox";
+        let mut stream = analyzer.token_stream(text);
+        let mut shingles = vec![];
+        while let Some(token) = stream.next() {
+            shingles.push(&text[token.offset_from..token.offset_to]);
+        }
+        assert_eq!(
+            shingles,
+            vec!["quick  brown", "quick  brown fox", "brown fox"]
+        );
+    }
+
+    #[rstest]
+    fn test_invalid_options() {
+        assert!(options(1, 2, true).validate().is_err());
+        assert!(options(3, 2, true).validate().is_err());
+        assert!(options(2, 3, true).validate().is_ok());
+    }
+}
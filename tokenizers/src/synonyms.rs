@@ -0,0 +1,297 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// A [`TokenFilter`] that expands tokens into their synonyms.
+///
+/// Rules are written in the same format as Solr's synonym files:
+///
+/// - `tv, television` is an equivalence.  Any of the words is expanded to all of them.
+/// - `nyc => new york` is one-way.  The words on the left are replaced by the words on the right.
+///
+/// Either side of a rule may have multiple words.  The words of a multi-word synonym are given
+/// consecutive positions, and the tokens after it are moved along to make room, so that phrase
+/// queries for the synonym still match.
+#[derive(Clone, Debug)]
+pub struct SynonymFilter {
+    rules: Arc<SynonymRules>,
+}
+
+#[derive(Debug, Default)]
+struct SynonymRules {
+    /// Each phrase that has synonyms, and the phrases it's expanded to
+    expansions: HashMap<Vec<String>, Vec<Vec<String>>>,
+    /// The most words in any phrase that has synonyms
+    longest: usize,
+}
+
+impl SynonymFilter {
+    pub fn new(rules: &[String]) -> Result<Self, anyhow::Error> {
+        Self::with_normalizer(rules, str::to_string)
+    }
+
+    /// Like [`SynonymFilter::new`], but every word of the rules is passed through `normalize`
+    /// first.  Tokens reach this filter after the ones before it, such as lowercasing, so
+    /// `normalize` should apply the same changes for the rules to match them.
+    pub fn with_normalizer(
+        rules: &[String],
+        mut normalize: impl FnMut(&str) -> String,
+    ) -> Result<Self, anyhow::Error> {
+        let mut expansions: HashMap<Vec<String>, Vec<Vec<String>>> = HashMap::new();
+        for rule in rules {
+            let mut normalized_phrases = |phrases: &str| -> Vec<Vec<String>> {
+                parse_phrases(phrases)
+                    .into_iter()
+                    .map(|phrase| phrase.iter().map(|word| normalize(word)).collect())
+                    .collect()
+            };
+            let (from, to) = match rule.split_once("=>") {
+                Some((from, to)) => (normalized_phrases(from), normalized_phrases(to)),
+                None => {
+                    let phrases = normalized_phrases(rule);
+                    (phrases.clone(), phrases)
+                }
+            };
+            if from.is_empty() || to.is_empty() {
+                anyhow::bail!("synonym rule '{rule}' must have words on both sides");
+            }
+
+            for phrase in from {
+                let expanded = expansions.entry(phrase).or_default();
+                for synonym in &to {
+                    if !expanded.contains(synonym) {
+                        expanded.push(synonym.clone());
+                    }
+                }
+            }
+        }
+
+        let longest = expansions.keys().map(Vec::len).max().unwrap_or_default();
+        Ok(Self {
+            rules: Arc::new(SynonymRules {
+                expansions,
+                longest,
+            }),
+        })
+    }
+}
+
+/// A comma-separated list of phrases, each of which is whitespace-separated words
+fn parse_phrases(phrases: &str) -> Vec<Vec<String>> {
+    phrases
+        .split(',')
+        .map(|phrase| phrase.split_whitespace().map(str::to_string).collect())
+        .filter(|phrase: &Vec<String>| !phrase.is_empty())
+        .collect()
+}
+
+impl TokenFilter for SynonymFilter {
+    type Tokenizer<T: Tokenizer> = SynonymFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> SynonymFilterWrapper<T> {
+        SynonymFilterWrapper {
+            rules: self.rules,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SynonymFilterWrapper<T> {
+    rules: Arc<SynonymRules>,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for SynonymFilterWrapper<T> {
+    type TokenStream<'a> = SynonymFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        SynonymFilterStream {
+            rules: &self.rules,
+            tail: self.inner.token_stream(text),
+            lookahead: VecDeque::new(),
+            pending: VecDeque::new(),
+            token: Token::default(),
+            shift: 0,
+        }
+    }
+}
+
+pub struct SynonymFilterStream<'a, T> {
+    rules: &'a SynonymRules,
+    tail: T,
+    /// Tokens read from `tail` while looking for a multi-word match
+    lookahead: VecDeque<Token>,
+    /// Synonyms waiting to be returned
+    pending: VecDeque<Token>,
+    token: Token,
+    /// How far the positions of the tokens from `tail` have been moved by the synonyms before them
+    shift: isize,
+}
+
+impl<T: TokenStream> SynonymFilterStream<'_, T> {
+    fn fill_lookahead(&mut self) {
+        while self.lookahead.len() < self.rules.longest.max(1) && self.tail.advance() {
+            self.lookahead.push_back(self.tail.token().clone());
+        }
+    }
+
+    fn shifted(&self, position: usize) -> usize {
+        (position as isize + self.shift).max(0) as usize
+    }
+
+    /// Expand the longest phrase at the start of `lookahead` that has synonyms into `pending`
+    fn expand(&mut self) -> bool {
+        for len in (1..=self.rules.longest.min(self.lookahead.len())).rev() {
+            let phrase = self
+                .lookahead
+                .iter()
+                .take(len)
+                .map(|token| token.text.clone())
+                .collect::<Vec<_>>();
+            let Some(synonyms) = self.rules.expansions.get(&phrase) else {
+                continue;
+            };
+
+            let matched = self.lookahead.drain(..len).collect::<Vec<_>>();
+            let first = &matched[0];
+            let last = &matched[len - 1];
+            let position = self.shifted(first.position);
+            let longest = synonyms.iter().map(Vec::len).max().unwrap_or(len);
+
+            for synonym in synonyms {
+                for (i, word) in synonym.iter().enumerate() {
+                    self.pending.push_back(Token {
+                        offset_from: first.offset_from,
+                        offset_to: last.offset_to,
+                        position: position + i,
+                        text: word.clone(),
+                        position_length: 1,
+                    });
+                }
+            }
+
+            // keep the tokens that follow where they'd be after the longest synonym
+            self.shift += longest as isize - len as isize;
+            return true;
+        }
+        false
+    }
+}
+
+impl<T: TokenStream> TokenStream for SynonymFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if self.pending.is_empty() {
+            self.fill_lookahead();
+            if !self.expand() {
+                let Some(mut token) = self.lookahead.pop_front() else {
+                    return false;
+                };
+                token.position = self.shifted(token.position);
+                self.pending.push_back(token);
+            }
+        }
+
+        match self.pending.pop_front() {
+            Some(token) => {
+                self.token = token;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+
+    fn tokens(rules: &[&str], text: &str) -> Vec<(String, usize)> {
+        let rules = rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<_>>();
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(SynonymFilter::new(&rules).unwrap())
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        tokens
+    }
+
+    fn expected(tokens: &[(&str, usize)]) -> Vec<(String, usize)> {
+        tokens
+            .iter()
+            .map(|(text, position)| (text.to_string(), *position))
+            .collect()
+    }
+
+    #[rstest]
+    fn test_equivalent_synonyms() {
+        assert_eq!(
+            tokens(&["tv, television"], "a TV stand"),
+            expected(&[("a", 0), ("tv", 1), ("television", 1), ("stand", 2)])
+        );
+    }
+
+    #[rstest]
+    fn test_one_way_synonyms() {
+        assert_eq!(
+            tokens(&["nyc => new york"], "nyc pizza"),
+            expected(&[("new", 0), ("york", 1), ("pizza", 2)])
+        );
+        assert_eq!(
+            tokens(&["nyc => new york"], "new york pizza"),
+            expected(&[("new", 0), ("york", 1), ("pizza", 2)])
+        );
+    }
+
+    #[rstest]
+    fn test_multi_word_match() {
+        assert_eq!(
+            tokens(&["new york, nyc"], "new york pizza"),
+            expected(&[("new", 0), ("york", 1), ("nyc", 0), ("pizza", 2)])
+        );
+        assert_eq!(
+            tokens(&["new york, nyc"], "nyc pizza"),
+            expected(&[("new", 0), ("york", 1), ("nyc", 0), ("pizza", 2)])
+        );
+    }
+
+    #[rstest]
+    fn test_invalid_rule() {
+        assert!(SynonymFilter::new(&["tv =>".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,479 @@
+// Copyright (c) 2023-2025 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// The configuration of a [`WordDelimiterFilter`]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WordDelimiterOptions {
+    /// Whether the parts made of letters are kept, like "Power" and "Shot" from "PowerShot"
+    #[serde(default = "default_true")]
+    pub generate_word_parts: bool,
+    /// Whether the parts made of digits are kept, like "13" from "XPS13"
+    #[serde(default = "default_true")]
+    pub generate_number_parts: bool,
+    /// Whether a lowercase letter followed by an uppercase one starts a new part
+    #[serde(default = "default_true")]
+    pub split_on_case_change: bool,
+    /// Whether a letter followed by a digit, or a digit by a letter, starts a new part
+    #[serde(default = "default_true")]
+    pub split_on_numerics: bool,
+    /// Whether the whole token is kept alongside its parts
+    #[serde(default)]
+    pub preserve_original: bool,
+    /// Whether runs of word parts are also joined together, like "WiFi" from "Wi-Fi"
+    #[serde(default)]
+    pub catenate_words: bool,
+    /// Whether runs of number parts are also joined together, like "9310" from "93-10"
+    #[serde(default)]
+    pub catenate_numbers: bool,
+    /// Whether all of the parts are also joined together, like "XPS139310" from "XPS13-9310"
+    #[serde(default)]
+    pub catenate_all: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for WordDelimiterOptions {
+    fn default() -> Self {
+        Self {
+            generate_word_parts: true,
+            generate_number_parts: true,
+            split_on_case_change: true,
+            split_on_numerics: true,
+            preserve_original: false,
+            catenate_words: false,
+            catenate_numbers: false,
+            catenate_all: false,
+        }
+    }
+}
+
+impl WordDelimiterOptions {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if !(self.generate_word_parts
+            || self.generate_number_parts
+            || self.preserve_original
+            || self.catenate_words
+            || self.catenate_numbers
+            || self.catenate_all)
+        {
+            anyhow::bail!("a 'word_delimiter' filter must generate or catenate some of its parts");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CharType {
+    Upper,
+    Lower,
+    Digit,
+    Delimiter,
+}
+
+impl CharType {
+    fn of(c: char) -> Self {
+        if c.is_alphabetic() {
+            if c.is_uppercase() {
+                CharType::Upper
+            } else {
+                CharType::Lower
+            }
+        } else if c.is_numeric() {
+            CharType::Digit
+        } else {
+            CharType::Delimiter
+        }
+    }
+
+    fn is_alpha(self) -> bool {
+        matches!(self, CharType::Upper | CharType::Lower)
+    }
+}
+
+/// A piece of a token, between delimiters, case changes, and letter/digit transitions
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Part {
+    range: Range<usize>,
+    is_number: bool,
+}
+
+impl WordDelimiterOptions {
+    /// The parts of `text`, in order
+    fn split(&self, text: &str) -> Vec<Part> {
+        let chars = text
+            .char_indices()
+            .map(|(offset, c)| (offset, CharType::of(c)))
+            .collect::<Vec<_>>();
+        let type_at = |i: usize| chars.get(i).map(|(_, t)| *t);
+
+        let mut parts = vec![];
+        let mut start: Option<usize> = None;
+        for (i, &(offset, char_type)) in chars.iter().enumerate() {
+            if char_type == CharType::Delimiter {
+                if let Some(start) = start.take() {
+                    parts.push(self.part(text, start..offset));
+                }
+                continue;
+            }
+
+            let Some(part_start) = start else {
+                start = Some(offset);
+                continue;
+            };
+            let previous = type_at(i - 1).expect("a part has a previous character");
+            let is_break = if previous.is_alpha() && char_type.is_alpha() {
+                // "PowerShot" is "Power" and "Shot", and "XMLParser" is "XML" and "Parser"
+                self.split_on_case_change
+                    && ((previous == CharType::Lower && char_type == CharType::Upper)
+                        || (previous == CharType::Upper
+                            && char_type == CharType::Upper
+                            && type_at(i + 1) == Some(CharType::Lower)))
+            } else {
+                self.split_on_numerics && previous != char_type
+            };
+            if is_break {
+                parts.push(self.part(text, part_start..offset));
+                start = Some(offset);
+            }
+        }
+        if let Some(start) = start {
+            parts.push(self.part(text, start..text.len()));
+        }
+        parts
+    }
+
+    fn part(&self, text: &str, range: Range<usize>) -> Part {
+        let is_number = text[range.clone()].chars().all(char::is_numeric);
+        Part { range, is_number }
+    }
+}
+
+/// A [`TokenFilter`] that splits tokens like product codes and mixed-case identifiers into their
+/// parts, so "XPS13-9310" can be found by "xps", "13" or "9310".
+///
+/// Parts are split at delimiters, which are dropped, at changes from lowercase to uppercase, and
+/// between letters and digits.  The parts are given consecutive positions, and the tokens after
+/// them are moved along to make room.  The original token and the catenated parts span the
+/// positions of the parts they're made of, so phrase queries match either form.
+///
+/// This filter runs first, before lowercasing, since it needs the case of the original text.
+#[derive(Clone, Copy, Debug)]
+pub struct WordDelimiterFilter {
+    options: WordDelimiterOptions,
+}
+
+impl WordDelimiterFilter {
+    pub fn new(options: WordDelimiterOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl TokenFilter for WordDelimiterFilter {
+    type Tokenizer<T: Tokenizer> = WordDelimiterFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> WordDelimiterFilterWrapper<T> {
+        WordDelimiterFilterWrapper {
+            options: self.options,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WordDelimiterFilterWrapper<T> {
+    options: WordDelimiterOptions,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for WordDelimiterFilterWrapper<T> {
+    type TokenStream<'a> = WordDelimiterFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        WordDelimiterFilterStream {
+            options: self.options,
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+            shift: 0,
+        }
+    }
+}
+
+pub struct WordDelimiterFilterStream<T> {
+    options: WordDelimiterOptions,
+    tail: T,
+    /// Parts waiting to be returned
+    pending: VecDeque<Token>,
+    token: Token,
+    /// How many positions the parts of earlier tokens have added
+    shift: usize,
+}
+
+impl<T: TokenStream> WordDelimiterFilterStream<T> {
+    /// Split the next token from `tail` into `pending`
+    fn split(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let mut original = self.tail.token().clone();
+        original.position += self.shift;
+        let parts = self.options.split(&original.text);
+        if parts.len() == 1 && parts[0].range.len() == original.text.len() {
+            self.pending.push_back(original);
+            return true;
+        }
+        if parts.is_empty() {
+            if self.options.preserve_original {
+                self.pending.push_back(original);
+            }
+            return true;
+        }
+
+        // if an earlier filter changed the token's text, its parts can't be located in the
+        // original text, so they're given the offsets of the whole token
+        let offsets_match = original.offset_to - original.offset_from == original.text.len();
+        let token = |range: Range<usize>, first: usize, last: usize| Token {
+            offset_from: if offsets_match {
+                original.offset_from + range.start
+            } else {
+                original.offset_from
+            },
+            offset_to: if offsets_match {
+                original.offset_from + range.end
+            } else {
+                original.offset_to
+            },
+            position: original.position + first,
+            text: original.text[range].to_string(),
+            position_length: last - first + 1,
+        };
+        let catenated = |first: usize, last: usize| Token {
+            text: parts[first..=last]
+                .iter()
+                .map(|part| &original.text[part.range.clone()])
+                .collect(),
+            ..token(parts[first].range.start..parts[last].range.end, first, last)
+        };
+
+        // tokens are produced in order of position, as the index requires
+        let last = parts.len() - 1;
+        for (i, part) in parts.iter().enumerate() {
+            let mut at_position = vec![];
+            if i == 0 && self.options.preserve_original {
+                at_position.push(Token {
+                    position_length: parts.len(),
+                    ..original.clone()
+                });
+            }
+            if i == 0 && self.options.catenate_all && last > 0 {
+                at_position.push(catenated(0, last));
+            }
+            let starts_run = i == 0 || parts[i - 1].is_number != part.is_number;
+            let run_end = i + parts[i..]
+                .iter()
+                .take_while(|next| next.is_number == part.is_number)
+                .count()
+                - 1;
+            let catenate_run = if part.is_number {
+                self.options.catenate_numbers
+            } else {
+                self.options.catenate_words
+            };
+            if starts_run && catenate_run && run_end > i {
+                at_position.push(catenated(i, run_end));
+            }
+            let generate = if part.is_number {
+                self.options.generate_number_parts
+            } else {
+                self.options.generate_word_parts
+            };
+            if generate {
+                at_position.push(token(part.range.clone(), i, i));
+            }
+
+            for token in at_position {
+                if !self
+                    .pending
+                    .iter()
+                    .any(|pending| pending.position == token.position && pending.text == token.text)
+                {
+                    self.pending.push_back(token);
+                }
+            }
+        }
+
+        self.shift += last;
+        true
+    }
+}
+
+impl<T: TokenStream> TokenStream for WordDelimiterFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.pending.is_empty() {
+            if !self.split() {
+                return false;
+            }
+        }
+
+        self.token = self
+            .pending
+            .pop_front()
+            .expect("pending should not be empty");
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tantivy::tokenizer::{TextAnalyzer, WhitespaceTokenizer};
+
+    fn tokens(options: WordDelimiterOptions, text: &str) -> Vec<(String, usize, usize)> {
+        let mut analyzer = TextAnalyzer::builder(WhitespaceTokenizer::default())
+            .filter(WordDelimiterFilter::new(options))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position, token.position_length));
+        }
+        tokens
+    }
+
+    fn expected(tokens: &[(&str, usize, usize)]) -> Vec<(String, usize, usize)> {
+        tokens
+            .iter()
+            .map(|(text, position, length)| (text.to_string(), *position, *length))
+            .collect()
+    }
+
+    #[rstest]
+    fn test_split() {
+        assert_eq!(
+            tokens(
+                WordDelimiterOptions::default(),
+                "PowerShot XPS13-9310 XMLParser"
+            ),
+            expected(&[
+                ("Power", 0, 1),
+                ("Shot", 1, 1),
+                ("XPS", 2, 1),
+                ("13", 3, 1),
+                ("9310", 4, 1),
+                ("XML", 5, 1),
+                ("Parser", 6, 1),
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_unsplit_tokens_are_unchanged() {
+        assert_eq!(
+            tokens(WordDelimiterOptions::default(), "camera 42 -"),
+            expected(&[("camera", 0, 1), ("42", 1, 1)])
+        );
+    }
+
+    #[rstest]
+    fn test_preserve_original_and_catenate() {
+        let options = WordDelimiterOptions {
+            preserve_original: true,
+            catenate_words: true,
+            catenate_numbers: true,
+            catenate_all: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokens(options, "WiFi-6E router"),
+            expected(&[
+                ("WiFi-6E", 0, 4),
+                ("WiFi6E", 0, 4),
+                ("WiFi", 0, 2),
+                ("Wi", 0, 1),
+                ("Fi", 1, 1),
+                ("6", 2, 1),
+                ("E", 3, 1),
+                ("router", 4, 1),
+            ])
+        );
+        assert_eq!(
+            tokens(options, "12-34-56"),
+            expected(&[
+                ("12-34-56", 0, 3),
+                ("123456", 0, 3),
+                ("12", 0, 1),
+                ("34", 1, 1),
+                ("56", 2, 1),
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_without_splitting_on_case_or_numerics() {
+        let options = WordDelimiterOptions {
+            split_on_case_change: false,
+            split_on_numerics: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokens(options, "PowerShot XPS13-9310"),
+            expected(&[("PowerShot", 0, 1), ("XPS13", 1, 1), ("9310", 2, 1)])
+        );
+    }
+
+    #[rstest]
+    fn test_offsets() {
+        let mut analyzer = TextAnalyzer::builder(WhitespaceTokenizer::default())
+            .filter(WordDelimiterFilter::new(WordDelimiterOptions::default()))
+            .build();
+        let text = "new XPS13-9310";
+        let mut stream = analyzer.token_stream(text);
+        let mut parts = vec![];
+        while let Some(token) = stream.next() {
+            parts.push(text[token.offset_from..token.offset_to].to_string());
+        }
+        assert_eq!(parts, vec!["new", "XPS", "13", "9310"]);
+    }
+
+    #[rstest]
+    fn test_invalid_options() {
+        let options = WordDelimiterOptions {
+            generate_word_parts: false,
+            generate_number_parts: false,
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+    }
+}